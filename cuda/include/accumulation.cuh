@@ -0,0 +1,12 @@
+#ifndef ACCUMULATION_H
+#define ACCUMULATION_H
+
+#include "fields.cuh"
+
+extern "C"
+void accumulate(m31 **column, m31 **other, int size);
+
+extern "C"
+void accumulate_sparse(m31 **column, uint32_t *indices, m31 *values, int n_values, qm31 coefficient);
+
+#endif // ACCUMULATION_H
\ No newline at end of file
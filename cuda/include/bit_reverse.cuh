@@ -10,4 +10,7 @@ void bit_reverse_base_field(m31*, int);
 extern "C"
 void bit_reverse_secure_field(qm31*, int);
 
+extern "C"
+void bit_reverse_blake2s_hash(uint32_t*, int);
+
 #endif // BIT_REVERSE_H
\ No newline at end of file
@@ -0,0 +1,24 @@
+#ifndef BLAKE2S_H
+#define BLAKE2S_H
+
+#include "fields.cuh"
+
+extern "C"
+void commit_on_layer(int log_size, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst);
+
+extern "C"
+void commit_on_layer_on_stream(int log_size, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst, cudaStream_t stream);
+
+extern "C"
+void commit_on_layer_batch(int log_size, int n_instances, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst);
+
+extern "C"
+void commit_on_layer_batch_on_stream(int log_size, int n_instances, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst, cudaStream_t stream);
+
+extern "C"
+void verify_merkle_paths(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, uint32_t *roots, int n_roots, uint32_t *root_indices, int n_paths, int *valid);
+
+extern "C"
+void merkle_path_nodes(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, int n_paths, uint32_t *nodes);
+
+#endif // BLAKE2S_H
\ No newline at end of file
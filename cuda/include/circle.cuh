@@ -16,7 +16,22 @@ void interpolate(m31 *values, m31 *inverse_twiddles_tree, int values_size);
 extern "C"
 void evaluate(m31 *values, m31 *inverse_twiddles_tree, int values_size);
 
+extern "C"
+void interpolate_batch(m31 *values, m31 *inverse_twiddles_tree, int values_size, int n_instances);
+
+extern "C"
+void evaluate_batch(m31 *values, m31 *inverse_twiddles_tree, int values_size, int n_instances);
+
+extern "C"
+void extend_batch(m31 *coeffs, int size, m31 *dst, int extended_size, int n_instances);
+
 extern "C"
 qm31 eval_at_point(m31 *coeffs, int coeffs_size, qm31 point_x, qm31 point_y);
 
+extern "C"
+void eval_polys_at_points(m31 **coeffs, uint32_t *log_sizes, int n_polys, qm31 *points_x, qm31 *points_y, int n_points, qm31 *result);
+
+extern "C"
+void eval_samples(m31 **coeffs, uint32_t *log_sizes, int n_polys, qm31 *points_x, qm31 *points_y, int n_points, uint32_t *sample_polys, uint32_t *sample_points, int n_samples, qm31 *result);
+
 #endif // CIRCLE_H
\ No newline at end of file
@@ -0,0 +1,12 @@
+#ifndef COMPRESSION_H
+#define COMPRESSION_H
+
+#include "fields.cuh"
+
+extern "C"
+void pack_base_field(m31 *column, int size, uint32_t *dst, int packed_size);
+
+extern "C"
+void unpack_base_field(uint32_t *packed, int packed_size, m31 *dst, int size);
+
+#endif // COMPRESSION_H
\ No newline at end of file
@@ -103,6 +103,18 @@ __host__ __device__ __forceinline__ qm31 sub(qm31 x, qm31 y) {
     return {sub(x.a, y.a), sub(x.b, y.b)};
 }
 
+__host__ __device__ __forceinline__ qm31 mul(qm31 x, m31 y) {
+    return {{mul(x.a.a, y), mul(x.a.b, y)}, {mul(x.b.a, y), mul(x.b.b, y)}};
+}
+
+__host__ __device__ __forceinline__ qm31 add(qm31 x, m31 y) {
+    return {{add(x.a.a, y), x.a.b}, x.b};
+}
+
+__host__ __device__ __forceinline__ qm31 sub(m31 x, qm31 y) {
+    return {{sub(x, y.a.a), neg(y.a.b)}, neg(y.b)};
+}
+
 __host__ __device__ __forceinline__ qm31 inv(qm31 t) {
     cm31 b2 = mul(t.b, t.b);
     cm31 ib2 = {neg(b2.b), b2.a};
@@ -0,0 +1,41 @@
+#ifndef FRI_H
+#define FRI_H
+
+#include "fields.cuh"
+
+extern "C"
+void fold_line(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha);
+
+extern "C"
+void fold_line_on_stream(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha, cudaStream_t stream);
+
+extern "C"
+void fold_line_and_hash(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha, uint32_t *leaves);
+
+extern "C"
+void fold_circle_into_line(m31 **dst, m31 **src, int dst_size, m31 *itwiddles, int twiddle_offset, qm31 alpha);
+
+extern "C"
+void fold_lines(m31 **evals, m31 **folded, int n_evals, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 *alphas);
+
+extern "C"
+void fold_line_by(m31 **eval, m31 **folded, int eval_size, int log_fold_factor, m31 *itwiddles, int *twiddle_offsets, qm31 alpha);
+
+// Writes the sum to result. Returns 0 on success, otherwise the CUDA error code of the launch.
+extern "C"
+int sum_secure_column(m31 **column, int size, qm31 *result);
+
+extern "C"
+void compute_g_values(m31 **f_values, m31 **dst, int size, qm31 lambda);
+
+// Writes lambda to result. Returns 0 on success, otherwise the CUDA error code of the launch.
+extern "C"
+int decompose(m31 **f_values, m31 **dst, int size, qm31 *result);
+
+extern "C"
+void check_fold_pairs(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, qm31 *folded, int n_checks, int *valid);
+
+extern "C"
+void fold_pairs(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, int n_pairs, qm31 *folded);
+
+#endif // FRI_H
\ No newline at end of file
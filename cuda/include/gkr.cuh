@@ -0,0 +1,24 @@
+#ifndef GKR_H
+#define GKR_H
+
+#include "fields.cuh"
+
+extern "C"
+void gkr_grand_product_sum(qm31 *eq_evals, qm31 *input, int n_terms, qm31 *result);
+
+extern "C"
+void gkr_logup_sum_base_field(qm31 *eq_evals, m31 *numerators, qm31 *denominators, int n_terms, qm31 lambda, qm31 *result);
+
+extern "C"
+void gkr_logup_sum_secure_field(qm31 *eq_evals, qm31 *numerators, qm31 *denominators, int n_terms, qm31 lambda, qm31 *result);
+
+extern "C"
+void gkr_next_grand_product_layer(qm31 *layer, int size, qm31 *dst);
+
+extern "C"
+void gkr_next_logup_layer_base_field(m31 *numerators, qm31 *denominators, int size, qm31 *dst_numerators, qm31 *dst_denominators);
+
+extern "C"
+void gkr_next_logup_layer_secure_field(qm31 *numerators, qm31 *denominators, int size, qm31 *dst_numerators, qm31 *dst_denominators);
+
+#endif // GKR_H
\ No newline at end of file
@@ -0,0 +1,18 @@
+#ifndef INNER_PRODUCT_H
+#define INNER_PRODUCT_H
+
+#include "fields.cuh"
+
+extern "C"
+m31 inner_product_base_field(m31 *a, m31 *b, int size);
+
+extern "C"
+qm31 inner_product_secure_field(m31 **a, m31 **b, int size);
+
+extern "C"
+void mul_add_base_field(m31 *dst, m31 *a, m31 *b, int size);
+
+extern "C"
+void mul_add_secure_field(m31 **dst, m31 **a, m31 **b, int size);
+
+#endif // INNER_PRODUCT_H
@@ -0,0 +1,30 @@
+#ifndef JIT_H
+#define JIT_H
+
+#include "fields.cuh"
+
+extern "C"
+int jit_target_architecture();
+
+extern "C"
+int jit_compile(const char *source, const char *fields_header, char **ptx, char **log);
+
+extern "C"
+void jit_free(char *ptr);
+
+extern "C"
+int jit_load_module(const char *ptx, void **module);
+
+extern "C"
+int jit_get_function(void *module, const char *name, void **function);
+
+extern "C"
+void jit_unload_module(void *module);
+
+extern "C"
+int jit_launch_constraint_kernel(void *function, m31 **columns, int n_columns, qm31 *random_coeff_powers, m31 **accumulator, int size);
+
+extern "C"
+int jit_launch_check_kernel(void *function, m31 **columns, int n_columns, int size, uint64_t *first_failure);
+
+#endif // JIT_H
\ No newline at end of file
@@ -0,0 +1,21 @@
+#ifndef LOGUP_H
+#define LOGUP_H
+
+#include "fields.cuh"
+
+__host__ __device__ __forceinline__ void add_fraction(qm31 numerator_a, qm31 denominator_a, qm31 numerator_b, qm31 denominator_b, qm31 &numerator, qm31 &denominator) {
+    // a / b + c / d = (a * d + c * b) / (b * d)
+    numerator = add(mul(numerator_a, denominator_b), mul(numerator_b, denominator_a));
+    denominator = mul(denominator_a, denominator_b);
+}
+
+extern "C"
+void add_fractions(qm31 *numerators_a, qm31 *denominators_a, qm31 *numerators_b, qm31 *denominators_b, qm31 *dst_numerators, qm31 *dst_denominators, int size);
+
+extern "C"
+void fractions_cumulative_sum(qm31 *numerators, qm31 *denominators, int size);
+
+extern "C"
+void accumulate_multiplicities(m31 *column, int size, m31 *multiplicities, int table_size);
+
+#endif // LOGUP_H
\ No newline at end of file
@@ -0,0 +1,24 @@
+#ifndef MASK_H
+#define MASK_H
+
+#include "fields.cuh"
+#include "utils.cuh"
+
+__device__ __forceinline__ int offset_bit_reversed_circle_domain_index(int index, int trace_log_size, int eval_log_size, int offset) {
+    // Index of the row shifted by `offset` trace steps, for a column stored in
+    // bit reversed order over a circle domain of size 2^eval_log_size.
+    int prev_index = bit_reverse(index, eval_log_size);
+    int half_size = 1 << (eval_log_size - 1);
+    int step_size = offset * (1 << (eval_log_size - trace_log_size - 1));
+    if (prev_index < half_size) {
+        prev_index = (((prev_index + step_size) % half_size) + half_size) % half_size;
+    } else {
+        prev_index = ((((prev_index - step_size) % half_size) + half_size) % half_size) + half_size;
+    }
+    return bit_reverse(prev_index, eval_log_size);
+}
+
+extern "C"
+void gather_mask_base_field(m31 *column, m31 *dst, int trace_log_size, int eval_log_size, int offset);
+
+#endif // MASK_H
\ No newline at end of file
@@ -0,0 +1,15 @@
+#ifndef MLE_H
+#define MLE_H
+
+#include "fields.cuh"
+
+extern "C"
+void fix_first_variable_base_field(m31 *evals, int evals_size, qm31 assignment, qm31 *dst);
+
+extern "C"
+void fix_first_variable_secure_field(qm31 *evals, int evals_size, qm31 assignment, qm31 *dst);
+
+extern "C"
+void gen_eq_evals(qm31 *y, int y_size, qm31 v, qm31 *dst);
+
+#endif // MLE_H
\ No newline at end of file
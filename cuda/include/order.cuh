@@ -0,0 +1,24 @@
+#ifndef ORDER_H
+#define ORDER_H
+
+#include "fields.cuh"
+
+extern "C"
+void natural_to_circle_domain_order(m31 *column, m31 *dst, int log_size);
+
+extern "C"
+void circle_domain_to_natural_order(m31 *column, m31 *dst, int log_size);
+
+extern "C"
+void gather_base_field(m31 *column, uint32_t *indices, int size, m31 *dst);
+
+extern "C"
+void gather_secure_field(m31 **column, uint32_t *indices, int size, m31 **dst);
+
+extern "C"
+void scatter_base_field(m31 *column, uint32_t *indices, int size, m31 *dst);
+
+extern "C"
+void scatter_secure_field(m31 **column, uint32_t *indices, int size, m31 **dst);
+
+#endif // ORDER_H
@@ -0,0 +1,9 @@
+#ifndef PADDING_H
+#define PADDING_H
+
+#include "fields.cuh"
+
+extern "C"
+void pad_base_field(m31 *column, int size, m31 *dst, int dst_size, bool repeat);
+
+#endif // PADDING_H
\ No newline at end of file
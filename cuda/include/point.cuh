@@ -39,4 +39,68 @@ __host__ __device__ __forceinline__ point point_pow(point p, int exponent) {
     return result;
 }
 
+typedef struct {
+    qm31 x;
+    qm31 y;
+} secure_point;
+
+/*##### Secure point ##### */
+
+__host__ __device__ __forceinline__ secure_point secure_one() {
+    return {{{1, 0}, {0, 0}}, {{0, 0}, {0, 0}}};
+}
+
+__host__ __device__ __forceinline__ secure_point point_mul(secure_point &p1, secure_point &p2) {
+    return {
+        sub(mul(p1.x, p2.x), mul(p1.y, p2.y)),
+        add(mul(p1.x, p2.y), mul(p1.y, p2.x)),
+    };
+}
+
+__host__ __device__ __forceinline__ secure_point point_square(secure_point &p1) {
+    return point_mul(p1, p1);
+}
+
+__host__ __device__ __forceinline__ secure_point point_pow(secure_point p, int exponent) {
+    secure_point result = secure_one();
+    while (exponent > 0) {
+        if (exponent & 1) {
+            result = point_mul(p, result);
+        }
+        p = point_square(p);
+        exponent >>= 1;
+    }
+    return result;
+}
+
+extern "C"
+void add_points(m31 *lhs_x, m31 *lhs_y, m31 *rhs_x, m31 *rhs_y, m31 *dst_x, m31 *dst_y, int size);
+
+extern "C"
+void double_points(m31 *x, m31 *y, m31 *dst_x, m31 *dst_y, int size);
+
+extern "C"
+void mul_points(m31 *x, m31 *y, uint32_t scalar, m31 *dst_x, m31 *dst_y, int size);
+
+extern "C"
+void add_secure_points(qm31 *lhs_x, qm31 *lhs_y, qm31 *rhs_x, qm31 *rhs_y, qm31 *dst_x, qm31 *dst_y, int size);
+
+extern "C"
+void double_secure_points(qm31 *x, qm31 *y, qm31 *dst_x, qm31 *dst_y, int size);
+
+extern "C"
+void mul_secure_points(qm31 *x, qm31 *y, uint32_t scalar, qm31 *dst_x, qm31 *dst_y, int size);
+
+extern "C"
+void coset_points(point initial, point step, m31 *dst_x, m31 *dst_y, int size);
+
+extern "C"
+void coset_vanishing(point half_coset_initial, point half_coset_step, int log_size, point shift, int log_coset_size, m31 *dst);
+
+extern "C"
+void pair_vanishing_denominators(point half_coset_initial, point half_coset_step, int log_size, qm31 sample_x, qm31 sample_y, m31 *dst_a, m31 *dst_b);
+
+extern "C"
+void pair_vanishing_denominator_inverses(point half_coset_initial, point half_coset_step, int log_size, qm31 sample_x, qm31 sample_y, m31 *dst_a, m31 *dst_b);
+
 #endif // POINT_H
\ No newline at end of file
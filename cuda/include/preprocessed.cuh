@@ -0,0 +1,15 @@
+#ifndef PREPROCESSED_H
+#define PREPROCESSED_H
+
+#include "fields.cuh"
+
+extern "C"
+void tile_base_field(m31 *pattern, int pattern_size, m31 *dst, int size);
+
+extern "C"
+void gen_step_selector(m31 *dst, int log_size, int step, int offset);
+
+extern "C"
+void gen_row_bits(m31 *dst, int log_size, int shift, uint32_t mask);
+
+#endif // PREPROCESSED_H
\ No newline at end of file
@@ -0,0 +1,12 @@
+#ifndef QUERY_H
+#define QUERY_H
+
+#include "fields.cuh"
+
+extern "C"
+void gather_query_values(m31 **columns, int n_columns, uint32_t *positions, int n_positions, m31 *result);
+
+extern "C"
+void gather_authentication_paths(uint32_t **layers, int n_layers, uint32_t *positions, int n_positions, uint32_t *result);
+
+#endif // QUERY_H
\ No newline at end of file
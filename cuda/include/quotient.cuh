@@ -0,0 +1,15 @@
+#ifndef QUOTIENT_H
+#define QUOTIENT_H
+
+#include "fields.cuh"
+
+extern "C"
+void accumulate_quotients(
+    m31 *half_coset_x, m31 *half_coset_y, int log_size,
+    m31 **columns, int n_columns,
+    qm31 *sample_points_x, qm31 *sample_points_y, qm31 *batch_random_coeffs, int *batch_sizes, int n_batches,
+    int *column_indices, qm31 *line_coeffs, int n_samples,
+    m31 **result
+);
+
+#endif // QUOTIENT_H
\ No newline at end of file
@@ -0,0 +1,87 @@
+#ifndef REDUCE_H
+#define REDUCE_H
+
+#include <cooperative_groups.h>
+
+#include "fields.cuh"
+
+// Sums are computed in two passes: up to SUM_MAX_BLOCKS blocks of SUM_BLOCK_DIM threads each
+// store a partial sum, which a single block then adds up with sum_partials_kernel.
+const int SUM_BLOCK_DIM = 256;
+const int SUM_MAX_BLOCKS = 1024;
+
+__host__ __forceinline__ int sum_num_blocks(int size) {
+    int num_blocks = min((size + SUM_BLOCK_DIM - 1) / SUM_BLOCK_DIM, SUM_MAX_BLOCKS);
+    return max(num_blocks, 1);
+}
+
+template<typename T>
+__device__ __forceinline__ void store_block_sum(T sum, T *partials) {
+    // Adds up the sums of the threads of the block and stores the result in position
+    // blockIdx.x of partials.
+    __shared__ T s_sums[SUM_BLOCK_DIM];
+
+    int idx = threadIdx.x;
+    s_sums[idx] = sum;
+    __syncthreads();
+
+    for (int half = blockDim.x >> 1; half > 0; half >>= 1) {
+        if (idx < half) {
+            s_sums[idx] = add(s_sums[idx], s_sums[idx + half]);
+        }
+        __syncthreads();
+    }
+
+    if (idx == 0) {
+        partials[blockIdx.x] = s_sums[0];
+    }
+}
+
+template<typename T>
+__global__ void sum_partials_kernel(T *partials, int size) {
+    // Leaves the sum of the size partials in position 0. Launched with a single block.
+    T sum = T{};
+    for (int i = threadIdx.x; i < size; i += blockDim.x) {
+        sum = add(sum, partials[i]);
+    }
+    store_block_sum(sum, partials);
+}
+
+template<typename T>
+__device__ __forceinline__ void grid_sum(T sum, T *partials) {
+    // Leaves the sum over all the threads of the grid in position 0 of partials, without a second
+    // launch. The kernel must be launched with cudaLaunchCooperativeKernel, on at most
+    // cooperative_sum_num_blocks blocks of SUM_BLOCK_DIM threads, and partials must hold one
+    // element per block.
+    store_block_sum(sum, partials);
+    cooperative_groups::this_grid().sync();
+
+    if (blockIdx.x == 0) {
+        T block_sum = T{};
+        for (int i = threadIdx.x; i < gridDim.x; i += blockDim.x) {
+            block_sum = add(block_sum, partials[i]);
+        }
+        store_block_sum(block_sum, partials);
+    }
+}
+
+template<typename K>
+int cooperative_sum_num_blocks(K kernel, int size) {
+    // Blocks of SUM_BLOCK_DIM threads to launch kernel cooperatively with on size elements: as
+    // many as sum_num_blocks, but no more than can be resident at once. 0 if the device doesn't
+    // support cooperative launches, in which case the two pass sum must be used.
+    int device;
+    cudaGetDevice(&device);
+    int supported;
+    cudaDeviceGetAttribute(&supported, cudaDevAttrCooperativeLaunch, device);
+    if (!supported) {
+        return 0;
+    }
+    int sm_count;
+    cudaDeviceGetAttribute(&sm_count, cudaDevAttrMultiProcessorCount, device);
+    int blocks_per_sm;
+    cudaOccupancyMaxActiveBlocksPerMultiprocessor(&blocks_per_sm, kernel, SUM_BLOCK_DIM, 0);
+    return min(sum_num_blocks(size), blocks_per_sm * sm_count);
+}
+
+#endif // REDUCE_H
@@ -0,0 +1,84 @@
+#ifndef ROW_CONSTRAINTS_H
+#define ROW_CONSTRAINTS_H
+
+#include "fields.cuh"
+#include "mask.cuh"
+#include "utils.cuh"
+
+// Building block for handwritten components: each thread loads the masked values of one row
+// into registers and hands them to `Evaluator::evaluate`, which returns the random linear
+// combination of the component's constraints at that row.
+//
+// An evaluator is a struct with a static device function:
+//
+//     struct my_evaluator {
+//         static __device__ __forceinline__ qm31 evaluate(const m31 *mask, const qm31 *random_coeff_powers);
+//     };
+//
+// where `mask[i]` is the value of column `mask_items[i].column` at row `row + mask_items[i].offset`
+// of the trace. Components expose a launcher with DEFINE_ROW_CONSTRAINTS.
+
+typedef struct {
+    int column;
+    int offset;
+} mask_item;
+
+template<int N_MASK, typename Evaluator>
+__global__ void evaluate_row_constraints_kernel(m31 **columns, mask_item *mask_items, int trace_log_size, int eval_log_size, qm31 *random_coeff_powers, secure_column accumulator) {
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (row < (1 << eval_log_size)) {
+        m31 mask[N_MASK];
+        #pragma unroll
+        for (int i = 0; i < N_MASK; i++) {
+            mask_item item = mask_items[i];
+            int index = item.offset == 0
+                ? row
+                : offset_bit_reversed_circle_domain_index(row, trace_log_size, eval_log_size, item.offset);
+            mask[i] = columns[item.column][index];
+        }
+
+        qm31 value = Evaluator::evaluate(mask, random_coeff_powers);
+        secure_column_set(accumulator, row, add(secure_column_at(accumulator, row), value));
+    }
+}
+
+template<int N_MASK, typename Evaluator>
+void evaluate_row_constraints(m31 **columns, int n_columns, mask_item *mask_items, int trace_log_size, int eval_log_size, qm31 *random_coeff_powers, int n_constraints, m31 **accumulator) {
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * n_columns);
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    mask_item *device_mask_items;
+    cudaMalloc((void**)&device_mask_items, sizeof(mask_item) * N_MASK);
+    cudaMemcpy(device_mask_items, mask_items, sizeof(mask_item) * N_MASK, cudaMemcpyHostToDevice);
+
+    qm31 *device_random_coeff_powers;
+    cudaMalloc((void**)&device_random_coeff_powers, sizeof(qm31) * n_constraints);
+    cudaMemcpy(device_random_coeff_powers, random_coeff_powers, sizeof(qm31) * n_constraints, cudaMemcpyHostToDevice);
+
+    int size = 1 << eval_log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    evaluate_row_constraints_kernel<N_MASK, Evaluator><<<num_blocks, block_dim>>>(
+        device_columns, device_mask_items, trace_log_size, eval_log_size, device_random_coeff_powers, make_secure_column(accumulator));
+    cudaDeviceSynchronize();
+
+    cudaFree(device_columns);
+    cudaFree(device_mask_items);
+    cudaFree(device_random_coeff_powers);
+}
+
+// Defines `extern "C" int NAME(...)`, the launcher passed to the Rust side. Returns a nonzero
+// value without launching when the mask does not have N_MASK items.
+#define DEFINE_ROW_CONSTRAINTS(NAME, N_MASK, EVALUATOR) \
+    extern "C" \
+    int NAME(m31 **columns, int n_columns, mask_item *mask_items, int n_mask_items, int trace_log_size, int eval_log_size, qm31 *random_coeff_powers, int n_constraints, m31 **accumulator) { \
+        if (n_mask_items != N_MASK) { \
+            return 1; \
+        } \
+        evaluate_row_constraints<N_MASK, EVALUATOR>(columns, n_columns, mask_items, trace_log_size, eval_log_size, random_coeff_powers, n_constraints, accumulator); \
+        return 0; \
+    }
+
+#endif // ROW_CONSTRAINTS_H
\ No newline at end of file
@@ -0,0 +1,18 @@
+#ifndef SCAN_H
+#define SCAN_H
+
+#include "fields.cuh"
+
+extern "C"
+void cumulative_sum_base_field(m31 *values, int size);
+
+extern "C"
+void cumulative_sum_secure_field(qm31 *values, int size);
+
+extern "C"
+void cumulative_product_base_field(m31 *values, int size);
+
+extern "C"
+void cumulative_product_secure_field(qm31 *values, int size);
+
+#endif // SCAN_H
@@ -0,0 +1,15 @@
+#ifndef SCRATCH_H
+#define SCRATCH_H
+
+#include <stddef.h>
+
+extern "C"
+void *scratch_buffer(const char *name, int log_size, size_t bytes);
+
+extern "C"
+void clear_scratch_buffers();
+
+extern "C"
+size_t scratch_buffers_size();
+
+#endif // SCRATCH_H
\ No newline at end of file
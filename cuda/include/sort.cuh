@@ -0,0 +1,15 @@
+#ifndef SORT_H
+#define SORT_H
+
+#include "fields.cuh"
+
+extern "C"
+void sort_base_field(m31 *values, int size);
+
+extern "C"
+void sort_base_field_by_key(m31 *keys, m31 *values, int size);
+
+extern "C"
+int unique_base_field(m31 *values, int size, m31 *dst);
+
+#endif // SORT_H
@@ -0,0 +1,9 @@
+#ifndef SPARSE_H
+#define SPARSE_H
+
+#include "fields.cuh"
+
+extern "C"
+void densify(uint32_t *indices, m31 *values, int n_values, m31 *dst, int size);
+
+#endif // SPARSE_H
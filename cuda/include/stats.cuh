@@ -0,0 +1,19 @@
+#ifndef STATS_H
+#define STATS_H
+
+#include "fields.cuh"
+
+typedef struct {
+    m31 min;
+    m31 max;
+    unsigned long long n_zeros;
+    unsigned long long n_out_of_range;
+} column_stats;
+
+extern "C"
+void base_field_column_stats(m31 *column, int size, m31 range_start, m31 range_end, column_stats *result);
+
+extern "C"
+int last_nonzero_index(m31 *column, int size);
+
+#endif // STATS_H
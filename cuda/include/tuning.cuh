@@ -0,0 +1,31 @@
+#ifndef TUNING_H
+#define TUNING_H
+
+typedef struct {
+    int fft_block_dim;
+    // Butterflies each thread of the FFT layers computes, one of 1, 2 or 4.
+    int fft_butterflies_per_thread;
+    int merkle_block_dim;
+    int fold_block_dim;
+} kernel_tuning;
+
+// Devices with fewer SMs, e.g. the 1g and 2g MIG slices of an A100 or H100, get smaller blocks.
+const int SMALL_DEVICE_SM_COUNT = 40;
+
+// Launch parameters of the hot kernels for the current device, selected from its compute
+// capability and SM count on its first use unless set with set_kernel_tuning while it was current.
+kernel_tuning tuning();
+
+extern "C"
+kernel_tuning kernel_tuning_for_architecture(int major, int minor);
+
+extern "C"
+kernel_tuning kernel_tuning_for_device(int major, int minor, int sm_count);
+
+extern "C"
+kernel_tuning get_kernel_tuning();
+
+extern "C"
+void set_kernel_tuning(kernel_tuning value);
+
+#endif // TUNING_H
@@ -4,23 +4,199 @@
 #include "fields.cuh"
 
 __device__ __forceinline__ uint32_t bit_reverse(uint32_t n, int bits) {
+    // Shifting by 32 is undefined, so a single bit reversed index is handled separately.
+    if (bits == 0) {
+        return 0;
+    }
     unsigned int reversed_n = __brev(n);
     return reversed_n >> (32 - bits);
 }
 
+__device__ __forceinline__ uint32_t bit_reversed_circle_domain_index_to_coset_index(uint32_t index, int log_size) {
+    // Maps a position in a bit reversed circle domain evaluation to the index of
+    // the corresponding point in the canonic coset (i.e. the trace row).
+    uint32_t circle_domain_index = bit_reverse(index, log_size);
+    uint32_t half_size = 1 << (log_size - 1);
+    if (circle_domain_index < half_size) {
+        return circle_domain_index << 1;
+    } else {
+        return (half_size << 2) - (circle_domain_index << 1) - 1;
+    }
+}
+
+__device__ __forceinline__ m31 get_twiddle(const m31 *__restrict__ twiddles, int index) {
+    // Circle twiddles (y coordinates) derived from the first line layer of twiddles (x coordinates).
+    // Twiddles are only read, and by many threads, so loads go through the read-only cache.
+    int k = index >> 2;
+    if (index % 4 == 0) {
+        return __ldg(&twiddles[2 * k + 1]);
+    } else if (index % 4 == 1) {
+        return neg(__ldg(&twiddles[2 * k + 1]));
+    } else if (index % 4 == 2) {
+        return neg(__ldg(&twiddles[2 * k]));
+    } else {
+        return __ldg(&twiddles[2 * k]);
+    }
+}
+
+typedef struct {
+    m31 *columns[4];
+} secure_column;
+
+__host__ __forceinline__ secure_column make_secure_column(m31 **columns) {
+    // columns: host array with the device pointers of the 4 coordinates.
+    return {{columns[0], columns[1], columns[2], columns[3]}};
+}
+
+__device__ __forceinline__ qm31 secure_column_at(secure_column column, int index) {
+    return {
+        {column.columns[0][index], column.columns[1][index]},
+        {column.columns[2][index], column.columns[3][index]}
+    };
+}
+
+__device__ __forceinline__ void secure_column_pair_at(secure_column column, int index, qm31 &even, qm31 &odd) {
+    // Values at positions 2 * index and 2 * index + 1, read with one 64 bit load per coordinate.
+    // The coordinates must be 8 byte aligned: columns are allocated 256 byte aligned, and
+    // fold_line_range only takes ranges of them starting at an even position.
+    uint2 a = ((uint2*) column.columns[0])[index];
+    uint2 b = ((uint2*) column.columns[1])[index];
+    uint2 c = ((uint2*) column.columns[2])[index];
+    uint2 d = ((uint2*) column.columns[3])[index];
+    even = {{a.x, b.x}, {c.x, d.x}};
+    odd = {{a.y, b.y}, {c.y, d.y}};
+}
+
+__device__ __forceinline__ void secure_column_set(secure_column column, int index, qm31 value) {
+    column.columns[0][index] = value.a.a;
+    column.columns[1][index] = value.a.b;
+    column.columns[2][index] = value.b.a;
+    column.columns[3][index] = value.b.b;
+}
+
 __host__ __forceinline__ int log_2(int value) {
     return __builtin_ctz(value);
 }
 
+extern "C"
+void set_managed_allocations(bool);
+
+extern "C"
+int reserve_memory_pool(size_t);
+
+extern "C"
+int trim_memory_pool(size_t);
+
+extern "C"
+int reserve_arena(size_t);
+
+extern "C"
+void reset_arena();
+
+extern "C"
+void release_arena();
+
+extern "C"
+size_t arena_used();
+
 extern "C"
 void copy_uint32_t_vec_from_device_to_host(uint32_t *, uint32_t*, int);
 
+extern "C"
+int copy_uint32_t_vec_from_device_to_host_checked(uint32_t *, uint32_t*, int);
+
+extern "C"
+void copy_uint32_t_vec_from_device_to_host_async(uint32_t *, uint32_t*, int, cudaStream_t);
+
+extern "C"
+void copy_uint32_t_vec_from_host_to_device_async(uint32_t *, uint32_t*, int, cudaStream_t);
+
+extern "C"
+cudaStream_t create_stream();
+
+extern "C"
+cudaStream_t create_non_blocking_stream();
+
+extern "C"
+void stream_wait_default_stream(cudaStream_t);
+
+extern "C"
+int register_host_memory(void *, size_t);
+
+extern "C"
+void unregister_host_memory(void *);
+
+extern "C"
+cudaEvent_t create_event();
+
+extern "C"
+void record_event(cudaEvent_t, cudaStream_t);
+
+extern "C"
+void stream_wait_event(cudaStream_t, cudaEvent_t);
+
+extern "C"
+void synchronize_event(cudaEvent_t);
+
+extern "C"
+bool query_event(cudaEvent_t);
+
+extern "C"
+void destroy_event(cudaEvent_t);
+
+extern "C"
+void synchronize_stream(cudaStream_t);
+
+extern "C"
+void launch_host_callback(cudaStream_t, void (*)(void *), void *);
+
+extern "C"
+void destroy_stream(cudaStream_t);
+
 extern "C"
 uint32_t* copy_uint32_t_vec_from_host_to_device(uint32_t*, int);
 
+extern "C"
+void copy_uint32_t_vec_from_host_to_existing_device(uint32_t *, uint32_t*, int);
+
 extern "C"
 void copy_uint32_t_vec_from_device_to_device(uint32_t *, uint32_t*, int);
 
+extern "C"
+void retain_memory_tracking();
+
+extern "C"
+void release_memory_tracking();
+
+extern "C"
+size_t device_memory_in_use();
+
+extern "C"
+uint64_t allocation_mark();
+
+extern "C"
+int free_allocations_since(uint64_t);
+
+extern "C"
+int reset_device();
+
+extern "C"
+int recover_device();
+
+extern "C"
+int clear_last_error();
+
+// Number of times the context was reset, for state kept across calls, in C++ and Rust, to tell
+// whether it belongs to the current context.
+extern "C"
+int context_generation();
+
+extern "C"
+int begin_memory_peak();
+
+extern "C"
+size_t end_memory_peak(int);
+
 extern "C"
 uint32_t* cuda_malloc_uint32_t(int);
 
@@ -30,5 +206,64 @@ uint32_t* cuda_alloc_zeroes_uint32_t(int);
 extern "C"
 void free_uint32_t_vec(uint32_t*);
 
+extern "C"
+void free_uint32_t_vecs(uint32_t**, int);
+
+extern "C"
+void free_uint32_t_vec_after_events(uint32_t*, cudaEvent_t*, int);
+
+extern "C"
+uint32_t* cuda_malloc_host_uint32_t(int);
+
+extern "C"
+void free_host_uint32_t_vec(uint32_t*);
+
+extern "C"
+int probe_cuda_device(int *device_count, int *driver_version, int *runtime_version);
+
+typedef struct {
+    char name[256];
+    size_t total_memory;
+    size_t free_memory;
+    int major;
+    int minor;
+    int sm_count;
+    unsigned char uuid[16];
+    char pci_bus_id[32];
+} device_properties;
+
+extern "C"
+int get_device_count(int *device_count);
+
+extern "C"
+int get_device_properties(int device, device_properties *properties);
+
+extern "C"
+int get_current_device();
+
+extern "C"
+int set_current_device(int device);
+
+extern "C"
+void fill_base_field(m31 *dst, m31 value, int size);
+
+extern "C"
+void fill_secure_field(qm31 *dst, qm31 value, int size);
+
+extern "C"
+void fill_random_base_field(m31 *dst, int size, uint64_t seed);
+
+extern "C"
+void fill_powers_secure_field(qm31 *dst, qm31 x, int size);
+
+extern "C"
+void embed_base_field(m31 *src, qm31 *dst, int size);
+
+extern "C"
+void embed_base_field_into_secure_column(m31 *src, m31 **dst, int size);
+
+extern "C"
+bool uint32_t_vec_equal(uint32_t *a, uint32_t *b, int size);
+
 #endif // UTILS_H
 
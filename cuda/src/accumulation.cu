@@ -0,0 +1,40 @@
+#include "../include/accumulation.cuh"
+#include "../include/utils.cuh"
+
+__global__ void accumulate_kernel(secure_column column, secure_column other, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        secure_column_set(column, idx, add(secure_column_at(column, idx), secure_column_at(other, idx)));
+    }
+}
+
+void accumulate(m31 **column, m31 **other, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    accumulate_kernel<<<num_blocks, block_dim>>>(make_secure_column(column), make_secure_column(other), size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void accumulate_sparse_kernel(secure_column column, uint32_t *indices, m31 *values, int n_values, qm31 coefficient) {
+    // Rows are distinct, so each value of column is updated by at most one thread.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_values) {
+        uint32_t row = indices[idx];
+        qm31 term = mul(coefficient, values[idx]);
+        secure_column_set(column, row, add(secure_column_at(column, row), term));
+    }
+}
+
+void accumulate_sparse(m31 **column, uint32_t *indices, m31 *values, int n_values, qm31 coefficient) {
+    // Adds coefficient times the sparse column of values at rows indices to column, only
+    // touching the nonzero rows.
+    if (n_values == 0) {
+        return;
+    }
+    int block_dim = 256;
+    int num_blocks = (n_values + block_dim - 1) / block_dim;
+    accumulate_sparse_kernel<<<num_blocks, block_dim>>>(make_secure_column(column), indices, values, n_values, coefficient);
+    cudaDeviceSynchronize();
+}
@@ -154,7 +154,23 @@ __global__ void batch_inverse_secure_field_kernel(qm31 *from, qm31 *dst, int siz
     batch_inverse(from, dst, size, log_size, s_from_qm31, s_inner_trees_qm31);
 }
 
+template<typename T>
+__global__ void inverse_kernel(T *from, T *dst, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = inv(from[idx]);
+    }
+}
+
 void batch_inverse_base_field(m31 *from, m31 *dst, int size) {
+    // Columns smaller than the tree handled by a block are inverted value by value.
+    if (size < 512) {
+        inverse_kernel<<<1, size>>>(from, dst, size);
+        cudaDeviceSynchronize();
+        return;
+    }
+
     int log_size = log_2(size);
     int block_size = 256;
     int half_size = size >> 1;
@@ -166,6 +182,12 @@ void batch_inverse_base_field(m31 *from, m31 *dst, int size) {
 }
 
 void batch_inverse_secure_field(qm31 *from, qm31 *dst, int size) {
+    if (size < 1024) {
+        inverse_kernel<<<1, size>>>(from, dst, size);
+        cudaDeviceSynchronize();
+        return;
+    }
+
     int log_size = log_2(size);
     int block_size = 512;
     int half_size = size >> 1;
@@ -13,19 +13,81 @@ __global__ void bit_reverse_generic(T *array, int size, int bits) {
     }
 }
 
+__device__ __forceinline__ uint32_t uint4_at(uint4 v, int i) {
+    return i == 0 ? v.x : i == 1 ? v.y : i == 2 ? v.z : v.w;
+}
+
+__device__ __forceinline__ uint4 reversed_transpose_row(uint4 *tile, int row) {
+    // Row row of the transpose of the 4x4 tile, with both indices bit reversed on 2 bits.
+    const int rev[4] = {0, 2, 1, 3};
+    int column = rev[row];
+    return make_uint4(uint4_at(tile[0], column), uint4_at(tile[2], column), uint4_at(tile[1], column), uint4_at(tile[3], column));
+}
+
+__global__ void bit_reverse_base_field_tiles_kernel(uint4 *array, int n_tiles, int bits) {
+    // Value i = (h << (bits - 2)) | (m << 2) | j goes to (rev(j) << (bits - 2)) | (rev(m) << 2) | rev(h),
+    // so the tile made of the 4 rows h of 4 consecutive values at m is transposed into the tile at
+    // rev(m), with both indices reversed. Each thread swaps one pair of tiles, moving 128 bits per
+    // load and store.
+    int m = blockIdx.x * blockDim.x + threadIdx.x;
+    if (m >= n_tiles) {
+        return;
+    }
+    int rev_m = bit_reverse(m, bits - 4);
+    if (rev_m < m) {
+        return;
+    }
+
+    uint4 tile[4];
+    uint4 rev_tile[4];
+    #pragma unroll
+    for (int h = 0; h < 4; h++) {
+        tile[h] = array[h * n_tiles + m];
+        rev_tile[h] = array[h * n_tiles + rev_m];
+    }
+    #pragma unroll
+    for (int h = 0; h < 4; h++) {
+        array[h * n_tiles + rev_m] = reversed_transpose_row(tile, h);
+        array[h * n_tiles + m] = reversed_transpose_row(rev_tile, h);
+    }
+}
+
 void bit_reverse_base_field(m31 *array, int size) {
+    // Accessed as uint4 from 16 values on, so array must then be 16 byte aligned: columns are
+    // allocated 256 byte aligned, and bit_reverse_range only takes ranges of them starting at a
+    // multiple of 4 values.
+    int bits = log_2(size);
+    if (bits < 4) {
+        bit_reverse_generic<<<1, size>>>(array, size, bits);
+    } else {
+        int n_tiles = size >> 4;
+        int block_size = 256;
+        int num_blocks = (n_tiles + block_size - 1) / block_size;
+        bit_reverse_base_field_tiles_kernel<<<num_blocks, block_size>>>((uint4*) array, n_tiles, bits);
+    }
+    cudaDeviceSynchronize();
+}
+
+
+void bit_reverse_secure_field(qm31 *array, int size) {
+    // Values are swapped as a whole with 128 bit loads and stores.
     int bits = log_2(size);
     int block_size = 1024;
     int num_blocks = (size + block_size - 1) / block_size;
-    bit_reverse_generic<<<num_blocks, block_size>>>(array, size, bits);
+    bit_reverse_generic<<<num_blocks, block_size>>>((uint4*) array, size, bits);
     cudaDeviceSynchronize();
 }
 
+// The 8 words of a hash, swapped as a whole.
+typedef struct {
+    uint4 low;
+    uint4 high;
+} hash_words;
 
-void bit_reverse_secure_field(qm31 *array, int size) {
+void bit_reverse_blake2s_hash(uint32_t *array, int size) {
     int bits = log_2(size);
     int block_size = 1024;
     int num_blocks = (size + block_size - 1) / block_size;
-    bit_reverse_generic<<<num_blocks, block_size>>>(array, size, bits);
+    bit_reverse_generic<<<num_blocks, block_size>>>((hash_words*) array, size, bits);
     cudaDeviceSynchronize();
-}
\ No newline at end of file
+}
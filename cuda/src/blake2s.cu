@@ -0,0 +1,286 @@
+#include "../include/blake2s.cuh"
+#include "../include/tuning.cuh"
+#include "../include/utils.cuh"
+
+__constant__ uint32_t BLAKE2S_IV[8] = {
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A,
+    0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+};
+
+__constant__ unsigned char BLAKE2S_SIGMA[10][16] = {
+    { 0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15},
+    {14, 10,  4,  8,  9, 15, 13,  6,  1, 12,  0,  2, 11,  7,  5,  3},
+    {11,  8, 12,  0,  5,  2, 15, 13, 10, 14,  3,  6,  7,  1,  9,  4},
+    { 7,  9,  3,  1, 13, 12, 11, 14,  2,  6,  5, 10,  4,  0, 15,  8},
+    { 9,  0,  5,  7,  2,  4, 10, 15, 14,  1, 11, 12,  6,  8,  3, 13},
+    { 2, 12,  6, 10,  0, 11,  8,  3,  4, 13,  7,  5, 15, 14,  1,  9},
+    {12,  5,  1, 15, 14, 13,  4, 10,  0,  7,  6,  3,  9,  2,  8, 11},
+    {13, 11,  7, 14, 12,  1,  3,  9,  5,  0, 15,  4,  8,  6,  2, 10},
+    { 6, 15, 14,  9, 11,  3,  0,  8, 12,  2, 13,  7,  1,  4, 10,  5},
+    {10,  2,  8,  4,  7,  6,  1,  5, 15, 11,  9, 14,  3, 12, 13,  0},
+};
+
+__device__ __forceinline__ uint32_t rotr(uint32_t x, int n) {
+    return (x >> n) | (x << (32 - n));
+}
+
+__device__ __forceinline__ void g(uint32_t *v, int a, int b, int c, int d, uint32_t x, uint32_t y) {
+    v[a] = v[a] + v[b] + x;
+    v[d] = rotr(v[d] ^ v[a], 16);
+    v[c] = v[c] + v[d];
+    v[b] = rotr(v[b] ^ v[c], 12);
+    v[a] = v[a] + v[b] + y;
+    v[d] = rotr(v[d] ^ v[a], 8);
+    v[c] = v[c] + v[d];
+    v[b] = rotr(v[b] ^ v[c], 7);
+}
+
+__device__ void blake2s_compress(uint32_t *h, uint32_t *m, uint32_t bytes_hashed, bool is_last) {
+    uint32_t v[16];
+    for (int i = 0; i < 8; i++) {
+        v[i] = h[i];
+        v[i + 8] = BLAKE2S_IV[i];
+    }
+    // Messages are shorter than 2^32 bytes, so the high word of the counter is zero.
+    v[12] ^= bytes_hashed;
+    if (is_last) {
+        v[14] = ~v[14];
+    }
+
+    for (int round = 0; round < 10; round++) {
+        const unsigned char *s = BLAKE2S_SIGMA[round];
+        g(v, 0, 4,  8, 12, m[s[0]],  m[s[1]]);
+        g(v, 1, 5,  9, 13, m[s[2]],  m[s[3]]);
+        g(v, 2, 6, 10, 14, m[s[4]],  m[s[5]]);
+        g(v, 3, 7, 11, 15, m[s[6]],  m[s[7]]);
+        g(v, 0, 5, 10, 15, m[s[8]],  m[s[9]]);
+        g(v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(v, 2, 7,  8, 13, m[s[12]], m[s[13]]);
+        g(v, 3, 4,  9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for (int i = 0; i < 8; i++) {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+__global__ void commit_on_layer_kernel(uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst, int size) {
+    // Computes the hash of node idx of the layer: Blake2s-256 of the hashes of
+    // its two children (if any) followed by the values of the columns at idx.
+    // Hashes are stored as 8 consecutive little endian words.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        int n_prefix_words = prev_layer != nullptr ? 16 : 0;
+        int n_words = n_prefix_words + n_columns;
+        int n_blocks = max((n_words + 15) / 16, 1);
+
+        uint32_t h[8];
+        for (int i = 0; i < 8; i++) {
+            h[i] = BLAKE2S_IV[i];
+        }
+        // Parameter block: 32 bytes digest, no key, fanout and depth 1.
+        h[0] ^= 0x01010020;
+
+        uint32_t m[16];
+        for (int block = 0; block < n_blocks; block++) {
+            for (int j = 0; j < 16; j++) {
+                int word = block * 16 + j;
+                if (word < n_prefix_words) {
+                    // Both children are stored consecutively.
+                    m[j] = prev_layer[16 * idx + word];
+                } else if (word < n_words) {
+                    m[j] = columns[word - n_prefix_words][idx];
+                } else {
+                    m[j] = 0;
+                }
+            }
+            bool is_last = block == n_blocks - 1;
+            uint32_t bytes_hashed = is_last ? 4 * n_words : 64 * (block + 1);
+            blake2s_compress(h, m, bytes_hashed, is_last);
+        }
+
+        for (int i = 0; i < 8; i++) {
+            dst[8 * idx + i] = h[i];
+        }
+    }
+}
+
+void commit_on_layer(int log_size, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst) {
+    // prev_layer: hashes of the previous (larger) layer, or NULL for the first layer.
+    //    columns: host array with the device pointers of the columns of this layer.
+    commit_on_layer_batch(log_size, 1, prev_layer, columns, n_columns, dst);
+}
+
+void commit_on_layer_on_stream(int log_size, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst, cudaStream_t stream) {
+    // Same as commit_on_layer, queued on stream without waiting for it. The column pointers are
+    // staged before returning, so columns can be released right away.
+    commit_on_layer_batch_on_stream(log_size, 1, prev_layer, columns, n_columns, dst, stream);
+}
+
+static void launch_commit_on_layer(int size, uint32_t *prev_layer, m31 **device_columns, int n_columns, uint32_t *dst, cudaStream_t stream) {
+    int block_dim = tuning().merkle_block_dim;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    commit_on_layer_kernel<<<num_blocks, block_dim, 0, stream>>>(prev_layer, device_columns, n_columns, dst, size);
+}
+
+void commit_on_layer_batch(int log_size, int n_instances, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst) {
+    // Hashes the layers of n_instances trees of the same shape at once. Layers and columns hold
+    // the nodes and values of each instance one after the other, so node i of an instance is
+    // node i of the batch past the previous instances and its children are still at 2i and
+    // 2i + 1 in the previous layer.
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * max(n_columns, 1));
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    launch_commit_on_layer(n_instances << log_size, prev_layer, device_columns, n_columns, dst, 0);
+    cudaDeviceSynchronize();
+
+    cudaFree(device_columns);
+}
+
+void commit_on_layer_batch_on_stream(int log_size, int n_instances, uint32_t *prev_layer, m31 **columns, int n_columns, uint32_t *dst, cudaStream_t stream) {
+    m31 **device_columns;
+    cudaMallocAsync((void**)&device_columns, sizeof(m31*) * max(n_columns, 1), stream);
+    cudaMemcpyAsync(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice, stream);
+
+    launch_commit_on_layer(n_instances << log_size, prev_layer, device_columns, n_columns, dst, stream);
+
+    cudaFreeAsync(device_columns, stream);
+}
+
+__device__ void blake2s_hash_words(const uint32_t *words, int n_words, uint32_t *h) {
+    // Blake2s-256 of a message of n_words consecutive words.
+    int n_blocks = max((n_words + 15) / 16, 1);
+    for (int i = 0; i < 8; i++) {
+        h[i] = BLAKE2S_IV[i];
+    }
+    h[0] ^= 0x01010020;
+
+    uint32_t m[16];
+    for (int block = 0; block < n_blocks; block++) {
+        for (int j = 0; j < 16; j++) {
+            int word = block * 16 + j;
+            m[j] = word < n_words ? words[word] : 0;
+        }
+        bool is_last = block == n_blocks - 1;
+        uint32_t bytes_hashed = is_last ? 4 * n_words : 64 * (block + 1);
+        blake2s_compress(h, m, bytes_hashed, is_last);
+    }
+}
+
+__global__ void verify_merkle_paths_kernel(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, uint32_t *roots, uint32_t *root_indices, int n_paths, int *valid) {
+    // Thread idx hashes the leaf of path idx and climbs up its siblings, then compares the
+    // computed root with the expected one. Paths only have columns at the leaves.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_paths) {
+        uint32_t node[16];
+        uint32_t h[8];
+        blake2s_hash_words(&leaf_values[n_columns * idx], n_columns, h);
+
+        uint32_t position = positions[idx];
+        uint32_t *path = &paths[8 * depth * idx];
+        for (int level = 0; level < depth; level++) {
+            // The current node goes first when it is a left child.
+            int own = (position & 1) * 8;
+            int sibling = 8 - own;
+            for (int i = 0; i < 8; i++) {
+                node[own + i] = h[i];
+                node[sibling + i] = path[8 * level + i];
+            }
+            blake2s_hash_words(node, 16, h);
+            position >>= 1;
+        }
+
+        uint32_t *root = &roots[8 * root_indices[idx]];
+        bool is_valid = true;
+        for (int i = 0; i < 8; i++) {
+            is_valid &= h[i] == root[i];
+        }
+        valid[idx] = is_valid;
+    }
+}
+
+__global__ void merkle_path_nodes_kernel(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, int n_paths, uint32_t *nodes) {
+    // Thread idx climbs path idx like verify_merkle_paths_kernel, storing the hash of each node
+    // on the way, from the leaf to the root.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_paths) {
+        uint32_t node[16];
+        uint32_t *path_nodes = &nodes[8 * (depth + 1) * idx];
+        blake2s_hash_words(&leaf_values[n_columns * idx], n_columns, path_nodes);
+
+        uint32_t position = positions[idx];
+        uint32_t *path = &paths[8 * depth * idx];
+        for (int level = 0; level < depth; level++) {
+            int own = (position & 1) * 8;
+            int sibling = 8 - own;
+            for (int i = 0; i < 8; i++) {
+                node[own + i] = path_nodes[8 * level + i];
+                node[sibling + i] = path[8 * level + i];
+            }
+            blake2s_hash_words(node, 16, &path_nodes[8 * (level + 1)]);
+            position >>= 1;
+        }
+    }
+}
+
+void merkle_path_nodes(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, int n_paths, uint32_t *nodes) {
+    // Same layout as verify_merkle_paths, without the roots, but all the arguments are device
+    // arrays, uploaded by the caller. nodes receives the depth + 1 hashes of each path.
+    if (n_paths == 0) {
+        return;
+    }
+
+    int block_dim = 256;
+    int num_blocks = (n_paths + block_dim - 1) / block_dim;
+    merkle_path_nodes_kernel<<<num_blocks, block_dim>>>(
+        leaf_values, n_columns, positions, paths, depth, n_paths, nodes);
+    cudaDeviceSynchronize();
+}
+
+void verify_merkle_paths(m31 *leaf_values, int n_columns, uint32_t *positions, uint32_t *paths, int depth, uint32_t *roots, int n_roots, uint32_t *root_indices, int n_paths, int *valid) {
+    // All the arguments are host arrays: n_columns leaf values, a position, depth sibling hashes
+    // and the index of the expected root per path. valid[i] is set to whether path i
+    // leads to its root.
+    if (n_paths == 0) {
+        return;
+    }
+
+    m31 *device_leaf_values;
+    cudaMalloc((void**)&device_leaf_values, sizeof(m31) * max(n_columns * n_paths, 1));
+    cudaMemcpy(device_leaf_values, leaf_values, sizeof(m31) * n_columns * n_paths, cudaMemcpyHostToDevice);
+
+    uint32_t *device_positions;
+    cudaMalloc((void**)&device_positions, sizeof(uint32_t) * 2 * n_paths);
+    cudaMemcpy(device_positions, positions, sizeof(uint32_t) * n_paths, cudaMemcpyHostToDevice);
+    uint32_t *device_root_indices = &device_positions[n_paths];
+    cudaMemcpy(device_root_indices, root_indices, sizeof(uint32_t) * n_paths, cudaMemcpyHostToDevice);
+
+    uint32_t *device_paths;
+    cudaMalloc((void**)&device_paths, sizeof(uint32_t) * max(8 * depth * n_paths, 1));
+    cudaMemcpy(device_paths, paths, sizeof(uint32_t) * 8 * depth * n_paths, cudaMemcpyHostToDevice);
+
+    uint32_t *device_roots;
+    cudaMalloc((void**)&device_roots, sizeof(uint32_t) * 8 * n_roots);
+    cudaMemcpy(device_roots, roots, sizeof(uint32_t) * 8 * n_roots, cudaMemcpyHostToDevice);
+
+    int *device_valid;
+    cudaMalloc((void**)&device_valid, sizeof(int) * n_paths);
+
+    int block_dim = 256;
+    int num_blocks = (n_paths + block_dim - 1) / block_dim;
+    verify_merkle_paths_kernel<<<num_blocks, block_dim>>>(
+        device_leaf_values, n_columns, device_positions, device_paths, depth, device_roots, device_root_indices, n_paths, device_valid);
+    cudaDeviceSynchronize();
+
+    cudaMemcpy(valid, device_valid, sizeof(int) * n_paths, cudaMemcpyDeviceToHost);
+
+    cudaFree(device_leaf_values);
+    cudaFree(device_positions);
+    cudaFree(device_paths);
+    cudaFree(device_roots);
+    cudaFree(device_valid);
+}
\ No newline at end of file
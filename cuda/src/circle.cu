@@ -3,29 +3,34 @@
 #include "../include/bit_reverse.cuh"
 #include "../include/fields.cuh"
 #include "../include/point.cuh"
+#include "../include/scratch.cuh"
+#include "../include/tuning.cuh"
 #include "../include/utils.cuh"
 
 __global__ void sort_values_kernel(m31 *from, m31 *dst, int size) {
+    // Each thread reads a pair of consecutive values with a single 64 bit load. The even one
+    // goes to the first half of dst, in order, and the odd one to the second half, reversed.
     int idx = blockIdx.x * blockDim.x + threadIdx.x;
 
-    if (idx < size) {
-        if(idx < (size >> 1)) {
-            dst[idx] = from[idx << 1];
-        } else {
-            int tmp = idx - (size >> 1);
-            dst[idx] = from[size - (tmp << 1) - 1];
-        }
+    if (idx < (size >> 1)) {
+        uint2 pair = ((uint2*) from)[idx];
+        dst[idx] = pair.x;
+        dst[size - 1 - idx] = pair.y;
     }
 }
 
 m31* sort_values_and_permute_with_bit_reverse_order(m31 *from, int size) {
     int block_dim = 256;
-    int num_blocks = (size + block_dim - 1) / block_dim;
+    int num_blocks = ((size >> 1) + block_dim - 1) / block_dim;
     m31 *dst;
     cudaMalloc((void**)&dst, sizeof(m31) * size);
 
-    sort_values_kernel<<<num_blocks, block_dim>>>(from, dst, size);
-    cudaDeviceSynchronize();
+    if (size == 1) {
+        cudaMemcpy(dst, from, sizeof(m31), cudaMemcpyDeviceToDevice);
+    } else {
+        sort_values_kernel<<<num_blocks, block_dim>>>(from, dst, size);
+        cudaDeviceSynchronize();
+    }
 
     bit_reverse_base_field(dst, size);
     return dst;
@@ -47,7 +52,7 @@ __global__ void precompute_twiddles_kernel(m31 *dst, point initial, point step,
 
     size = size >> 1;
     if (idx < size) {
-        point pow = point_pow(step, bit_reverse(idx, log_size - 1)); // TODO: Be aware when log_size == 0 !
+        point pow = point_pow(step, bit_reverse(idx, log_size - 1));
         dst[offset + idx] = point_mul(initial, pow).x;
     }
 }
@@ -77,20 +82,9 @@ m31* precompute_twiddles(point initial, point step, int size) {
     return twiddles;
 }
 
-__device__ int get_twiddle(m31 *twiddles, int index) {
-    int k = index >> 2;
-    if (index % 4 == 0) {
-        return twiddles[2 * k + 1];
-    } else if (index % 4 == 1) {
-        return neg(twiddles[2 * k + 1]);
-    } else if (index % 4 == 2) {
-        return neg(twiddles[2 * k]);
-    } else {
-        return twiddles[2 * k];
-    }
-}
-
-__global__ void ifft_circle_part(m31 *values, m31 *inverse_twiddles_tree, int values_size) {
+__global__ void ifft_circle_part(m31 *values, const m31 *__restrict__ inverse_twiddles_tree, int values_size) {
+    // Instances of a batch are stored one after the other, one per row of the grid.
+    values += (size_t)blockIdx.y * values_size;
     int idx = blockIdx.x * blockDim.x + threadIdx.x;
 
     if (idx < (values_size >> 1)) {
@@ -104,26 +98,34 @@ __global__ void ifft_circle_part(m31 *values, m31 *inverse_twiddles_tree, int va
 }
 
 
-__global__ void ifft_line_part(m31 *values, m31 *inverse_twiddles_tree, int values_size, int inverse_twiddles_size, int layer_domain_offset, int layer) {
-    int idx = blockIdx.x * blockDim.x + threadIdx.x;
-
-    if (idx < (values_size >> 1)) {
-        int number_polynomials = 1 << layer;
-        int h = idx >> layer;
-        int l = idx & (number_polynomials - 1);
-        int idx0 = (h << (layer + 1)) + l;
-        int idx1 = idx0 + number_polynomials;
-
-        m31 val0 = values[idx0];
-        m31 val1 = values[idx1];
-        m31 twiddle = inverse_twiddles_tree[layer_domain_offset + h];
-        
-        values[idx0] = add(val0, val1);
-        values[idx1] = mul(sub(val0, val1), twiddle);
+template<int BUTTERFLIES>
+__global__ void ifft_line_part(m31 *values, const m31 *__restrict__ inverse_twiddles_tree, int values_size, int inverse_twiddles_size, int layer_domain_offset, int layer) {
+    values += (size_t)blockIdx.y * values_size;
+    // Each thread computes BUTTERFLIES butterflies, blockDim.x apart so accesses stay coalesced.
+    int first_idx = blockIdx.x * blockDim.x * BUTTERFLIES + threadIdx.x;
+
+    #pragma unroll
+    for (int k = 0; k < BUTTERFLIES; k++) {
+        int idx = first_idx + k * blockDim.x;
+        if (idx < (values_size >> 1)) {
+            int number_polynomials = 1 << layer;
+            int h = idx >> layer;
+            int l = idx & (number_polynomials - 1);
+            int idx0 = (h << (layer + 1)) + l;
+            int idx1 = idx0 + number_polynomials;
+
+            m31 val0 = values[idx0];
+            m31 val1 = values[idx1];
+            m31 twiddle = __ldg(&inverse_twiddles_tree[layer_domain_offset + h]);
+
+            values[idx0] = add(val0, val1);
+            values[idx1] = mul(sub(val0, val1), twiddle);
+        }
     }
 }
 
-__global__ void rfft_circle_part(m31 *values, m31 *inverse_twiddles_tree, int values_size) {
+__global__ void rfft_circle_part(m31 *values, const m31 *__restrict__ inverse_twiddles_tree, int values_size) {
+    values += (size_t)blockIdx.y * values_size;
     int idx = blockIdx.x * blockDim.x + threadIdx.x;
 
     
@@ -139,28 +141,55 @@ __global__ void rfft_circle_part(m31 *values, m31 *inverse_twiddles_tree, int va
     }
 }
 
-__global__ void rfft_line_part(m31 *values, m31 *inverse_twiddles_tree, int values_size, int inverse_twiddles_size, int layer_domain_offset, int layer) {
-    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+template<int BUTTERFLIES>
+__global__ void rfft_line_part(m31 *values, const m31 *__restrict__ inverse_twiddles_tree, int values_size, int inverse_twiddles_size, int layer_domain_offset, int layer) {
+    values += (size_t)blockIdx.y * values_size;
+    int first_idx = blockIdx.x * blockDim.x * BUTTERFLIES + threadIdx.x;
+
+    #pragma unroll
+    for (int k = 0; k < BUTTERFLIES; k++) {
+        int idx = first_idx + k * blockDim.x;
+        if (idx < (values_size >> 1)) {
+            int number_polynomials = 1 << layer;
+            int h = idx / number_polynomials;
+            int l = idx % number_polynomials;
+            int idx0 = (h << (layer + 1)) + l;
+            int idx1 = idx0 + number_polynomials;
+
+            m31 val0 = values[idx0];
+            m31 val1 = values[idx1];
+            m31 twiddle = __ldg(&inverse_twiddles_tree[layer_domain_offset + h]);
+
+            m31 temp = mul(val1, twiddle);
+
+            values[idx0] = add(val0, temp);
+            values[idx1] = sub(val0, temp);
+        }
+    }
+}
 
-    if (idx < (values_size >> 1)) {
-        int number_polynomials = 1 << layer;
-        int h = idx / number_polynomials;
-        int l = idx % number_polynomials;
-        int idx0 = (h << (layer + 1)) + l;
-        int idx1 = idx0 + number_polynomials;
-
-        m31 val0 = values[idx0];
-        m31 val1 = values[idx1];
-        m31 twiddle = inverse_twiddles_tree[layer_domain_offset + h];
-        
-        m31 temp = mul(val1, twiddle);
-        
-        values[idx0] = add(val0, temp);
-        values[idx1] = sub(val0, temp);
+template<bool INVERSE>
+void fft_line_part(dim3 grid, int block_dim, int butterflies, m31 *values, m31 *inverse_twiddles_tree, int values_size, int layer_domain_size, int layer_domain_offset, int layer) {
+    // Launches the variant of the line layer computing butterflies butterflies per thread, on a
+    // grid shrunk accordingly.
+    grid.x = (grid.x + butterflies - 1) / butterflies;
+    switch (butterflies) {
+        case 4:
+            if (INVERSE) ifft_line_part<4><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
+            else rfft_line_part<4><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
+            break;
+        case 2:
+            if (INVERSE) ifft_line_part<2><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
+            else rfft_line_part<2><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
+            break;
+        default:
+            if (INVERSE) ifft_line_part<1><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
+            else rfft_line_part<1><<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, layer);
     }
 }
 
 __global__ void rescale(m31 *values, int size, m31 factor) {
+    values += (size_t)blockIdx.y * size;
     int idx = blockIdx.x * blockDim.x + threadIdx.x;
 
     if(idx < size) {
@@ -169,16 +198,23 @@ __global__ void rescale(m31 *values, int size, m31 factor) {
 }
 
 void interpolate(m31 *values, m31 *inverse_twiddles_tree, int values_size) {
-    int block_dim = 256;
-    int num_blocks = ((values_size >> 1) + block_dim - 1) / block_dim;
-    ifft_circle_part<<<num_blocks, block_dim>>>(values, inverse_twiddles_tree, values_size);
+    interpolate_batch(values, inverse_twiddles_tree, values_size, 1);
+}
+
+void interpolate_batch(m31 *values, m31 *inverse_twiddles_tree, int values_size, int n_instances) {
+    // values: n_instances evaluations of values_size values, one after the other, all
+    // interpolated with the same launches.
+    kernel_tuning launch = tuning();
+    int block_dim = launch.fft_block_dim;
+    dim3 grid(((values_size >> 1) + block_dim - 1) / block_dim, n_instances);
+    ifft_circle_part<<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size);
 
     int log_values_size = log_2(values_size);
     int layer_domain_size = values_size >> 1;
     int layer_domain_offset = 0;
     int i = 1;
     while (i < log_values_size) {
-        ifft_line_part<<<num_blocks, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, i);
+        fft_line_part<true>(grid, block_dim, launch.fft_butterflies_per_thread, values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, i);
 
         layer_domain_size >>= 1;
         layer_domain_offset += layer_domain_size;
@@ -187,28 +223,42 @@ void interpolate(m31 *values, m31 *inverse_twiddles_tree, int values_size) {
     cudaDeviceSynchronize();
         
     block_dim = 1024;
-    num_blocks = (values_size + block_dim - 1) / block_dim;
+    grid = dim3((values_size + block_dim - 1) / block_dim, n_instances);
     m31 factor = inv(pow(m31{ 2 }, log_values_size));
-    rescale<<<num_blocks, block_dim>>>(values, values_size, factor);
+    rescale<<<grid, block_dim>>>(values, values_size, factor);
     cudaDeviceSynchronize();
 }
 
 void evaluate(m31 *values, m31 *inverse_twiddles_tree, int values_size) {
-    int block_dim = 256;
-    int num_blocks = ((values_size >> 1) + block_dim - 1) / block_dim;
+    evaluate_batch(values, inverse_twiddles_tree, values_size, 1);
+}
+
+void evaluate_batch(m31 *values, m31 *inverse_twiddles_tree, int values_size, int n_instances) {
+    // Same layout as interpolate_batch.
+    kernel_tuning launch = tuning();
+    int block_dim = launch.fft_block_dim;
+    dim3 grid(((values_size >> 1) + block_dim - 1) / block_dim, n_instances);
 
     int log_values_size = log_2(values_size);
     int layer_domain_size = 1;
     int layer_domain_offset = (values_size >> 1) - 2;
     int i = log_values_size - 1;
     while (i > 0) {
-        rfft_line_part<<<num_blocks, block_dim>>>(values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, i);
+        fft_line_part<false>(grid, block_dim, launch.fft_butterflies_per_thread, values, inverse_twiddles_tree, values_size, layer_domain_size, layer_domain_offset, i);
         layer_domain_size <<= 1;
         layer_domain_offset -= layer_domain_size;
         i -= 1;
     }
 
-    rfft_circle_part<<<num_blocks, block_dim>>>(values, inverse_twiddles_tree, values_size);
+    rfft_circle_part<<<grid, block_dim>>>(values, inverse_twiddles_tree, values_size);
+    cudaDeviceSynchronize();
+}
+
+void extend_batch(m31 *coeffs, int size, m31 *dst, int extended_size, int n_instances) {
+    // Copies the coefficients of each instance to the start of its extended_size values in dst,
+    // padding them with zeros.
+    cudaMemset(dst, 0, sizeof(m31) * extended_size * n_instances);
+    cudaMemcpy2D(dst, sizeof(m31) * extended_size, coeffs, sizeof(m31) * size, sizeof(m31) * size, n_instances, cudaMemcpyDeviceToDevice);
     cudaDeviceSynchronize();
 }
 
@@ -226,33 +276,46 @@ __global__ void eval_at_point_first_pass(m31* g_coeffs, qm31 *temp, qm31 *factor
     extern __shared__ m31 s_coeffs[];
     extern __shared__ qm31 s_level[];
 
-    s_coeffs[idx] = g_coeffs[2 * blockIdx.x * blockDim.x + idx];
-    s_coeffs[idx + blockDim.x] = g_coeffs[2 * blockIdx.x * blockDim.x + idx + blockDim.x];
+    // Small polynomials don't fill the block.
+    if(idx < coeffs_size) {
+        s_coeffs[idx] = g_coeffs[2 * blockIdx.x * blockDim.x + idx];
+    }
+    if(idx + blockDim.x < coeffs_size) {
+        s_coeffs[idx + blockDim.x] = g_coeffs[2 * blockIdx.x * blockDim.x + idx + blockDim.x];
+    }
     __syncthreads();
     
     int level_size = coeffs_size >> 1;
     int factor_idx = factors_size - 1;
 
+    // s_level shares its memory with s_coeffs, so every coefficient is read before the first
+    // write. Barriers are kept out of the branches, since the whole block must reach them.
+    qm31 result;
     if(idx < level_size) {
         m31 alpha = s_coeffs[2 * idx];
         m31 beta = s_coeffs[2 * idx + 1];
         qm31 factor = factors[factor_idx];
-        __syncthreads();
-        qm31 result = { 
+        result = { 
             {add(mul(beta, factor.a.a), alpha), mul(factor.a.b, beta)}, 
             {mul(beta,  factor.b.a), mul(beta, factor.b.b)} 
         };
+    }
+    __syncthreads();
+    if(idx < level_size) {
         s_level[idx] = result;
     }
     factor_idx -= 1;
     level_size >>= 1;
 
     while(level_size > 0) {
+        __syncthreads();
+        qm31 a, b;
+        if(idx < level_size) {
+            a = s_level[2 * idx];
+            b = s_level[2 * idx + 1];
+        }
+        __syncthreads();
         if(idx < level_size) {
-            __syncthreads();
-            qm31 a = s_level[2 * idx];
-            qm31 b = s_level[2 * idx + 1];
-            __syncthreads();
             s_level[idx] = add(a, mul(b, factors[factor_idx]));
         }
         factor_idx -= 1;
@@ -280,19 +343,26 @@ __global__ void eval_at_point_second_pass(qm31* temp, qm31 *factors, int level_s
     
     extern __shared__ qm31 s_level[];
 
-    s_level[idx] = level[2 * blockIdx.x * blockDim.x + idx];
-    s_level[idx + blockDim.x] = level[2 * blockIdx.x * blockDim.x + idx + blockDim.x];
+    if(idx < level_size) {
+        s_level[idx] = level[2 * blockIdx.x * blockDim.x + idx];
+    }
+    if(idx + blockDim.x < level_size) {
+        s_level[idx + blockDim.x] = level[2 * blockIdx.x * blockDim.x + idx + blockDim.x];
+    }
 
     level_size >>= 1;
 
     int factor_idx = factor_offset;
 
     while(level_size > 0) {
+        __syncthreads();
+        qm31 a, b;
+        if(idx < level_size) {
+            a = s_level[2 * idx];
+            b = s_level[2 * idx + 1];
+        }
+        __syncthreads();
         if(idx < level_size) {
-            __syncthreads();
-            qm31 a = s_level[2 * idx];
-            qm31 b = s_level[2 * idx + 1];
-            __syncthreads();
             s_level[idx] = add(a, mul(b, factors[factor_idx]));
         }
         factor_idx -= 1;
@@ -310,7 +380,9 @@ qm31 eval_at_point(m31 *coeffs, int coeffs_size, qm31 point_x, qm31 point_y) {
 
     qm31 *host_mappings = (qm31*)malloc(sizeof(qm31) * log_coeffs_size);
     host_mappings[log_coeffs_size - 1] = point_y;
-    host_mappings[log_coeffs_size - 2] = point_x;
+    if(log_coeffs_size > 1) {
+        host_mappings[log_coeffs_size - 2] = point_x;
+    }
     qm31 x = point_x;
     for(int i = 2; i < log_coeffs_size; i+=1) {
         x = sub(mul(qm31{cm31{2, 0}, cm31{0, 0}}, mul(x, x)), qm31{cm31{1, 0}, cm31{0, 0}});
@@ -324,11 +396,9 @@ qm31 eval_at_point(m31 *coeffs, int coeffs_size, qm31 point_x, qm31 point_y) {
         temp_memory_size += size;
     }
 
-    qm31* temp;
-    cudaMalloc((void**)&temp, sizeof(qm31) * temp_memory_size);
+    qm31* temp = (qm31*) scratch_buffer("eval_at_point_temp", log_coeffs_size, sizeof(qm31) * temp_memory_size);
 
-    qm31* device_mappings;
-    cudaMalloc((void**)&device_mappings, sizeof(qm31) * log_coeffs_size);
+    qm31* device_mappings = (qm31*) scratch_buffer("eval_at_point_mappings", log_coeffs_size, sizeof(qm31) * log_coeffs_size);
     cudaMemcpy(device_mappings, host_mappings, sizeof(qm31) * log_coeffs_size, cudaMemcpyHostToDevice);
     free(host_mappings);
 
@@ -355,8 +425,149 @@ qm31 eval_at_point(m31 *coeffs, int coeffs_size, qm31 point_x, qm31 point_y) {
 
     qm31 result = qm31{cm31{0,0}, cm31{0,1}};
     cudaMemcpy(&result, temp, sizeof(qm31), cudaMemcpyDeviceToHost);
-    cudaFree(temp);
-    cudaFree(device_mappings);
     return result;
 }
 
+
+
+const int EVAL_BLOCK_DIM = 256;
+const int EVAL_BLOCKS_PER_EVALUATION = 64;
+// Largest gridDim.y a kernel can be launched with.
+const int MAX_GRID_DIM_Y = 65535;
+
+__device__ void block_reduce_sum(qm31 sum, qm31 *dst) {
+    // Adds up the values of all threads in the block and stores the result in dst.
+    __shared__ qm31 s_sums[EVAL_BLOCK_DIM];
+
+    int idx = threadIdx.x;
+    s_sums[idx] = sum;
+    __syncthreads();
+
+    for (int half = blockDim.x >> 1; half > 0; half >>= 1) {
+        if (idx < half) {
+            s_sums[idx] = add(s_sums[idx], s_sums[idx + half]);
+        }
+        __syncthreads();
+    }
+
+    if (idx == 0) {
+        *dst = s_sums[0];
+    }
+}
+
+__global__ void eval_samples_kernel(m31 **coeffs, uint32_t *log_sizes, qm31 *mappings, int max_log_size, uint32_t *sample_polys, uint32_t *sample_points, qm31 *partials) {
+    // Block (x, y) adds up a strided slice of the terms of sample y, the evaluation of polynomial
+    // sample_polys[y] at point sample_points[y]. The term of coefficient i is the coefficient
+    // times the mappings of the bits set in i, where bit 0 maps to y, bit 1 to x and bit k to the
+    // (k - 1)-th doubling of x.
+    int sample = blockIdx.y;
+    int poly = sample_polys[sample];
+    qm31 *point_mappings = &mappings[sample_points[sample] * max_log_size];
+    m31 *poly_coeffs = coeffs[poly];
+    int size = 1 << log_sizes[poly];
+
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        qm31 term = {{1, 0}, {0, 0}};
+        for (int bit = 0; (i >> bit) > 0; bit++) {
+            if ((i >> bit) & 1) {
+                term = mul(term, point_mappings[bit]);
+            }
+        }
+        sum = add(sum, mul(term, poly_coeffs[i]));
+    }
+
+    block_reduce_sum(sum, &partials[sample * gridDim.x + blockIdx.x]);
+}
+
+__global__ void reduce_evaluations_kernel(qm31 *partials, int n_partials, qm31 *evaluations) {
+    // Block i adds up the partials of evaluation i.
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = threadIdx.x; i < n_partials; i += blockDim.x) {
+        sum = add(sum, partials[blockIdx.x * n_partials + i]);
+    }
+
+    block_reduce_sum(sum, &evaluations[blockIdx.x]);
+}
+
+void eval_polys_at_points(m31 **coeffs, uint32_t *log_sizes, int n_polys, qm31 *points_x, qm31 *points_y, int n_points, qm31 *result) {
+    //    coeffs: host array with the device pointers of the coefficients of each polynomial.
+    // log_sizes: host array with the log size of each polynomial.
+    //    result: host buffer of n_polys * n_points values, laid out polynomial by polynomial.
+    int n_samples = n_polys * n_points;
+    uint32_t *sample_polys = (uint32_t*)malloc(sizeof(uint32_t) * n_samples);
+    uint32_t *sample_points = (uint32_t*)malloc(sizeof(uint32_t) * n_samples);
+    for (int i = 0; i < n_samples; i++) {
+        sample_polys[i] = i / n_points;
+        sample_points[i] = i % n_points;
+    }
+    eval_samples(coeffs, log_sizes, n_polys, points_x, points_y, n_points, sample_polys, sample_points, n_samples, result);
+    free(sample_polys);
+    free(sample_points);
+}
+
+void eval_samples(m31 **coeffs, uint32_t *log_sizes, int n_polys, qm31 *points_x, qm31 *points_y, int n_points, uint32_t *sample_polys, uint32_t *sample_points, int n_samples, qm31 *result) {
+    //        coeffs: host array with the device pointers of the coefficients of each polynomial.
+    //     log_sizes: host array with the log size of each polynomial.
+    //  sample_polys: host array with the polynomial of each sample.
+    // sample_points: host array with the index in points_x and points_y of the point of each sample.
+    //        result: host buffer of n_samples values.
+    // The mappings of each point are computed once, however many polynomials are sampled at it.
+    if (n_samples == 0) {
+        return;
+    }
+
+    int max_log_size = 1;
+    for (int i = 0; i < n_polys; i++) {
+        max_log_size = max(max_log_size, (int) log_sizes[i]);
+    }
+
+    qm31 *host_mappings = (qm31*)malloc(sizeof(qm31) * max_log_size * n_points);
+    for (int point = 0; point < n_points; point++) {
+        qm31 *point_mappings = &host_mappings[point * max_log_size];
+        point_mappings[0] = points_y[point];
+        qm31 x = points_x[point];
+        for (int bit = 1; bit < max_log_size; bit++) {
+            point_mappings[bit] = x;
+            x = sub(mul(qm31{cm31{2, 0}, cm31{0, 0}}, mul(x, x)), qm31{cm31{1, 0}, cm31{0, 0}});
+        }
+    }
+
+    qm31 *device_mappings;
+    cudaMalloc((void**)&device_mappings, sizeof(qm31) * max_log_size * n_points);
+    cudaMemcpy(device_mappings, host_mappings, sizeof(qm31) * max_log_size * n_points, cudaMemcpyHostToDevice);
+    free(host_mappings);
+
+    m31 **device_coeffs;
+    cudaMalloc((void**)&device_coeffs, sizeof(m31*) * n_polys);
+    cudaMemcpy(device_coeffs, coeffs, sizeof(m31*) * n_polys, cudaMemcpyHostToDevice);
+
+    uint32_t *device_log_sizes;
+    cudaMalloc((void**)&device_log_sizes, sizeof(uint32_t) * n_polys);
+    cudaMemcpy(device_log_sizes, log_sizes, sizeof(uint32_t) * n_polys, cudaMemcpyHostToDevice);
+
+    uint32_t *device_samples;
+    cudaMalloc((void**)&device_samples, sizeof(uint32_t) * 2 * n_samples);
+    cudaMemcpy(device_samples, sample_polys, sizeof(uint32_t) * n_samples, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_samples[n_samples], sample_points, sizeof(uint32_t) * n_samples, cudaMemcpyHostToDevice);
+
+    qm31 *partials;
+    cudaMalloc((void**)&partials, sizeof(qm31) * n_samples * (EVAL_BLOCKS_PER_EVALUATION + 1));
+    qm31 *evaluations = &partials[n_samples * EVAL_BLOCKS_PER_EVALUATION];
+
+    // Samples go along gridDim.y, so they are evaluated in chunks of at most MAX_GRID_DIM_Y.
+    for (int first_sample = 0; first_sample < n_samples; first_sample += MAX_GRID_DIM_Y) {
+        dim3 num_blocks(EVAL_BLOCKS_PER_EVALUATION, min(n_samples - first_sample, MAX_GRID_DIM_Y));
+        eval_samples_kernel<<<num_blocks, EVAL_BLOCK_DIM>>>(device_coeffs, device_log_sizes, device_mappings, max_log_size, &device_samples[first_sample], &device_samples[n_samples + first_sample], &partials[first_sample * EVAL_BLOCKS_PER_EVALUATION]);
+    }
+    reduce_evaluations_kernel<<<n_samples, EVAL_BLOCK_DIM>>>(partials, EVAL_BLOCKS_PER_EVALUATION, evaluations);
+    cudaDeviceSynchronize();
+
+    cudaMemcpy(result, evaluations, sizeof(qm31) * n_samples, cudaMemcpyDeviceToHost);
+
+    cudaFree(device_mappings);
+    cudaFree(device_coeffs);
+    cudaFree(device_log_sizes);
+    cudaFree(device_samples);
+    cudaFree(partials);
+}
@@ -0,0 +1,49 @@
+#include "../include/compression.cuh"
+
+// M31 values only use 31 bits, so size values are stored as a stream of 31 * size bits,
+// value i taking bits [31 * i, 31 * (i + 1)), in ceil(31 * size / 32) words.
+
+__global__ void pack_base_field_kernel(m31 *column, int size, uint32_t *dst, int packed_size) {
+    // Each thread builds one packed word out of the (at most) two values it overlaps.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < packed_size) {
+        uint64_t first_bit = (uint64_t) idx * 32;
+        int value_index = first_bit / 31;
+        int shift = first_bit % 31;
+        uint32_t word = column[value_index] >> shift;
+        if (value_index + 1 < size) {
+            word |= column[value_index + 1] << (31 - shift);
+        }
+        dst[idx] = word;
+    }
+}
+
+__global__ void unpack_base_field_kernel(uint32_t *packed, int packed_size, m31 *dst, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        uint64_t first_bit = (uint64_t) idx * 31;
+        int word_index = first_bit / 32;
+        int shift = first_bit % 32;
+        uint32_t value = packed[word_index] >> shift;
+        if (shift > 1 && word_index + 1 < packed_size) {
+            value |= packed[word_index + 1] << (32 - shift);
+        }
+        dst[idx] = value & P;
+    }
+}
+
+void pack_base_field(m31 *column, int size, uint32_t *dst, int packed_size) {
+    int block_dim = 256;
+    int num_blocks = (packed_size + block_dim - 1) / block_dim;
+    pack_base_field_kernel<<<num_blocks, block_dim>>>(column, size, dst, packed_size);
+    cudaDeviceSynchronize();
+}
+
+void unpack_base_field(uint32_t *packed, int packed_size, m31 *dst, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    unpack_base_field_kernel<<<num_blocks, block_dim>>>(packed, packed_size, dst, size);
+    cudaDeviceSynchronize();
+}
\ No newline at end of file
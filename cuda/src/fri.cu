@@ -0,0 +1,351 @@
+#include "../include/fri.cuh"
+#include "../include/blake2s.cuh"
+#include "../include/reduce.cuh"
+#include "../include/scratch.cuh"
+#include "../include/tuning.cuh"
+#include "../include/utils.cuh"
+
+__device__ __forceinline__ void ibutterfly(qm31 &v0, qm31 &v1, m31 itwid) {
+    qm31 tmp = v0;
+    v0 = add(tmp, v1);
+    v1 = mul(sub(tmp, v1), itwid);
+}
+
+__device__ __forceinline__ qm31 fold_pair(qm31 f_x, qm31 f_neg_x, m31 itwid, qm31 alpha) {
+    // Computes f0 + alpha * f1 where 2f(x) = f0(pi(x)) + x * f1(pi(x)).
+    ibutterfly(f_x, f_neg_x, itwid);
+    return add(f_x, mul(alpha, f_neg_x));
+}
+
+__global__ void fold_line_kernel(secure_column eval, secure_column folded, int folded_size, const m31 *__restrict__ itwiddles, qm31 alpha) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < folded_size) {
+        qm31 f_x, f_neg_x;
+        secure_column_pair_at(eval, idx, f_x, f_neg_x);
+        secure_column_set(folded, idx, fold_pair(f_x, f_neg_x, __ldg(&itwiddles[idx]), alpha));
+    }
+}
+
+__global__ void fold_circle_into_line_kernel(secure_column dst, secure_column src, int dst_size, const m31 *__restrict__ itwiddles, qm31 alpha, qm31 alpha_sq) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < dst_size) {
+        // Folds the pair of conjugate points at position idx into dst[idx].
+        qm31 f_p, f_neg_p;
+        secure_column_pair_at(src, idx, f_p, f_neg_p);
+        qm31 f_prime = fold_pair(f_p, f_neg_p, get_twiddle(itwiddles, idx), alpha);
+        secure_column_set(dst, idx, add(mul(secure_column_at(dst, idx), alpha_sq), f_prime));
+    }
+}
+
+void fold_line(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha) {
+    fold_line_on_stream(eval, folded, eval_size, itwiddles, twiddle_offset, alpha, 0);
+    cudaDeviceSynchronize();
+}
+
+void fold_line_on_stream(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha, cudaStream_t stream) {
+    // Same as fold_line, queued on stream without waiting for it.
+    int folded_size = eval_size >> 1;
+    int block_dim = tuning().fold_block_dim;
+    int num_blocks = (folded_size + block_dim - 1) / block_dim;
+    fold_line_kernel<<<num_blocks, block_dim, 0, stream>>>(make_secure_column(eval), make_secure_column(folded), folded_size, &itwiddles[twiddle_offset], alpha);
+}
+
+void fold_line_and_hash(m31 **eval, m31 **folded, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 alpha, uint32_t *leaves) {
+    // Folds like fold_line and hashes the folded values into the leaves of the commitment of the
+    // folded layer. Both launches are queued back to back on the same stream, so the hashing
+    // reads the freshly written values from L2 when they fit, and the host waits only once.
+    fold_line_on_stream(eval, folded, eval_size, itwiddles, twiddle_offset, alpha, 0);
+    commit_on_layer_on_stream(log_2(eval_size >> 1), nullptr, folded, 4, leaves, 0);
+    cudaDeviceSynchronize();
+}
+
+void fold_circle_into_line(m31 **dst, m31 **src, int dst_size, m31 *itwiddles, int twiddle_offset, qm31 alpha) {
+    int block_dim = tuning().fold_block_dim;
+    int num_blocks = (dst_size + block_dim - 1) / block_dim;
+    fold_circle_into_line_kernel<<<num_blocks, block_dim>>>(make_secure_column(dst), make_secure_column(src), dst_size, &itwiddles[twiddle_offset], alpha, mul(alpha, alpha));
+    cudaDeviceSynchronize();
+}
+
+// Per-launch scalars, including the per-evaluation alphas below, are passed by value: kernel
+// parameters live in constant memory, so they are broadcast to all threads without the device
+// allocations and copies that would otherwise synchronize the host with the device.
+const int FOLD_LINES_BATCH_SIZE = 32;
+
+typedef struct {
+    secure_column evals[FOLD_LINES_BATCH_SIZE];
+    secure_column folded[FOLD_LINES_BATCH_SIZE];
+    qm31 alphas[FOLD_LINES_BATCH_SIZE];
+} fold_lines_params;
+
+__global__ void fold_lines_kernel(const fold_lines_params params, int folded_size, const m31 *__restrict__ itwiddles) {
+    // blockIdx.y selects the evaluation within the batch.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int eval_index = blockIdx.y;
+
+    if (idx < folded_size) {
+        secure_column eval = params.evals[eval_index];
+        qm31 f_x, f_neg_x;
+        secure_column_pair_at(eval, idx, f_x, f_neg_x);
+        secure_column_set(params.folded[eval_index], idx, fold_pair(f_x, f_neg_x, __ldg(&itwiddles[idx]), params.alphas[eval_index]));
+    }
+}
+
+void fold_lines(m31 **evals, m31 **folded, int n_evals, int eval_size, m31 *itwiddles, int twiddle_offset, qm31 *alphas) {
+    //  evals, folded: host arrays with the device pointers of the 4 coordinates of each evaluation.
+    //  alphas: host array with the folding coefficient of each evaluation.
+    // Evaluations are folded FOLD_LINES_BATCH_SIZE at a time, as many as fit in the parameters.
+    int folded_size = eval_size >> 1;
+    int block_dim = 256;
+    for (int start = 0; start < n_evals; start += FOLD_LINES_BATCH_SIZE) {
+        int batch_size = min(n_evals - start, FOLD_LINES_BATCH_SIZE);
+        fold_lines_params params;
+        for (int i = 0; i < batch_size; i++) {
+            params.evals[i] = make_secure_column(&evals[4 * (start + i)]);
+            params.folded[i] = make_secure_column(&folded[4 * (start + i)]);
+            params.alphas[i] = alphas[start + i];
+        }
+
+        dim3 num_blocks((folded_size + block_dim - 1) / block_dim, batch_size);
+        fold_lines_kernel<<<num_blocks, block_dim>>>(params, folded_size, &itwiddles[twiddle_offset]);
+    }
+    cudaDeviceSynchronize();
+}
+
+// Bounds the values a thread folds at once. The array holding them is indexed dynamically, so it
+// is placed in local memory rather than registers, and only stays close through the L1 cache.
+const int MAX_LOG_FOLD_FACTOR = 3;
+
+typedef struct {
+    int twiddle_offsets[MAX_LOG_FOLD_FACTOR];
+    qm31 alphas[MAX_LOG_FOLD_FACTOR];
+} fold_line_by_params;
+
+__global__ void fold_line_by_kernel(secure_column eval, secure_column folded, int folded_size, int log_fold_factor, const m31 *__restrict__ itwiddles, const fold_line_by_params params) {
+    // Each thread folds the 2^log_fold_factor consecutive values of eval giving folded[idx],
+    // without writing the intermediate layers back.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < folded_size) {
+        qm31 values[1 << MAX_LOG_FOLD_FACTOR];
+        int n_values = 1 << log_fold_factor;
+        for (int i = 0; i < n_values; i++) {
+            values[i] = secure_column_at(eval, (idx << log_fold_factor) + i);
+        }
+
+        for (int layer = 0; layer < log_fold_factor; layer++) {
+            n_values >>= 1;
+            // The pairs of this thread are pairs idx * n_values onwards of the layer.
+            const m31 *layer_itwiddles = &itwiddles[params.twiddle_offsets[layer] + idx * n_values];
+            for (int i = 0; i < n_values; i++) {
+                values[i] = fold_pair(values[2 * i], values[2 * i + 1], __ldg(&layer_itwiddles[i]), params.alphas[layer]);
+            }
+        }
+        secure_column_set(folded, idx, values[0]);
+    }
+}
+
+void fold_line_by(m31 **eval, m31 **folded, int eval_size, int log_fold_factor, m31 *itwiddles, int *twiddle_offsets, qm31 alpha) {
+    //  twiddle_offsets: host array with the offset of the line twiddles of each of the
+    //  log_fold_factor domains folded, from the largest.
+    // Layer i folds with alpha^(2^i), as log_fold_factor folds with alpha, alpha^2, ... would.
+    fold_line_by_params params;
+    qm31 layer_alpha = alpha;
+    for (int layer = 0; layer < log_fold_factor; layer++) {
+        params.twiddle_offsets[layer] = twiddle_offsets[layer];
+        params.alphas[layer] = layer_alpha;
+        layer_alpha = mul(layer_alpha, layer_alpha);
+    }
+
+    int folded_size = eval_size >> log_fold_factor;
+    int block_dim = tuning().fold_block_dim;
+    int num_blocks = (folded_size + block_dim - 1) / block_dim;
+    fold_line_by_kernel<<<num_blocks, block_dim>>>(make_secure_column(eval), make_secure_column(folded), folded_size, log_fold_factor, itwiddles, params);
+    cudaDeviceSynchronize();
+}
+
+__global__ void sum_secure_column_kernel(secure_column column, int size, qm31 *partials) {
+    // Each block adds up a strided slice of the column, all four coordinates at once.
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        sum = add(sum, secure_column_at(column, i));
+    }
+    store_block_sum(sum, partials);
+}
+
+__global__ void sum_secure_column_cooperative_kernel(secure_column column, int size, qm31 *partials) {
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        sum = add(sum, secure_column_at(column, i));
+    }
+    grid_sum(sum, partials);
+}
+
+int sum_secure_column(m31 **column, int size, qm31 *result) {
+    // A single cooperative launch where supported, otherwise a kernel per pass.
+    int cooperative_blocks = cooperative_sum_num_blocks(sum_secure_column_cooperative_kernel, size);
+    int num_blocks = cooperative_blocks > 0 ? cooperative_blocks : sum_num_blocks(size);
+    qm31 *partials = (qm31*) scratch_buffer("sum_secure_column", log_2(size), num_blocks * sizeof(qm31));
+
+    secure_column values = make_secure_column(column);
+    if (cooperative_blocks > 0) {
+        void *args[] = { &values, &size, &partials };
+        cudaError_t error = cudaLaunchCooperativeKernel((void*)sum_secure_column_cooperative_kernel, num_blocks, SUM_BLOCK_DIM, args);
+        if (error != cudaSuccess) {
+            return error;
+        }
+    } else {
+        sum_secure_column_kernel<<<num_blocks, SUM_BLOCK_DIM>>>(values, size, partials);
+        sum_partials_kernel<<<1, SUM_BLOCK_DIM>>>(partials, num_blocks);
+    }
+    cudaDeviceSynchronize();
+
+    return cudaMemcpy(result, partials, sizeof(qm31), cudaMemcpyDeviceToHost);
+}
+
+__global__ void compute_g_values_kernel(secure_column f_values, secure_column dst, int size, qm31 lambda) {
+    // g = f - lambda * v_n, where v_n is 1 on the first half of the domain and -1 on the second.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        qm31 value = secure_column_at(f_values, idx);
+        if (idx < (size >> 1)) {
+            secure_column_set(dst, idx, sub(value, lambda));
+        } else {
+            secure_column_set(dst, idx, add(value, lambda));
+        }
+    }
+}
+
+void compute_g_values(m31 **f_values, m31 **dst, int size, qm31 lambda) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    compute_g_values_kernel<<<num_blocks, block_dim>>>(make_secure_column(f_values), make_secure_column(dst), size, lambda);
+    cudaDeviceSynchronize();
+}
+
+__global__ void decompose_kernel(secure_column f_values, secure_column dst, int size, m31 inv_size, qm31 *partials) {
+    // lambda = (sum of the first half - sum of the second half) / size, reduced over the whole
+    // grid, after which each thread writes the g values of its slice. Launched cooperatively.
+    int half_size = size >> 1;
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        qm31 value = secure_column_at(f_values, i);
+        sum = i < half_size ? add(sum, value) : sub(sum, value);
+    }
+    grid_sum(sum, partials);
+    cooperative_groups::this_grid().sync();
+
+    qm31 lambda = mul(partials[0], inv_size);
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        qm31 value = secure_column_at(f_values, i);
+        secure_column_set(dst, i, i < half_size ? sub(value, lambda) : add(value, lambda));
+    }
+}
+
+int decompose(m31 **f_values, m31 **dst, int size, qm31 *result) {
+    // Writes g = f - lambda * v_n to dst and returns lambda, in a single launch where cooperative
+    // launches are supported.
+    m31 inv_size = inv((m31) size);
+    int num_blocks = cooperative_sum_num_blocks(decompose_kernel, size);
+    if (num_blocks == 0) {
+        int half_size = size >> 1;
+        m31 *second_half[4];
+        for (int i = 0; i < 4; i++) {
+            second_half[i] = &f_values[i][half_size];
+        }
+        qm31 first_sum, second_sum;
+        int error = sum_secure_column(f_values, half_size, &first_sum);
+        if (error == 0) {
+            error = sum_secure_column(second_half, half_size, &second_sum);
+        }
+        if (error != 0) {
+            return error;
+        }
+        *result = mul(sub(first_sum, second_sum), inv_size);
+        compute_g_values(f_values, dst, size, *result);
+        return 0;
+    }
+
+    qm31 *partials = (qm31*) scratch_buffer("decompose", log_2(size), num_blocks * sizeof(qm31));
+    secure_column f_column = make_secure_column(f_values);
+    secure_column dst_column = make_secure_column(dst);
+    void *args[] = { &f_column, &dst_column, &size, &inv_size, &partials };
+    cudaError_t error = cudaLaunchCooperativeKernel((void*)decompose_kernel, num_blocks, SUM_BLOCK_DIM, args);
+    if (error != cudaSuccess) {
+        return error;
+    }
+    cudaDeviceSynchronize();
+
+    qm31 sum;
+    error = cudaMemcpy(&sum, partials, sizeof(qm31), cudaMemcpyDeviceToHost);
+    *result = mul(sum, inv_size);
+    return error;
+}
+
+__global__ void check_fold_pairs_kernel(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, qm31 *folded, int n_checks, int *valid) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_checks) {
+        qm31 expected = fold_pair(f_x[idx], f_neg_x[idx], inv(x[idx]), alphas[idx]);
+        qm31 actual = folded[idx];
+        valid[idx] = expected.a.a == actual.a.a && expected.a.b == actual.a.b
+            && expected.b.a == actual.b.a && expected.b.b == actual.b.b;
+    }
+}
+
+void check_fold_pairs(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, qm31 *folded, int n_checks, int *valid) {
+    // All the arguments are host arrays of n_checks elements. valid[i] is set to whether
+    // folding the pair (f_x[i], f_neg_x[i]) at x[i] with alphas[i] gives folded[i].
+    if (n_checks == 0) {
+        return;
+    }
+
+    qm31 *device_values;
+    cudaMalloc((void**)&device_values, sizeof(qm31) * 4 * n_checks);
+    cudaMemcpy(device_values, f_x, sizeof(qm31) * n_checks, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_values[n_checks], f_neg_x, sizeof(qm31) * n_checks, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_values[2 * n_checks], alphas, sizeof(qm31) * n_checks, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_values[3 * n_checks], folded, sizeof(qm31) * n_checks, cudaMemcpyHostToDevice);
+
+    m31 *device_x;
+    cudaMalloc((void**)&device_x, sizeof(m31) * n_checks);
+    cudaMemcpy(device_x, x, sizeof(m31) * n_checks, cudaMemcpyHostToDevice);
+
+    int *device_valid;
+    cudaMalloc((void**)&device_valid, sizeof(int) * n_checks);
+
+    int block_dim = 256;
+    int num_blocks = (n_checks + block_dim - 1) / block_dim;
+    check_fold_pairs_kernel<<<num_blocks, block_dim>>>(
+        device_values, &device_values[n_checks], device_x, &device_values[2 * n_checks], &device_values[3 * n_checks], n_checks, device_valid);
+    cudaDeviceSynchronize();
+
+    cudaMemcpy(valid, device_valid, sizeof(int) * n_checks, cudaMemcpyDeviceToHost);
+
+    cudaFree(device_values);
+    cudaFree(device_x);
+    cudaFree(device_valid);
+}
+
+__global__ void fold_pairs_kernel(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, qm31 *folded, int n_pairs) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_pairs) {
+        folded[idx] = fold_pair(f_x[idx], f_neg_x[idx], inv(x[idx]), alphas[idx]);
+    }
+}
+
+void fold_pairs(qm31 *f_x, qm31 *f_neg_x, m31 *x, qm31 *alphas, int n_pairs, qm31 *folded) {
+    // Same layout as check_fold_pairs, but all the arguments are device arrays, uploaded by the
+    // caller, and the folded value of each pair is written to folded instead of compared.
+    if (n_pairs == 0) {
+        return;
+    }
+
+    int block_dim = 256;
+    int num_blocks = (n_pairs + block_dim - 1) / block_dim;
+    fold_pairs_kernel<<<num_blocks, block_dim>>>(f_x, f_neg_x, x, alphas, folded, n_pairs);
+    cudaDeviceSynchronize();
+}
@@ -0,0 +1,197 @@
+#include "../include/gkr.cuh"
+#include "../include/utils.cuh"
+
+const int GKR_BLOCK_DIM = 256;
+const int GKR_MAX_NUM_BLOCKS = 1024;
+
+__device__ void block_reduce_sums(qm31 eval_at_0, qm31 eval_at_2, qm31 *eval_at_0_partials, qm31 *eval_at_2_partials) {
+    // Adds up the values of all threads in the block and stores the result
+    // in position blockIdx.x of the partials arrays.
+    extern __shared__ qm31 s_sums[];
+    qm31 *s_eval_at_0 = s_sums;
+    qm31 *s_eval_at_2 = &s_sums[blockDim.x];
+
+    int idx = threadIdx.x;
+    s_eval_at_0[idx] = eval_at_0;
+    s_eval_at_2[idx] = eval_at_2;
+    __syncthreads();
+
+    for (int half = blockDim.x >> 1; half > 0; half >>= 1) {
+        if (idx < half) {
+            s_eval_at_0[idx] = add(s_eval_at_0[idx], s_eval_at_0[idx + half]);
+            s_eval_at_2[idx] = add(s_eval_at_2[idx], s_eval_at_2[idx + half]);
+        }
+        __syncthreads();
+    }
+
+    if (idx == 0) {
+        eval_at_0_partials[blockIdx.x] = s_eval_at_0[0];
+        eval_at_2_partials[blockIdx.x] = s_eval_at_2[0];
+    }
+}
+
+__global__ void reduce_partials_kernel(qm31 *eval_at_0_partials, qm31 *eval_at_2_partials, int size) {
+    // Launched with a single block, leaves the totals in position 0.
+    qm31 eval_at_0 = {{0, 0}, {0, 0}};
+    qm31 eval_at_2 = {{0, 0}, {0, 0}};
+    for (int i = threadIdx.x; i < size; i += blockDim.x) {
+        eval_at_0 = add(eval_at_0, eval_at_0_partials[i]);
+        eval_at_2 = add(eval_at_2, eval_at_2_partials[i]);
+    }
+    block_reduce_sums(eval_at_0, eval_at_2, eval_at_0_partials, eval_at_2_partials);
+}
+
+__global__ void grand_product_sum_kernel(qm31 *eq_evals, qm31 *input, int n_terms, qm31 *eval_at_0_partials, qm31 *eval_at_2_partials) {
+    // Computes the sums over the boolean hypercube of
+    //      eq(0, x) * inp(r, {0, 2}, x, 0) * inp(r, {0, 2}, x, 1)
+    // Note inp(r, 2, x) = 2 * inp(r, 1, x) - inp(r, 0, x).
+    qm31 eval_at_0 = {{0, 0}, {0, 0}};
+    qm31 eval_at_2 = {{0, 0}, {0, 0}};
+
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < n_terms; i += gridDim.x * blockDim.x) {
+        qm31 inp_at_r0i0 = input[i * 2];
+        qm31 inp_at_r0i1 = input[i * 2 + 1];
+        qm31 inp_at_r1i0 = input[(n_terms + i) * 2];
+        qm31 inp_at_r1i1 = input[(n_terms + i) * 2 + 1];
+        qm31 inp_at_r2i0 = sub(add(inp_at_r1i0, inp_at_r1i0), inp_at_r0i0);
+        qm31 inp_at_r2i1 = sub(add(inp_at_r1i1, inp_at_r1i1), inp_at_r0i1);
+
+        qm31 eq_eval_at_0i = eq_evals[i];
+        eval_at_0 = add(eval_at_0, mul(eq_eval_at_0i, mul(inp_at_r0i0, inp_at_r0i1)));
+        eval_at_2 = add(eval_at_2, mul(eq_eval_at_0i, mul(inp_at_r2i0, inp_at_r2i1)));
+    }
+
+    block_reduce_sums(eval_at_0, eval_at_2, eval_at_0_partials, eval_at_2_partials);
+}
+
+__device__ __forceinline__ m31 numerator_at(m31 *numerators, int index) {
+    // A null pointer stands for a column of ones (LogUp singles).
+    return numerators == nullptr ? 1 : numerators[index];
+}
+
+__device__ __forceinline__ qm31 numerator_at(qm31 *numerators, int index) {
+    return numerators[index];
+}
+
+template<typename T>
+__global__ void logup_sum_kernel(qm31 *eq_evals, T *numerators, qm31 *denominators, int n_terms, qm31 lambda, qm31 *eval_at_0_partials, qm31 *eval_at_2_partials) {
+    // Computes the sums over the boolean hypercube of
+    //      eq(0, x) * (numer(r, {0, 2}, x) + lambda * denom(r, {0, 2}, x))
+    // where numer / denom is the sum of the fractions at (x, 0) and (x, 1).
+    qm31 eval_at_0 = {{0, 0}, {0, 0}};
+    qm31 eval_at_2 = {{0, 0}, {0, 0}};
+
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < n_terms; i += gridDim.x * blockDim.x) {
+        T numer_at_r0i0 = numerator_at(numerators, i * 2);
+        T numer_at_r0i1 = numerator_at(numerators, i * 2 + 1);
+        T numer_at_r1i0 = numerator_at(numerators, (n_terms + i) * 2);
+        T numer_at_r1i1 = numerator_at(numerators, (n_terms + i) * 2 + 1);
+        qm31 denom_at_r0i0 = denominators[i * 2];
+        qm31 denom_at_r0i1 = denominators[i * 2 + 1];
+        qm31 denom_at_r1i0 = denominators[(n_terms + i) * 2];
+        qm31 denom_at_r1i1 = denominators[(n_terms + i) * 2 + 1];
+
+        T numer_at_r2i0 = sub(add(numer_at_r1i0, numer_at_r1i0), numer_at_r0i0);
+        T numer_at_r2i1 = sub(add(numer_at_r1i1, numer_at_r1i1), numer_at_r0i1);
+        qm31 denom_at_r2i0 = sub(add(denom_at_r1i0, denom_at_r1i0), denom_at_r0i0);
+        qm31 denom_at_r2i1 = sub(add(denom_at_r1i1, denom_at_r1i1), denom_at_r0i1);
+
+        qm31 numer_at_r0i = add(mul(denom_at_r0i1, numer_at_r0i0), mul(denom_at_r0i0, numer_at_r0i1));
+        qm31 denom_at_r0i = mul(denom_at_r0i0, denom_at_r0i1);
+        qm31 numer_at_r2i = add(mul(denom_at_r2i1, numer_at_r2i0), mul(denom_at_r2i0, numer_at_r2i1));
+        qm31 denom_at_r2i = mul(denom_at_r2i0, denom_at_r2i1);
+
+        qm31 eq_eval_at_0i = eq_evals[i];
+        eval_at_0 = add(eval_at_0, mul(eq_eval_at_0i, add(numer_at_r0i, mul(lambda, denom_at_r0i))));
+        eval_at_2 = add(eval_at_2, mul(eq_eval_at_0i, add(numer_at_r2i, mul(lambda, denom_at_r2i))));
+    }
+
+    block_reduce_sums(eval_at_0, eval_at_2, eval_at_0_partials, eval_at_2_partials);
+}
+
+int gkr_num_blocks(int n_terms) {
+    int num_blocks = (n_terms + GKR_BLOCK_DIM - 1) / GKR_BLOCK_DIM;
+    return min(num_blocks, GKR_MAX_NUM_BLOCKS);
+}
+
+void reduce_and_copy_to_host(qm31 *partials, int num_blocks, qm31 *result) {
+    // partials: eval_at_0 partials followed by eval_at_2 partials.
+    //   result: host pointer where eval_at_0 and eval_at_2 are written.
+    int shared_memory_bytes = 2 * GKR_BLOCK_DIM * sizeof(qm31);
+    reduce_partials_kernel<<<1, GKR_BLOCK_DIM, shared_memory_bytes>>>(partials, &partials[num_blocks], num_blocks);
+    cudaMemcpy(&result[0], &partials[0], sizeof(qm31), cudaMemcpyDeviceToHost);
+    cudaMemcpy(&result[1], &partials[num_blocks], sizeof(qm31), cudaMemcpyDeviceToHost);
+    cudaFree(partials);
+}
+
+void gkr_grand_product_sum(qm31 *eq_evals, qm31 *input, int n_terms, qm31 *result) {
+    int num_blocks = gkr_num_blocks(n_terms);
+    int shared_memory_bytes = 2 * GKR_BLOCK_DIM * sizeof(qm31);
+    qm31 *partials;
+    cudaMalloc((void**)&partials, 2 * num_blocks * sizeof(qm31));
+
+    grand_product_sum_kernel<<<num_blocks, GKR_BLOCK_DIM, shared_memory_bytes>>>(eq_evals, input, n_terms, partials, &partials[num_blocks]);
+    reduce_and_copy_to_host(partials, num_blocks, result);
+}
+
+void gkr_logup_sum_base_field(qm31 *eq_evals, m31 *numerators, qm31 *denominators, int n_terms, qm31 lambda, qm31 *result) {
+    int num_blocks = gkr_num_blocks(n_terms);
+    int shared_memory_bytes = 2 * GKR_BLOCK_DIM * sizeof(qm31);
+    qm31 *partials;
+    cudaMalloc((void**)&partials, 2 * num_blocks * sizeof(qm31));
+
+    logup_sum_kernel<<<num_blocks, GKR_BLOCK_DIM, shared_memory_bytes>>>(eq_evals, numerators, denominators, n_terms, lambda, partials, &partials[num_blocks]);
+    reduce_and_copy_to_host(partials, num_blocks, result);
+}
+
+void gkr_logup_sum_secure_field(qm31 *eq_evals, qm31 *numerators, qm31 *denominators, int n_terms, qm31 lambda, qm31 *result) {
+    int num_blocks = gkr_num_blocks(n_terms);
+    int shared_memory_bytes = 2 * GKR_BLOCK_DIM * sizeof(qm31);
+    qm31 *partials;
+    cudaMalloc((void**)&partials, 2 * num_blocks * sizeof(qm31));
+
+    logup_sum_kernel<<<num_blocks, GKR_BLOCK_DIM, shared_memory_bytes>>>(eq_evals, numerators, denominators, n_terms, lambda, partials, &partials[num_blocks]);
+    reduce_and_copy_to_host(partials, num_blocks, result);
+}
+
+__global__ void next_grand_product_layer_kernel(qm31 *layer, int size, qm31 *dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = mul(layer[idx * 2], layer[idx * 2 + 1]);
+    }
+}
+
+template<typename T>
+__global__ void next_logup_layer_kernel(T *numerators, qm31 *denominators, int size, qm31 *dst_numerators, qm31 *dst_denominators) {
+    // Adds up the fractions at (x, 0) and (x, 1).
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        T numer_at_i0 = numerator_at(numerators, idx * 2);
+        T numer_at_i1 = numerator_at(numerators, idx * 2 + 1);
+        qm31 denom_at_i0 = denominators[idx * 2];
+        qm31 denom_at_i1 = denominators[idx * 2 + 1];
+        dst_numerators[idx] = add(mul(denom_at_i1, numer_at_i0), mul(denom_at_i0, numer_at_i1));
+        dst_denominators[idx] = mul(denom_at_i0, denom_at_i1);
+    }
+}
+
+void gkr_next_grand_product_layer(qm31 *layer, int size, qm31 *dst) {
+    //  size: number of values of the next layer.
+    int num_blocks = (size + GKR_BLOCK_DIM - 1) / GKR_BLOCK_DIM;
+    next_grand_product_layer_kernel<<<num_blocks, GKR_BLOCK_DIM>>>(layer, size, dst);
+    cudaDeviceSynchronize();
+}
+
+void gkr_next_logup_layer_base_field(m31 *numerators, qm31 *denominators, int size, qm31 *dst_numerators, qm31 *dst_denominators) {
+    int num_blocks = (size + GKR_BLOCK_DIM - 1) / GKR_BLOCK_DIM;
+    next_logup_layer_kernel<<<num_blocks, GKR_BLOCK_DIM>>>(numerators, denominators, size, dst_numerators, dst_denominators);
+    cudaDeviceSynchronize();
+}
+
+void gkr_next_logup_layer_secure_field(qm31 *numerators, qm31 *denominators, int size, qm31 *dst_numerators, qm31 *dst_denominators) {
+    int num_blocks = (size + GKR_BLOCK_DIM - 1) / GKR_BLOCK_DIM;
+    next_logup_layer_kernel<<<num_blocks, GKR_BLOCK_DIM>>>(numerators, denominators, size, dst_numerators, dst_denominators);
+    cudaDeviceSynchronize();
+}
\ No newline at end of file
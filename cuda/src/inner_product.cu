@@ -0,0 +1,79 @@
+#include "../include/inner_product.cuh"
+#include "../include/reduce.cuh"
+#include "../include/scratch.cuh"
+#include "../include/utils.cuh"
+
+__global__ void inner_product_base_field_kernel(m31 *a, m31 *b, int size, m31 *partials) {
+    // Each block adds up the products of a strided slice of the columns.
+    m31 sum = 0;
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        sum = add(sum, mul(a[i], b[i]));
+    }
+    store_block_sum(sum, partials);
+}
+
+__global__ void inner_product_secure_field_kernel(secure_column a, secure_column b, int size, qm31 *partials) {
+    qm31 sum = {{0, 0}, {0, 0}};
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        sum = add(sum, mul(secure_column_at(a, i), secure_column_at(b, i)));
+    }
+    store_block_sum(sum, partials);
+}
+
+m31 inner_product_base_field(m31 *a, m31 *b, int size) {
+    int num_blocks = sum_num_blocks(size);
+    m31 *partials = (m31*) scratch_buffer("inner_product_base_field", log_2(size), num_blocks * sizeof(m31));
+
+    inner_product_base_field_kernel<<<num_blocks, SUM_BLOCK_DIM>>>(a, b, size, partials);
+    sum_partials_kernel<<<1, SUM_BLOCK_DIM>>>(partials, num_blocks);
+    cudaDeviceSynchronize();
+
+    m31 result;
+    cudaMemcpy(&result, partials, sizeof(m31), cudaMemcpyDeviceToHost);
+    return result;
+}
+
+qm31 inner_product_secure_field(m31 **a, m31 **b, int size) {
+    int num_blocks = sum_num_blocks(size);
+    qm31 *partials = (qm31*) scratch_buffer("inner_product_secure_field", log_2(size), num_blocks * sizeof(qm31));
+
+    inner_product_secure_field_kernel<<<num_blocks, SUM_BLOCK_DIM>>>(make_secure_column(a), make_secure_column(b), size, partials);
+    sum_partials_kernel<<<1, SUM_BLOCK_DIM>>>(partials, num_blocks);
+    cudaDeviceSynchronize();
+
+    qm31 result;
+    cudaMemcpy(&result, partials, sizeof(qm31), cudaMemcpyDeviceToHost);
+    return result;
+}
+
+__global__ void mul_add_base_field_kernel(m31 *dst, m31 *a, m31 *b, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = add(dst[idx], mul(a[idx], b[idx]));
+    }
+}
+
+__global__ void mul_add_secure_field_kernel(secure_column dst, secure_column a, secure_column b, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        qm31 product = mul(secure_column_at(a, idx), secure_column_at(b, idx));
+        secure_column_set(dst, idx, add(secure_column_at(dst, idx), product));
+    }
+}
+
+void mul_add_base_field(m31 *dst, m31 *a, m31 *b, int size) {
+    // dst[i] += a[i] * b[i], reading each column once.
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    mul_add_base_field_kernel<<<num_blocks, block_dim>>>(dst, a, b, size);
+    cudaDeviceSynchronize();
+}
+
+void mul_add_secure_field(m31 **dst, m31 **a, m31 **b, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    mul_add_secure_field_kernel<<<num_blocks, block_dim>>>(make_secure_column(dst), make_secure_column(a), make_secure_column(b), size);
+    cudaDeviceSynchronize();
+}
@@ -0,0 +1,128 @@
+#include <cuda.h>
+#include <nvrtc.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "../include/jit.cuh"
+
+int jit_target_architecture() {
+    // Compute capability of the current device, e.g. 89 for sm_89.
+    int device, major, minor;
+    cudaGetDevice(&device);
+    cudaDeviceGetAttribute(&major, cudaDevAttrComputeCapabilityMajor, device);
+    cudaDeviceGetAttribute(&minor, cudaDevAttrComputeCapabilityMinor, device);
+    return major * 10 + minor;
+}
+
+int jit_compile(const char *source, const char *fields_header, char **ptx, char **log) {
+    // Compiles source to PTX for the architecture of the current device.
+    // The generated source may include "fields.cuh", whose content is fields_header.
+    // Returns the nvrtcResult. On success *ptx is set, otherwise *log is set to the NVRTC log if
+    // there is one. Both are freed with jit_free.
+    *ptx = NULL;
+    *log = NULL;
+    const char *header_names[] = {"fields.cuh"};
+    nvrtcProgram program;
+    nvrtcResult result = nvrtcCreateProgram(&program, source, "constraints.cu", 1, &fields_header, header_names);
+    if (result != NVRTC_SUCCESS) {
+        return result;
+    }
+
+    char architecture[64];
+    snprintf(architecture, sizeof(architecture), "--gpu-architecture=compute_%d", jit_target_architecture());
+    const char *options[] = {architecture};
+
+    result = nvrtcCompileProgram(program, 1, options);
+    if (result != NVRTC_SUCCESS) {
+        size_t log_size;
+        if (nvrtcGetProgramLogSize(program, &log_size) == NVRTC_SUCCESS) {
+            *log = (char*)malloc(log_size);
+            nvrtcGetProgramLog(program, *log);
+        }
+        nvrtcDestroyProgram(&program);
+        return result;
+    }
+
+    size_t ptx_size;
+    result = nvrtcGetPTXSize(program, &ptx_size);
+    if (result == NVRTC_SUCCESS) {
+        *ptx = (char*)malloc(ptx_size);
+        result = nvrtcGetPTX(program, *ptx);
+        if (result != NVRTC_SUCCESS) {
+            free(*ptx);
+            *ptx = NULL;
+        }
+    }
+    nvrtcDestroyProgram(&program);
+    return result;
+}
+
+void jit_free(char *ptr) {
+    free(ptr);
+}
+
+int jit_load_module(const char *ptx, void **module) {
+    // Returns the CUresult of loading the module.
+    // Make sure the primary context used by the runtime API is current.
+    cudaFree(0);
+    return cuModuleLoadData((CUmodule*) module, ptx);
+}
+
+int jit_get_function(void *module, const char *name, void **function) {
+    return cuModuleGetFunction((CUfunction*) function, (CUmodule) module, name);
+}
+
+void jit_unload_module(void *module) {
+    cuModuleUnload((CUmodule) module);
+}
+
+int jit_launch_constraint_kernel(void *function, m31 **columns, int n_columns, qm31 *random_coeff_powers, m31 **accumulator, int size) {
+    // columns    : host array with the device pointers of the trace columns.
+    // accumulator: host array with the device pointers of the 4 accumulator coordinates.
+    // Returns the CUresult of the launch, or of the kernel if it launched.
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * n_columns);
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    m31 **device_accumulator;
+    cudaMalloc((void**)&device_accumulator, sizeof(m31*) * 4);
+    cudaMemcpy(device_accumulator, accumulator, sizeof(m31*) * 4, cudaMemcpyHostToDevice);
+
+    void *args[] = {&device_columns, &random_coeff_powers, &device_accumulator, &size};
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    CUresult result = cuLaunchKernel((CUfunction) function, num_blocks, 1, 1, block_dim, 1, 1, 0, NULL, args, NULL);
+    if (result == CUDA_SUCCESS) {
+        result = cuCtxSynchronize();
+    }
+
+    cudaFree(device_columns);
+    cudaFree(device_accumulator);
+    return result;
+}
+
+int jit_launch_check_kernel(void *function, m31 **columns, int n_columns, int size, uint64_t *first_failure) {
+    // columns: host array with the device pointers of the trace columns.
+    // Sets *first_failure to row * n_constraints + constraint for the first failing pair, or
+    // UINT64_MAX, and returns the CUresult of the launch, or of the kernel if it launched.
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * n_columns);
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    uint64_t *device_first_failure;
+    cudaMalloc((void**)&device_first_failure, sizeof(uint64_t));
+    cudaMemset(device_first_failure, 0xff, sizeof(uint64_t));
+
+    void *args[] = {&device_columns, &device_first_failure, &size};
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    CUresult result = cuLaunchKernel((CUfunction) function, num_blocks, 1, 1, block_dim, 1, 1, 0, NULL, args, NULL);
+    if (result == CUDA_SUCCESS) {
+        result = cuCtxSynchronize();
+    }
+
+    cudaMemcpy(first_failure, device_first_failure, sizeof(uint64_t), cudaMemcpyDeviceToHost);
+    cudaFree(device_columns);
+    cudaFree(device_first_failure);
+    return result;
+}
\ No newline at end of file
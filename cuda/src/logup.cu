@@ -0,0 +1,130 @@
+#include "../include/logup.cuh"
+#include "../include/utils.cuh"
+
+__global__ void add_fractions_kernel(qm31 *numerators_a, qm31 *denominators_a, qm31 *numerators_b, qm31 *denominators_b, qm31 *dst_numerators, qm31 *dst_denominators, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        add_fraction(
+            numerators_a[idx], denominators_a[idx],
+            numerators_b[idx], denominators_b[idx],
+            dst_numerators[idx], dst_denominators[idx]
+        );
+    }
+}
+
+void add_fractions(qm31 *numerators_a, qm31 *denominators_a, qm31 *numerators_b, qm31 *denominators_b, qm31 *dst_numerators, qm31 *dst_denominators, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    add_fractions_kernel<<<num_blocks, block_dim>>>(numerators_a, denominators_a, numerators_b, denominators_b, dst_numerators, dst_denominators, size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void fractions_up_sweep_kernel(qm31 *numerators, qm31 *denominators, int size, int stride) {
+    // Each thread adds the fraction at the end of the left half of its
+    // subtree into the fraction at the end of the right half.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int right = (idx + 1) * stride - 1;
+
+    if (right < size) {
+        int left = right - (stride >> 1);
+        add_fraction(
+            numerators[left], denominators[left],
+            numerators[right], denominators[right],
+            numerators[right], denominators[right]
+        );
+    }
+}
+
+__global__ void fractions_down_sweep_kernel(qm31 *numerators, qm31 *denominators, int size, int stride) {
+    // Propagates the partial sums computed in the up-sweep to the
+    // middle of each subtree.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int left = (idx + 1) * stride - 1;
+    int right = left + (stride >> 1);
+
+    if (right < size) {
+        add_fraction(
+            numerators[left], denominators[left],
+            numerators[right], denominators[right],
+            numerators[right], denominators[right]
+        );
+    }
+}
+
+void fractions_cumulative_sum(qm31 *numerators, qm31 *denominators, int size) {
+    // In-place inclusive scan (work-efficient, Blelloch style) of the fractions
+    // numerators[i] / denominators[i]. Sizes need not be powers of two.
+    int block_dim = 256;
+
+    int stride = 2;
+    while (stride <= size) {
+        int num_threads = size / stride;
+        int num_blocks = (num_threads + block_dim - 1) / block_dim;
+        fractions_up_sweep_kernel<<<num_blocks, block_dim>>>(numerators, denominators, size, stride);
+        stride <<= 1;
+    }
+
+    stride >>= 1;
+    while (stride >= 2) {
+        int num_threads = size / stride;
+        int num_blocks = (num_threads + block_dim - 1) / block_dim;
+        fractions_down_sweep_kernel<<<num_blocks, block_dim>>>(numerators, denominators, size, stride);
+        stride >>= 1;
+    }
+    cudaDeviceSynchronize();
+}
+
+// Tables up to this size are counted in shared memory by each block first, so that atomics on
+// popular entries don't all contend on the same global word.
+const int SHARED_HISTOGRAM_MAX_SIZE = 8192;
+
+__global__ void shared_histogram_kernel(m31 *column, int size, uint32_t *multiplicities, int table_size) {
+    __shared__ uint32_t s_counts[SHARED_HISTOGRAM_MAX_SIZE];
+    for (int i = threadIdx.x; i < table_size; i += blockDim.x) {
+        s_counts[i] = 0;
+    }
+    __syncthreads();
+
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        m31 value = column[i];
+        if (value < (m31) table_size) {
+            atomicAdd(&s_counts[value], 1u);
+        }
+    }
+    __syncthreads();
+
+    for (int i = threadIdx.x; i < table_size; i += blockDim.x) {
+        if (s_counts[i] != 0) {
+            atomicAdd(&multiplicities[i], s_counts[i]);
+        }
+    }
+}
+
+__global__ void global_histogram_kernel(m31 *column, int size, uint32_t *multiplicities, int table_size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        m31 value = column[idx];
+        if (value < (m31) table_size) {
+            atomicAdd(&multiplicities[value], 1u);
+        }
+    }
+}
+
+void accumulate_multiplicities(m31 *column, int size, m31 *multiplicities, int table_size) {
+    // Adds to multiplicities[v] the number of occurrences of v in the column. Values outside the
+    // table are ignored. Counts are below 2^31, so they are already reduced field elements.
+    // One of the two histogram kernels above runs, depending on whether the table fits in shared
+    // memory.
+    int block_dim = 256;
+    if (table_size <= SHARED_HISTOGRAM_MAX_SIZE) {
+        int num_blocks = min((size + block_dim - 1) / block_dim, 1024);
+        num_blocks = max(num_blocks, 1);
+        shared_histogram_kernel<<<num_blocks, block_dim>>>(column, size, multiplicities, table_size);
+    } else {
+        int num_blocks = (size + block_dim - 1) / block_dim;
+        global_histogram_kernel<<<num_blocks, block_dim>>>(column, size, multiplicities, table_size);
+    }
+    cudaDeviceSynchronize();
+}
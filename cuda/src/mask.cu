@@ -0,0 +1,18 @@
+#include "../include/mask.cuh"
+#include "../include/utils.cuh"
+
+__global__ void gather_mask_kernel(m31 *column, m31 *dst, int trace_log_size, int eval_log_size, int offset) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < (1 << eval_log_size)) {
+        dst[idx] = column[offset_bit_reversed_circle_domain_index(idx, trace_log_size, eval_log_size, offset)];
+    }
+}
+
+void gather_mask_base_field(m31 *column, m31 *dst, int trace_log_size, int eval_log_size, int offset) {
+    int size = 1 << eval_log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gather_mask_kernel<<<num_blocks, block_dim>>>(column, dst, trace_log_size, eval_log_size, offset);
+    cudaDeviceSynchronize();
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+#include "../include/mle.cuh"
+#include "../include/utils.cuh"
+
+template<typename T>
+__global__ void fix_first_variable_kernel(T *evals, int half_size, qm31 assignment, qm31 *dst) {
+    // Fixes the first (most significant) variable of a multilinear extension.
+    //      evals: evaluations over the boolean hypercube, of size 2 * half_size.
+    // assignment: value assigned to the first variable.
+    //        dst: evaluations of the resulting MLE, of size half_size.
+    //
+    // dst[i] = lhs[i] + assignment * (rhs[i] - lhs[i]), where lhs and rhs are
+    // the lower and upper halves of evals.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < half_size) {
+        T lhs = evals[idx];
+        T rhs = evals[idx + half_size];
+        dst[idx] = add(mul(assignment, sub(rhs, lhs)), lhs);
+    }
+}
+
+void fix_first_variable_base_field(m31 *evals, int evals_size, qm31 assignment, qm31 *dst) {
+    int half_size = evals_size >> 1;
+    int block_dim = 256;
+    int num_blocks = (half_size + block_dim - 1) / block_dim;
+    fix_first_variable_kernel<<<num_blocks, block_dim>>>(evals, half_size, assignment, dst);
+    cudaDeviceSynchronize();
+}
+
+void fix_first_variable_secure_field(qm31 *evals, int evals_size, qm31 assignment, qm31 *dst) {
+    int half_size = evals_size >> 1;
+    int block_dim = 256;
+    int num_blocks = (half_size + block_dim - 1) / block_dim;
+    fix_first_variable_kernel<<<num_blocks, block_dim>>>(evals, half_size, assignment, dst);
+    cudaDeviceSynchronize();
+}
+
+__global__ void gen_eq_evals_kernel(qm31 *y, int y_size, qm31 v, qm31 *dst, int size) {
+    // Computes eq(x, y) * v for every x in the boolean hypercube {0, 1}^y_size.
+    // The first coordinate of y corresponds to the most significant bit of the index.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        qm31 result = v;
+        for (int i = 0; i < y_size; i++) {
+            qm31 y_i = y[i];
+            if ((idx >> (y_size - 1 - i)) & 1) {
+                result = mul(result, y_i);
+            } else {
+                result = mul(result, sub(m31{ 1 }, y_i));
+            }
+        }
+        dst[idx] = result;
+    }
+}
+
+void gen_eq_evals(qm31 *y, int y_size, qm31 v, qm31 *dst) {
+    int size = 1 << y_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gen_eq_evals_kernel<<<num_blocks, block_dim>>>(y, y_size, v, dst, size);
+    cudaDeviceSynchronize();
+}
\ No newline at end of file
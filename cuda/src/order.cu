@@ -0,0 +1,101 @@
+#include "../include/order.cuh"
+#include "../include/utils.cuh"
+
+// Position idx of a column in bit reversed circle domain order, as committed to, holds the
+// value of row bit_reversed_circle_domain_index_to_coset_index(idx) of the trace.
+
+__global__ void natural_to_circle_domain_order_kernel(m31 *column, m31 *dst, int log_size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < (1 << log_size)) {
+        dst[idx] = column[bit_reversed_circle_domain_index_to_coset_index(idx, log_size)];
+    }
+}
+
+__global__ void circle_domain_to_natural_order_kernel(m31 *column, m31 *dst, int log_size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < (1 << log_size)) {
+        dst[bit_reversed_circle_domain_index_to_coset_index(idx, log_size)] = column[idx];
+    }
+}
+
+void natural_to_circle_domain_order(m31 *column, m31 *dst, int log_size) {
+    int size = 1 << log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    natural_to_circle_domain_order_kernel<<<num_blocks, block_dim>>>(column, dst, log_size);
+    cudaDeviceSynchronize();
+}
+
+void circle_domain_to_natural_order(m31 *column, m31 *dst, int log_size) {
+    int size = 1 << log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    circle_domain_to_natural_order_kernel<<<num_blocks, block_dim>>>(column, dst, log_size);
+    cudaDeviceSynchronize();
+}
+
+// Arbitrary permutations given by an index column of size values: gathers set dst[i] to
+// column[indices[i]] and scatters set dst[indices[i]] to column[i]. The callers check that the
+// indices are in 0..size; the kernels only skip the others to stay in bounds.
+
+__global__ void gather_base_field_kernel(m31 *column, uint32_t *indices, int size, m31 *dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && indices[idx] < size) {
+        dst[idx] = column[indices[idx]];
+    }
+}
+
+__global__ void gather_secure_field_kernel(secure_column column, uint32_t *indices, int size, secure_column dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && indices[idx] < size) {
+        secure_column_set(dst, idx, secure_column_at(column, indices[idx]));
+    }
+}
+
+__global__ void scatter_base_field_kernel(m31 *column, uint32_t *indices, int size, m31 *dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && indices[idx] < size) {
+        dst[indices[idx]] = column[idx];
+    }
+}
+
+__global__ void scatter_secure_field_kernel(secure_column column, uint32_t *indices, int size, secure_column dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && indices[idx] < size) {
+        secure_column_set(dst, indices[idx], secure_column_at(column, idx));
+    }
+}
+
+void gather_base_field(m31 *column, uint32_t *indices, int size, m31 *dst) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gather_base_field_kernel<<<num_blocks, block_dim>>>(column, indices, size, dst);
+    cudaDeviceSynchronize();
+}
+
+void gather_secure_field(m31 **column, uint32_t *indices, int size, m31 **dst) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gather_secure_field_kernel<<<num_blocks, block_dim>>>(make_secure_column(column), indices, size, make_secure_column(dst));
+    cudaDeviceSynchronize();
+}
+
+void scatter_base_field(m31 *column, uint32_t *indices, int size, m31 *dst) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    scatter_base_field_kernel<<<num_blocks, block_dim>>>(column, indices, size, dst);
+    cudaDeviceSynchronize();
+}
+
+void scatter_secure_field(m31 **column, uint32_t *indices, int size, m31 **dst) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    scatter_secure_field_kernel<<<num_blocks, block_dim>>>(make_secure_column(column), indices, size, make_secure_column(dst));
+    cudaDeviceSynchronize();
+}
@@ -0,0 +1,22 @@
+#include "../include/padding.cuh"
+
+__global__ void pad_base_field_kernel(m31 *column, int size, m31 *dst, int dst_size, bool repeat) {
+    // Copies the column to the start of dst and fills the rest with zeros,
+    // or with the column repeated as many times as needed.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < dst_size) {
+        if (idx < size) {
+            dst[idx] = column[idx];
+        } else {
+            dst[idx] = repeat ? column[idx % size] : 0;
+        }
+    }
+}
+
+void pad_base_field(m31 *column, int size, m31 *dst, int dst_size, bool repeat) {
+    int block_dim = 256;
+    int num_blocks = (dst_size + block_dim - 1) / block_dim;
+    pad_base_field_kernel<<<num_blocks, block_dim>>>(column, size, dst, dst_size, repeat);
+    cudaDeviceSynchronize();
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+#include "../include/point.cuh"
+#include "../include/batch_inverse.cuh"
+#include "../include/utils.cuh"
+
+// The group operation of the circle is written multiplicatively here:
+// point_mul adds two points, point_square doubles and point_pow multiplies by a scalar.
+
+template<typename T, typename P>
+__global__ void add_points_kernel(T *lhs_x, T *lhs_y, T *rhs_x, T *rhs_y, T *dst_x, T *dst_y, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        P lhs = {lhs_x[idx], lhs_y[idx]};
+        P rhs = {rhs_x[idx], rhs_y[idx]};
+        P result = point_mul(lhs, rhs);
+        dst_x[idx] = result.x;
+        dst_y[idx] = result.y;
+    }
+}
+
+template<typename T, typename P>
+__global__ void double_points_kernel(T *x, T *y, T *dst_x, T *dst_y, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        P p = {x[idx], y[idx]};
+        P result = point_square(p);
+        dst_x[idx] = result.x;
+        dst_y[idx] = result.y;
+    }
+}
+
+__device__ __forceinline__ point identity(point) {
+    return one();
+}
+
+__device__ __forceinline__ secure_point identity(secure_point) {
+    return secure_one();
+}
+
+template<typename T, typename P>
+__global__ void mul_points_kernel(T *x, T *y, uint32_t scalar, T *dst_x, T *dst_y, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        P p = {x[idx], y[idx]};
+        P result = identity(p);
+        // The scalar may not fit in the int exponent of point_pow.
+        for (uint32_t exponent = scalar; exponent > 0; exponent >>= 1) {
+            if (exponent & 1) {
+                result = point_mul(p, result);
+            }
+            p = point_square(p);
+        }
+        dst_x[idx] = result.x;
+        dst_y[idx] = result.y;
+    }
+}
+
+__global__ void coset_points_kernel(point initial, point step, m31 *dst_x, m31 *dst_y, int size) {
+    // Point idx of the coset is initial + idx * step.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        point offset = point_pow(step, idx);
+        point result = point_mul(initial, offset);
+        dst_x[idx] = result.x;
+        dst_y[idx] = result.y;
+    }
+}
+
+void add_points(m31 *lhs_x, m31 *lhs_y, m31 *rhs_x, m31 *rhs_y, m31 *dst_x, m31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    add_points_kernel<m31, point><<<num_blocks, block_dim>>>(lhs_x, lhs_y, rhs_x, rhs_y, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void double_points(m31 *x, m31 *y, m31 *dst_x, m31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    double_points_kernel<m31, point><<<num_blocks, block_dim>>>(x, y, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void mul_points(m31 *x, m31 *y, uint32_t scalar, m31 *dst_x, m31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    mul_points_kernel<m31, point><<<num_blocks, block_dim>>>(x, y, scalar, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void add_secure_points(qm31 *lhs_x, qm31 *lhs_y, qm31 *rhs_x, qm31 *rhs_y, qm31 *dst_x, qm31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    add_points_kernel<qm31, secure_point><<<num_blocks, block_dim>>>(lhs_x, lhs_y, rhs_x, rhs_y, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void double_secure_points(qm31 *x, qm31 *y, qm31 *dst_x, qm31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    double_points_kernel<qm31, secure_point><<<num_blocks, block_dim>>>(x, y, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void mul_secure_points(qm31 *x, qm31 *y, uint32_t scalar, qm31 *dst_x, qm31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    mul_points_kernel<qm31, secure_point><<<num_blocks, block_dim>>>(x, y, scalar, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+void coset_points(point initial, point step, m31 *dst_x, m31 *dst_y, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    coset_points_kernel<<<num_blocks, block_dim>>>(initial, step, dst_x, dst_y, size);
+    cudaDeviceSynchronize();
+}
+
+__device__ __forceinline__ point circle_domain_point_at(point half_coset_initial, point half_coset_step, int log_size, int row) {
+    // The point of position row of the bit reversed circle domain of the given half coset.
+    // The second half of the circle domain is the conjugate of the half coset.
+    int index = bit_reverse(row, log_size);
+    int half_size = 1 << (log_size - 1);
+    point offset = point_pow(half_coset_step, index < half_size ? index : index - half_size);
+    point p = point_mul(half_coset_initial, offset);
+    if (index >= half_size) {
+        p.y = neg(p.y);
+    }
+    return p;
+}
+
+__global__ void coset_vanishing_kernel(point half_coset_initial, point half_coset_step, int log_size, point shift, int log_coset_size, m31 *dst) {
+    // dst[row] is the vanishing polynomial of a coset at the point of position row of the bit
+    // reversed circle domain of the given half coset. As in stwo's coset_vanishing, the point is
+    // moved by shift = step_size.half() - coset.initial, which maps the coset onto the points
+    // whose x coordinate vanishes after log_coset_size - 1 doublings.
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+    int size = 1 << log_size;
+
+    if (row < size) {
+        point p = circle_domain_point_at(half_coset_initial, half_coset_step, log_size, row);
+        p = point_mul(p, shift);
+        m31 x = p.x;
+        for (int i = 1; i < log_coset_size; i++) {
+            m31 square = mul(x, x);
+            x = sub(add(square, square), 1);
+        }
+        dst[row] = x;
+    }
+}
+
+void coset_vanishing(point half_coset_initial, point half_coset_step, int log_size, point shift, int log_coset_size, m31 *dst) {
+    int size = 1 << log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    coset_vanishing_kernel<<<num_blocks, block_dim>>>(half_coset_initial, half_coset_step, log_size, shift, log_coset_size, dst);
+    cudaDeviceSynchronize();
+}
+
+__global__ void pair_vanishing_denominators_kernel(point half_coset_initial, point half_coset_step, int log_size, qm31 sample_x, qm31 sample_y, m31 *dst_a, m31 *dst_b, m31 *norms) {
+    // The denominator of the quotient by a sample point and its complex conjugate at each row, as
+    // in accumulate_quotients: it vanishes on the line through both points. Its norm, whose
+    // inverse gives that of the denominator, is also written when norms isn't null.
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (row < (1 << log_size)) {
+        point p = circle_domain_point_at(half_coset_initial, half_coset_step, log_size, row);
+        cm31 dx = {sub(sample_x.a.a, p.x), sample_x.a.b};
+        cm31 dy = {sub(sample_y.a.a, p.y), sample_y.a.b};
+        cm31 denominator = sub(mul(dx, sample_y.b), mul(dy, sample_x.b));
+        dst_a[row] = denominator.a;
+        dst_b[row] = denominator.b;
+        if (norms != nullptr) {
+            norms[row] = add(mul(denominator.a, denominator.a), mul(denominator.b, denominator.b));
+        }
+    }
+}
+
+__global__ void scale_by_inverse_norms_kernel(m31 *dst_a, m31 *dst_b, m31 *inverse_norms, int size) {
+    // The inverse of a + bi is (a - bi) / (a^2 + b^2).
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (row < size) {
+        dst_a[row] = mul(dst_a[row], inverse_norms[row]);
+        dst_b[row] = mul(neg(dst_b[row]), inverse_norms[row]);
+    }
+}
+
+void pair_vanishing_denominators(point half_coset_initial, point half_coset_step, int log_size, qm31 sample_x, qm31 sample_y, m31 *dst_a, m31 *dst_b) {
+    //  dst_a, dst_b: the real and imaginary parts of the denominators.
+    int size = 1 << log_size;
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    pair_vanishing_denominators_kernel<<<num_blocks, block_dim>>>(half_coset_initial, half_coset_step, log_size, sample_x, sample_y, dst_a, dst_b, nullptr);
+    cudaDeviceSynchronize();
+}
+
+void pair_vanishing_denominator_inverses(point half_coset_initial, point half_coset_step, int log_size, qm31 sample_x, qm31 sample_y, m31 *dst_a, m31 *dst_b) {
+    // Same as pair_vanishing_denominators, inverting the denominators with a single batch
+    // inverse of their norms.
+    int size = 1 << log_size;
+    m31 *norms;
+    cudaMalloc((void**)&norms, sizeof(m31) * 2 * size);
+    m31 *inverse_norms = &norms[size];
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    pair_vanishing_denominators_kernel<<<num_blocks, block_dim>>>(half_coset_initial, half_coset_step, log_size, sample_x, sample_y, dst_a, dst_b, norms);
+    batch_inverse_base_field(norms, inverse_norms, size);
+    scale_by_inverse_norms_kernel<<<num_blocks, block_dim>>>(dst_a, dst_b, inverse_norms, size);
+    cudaDeviceSynchronize();
+
+    cudaFree(norms);
+}
@@ -0,0 +1,55 @@
+#include "../include/preprocessed.cuh"
+#include "../include/utils.cuh"
+
+__global__ void tile_kernel(m31 *pattern, int pattern_size, m31 *dst, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = pattern[idx % pattern_size];
+    }
+}
+
+void tile_base_field(m31 *pattern, int pattern_size, m31 *dst, int size) {
+    int block_dim = 1024;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    tile_kernel<<<num_blocks, block_dim>>>(pattern, pattern_size, dst, size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void gen_step_selector_kernel(m31 *dst, int log_size, int step, int offset) {
+    // dst[i] = 1 if the trace row of position i is congruent to offset modulo step, 0 otherwise.
+    // dst is in bit reversed circle domain order.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < (1 << log_size)) {
+        uint32_t row = bit_reversed_circle_domain_index_to_coset_index(idx, log_size);
+        dst[idx] = row % step == offset;
+    }
+}
+
+void gen_step_selector(m31 *dst, int log_size, int step, int offset) {
+    int size = 1 << log_size;
+    int block_dim = 1024;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gen_step_selector_kernel<<<num_blocks, block_dim>>>(dst, log_size, step, offset);
+    cudaDeviceSynchronize();
+}
+
+__global__ void gen_row_bits_kernel(m31 *dst, int log_size, int shift, uint32_t mask) {
+    // dst[i] = (row >> shift) & mask, where row is the trace row of position i: the row index
+    // itself, one of its bits or one of its limbs. dst is in bit reversed circle domain order.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < (1 << log_size)) {
+        uint32_t row = bit_reversed_circle_domain_index_to_coset_index(idx, log_size);
+        dst[idx] = (row >> shift) & mask;
+    }
+}
+
+void gen_row_bits(m31 *dst, int log_size, int shift, uint32_t mask) {
+    int size = 1 << log_size;
+    int block_dim = 1024;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gen_row_bits_kernel<<<num_blocks, block_dim>>>(dst, log_size, shift, mask);
+    cudaDeviceSynchronize();
+}
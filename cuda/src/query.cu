@@ -0,0 +1,95 @@
+#include "../include/query.cuh"
+
+__global__ void gather_query_values_kernel(m31 **columns, int n_columns, uint32_t *positions, int n_positions, m31 *result) {
+    // Thread idx copies the value of column idx / n_positions at the queried
+    // position idx % n_positions, so the result is laid out column by column.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_columns * n_positions) {
+        int column = idx / n_positions;
+        int query = idx % n_positions;
+        result[idx] = columns[column][positions[query]];
+    }
+}
+
+__global__ void gather_authentication_paths_kernel(uint32_t **layers, int n_layers, uint32_t *positions, int n_positions, uint32_t *result) {
+    // Thread idx copies the sibling, at depth idx % depth above the leaves, of the
+    // path of query idx / depth. Layers are ordered from the root to the leaves.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int depth = n_layers - 1;
+
+    if (idx < n_positions * depth) {
+        int query = idx / depth;
+        int level = idx % depth;
+        uint32_t *layer = layers[depth - level];
+        uint32_t sibling = (positions[query] >> level) ^ 1;
+        for (int i = 0; i < 8; i++) {
+            result[8 * idx + i] = layer[8 * sibling + i];
+        }
+    }
+}
+
+void gather_query_values(m31 **columns, int n_columns, uint32_t *positions, int n_positions, m31 *result) {
+    //   columns: host array with the device pointers of the columns.
+    // positions: host array with the queried positions.
+    //    result: host buffer of n_columns * n_positions values.
+    int size = n_columns * n_positions;
+    if (size == 0) {
+        return;
+    }
+
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * n_columns);
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    uint32_t *device_positions;
+    cudaMalloc((void**)&device_positions, sizeof(uint32_t) * n_positions);
+    cudaMemcpy(device_positions, positions, sizeof(uint32_t) * n_positions, cudaMemcpyHostToDevice);
+
+    m31 *device_result;
+    cudaMalloc((void**)&device_result, sizeof(m31) * size);
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gather_query_values_kernel<<<num_blocks, block_dim>>>(device_columns, n_columns, device_positions, n_positions, device_result);
+    cudaDeviceSynchronize();
+
+    // A single transfer for all the queried values.
+    cudaMemcpy(result, device_result, sizeof(m31) * size, cudaMemcpyDeviceToHost);
+
+    cudaFree(device_columns);
+    cudaFree(device_positions);
+    cudaFree(device_result);
+}
+
+void gather_authentication_paths(uint32_t **layers, int n_layers, uint32_t *positions, int n_positions, uint32_t *result) {
+    //    layers: host array with the device pointers of the tree layers, root first.
+    // positions: host array with the queried leaf positions.
+    //    result: host buffer of n_positions * (n_layers - 1) hashes.
+    int size = n_positions * (n_layers - 1);
+    if (size == 0) {
+        return;
+    }
+
+    uint32_t **device_layers;
+    cudaMalloc((void**)&device_layers, sizeof(uint32_t*) * n_layers);
+    cudaMemcpy(device_layers, layers, sizeof(uint32_t*) * n_layers, cudaMemcpyHostToDevice);
+
+    uint32_t *device_positions;
+    cudaMalloc((void**)&device_positions, sizeof(uint32_t) * n_positions);
+    cudaMemcpy(device_positions, positions, sizeof(uint32_t) * n_positions, cudaMemcpyHostToDevice);
+
+    uint32_t *device_result;
+    cudaMalloc((void**)&device_result, sizeof(uint32_t) * 8 * size);
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    gather_authentication_paths_kernel<<<num_blocks, block_dim>>>(device_layers, n_layers, device_positions, n_positions, device_result);
+    cudaDeviceSynchronize();
+
+    cudaMemcpy(result, device_result, sizeof(uint32_t) * 8 * size, cudaMemcpyDeviceToHost);
+
+    cudaFree(device_layers);
+    cudaFree(device_positions);
+    cudaFree(device_result);
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+#include "../include/quotient.cuh"
+#include "../include/utils.cuh"
+
+__device__ __forceinline__ qm31 mul(qm31 x, cm31 y) {
+    return {mul(x.a, y), mul(x.b, y)};
+}
+
+__device__ __forceinline__ cm31 sub(cm31 x, m31 y) {
+    return {sub(x.a, y), x.b};
+}
+
+__global__ void accumulate_quotients_kernel(
+    m31 *half_coset_x, m31 *half_coset_y, int log_size,
+    m31 **columns,
+    qm31 *sample_points_x, qm31 *sample_points_y, qm31 *batch_random_coeffs, int *batch_sizes, int n_batches,
+    int *column_indices, qm31 *line_coeffs,
+    secure_column result
+) {
+    // Same as the CPU backend's accumulate_row_quotients, for each row of the bit reversed
+    // evaluation. The line coefficients (a, b, c) of the samples are stored in batch order.
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+    int size = 1 << log_size;
+
+    if (row < size) {
+        // The second half of the circle domain is the conjugate of the half coset.
+        int index = bit_reverse(row, log_size);
+        int half_size = size >> 1;
+        m31 x = half_coset_x[index < half_size ? index : index - half_size];
+        m31 y = half_coset_y[index < half_size ? index : index - half_size];
+        if (index >= half_size) {
+            y = neg(y);
+        }
+
+        qm31 row_accumulator = {{0, 0}, {0, 0}};
+        int sample = 0;
+        for (int batch = 0; batch < n_batches; batch++) {
+            qm31 numerator = {{0, 0}, {0, 0}};
+            for (int i = 0; i < batch_sizes[batch]; i++, sample++) {
+                qm31 a = line_coeffs[3 * sample];
+                qm31 b = line_coeffs[3 * sample + 1];
+                qm31 c = line_coeffs[3 * sample + 2];
+                qm31 value = mul(c, columns[column_indices[sample]][row]);
+                numerator = add(numerator, sub(value, add(mul(a, y), b)));
+            }
+
+            // The denominator vanishes on the line through the sample point and its conjugate.
+            qm31 point_x = sample_points_x[batch];
+            qm31 point_y = sample_points_y[batch];
+            cm31 denominator = sub(mul(sub(point_x.a, x), point_y.b), mul(sub(point_y.a, y), point_x.b));
+            row_accumulator = add(mul(row_accumulator, batch_random_coeffs[batch]), mul(numerator, inv(denominator)));
+        }
+        secure_column_set(result, row, row_accumulator);
+    }
+}
+
+void accumulate_quotients(
+    m31 *half_coset_x, m31 *half_coset_y, int log_size,
+    m31 **columns, int n_columns,
+    qm31 *sample_points_x, qm31 *sample_points_y, qm31 *batch_random_coeffs, int *batch_sizes, int n_batches,
+    int *column_indices, qm31 *line_coeffs, int n_samples,
+    m31 **result
+) {
+    //  columns, result: host arrays of device pointers.
+    //  Everything else describing the samples is a host array, copied to the device here.
+    m31 **device_columns;
+    cudaMalloc((void**)&device_columns, sizeof(m31*) * n_columns);
+    cudaMemcpy(device_columns, columns, sizeof(m31*) * n_columns, cudaMemcpyHostToDevice);
+
+    qm31 *device_batches;
+    cudaMalloc((void**)&device_batches, sizeof(qm31) * 3 * n_batches);
+    cudaMemcpy(device_batches, sample_points_x, sizeof(qm31) * n_batches, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_batches[n_batches], sample_points_y, sizeof(qm31) * n_batches, cudaMemcpyHostToDevice);
+    cudaMemcpy(&device_batches[2 * n_batches], batch_random_coeffs, sizeof(qm31) * n_batches, cudaMemcpyHostToDevice);
+
+    int *device_batch_sizes;
+    cudaMalloc((void**)&device_batch_sizes, sizeof(int) * n_batches);
+    cudaMemcpy(device_batch_sizes, batch_sizes, sizeof(int) * n_batches, cudaMemcpyHostToDevice);
+
+    int *device_column_indices;
+    cudaMalloc((void**)&device_column_indices, sizeof(int) * n_samples);
+    cudaMemcpy(device_column_indices, column_indices, sizeof(int) * n_samples, cudaMemcpyHostToDevice);
+
+    qm31 *device_line_coeffs;
+    cudaMalloc((void**)&device_line_coeffs, sizeof(qm31) * 3 * n_samples);
+    cudaMemcpy(device_line_coeffs, line_coeffs, sizeof(qm31) * 3 * n_samples, cudaMemcpyHostToDevice);
+
+    int block_dim = 256;
+    int num_blocks = ((1 << log_size) + block_dim - 1) / block_dim;
+    accumulate_quotients_kernel<<<num_blocks, block_dim>>>(
+        half_coset_x, half_coset_y, log_size,
+        device_columns,
+        device_batches, &device_batches[n_batches], &device_batches[2 * n_batches], device_batch_sizes, n_batches,
+        device_column_indices, device_line_coeffs,
+        make_secure_column(result)
+    );
+    cudaDeviceSynchronize();
+
+    cudaFree(device_columns);
+    cudaFree(device_batches);
+    cudaFree(device_batch_sizes);
+    cudaFree(device_column_indices);
+    cudaFree(device_line_coeffs);
+}
\ No newline at end of file
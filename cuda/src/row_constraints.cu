@@ -0,0 +1,23 @@
+#include "../include/row_constraints.cuh"
+
+// Fibonacci-like component used by the tests: a(row + 2) = a(row)^2 + a(row + 1)^2, read through
+// the mask [(0, 0), (0, 1), (0, 2)].
+struct example_evaluator {
+    static __device__ __forceinline__ qm31 evaluate(const m31 *mask, const qm31 *random_coeff_powers) {
+        m31 constraint = sub(mask[2], add(mul(mask[0], mask[0]), mul(mask[1], mask[1])));
+        return mul(random_coeff_powers[0], constraint);
+    }
+};
+
+DEFINE_ROW_CONSTRAINTS(example_row_constraints, 3, example_evaluator)
+
+// The constraint b(row) = a(row)^2 of SquaresComponent on the Rust side, read through the mask
+// [(0, 0), (1, 0)]. It is only proved by the smoke tests of the C and Python interfaces.
+struct squares_evaluator {
+    static __device__ __forceinline__ qm31 evaluate(const m31 *mask, const qm31 *random_coeff_powers) {
+        m31 constraint = sub(mask[1], mul(mask[0], mask[0]));
+        return mul(random_coeff_powers[0], constraint);
+    }
+};
+
+DEFINE_ROW_CONSTRAINTS(squares_row_constraints, 2, squares_evaluator)
\ No newline at end of file
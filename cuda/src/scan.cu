@@ -0,0 +1,82 @@
+#include "../include/scan.cuh"
+
+// Associative operations the scans are specialized for.
+struct sum_op {
+    template<typename T>
+    __device__ __forceinline__ T operator()(T a, T b) const {
+        return add(a, b);
+    }
+};
+
+struct product_op {
+    template<typename T>
+    __device__ __forceinline__ T operator()(T a, T b) const {
+        return mul(a, b);
+    }
+};
+
+template<typename T, typename Op>
+__global__ void up_sweep_kernel(T *values, int size, int stride, Op op) {
+    // Each thread combines the value at the end of the left half of its
+    // subtree into the value at the end of the right half.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int right = (idx + 1) * stride - 1;
+
+    if (right < size) {
+        int left = right - (stride >> 1);
+        values[right] = op(values[left], values[right]);
+    }
+}
+
+template<typename T, typename Op>
+__global__ void down_sweep_kernel(T *values, int size, int stride, Op op) {
+    // Propagates the partial results computed in the up-sweep to the
+    // middle of each subtree.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+    int left = (idx + 1) * stride - 1;
+    int right = left + (stride >> 1);
+
+    if (right < size) {
+        values[right] = op(values[left], values[right]);
+    }
+}
+
+template<typename T, typename Op>
+void inclusive_scan(T *values, int size, Op op) {
+    // In-place inclusive scan (work-efficient, Blelloch style) of the values under op, in
+    // O(log size) passes. Sizes need not be powers of two.
+    int block_dim = 256;
+
+    int stride = 2;
+    while (stride <= size) {
+        int num_threads = size / stride;
+        int num_blocks = (num_threads + block_dim - 1) / block_dim;
+        up_sweep_kernel<<<num_blocks, block_dim>>>(values, size, stride, op);
+        stride <<= 1;
+    }
+
+    stride >>= 1;
+    while (stride >= 2) {
+        int num_threads = size / stride;
+        int num_blocks = (num_threads + block_dim - 1) / block_dim;
+        down_sweep_kernel<<<num_blocks, block_dim>>>(values, size, stride, op);
+        stride >>= 1;
+    }
+    cudaDeviceSynchronize();
+}
+
+void cumulative_sum_base_field(m31 *values, int size) {
+    inclusive_scan(values, size, sum_op());
+}
+
+void cumulative_sum_secure_field(qm31 *values, int size) {
+    inclusive_scan(values, size, sum_op());
+}
+
+void cumulative_product_base_field(m31 *values, int size) {
+    inclusive_scan(values, size, product_op());
+}
+
+void cumulative_product_secure_field(qm31 *values, int size) {
+    inclusive_scan(values, size, product_op());
+}
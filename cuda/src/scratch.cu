@@ -0,0 +1,69 @@
+#include "../include/scratch.cuh"
+#include "../include/utils.cuh"
+
+#include <map>
+#include <string>
+#include <utility>
+
+// Device temporaries of host wrappers, keyed by call site name and log size. They are created
+// on first use and kept, so the same buffer serves every layer of that size and every proof.
+// Each thread has its own registry, as concurrent provers would otherwise share them.
+struct scratch_entry {
+    void *ptr;
+    size_t bytes;
+};
+
+struct scratch_registry {
+    std::map<std::pair<std::string, int>, scratch_entry> buffers;
+    int generation = 0;
+
+    void forget_if_stale() {
+        // The buffers of a context that was reset went with it.
+        if (generation != context_generation()) {
+            buffers.clear();
+            generation = context_generation();
+        }
+    }
+
+    void clear() {
+        forget_if_stale();
+        for (auto &buffer : buffers) {
+            cudaFree(buffer.second.ptr);
+        }
+        buffers.clear();
+    }
+
+    ~scratch_registry() {
+        clear();
+    }
+};
+
+static thread_local scratch_registry registry;
+
+void *scratch_buffer(const char *name, int log_size, size_t bytes) {
+    // Returns a device buffer of at least bytes bytes, which stays valid until the next request
+    // with the same name and log size or clear_scratch_buffers. Callers must be done with it
+    // (i.e. synchronize) before returning.
+    registry.forget_if_stale();
+    scratch_entry &entry = registry.buffers[std::make_pair(std::string(name), log_size)];
+    if (entry.bytes < bytes) {
+        cudaFree(entry.ptr);
+        cudaMalloc(&entry.ptr, bytes);
+        entry.bytes = bytes;
+    }
+    return entry.ptr;
+}
+
+void clear_scratch_buffers() {
+    cudaDeviceSynchronize();
+    registry.clear();
+}
+
+size_t scratch_buffers_size() {
+    registry.forget_if_stale();
+    size_t size = 0;
+    for (auto &buffer : registry.buffers) {
+        size += buffer.second.bytes;
+    }
+    return size;
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+#include <cub/cub.cuh>
+
+#include "../include/sort.cuh"
+#include "../include/scratch.cuh"
+#include "../include/utils.cuh"
+
+// Field elements are below 2^31, so radix sorts can skip the top bit.
+const int M31_BITS = 31;
+
+void sort_base_field(m31 *values, int size) {
+    // In place, in increasing order. CUB sorts between values and an alternate buffer of the same
+    // size, so the result is copied back if it ends up in the latter.
+    if (size == 0) {
+        return;
+    }
+    m31 *alternate = cuda_malloc_uint32_t(size);
+    cub::DoubleBuffer<m31> keys(values, alternate);
+
+    size_t temp_bytes = 0;
+    cub::DeviceRadixSort::SortKeys(nullptr, temp_bytes, keys, size, 0, M31_BITS);
+    void *temp = scratch_buffer("sort_base_field", log_2(size), temp_bytes);
+    cub::DeviceRadixSort::SortKeys(temp, temp_bytes, keys, size, 0, M31_BITS);
+
+    if (keys.Current() != values) {
+        cudaMemcpy(values, keys.Current(), sizeof(m31) * size, cudaMemcpyDeviceToDevice);
+    }
+    cudaDeviceSynchronize();
+    free_uint32_t_vec(alternate);
+}
+
+void sort_base_field_by_key(m31 *keys, m31 *values, int size) {
+    // Sorts keys in place in increasing order, moving values along with them. The sort is stable,
+    // so values of equal keys keep their order.
+    if (size == 0) {
+        return;
+    }
+    m31 *alternate_keys = cuda_malloc_uint32_t(size);
+    m31 *alternate_values = cuda_malloc_uint32_t(size);
+    cub::DoubleBuffer<m31> key_buffers(keys, alternate_keys);
+    cub::DoubleBuffer<m31> value_buffers(values, alternate_values);
+
+    size_t temp_bytes = 0;
+    cub::DeviceRadixSort::SortPairs(nullptr, temp_bytes, key_buffers, value_buffers, size, 0, M31_BITS);
+    void *temp = scratch_buffer("sort_base_field_by_key", log_2(size), temp_bytes);
+    cub::DeviceRadixSort::SortPairs(temp, temp_bytes, key_buffers, value_buffers, size, 0, M31_BITS);
+
+    if (key_buffers.Current() != keys) {
+        cudaMemcpy(keys, key_buffers.Current(), sizeof(m31) * size, cudaMemcpyDeviceToDevice);
+    }
+    if (value_buffers.Current() != values) {
+        cudaMemcpy(values, value_buffers.Current(), sizeof(m31) * size, cudaMemcpyDeviceToDevice);
+    }
+    cudaDeviceSynchronize();
+    free_uint32_t_vec(alternate_keys);
+    free_uint32_t_vec(alternate_values);
+}
+
+int unique_base_field(m31 *values, int size, m31 *dst) {
+    // Writes the first value of each run of equal values to dst, e.g. the distinct values of a
+    // sorted column, and returns how many were written. dst must have room for size values.
+    if (size == 0) {
+        return 0;
+    }
+    int *n_selected = (int*) scratch_buffer("unique_base_field_count", 0, sizeof(int));
+
+    size_t temp_bytes = 0;
+    cub::DeviceSelect::Unique(nullptr, temp_bytes, values, dst, n_selected, size);
+    void *temp = scratch_buffer("unique_base_field", log_2(size), temp_bytes);
+    cub::DeviceSelect::Unique(temp, temp_bytes, values, dst, n_selected, size);
+
+    int result;
+    cudaMemcpy(&result, n_selected, sizeof(int), cudaMemcpyDeviceToHost);
+    return result;
+}
@@ -0,0 +1,22 @@
+#include "../include/sparse.cuh"
+
+// A sparse column of size rows holds n_values values and the distinct rows they are at, all the
+// other values being zero.
+
+__global__ void scatter_sparse_kernel(uint32_t *indices, m31 *values, int n_values, m31 *dst) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < n_values) {
+        dst[indices[idx]] = values[idx];
+    }
+}
+
+void densify(uint32_t *indices, m31 *values, int n_values, m31 *dst, int size) {
+    cudaMemsetAsync(dst, 0, sizeof(m31) * size);
+    if (n_values > 0) {
+        int block_dim = 256;
+        int num_blocks = (n_values + block_dim - 1) / block_dim;
+        scatter_sparse_kernel<<<num_blocks, block_dim>>>(indices, values, n_values, dst);
+    }
+    cudaDeviceSynchronize();
+}
@@ -0,0 +1,75 @@
+#include "../include/stats.cuh"
+#include "../include/reduce.cuh"
+#include "../include/scratch.cuh"
+
+__global__ void column_stats_kernel(m31 *column, int size, m31 range_start, m31 range_end, column_stats *stats) {
+    // Each thread reduces a strided slice of the column, then the block merges the results of its
+    // threads in shared memory before merging them into stats, so there are few global atomics.
+    __shared__ column_stats s_stats;
+    if (threadIdx.x == 0) {
+        s_stats = {0xFFFFFFFF, 0, 0, 0};
+    }
+    __syncthreads();
+
+    m31 min_value = 0xFFFFFFFF;
+    m31 max_value = 0;
+    unsigned int n_zeros = 0;
+    unsigned int n_out_of_range = 0;
+    for (int i = blockIdx.x * blockDim.x + threadIdx.x; i < size; i += gridDim.x * blockDim.x) {
+        m31 value = column[i];
+        min_value = min(min_value, value);
+        max_value = max(max_value, value);
+        n_zeros += value == 0;
+        n_out_of_range += value < range_start || value >= range_end;
+    }
+    atomicMin(&s_stats.min, min_value);
+    atomicMax(&s_stats.max, max_value);
+    atomicAdd(&s_stats.n_zeros, (unsigned long long) n_zeros);
+    atomicAdd(&s_stats.n_out_of_range, (unsigned long long) n_out_of_range);
+    __syncthreads();
+
+    if (threadIdx.x == 0) {
+        atomicMin(&stats->min, s_stats.min);
+        atomicMax(&stats->max, s_stats.max);
+        atomicAdd(&stats->n_zeros, s_stats.n_zeros);
+        atomicAdd(&stats->n_out_of_range, s_stats.n_out_of_range);
+    }
+}
+
+void base_field_column_stats(m31 *column, int size, m31 range_start, m31 range_end, column_stats *result) {
+    // Values outside range_start..range_end are counted as out of range. An empty column leaves
+    // min above max.
+    column_stats *stats = (column_stats*) scratch_buffer("column_stats", 0, sizeof(column_stats));
+    column_stats initial = {0xFFFFFFFF, 0, 0, 0};
+    cudaMemcpy(stats, &initial, sizeof(column_stats), cudaMemcpyHostToDevice);
+
+    int num_blocks = sum_num_blocks(size);
+    column_stats_kernel<<<num_blocks, SUM_BLOCK_DIM>>>(column, size, range_start, range_end, stats);
+    cudaDeviceSynchronize();
+
+    cudaMemcpy(result, stats, sizeof(column_stats), cudaMemcpyDeviceToHost);
+}
+
+__global__ void last_nonzero_index_kernel(m31 *column, int size, int *last) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && column[idx] != 0) {
+        atomicMax(last, idx);
+    }
+}
+
+int last_nonzero_index(m31 *column, int size) {
+    // Returns the index of the last nonzero value of the column, or -1 if they are all zero.
+    int *last = (int*) scratch_buffer("last_nonzero_index", 0, sizeof(int));
+    int initial = -1;
+    cudaMemcpy(last, &initial, sizeof(int), cudaMemcpyHostToDevice);
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    last_nonzero_index_kernel<<<num_blocks, block_dim>>>(column, size, last);
+    cudaDeviceSynchronize();
+
+    int result;
+    cudaMemcpy(&result, last, sizeof(int), cudaMemcpyDeviceToHost);
+    return result;
+}
@@ -0,0 +1,63 @@
+#include "../include/tuning.cuh"
+
+#include <mutex>
+#include <unordered_map>
+
+// The tuning of each device, by ordinal, selected on its first use or set explicitly.
+static std::mutex tunings_mutex;
+static std::unordered_map<int, kernel_tuning> tunings;
+
+kernel_tuning kernel_tuning_for_architecture(int major, int minor) {
+    // Volta and Turing have the smallest register files per SM, so they run more, smaller blocks
+    // of hashes and one butterfly per thread.
+    if (major < 8) {
+        return kernel_tuning{ 256, 1, 128, 256 };
+    }
+    // Ampere, except the sm_89 Ada parts, which have a larger L2 cache the folds benefit from.
+    if (major == 8 && minor < 9) {
+        return kernel_tuning{ 256, 2, 256, 256 };
+    }
+    if (major == 8) {
+        return kernel_tuning{ 256, 2, 256, 512 };
+    }
+    // Hopper and later: larger blocks and unrolled butterflies to hide the latency of their
+    // higher memory bandwidth.
+    return kernel_tuning{ 512, 4, 256, 512 };
+}
+
+kernel_tuning kernel_tuning_for_device(int major, int minor, int sm_count) {
+    kernel_tuning result = kernel_tuning_for_architecture(major, minor);
+    // MIG slices and other small devices run few blocks at once: smaller blocks of hashes and
+    // folds keep all their SMs busy on the small layers.
+    if (sm_count < SMALL_DEVICE_SM_COUNT) {
+        result.merkle_block_dim = max(result.merkle_block_dim / 2, 128);
+        result.fold_block_dim = max(result.fold_block_dim / 2, 128);
+    }
+    return result;
+}
+
+kernel_tuning tuning() {
+    int device;
+    cudaGetDevice(&device);
+    std::lock_guard<std::mutex> lock(tunings_mutex);
+    auto selected = tunings.find(device);
+    if (selected != tunings.end()) {
+        return selected->second;
+    }
+    cudaDeviceProp prop;
+    cudaGetDeviceProperties(&prop, device);
+    kernel_tuning result = kernel_tuning_for_device(prop.major, prop.minor, prop.multiProcessorCount);
+    tunings[device] = result;
+    return result;
+}
+
+kernel_tuning get_kernel_tuning() {
+    return tuning();
+}
+
+void set_kernel_tuning(kernel_tuning value) {
+    int device;
+    cudaGetDevice(&device);
+    std::lock_guard<std::mutex> lock(tunings_mutex);
+    tunings[device] = value;
+}
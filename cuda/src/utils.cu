@@ -1,32 +1,760 @@
+#include <algorithm>
+#include <atomic>
+#include <mutex>
+#include <thread>
+#include <unordered_map>
+#include <vector>
+
 #include "../include/utils.cuh"
 
 void copy_uint32_t_vec_from_device_to_host(uint32_t *device_ptr, uint32_t *host_ptr, int size) {
     cudaMemcpy(host_ptr, device_ptr, sizeof(uint32_t) * size, cudaMemcpyDeviceToHost);
 }
 
+int copy_uint32_t_vec_from_device_to_host_checked(uint32_t *device_ptr, uint32_t *host_ptr, int size) {
+    cudaError_t error = cudaMemcpy(host_ptr, device_ptr, sizeof(uint32_t) * size, cudaMemcpyDeviceToHost);
+    // The host only reads the values once the copy is known to be done. Errors of kernels queued
+    // before the copy, whose results it would return, are reported too.
+    if (error == cudaSuccess) {
+        error = cudaStreamSynchronize(0);
+    }
+    return error;
+}
+
+void copy_uint32_t_vec_from_device_to_host_async(uint32_t *device_ptr, uint32_t *host_ptr, int size, cudaStream_t stream) {
+    cudaMemcpyAsync(host_ptr, device_ptr, sizeof(uint32_t) * size, cudaMemcpyDeviceToHost, stream);
+}
+
+void copy_uint32_t_vec_from_host_to_device_async(uint32_t *host_ptr, uint32_t *device_ptr, int size, cudaStream_t stream) {
+    cudaMemcpyAsync(device_ptr, host_ptr, sizeof(uint32_t) * size, cudaMemcpyHostToDevice, stream);
+}
+
+cudaStream_t create_stream() {
+    // A blocking stream, so work on the default stream still waits for it.
+    cudaStream_t stream;
+    cudaStreamCreate(&stream);
+    return stream;
+}
+
+cudaStream_t create_non_blocking_stream() {
+    // Work on the default stream doesn't wait for this one.
+    cudaStream_t stream;
+    cudaStreamCreateWithFlags(&stream, cudaStreamNonBlocking);
+    return stream;
+}
+
+void stream_wait_default_stream(cudaStream_t stream) {
+    // Makes the work queued on stream from now on wait for that already queued on the default
+    // stream, e.g. asynchronous allocations from the memory pool.
+    cudaEvent_t event;
+    cudaEventCreateWithFlags(&event, cudaEventDisableTiming);
+    cudaEventRecord(event, 0);
+    cudaStreamWaitEvent(stream, event, 0);
+    cudaEventDestroy(event);
+}
+
+int register_host_memory(void *host_ptr, size_t bytes) {
+    // Page-locks existing host memory, so asynchronous copies from it don't block the host.
+    // Returns 0 on success, otherwise the CUDA error code.
+    return cudaHostRegister(host_ptr, bytes, cudaHostRegisterDefault);
+}
+
+void unregister_host_memory(void *host_ptr) {
+    cudaHostUnregister(host_ptr);
+}
+
+cudaEvent_t create_event() {
+    // Only used for ordering, so timing is disabled to keep recording cheap.
+    cudaEvent_t event;
+    cudaEventCreateWithFlags(&event, cudaEventDisableTiming);
+    return event;
+}
+
+void record_event(cudaEvent_t event, cudaStream_t stream) {
+    cudaEventRecord(event, stream);
+}
+
+void stream_wait_event(cudaStream_t stream, cudaEvent_t event) {
+    // Work queued on stream from now on waits for the work before the last record of event.
+    cudaStreamWaitEvent(stream, event, 0);
+}
+
+void synchronize_event(cudaEvent_t event) {
+    cudaEventSynchronize(event);
+}
+
+bool query_event(cudaEvent_t event) {
+    // Whether the work before the last record of event is done, without blocking.
+    return cudaEventQuery(event) == cudaSuccess;
+}
+
+void destroy_event(cudaEvent_t event) {
+    cudaEventDestroy(event);
+}
+
+void synchronize_stream(cudaStream_t stream) {
+    cudaStreamSynchronize(stream);
+}
+
+void launch_host_callback(cudaStream_t stream, void (*callback)(void *), void *data) {
+    // The callback runs on a driver thread once the work queued before it is done. It must not
+    // call into CUDA.
+    cudaLaunchHostFunc(stream, callback, data);
+}
+
+void destroy_stream(cudaStream_t stream) {
+    // Work still queued on the stream completes before its resources are released.
+    cudaStreamDestroy(stream);
+}
+
+// When set, vectors are allocated as managed memory, see set_managed_allocations.
+static std::atomic<bool> use_managed_allocations(false);
+
+void set_managed_allocations(bool enabled) {
+    use_managed_allocations = enabled;
+}
+
+// Devices whose vectors are allocated from their default memory pool, one bit per ordinal, see
+// reserve_memory_pool.
+static std::atomic<uint64_t> memory_pool_devices(0);
+
+static int current_device() {
+    int device;
+    cudaGetDevice(&device);
+    return device;
+}
+
+static bool uses_memory_pool(int device) {
+    return device >= 0 && device < 64 && ((memory_pool_devices >> device) & 1);
+}
+
+int reserve_memory_pool(size_t bytes) {
+    // Grows the default memory pool of the current device to bytes and keeps that memory
+    // reserved, so later allocations don't have to go to the driver.
+    // Returns 0 on success, otherwise the CUDA error code.
+    cudaMemPool_t pool;
+    int device;
+    cudaGetDevice(&device);
+    cudaError_t error = cudaDeviceGetDefaultMemPool(&pool, device);
+    if (error != cudaSuccess) {
+        return error;
+    }
+    uint64_t threshold = bytes;
+    cudaMemPoolSetAttribute(pool, cudaMemPoolAttrReleaseThreshold, &threshold);
+
+    void *reservation;
+    error = cudaMallocAsync(&reservation, bytes, 0);
+    if (error != cudaSuccess) {
+        return error;
+    }
+    cudaFreeAsync(reservation, 0);
+    error = cudaDeviceSynchronize();
+    if (device < 64) {
+        if (error == cudaSuccess) {
+            memory_pool_devices |= 1ull << device;
+        } else {
+            memory_pool_devices &= ~(1ull << device);
+        }
+    }
+    return error;
+}
+
+int trim_memory_pool(size_t bytes_to_keep) {
+    // Releases the unused memory of the default memory pool of the current device beyond
+    // bytes_to_keep. Returns 0 on success, otherwise the CUDA error code.
+    cudaMemPool_t pool;
+    int device;
+    cudaGetDevice(&device);
+    cudaError_t error = cudaDeviceGetDefaultMemPool(&pool, device);
+    if (error != cudaSuccess) {
+        return error;
+    }
+    // Frees still pending on the default stream only return their memory to the pool once done.
+    cudaStreamSynchronize(0);
+    return cudaMemPoolTrimTo(pool, bytes_to_keep);
+}
+
+// Number of times the context was reset, see reset_device. Memory of an older context is gone
+// and must be forgotten rather than freed.
+static std::atomic<int> context_generation_counter(0);
+
+int context_generation() {
+    return context_generation_counter.load();
+}
+
+// Region vectors of the current thread are carved from while reserved, see reserve_arena.
+// Each thread has its own, so concurrent proofs don't reset each other's temporaries.
+static thread_local char *arena = nullptr;
+static thread_local size_t arena_capacity = 0;
+static thread_local size_t arena_offset = 0;
+static thread_local int arena_generation = 0;
+// The device the arena was reserved on. Allocations while another device is current bypass it.
+static thread_local int arena_device = -1;
+
+// The arenas of all the threads, by start and capacity. Vectors can be dropped on another thread
+// than the one that allocated them, which must tell they come from an arena all the same.
+static std::mutex arenas_mutex;
+static std::vector<std::pair<char*, size_t>> arenas;
+static std::atomic<int> arena_count(0);
+
+static void register_arena(char *start, size_t capacity) {
+    std::lock_guard<std::mutex> lock(arenas_mutex);
+    arenas.push_back({start, capacity});
+    arena_count = arenas.size();
+}
+
+static void unregister_arena(char *start) {
+    std::lock_guard<std::mutex> lock(arenas_mutex);
+    arenas.erase(std::remove_if(arenas.begin(), arenas.end(), [start](const std::pair<char*, size_t> &region) {
+        return region.first == start;
+    }), arenas.end());
+    arena_count = arenas.size();
+}
+
+static void forget_stale_arena() {
+    // The arena of a context that was reset went with it, and was unregistered by reset_device.
+    if (arena != nullptr && arena_generation != context_generation()) {
+        arena = nullptr;
+        arena_capacity = 0;
+        arena_offset = 0;
+    }
+}
+
+static void synchronize_arena_device() {
+    // Work reading the arena may still be queued on its device while another one is current.
+    int device = current_device();
+    cudaSetDevice(arena_device);
+    cudaDeviceSynchronize();
+    cudaSetDevice(device);
+}
+
+// Allocations from the arena are aligned like those of cudaMalloc.
+const size_t ARENA_ALIGNMENT = 256;
+
+int reserve_arena(size_t bytes) {
+    // Reserves bytes of device memory that the following allocations of this thread are
+    // bumped from, until they don't fit. Returns 0 on success, otherwise the CUDA error code.
+    release_arena();
+    cudaError_t error = cudaMalloc((void**)&arena, bytes);
+    if (error != cudaSuccess) {
+        arena = nullptr;
+        return error;
+    }
+    arena_capacity = bytes;
+    arena_generation = context_generation();
+    arena_device = current_device();
+    register_arena(arena, bytes);
+    return cudaSuccess;
+}
+
+void reset_arena() {
+    // Makes the whole arena available again, without returning it to the driver.
+    forget_stale_arena();
+    if (arena != nullptr) {
+        synchronize_arena_device();
+    }
+    arena_offset = 0;
+}
+
+void release_arena() {
+    forget_stale_arena();
+    if (arena != nullptr) {
+        unregister_arena(arena);
+        synchronize_arena_device();
+        cudaFree(arena);
+    }
+    arena = nullptr;
+    arena_capacity = 0;
+    arena_offset = 0;
+}
+
+size_t arena_used() {
+    forget_stale_arena();
+    return arena_offset;
+}
+
+static bool is_in_arena(uint32_t *device_ptr) {
+    // In the arena of any thread, not only the current one.
+    if (arena_count == 0) {
+        return false;
+    }
+    std::lock_guard<std::mutex> lock(arenas_mutex);
+    char *ptr = (char*) device_ptr;
+    for (const std::pair<char*, size_t> &region : arenas) {
+        if (ptr >= region.first && ptr < region.first + region.second) {
+            return true;
+        }
+    }
+    return false;
+}
+
 uint32_t* copy_uint32_t_vec_from_host_to_device(uint32_t *host_ptr, int size) {
-    uint32_t* device_ptr;
-    cudaMalloc((void**)&device_ptr, sizeof(uint32_t) * size);
+    uint32_t* device_ptr = cuda_malloc_uint32_t(size);
     cudaMemcpy(device_ptr, host_ptr, sizeof(uint32_t) * size, cudaMemcpyHostToDevice);
     return device_ptr;
 }
 
+void copy_uint32_t_vec_from_host_to_existing_device(uint32_t *host_ptr, uint32_t *device_ptr, int size) {
+    cudaMemcpy(device_ptr, host_ptr, sizeof(uint32_t) * size, cudaMemcpyHostToDevice);
+}
+
 void copy_uint32_t_vec_from_device_to_device(uint32_t *from, uint32_t *dst, int size) {
     cudaMemcpy(dst, from, sizeof(uint32_t) * size, cudaMemcpyDeviceToDevice);
 }
 
+typedef struct {
+    size_t bytes;
+    // Order of the allocation among all of them, see allocation_mark.
+    uint64_t sequence;
+    std::thread::id thread;
+} allocation;
+
+// The vectors allocated by cuda_malloc_uint32_t while tracking is on and not freed yet, by
+// pointer, and their total bytes. Each peak being tracked holds the highest total since it began,
+// see begin_memory_peak. Tracking is off unless a user of it retained it, so allocations don't
+// take the lock otherwise; frees only take it while some allocation is still tracked.
+static std::atomic<int> memory_tracking_users(0);
+static std::atomic<size_t> tracked_allocations(0);
+static std::mutex allocations_mutex;
+static std::unordered_map<uint32_t*, allocation> allocations;
+static uint64_t next_allocation_sequence = 0;
+static size_t bytes_in_use = 0;
+static std::unordered_map<int, size_t> memory_peaks;
+static int next_memory_peak = 0;
+
+void retain_memory_tracking() {
+    // Turns the tracking of allocations on until the matching release_memory_tracking.
+    memory_tracking_users++;
+}
+
+void release_memory_tracking() {
+    memory_tracking_users--;
+}
+
+static void track_allocation(uint32_t *device_ptr, size_t bytes) {
+    if (memory_tracking_users.load(std::memory_order_relaxed) == 0) {
+        return;
+    }
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    allocations[device_ptr] = {bytes, next_allocation_sequence++, std::this_thread::get_id()};
+    tracked_allocations = allocations.size();
+    bytes_in_use += bytes;
+    for (auto &peak : memory_peaks) {
+        peak.second = std::max(peak.second, bytes_in_use);
+    }
+}
+
+static void track_free(uint32_t *device_ptr) {
+    if (tracked_allocations.load(std::memory_order_relaxed) == 0) {
+        return;
+    }
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    auto allocation = allocations.find(device_ptr);
+    if (allocation != allocations.end()) {
+        bytes_in_use -= allocation->second.bytes;
+        allocations.erase(allocation);
+        tracked_allocations = allocations.size();
+    }
+}
+
+uint64_t allocation_mark() {
+    // Marks the vectors allocated from now on, see free_allocations_since.
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    return next_allocation_sequence;
+}
+
+int free_allocations_since(uint64_t mark) {
+    // Frees the vectors the current thread allocated since mark and didn't free, e.g. those
+    // leaked by a panic. Returns how many there were.
+    std::vector<uint32_t*> leaked;
+    {
+        std::lock_guard<std::mutex> lock(allocations_mutex);
+        for (auto it = allocations.begin(); it != allocations.end();) {
+            if (it->second.sequence >= mark && it->second.thread == std::this_thread::get_id()) {
+                leaked.push_back(it->first);
+                bytes_in_use -= it->second.bytes;
+                it = allocations.erase(it);
+            } else {
+                ++it;
+            }
+        }
+        tracked_allocations = allocations.size();
+    }
+    cudaDeviceSynchronize();
+    for (uint32_t *device_ptr : leaked) {
+        if (!is_in_arena(device_ptr)) {
+            cudaFree(device_ptr);
+        }
+    }
+    return leaked.size();
+}
+
+int reset_device() {
+    // Destroys the context, e.g. after a sticky error, and creates a new one. The memory of the
+    // old context is only forgotten: the scratch buffers and arenas of every thread are dropped
+    // on their next use. Returns 0 on success, otherwise the CUDA error code.
+    cudaDeviceReset();
+    {
+        std::lock_guard<std::mutex> lock(allocations_mutex);
+        allocations.clear();
+        tracked_allocations = 0;
+        bytes_in_use = 0;
+    }
+    {
+        std::lock_guard<std::mutex> lock(arenas_mutex);
+        arenas.clear();
+        arena_count = 0;
+    }
+    context_generation_counter++;
+    cudaGetLastError();
+    return cudaFree(0);
+}
+
+int recover_device() {
+    // Waits for the work in flight and clears the error it may have left, so the context can be
+    // used again. Returns 0 if it can, otherwise the CUDA error code of the sticky error that
+    // corrupted it.
+    cudaDeviceSynchronize();
+    cudaGetLastError();
+    return cudaDeviceSynchronize();
+}
+
+int clear_last_error() {
+    // Clears the error left by a failed launch, without waiting for the device. Returns its CUDA
+    // error code, 0 if there was none.
+    return cudaGetLastError();
+}
+
+size_t device_memory_in_use() {
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    return bytes_in_use;
+}
+
+int begin_memory_peak() {
+    // Starts tracking the highest bytes in use from now on, returning the id to end it with.
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    int id = next_memory_peak++;
+    memory_peaks[id] = bytes_in_use;
+    return id;
+}
+
+size_t end_memory_peak(int id) {
+    // Stops tracking the peak begun as id and returns it.
+    std::lock_guard<std::mutex> lock(allocations_mutex);
+    size_t peak = memory_peaks[id];
+    memory_peaks.erase(id);
+    return peak;
+}
+
 uint32_t* cuda_malloc_uint32_t(int size) {
     uint32_t* device_ptr;
-    cudaMalloc((void**)&device_ptr, sizeof(uint32_t) * size);
+    forget_stale_arena();
+    if (arena != nullptr && arena_device == current_device()) {
+        size_t bytes = (sizeof(uint32_t) * size + ARENA_ALIGNMENT - 1) / ARENA_ALIGNMENT * ARENA_ALIGNMENT;
+        if (arena_offset + bytes <= arena_capacity) {
+            device_ptr = (uint32_t*) (arena + arena_offset);
+            arena_offset += bytes;
+            track_allocation(device_ptr, sizeof(uint32_t) * size);
+            return device_ptr;
+        }
+        // Allocations that don't fit fall back to the usual allocator.
+    }
+    if (use_managed_allocations) {
+        cudaMallocManaged((void**)&device_ptr, sizeof(uint32_t) * size);
+    } else if (uses_memory_pool(current_device())) {
+        // Kernels run on the default stream, so the allocation is ready for them.
+        cudaMallocAsync((void**)&device_ptr, sizeof(uint32_t) * size, 0);
+    } else {
+        cudaMalloc((void**)&device_ptr, sizeof(uint32_t) * size);
+    }
+    track_allocation(device_ptr, sizeof(uint32_t) * size);
     return device_ptr;
 }
 
 uint32_t* cuda_alloc_zeroes_uint32_t(int size) {
     uint32_t* device_ptr = cuda_malloc_uint32_t(size);
-    cudaMemset((void**)&device_ptr, 0, sizeof(uint32_t) * size);
+    cudaMemset(device_ptr, 0, sizeof(uint32_t) * size);
     return device_ptr;
 }
 
 void free_uint32_t_vec(uint32_t *device_ptr) {
+    track_free(device_ptr);
+    // Arena memory is only reclaimed by reset_arena.
+    if (is_in_arena(device_ptr)) {
+        return;
+    }
     cudaFree(device_ptr);
 }
+
+void free_uint32_t_vecs(uint32_t **device_ptrs, int count) {
+    // Frees a batch of vectors with a single synchronization. Vectors from the memory pool go
+    // back to it in stream order instead of waiting for the device one cudaFree at a time.
+    for (int i = 0; i < count; i++) {
+        uint32_t *device_ptr = device_ptrs[i];
+        track_free(device_ptr);
+        if (is_in_arena(device_ptr)) {
+            continue;
+        }
+        cudaPointerAttributes attributes;
+        bool from_pool = cudaPointerGetAttributes(&attributes, device_ptr) == cudaSuccess
+            && attributes.type != cudaMemoryTypeManaged && uses_memory_pool(attributes.device);
+        if (from_pool) {
+            cudaFreeAsync(device_ptr, 0);
+        } else {
+            cudaFree(device_ptr);
+        }
+    }
+    cudaDeviceSynchronize();
+}
+
+static cudaStream_t free_stream() {
+    // Stream the stream-ordered frees are queued on, so they don't hold back the default stream.
+    // Created again for each context.
+    static std::mutex mutex;
+    static cudaStream_t stream = nullptr;
+    static int stream_generation = -1;
+    std::lock_guard<std::mutex> lock(mutex);
+    if (stream_generation != context_generation()) {
+        cudaStreamCreateWithFlags(&stream, cudaStreamNonBlocking);
+        stream_generation = context_generation();
+    }
+    return stream;
+}
+
+void free_uint32_t_vec_after_events(uint32_t *device_ptr, cudaEvent_t *events, int n_events) {
+    // Frees a vector once the work before each of the events is done. Memory from the pool is
+    // returned to it in stream order without blocking the host; other memory waits for the
+    // events on the host first.
+    track_free(device_ptr);
+    if (is_in_arena(device_ptr)) {
+        return;
+    }
+    cudaPointerAttributes attributes;
+    bool from_pool = cudaPointerGetAttributes(&attributes, device_ptr) == cudaSuccess
+        && attributes.type != cudaMemoryTypeManaged && uses_memory_pool(attributes.device);
+    if (from_pool) {
+        cudaStream_t stream = free_stream();
+        for (int i = 0; i < n_events; i++) {
+            cudaStreamWaitEvent(stream, events[i], 0);
+        }
+        cudaFreeAsync(device_ptr, stream);
+    } else {
+        for (int i = 0; i < n_events; i++) {
+            cudaEventSynchronize(events[i]);
+        }
+        cudaFree(device_ptr);
+    }
+}
+
+uint32_t* cuda_malloc_host_uint32_t(int size) {
+    // Page-locked host memory, so device to host copies into it run at full bandwidth.
+    uint32_t* host_ptr;
+    cudaMallocHost((void**)&host_ptr, sizeof(uint32_t) * size);
+    return host_ptr;
+}
+
+void free_host_uint32_t_vec(uint32_t *host_ptr) {
+    cudaFreeHost(host_ptr);
+}
+
+int probe_cuda_device(int *device_count, int *driver_version, int *runtime_version) {
+    // Returns 0 when a device can be used, otherwise the CUDA error code.
+    // A driver version of 0 means no driver is installed.
+    *device_count = 0;
+    cudaDriverGetVersion(driver_version);
+    cudaRuntimeGetVersion(runtime_version);
+
+    cudaError_t error = cudaGetDeviceCount(device_count);
+    if (error != cudaSuccess) {
+        *device_count = 0;
+        return error;
+    }
+    // Create the context, so that failures show up here rather than in the first kernel.
+    return cudaFree(0);
+}
+
+int get_device_count(int *device_count) {
+    // Unlike probe_cuda_device, doesn't create a context, so the environment variables read on
+    // context creation can still be set afterwards.
+    // Returns 0 on success, otherwise the CUDA error code.
+    *device_count = 0;
+    return cudaGetDeviceCount(device_count);
+}
+
+int get_device_properties(int device, device_properties *properties) {
+    // Returns 0 on success, otherwise the CUDA error code.
+    cudaDeviceProp prop;
+    cudaError_t error = cudaGetDeviceProperties(&prop, device);
+    if (error != cudaSuccess) {
+        return error;
+    }
+    memcpy(properties->name, prop.name, sizeof(properties->name));
+    properties->name[sizeof(properties->name) - 1] = 0;
+    properties->total_memory = prop.totalGlobalMem;
+    properties->major = prop.major;
+    properties->minor = prop.minor;
+    properties->sm_count = prop.multiProcessorCount;
+    memcpy(properties->uuid, prop.uuid.bytes, sizeof(properties->uuid));
+    cudaDeviceGetPCIBusId(properties->pci_bus_id, sizeof(properties->pci_bus_id), device);
+
+    // Free memory is only reported for the current device.
+    int current_device;
+    cudaGetDevice(&current_device);
+    cudaSetDevice(device);
+    size_t total_memory;
+    error = cudaMemGetInfo(&properties->free_memory, &total_memory);
+    cudaSetDevice(current_device);
+    return error;
+}
+
+int get_current_device() {
+    int device = 0;
+    cudaGetDevice(&device);
+    return device;
+}
+
+int set_current_device(int device) {
+    // Selects the device of the calling thread. Returns 0 on success, otherwise the CUDA error
+    // code.
+    return cudaSetDevice(device);
+}
+
+
+template<typename T>
+__global__ void fill_kernel(T *dst, T value, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = value;
+    }
+}
+
+void fill_base_field(m31 *dst, m31 value, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    fill_kernel<<<num_blocks, block_dim>>>(dst, value, size);
+    cudaDeviceSynchronize();
+}
+
+void fill_secure_field(qm31 *dst, qm31 value, int size) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    fill_kernel<<<num_blocks, block_dim>>>(dst, value, size);
+    cudaDeviceSynchronize();
+}
+
+__device__ __forceinline__ uint64_t splitmix64(uint64_t z) {
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+__global__ void fill_random_kernel(m31 *dst, int size, uint64_t seed) {
+    // Counter based: value idx only depends on the seed and idx, so any launch configuration
+    // produces the same column.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        uint64_t hash = splitmix64(seed + (uint64_t) (idx + 1) * 0x9E3779B97F4A7C15ull);
+        dst[idx] = (m31) (hash >> 33) % P;
+    }
+}
+
+void fill_random_base_field(m31 *dst, int size, uint64_t seed) {
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    fill_random_kernel<<<num_blocks, block_dim>>>(dst, size, seed);
+    cudaDeviceSynchronize();
+}
+
+// Enough squares of x for any exponent of an int.
+const int MAX_LOG_POWERS = 32;
+
+typedef struct {
+    qm31 squares[MAX_LOG_POWERS];
+} powers_params;
+
+__global__ void powers_kernel(qm31 *dst, const powers_params params, int size) {
+    // Each thread computes its power from the squares x^(2^k) of the bits of its index, in
+    // log(size) multiplications, independently of the other threads.
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        qm31 power = {{1, 0}, {0, 0}};
+        for (int bit = 0; (idx >> bit) != 0; bit++) {
+            if ((idx >> bit) & 1) {
+                power = mul(power, params.squares[bit]);
+            }
+        }
+        dst[idx] = power;
+    }
+}
+
+void fill_powers_secure_field(qm31 *dst, qm31 x, int size) {
+    // dst[i] = x^i.
+    powers_params params;
+    params.squares[0] = x;
+    for (int bit = 1; bit < MAX_LOG_POWERS; bit++) {
+        params.squares[bit] = mul(params.squares[bit - 1], params.squares[bit - 1]);
+    }
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    powers_kernel<<<num_blocks, block_dim>>>(dst, params, size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void embed_base_field_kernel(const m31 *__restrict__ src, qm31 *dst, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        dst[idx] = {{src[idx], 0}, {0, 0}};
+    }
+}
+
+void embed_base_field(m31 *src, qm31 *dst, int size) {
+    // dst[i] = (src[i], 0, 0, 0), the base field value as a secure field one.
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    embed_base_field_kernel<<<num_blocks, block_dim>>>(src, dst, size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void embed_base_field_into_secure_column_kernel(const m31 *__restrict__ src, secure_column dst, int size) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size) {
+        secure_column_set(dst, idx, {{src[idx], 0}, {0, 0}});
+    }
+}
+
+void embed_base_field_into_secure_column(m31 *src, m31 **dst, int size) {
+    // Same as embed_base_field, writing the coordinates to the 4 columns of dst.
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    embed_base_field_into_secure_column_kernel<<<num_blocks, block_dim>>>(src, make_secure_column(dst), size);
+    cudaDeviceSynchronize();
+}
+
+__global__ void compare_kernel(uint32_t *a, uint32_t *b, int size, bool *equal) {
+    int idx = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (idx < size && a[idx] != b[idx]) {
+        *equal = false;
+    }
+}
+
+bool uint32_t_vec_equal(uint32_t *a, uint32_t *b, int size) {
+    bool *device_equal;
+    cudaMalloc((void**)&device_equal, sizeof(bool));
+    cudaMemset(device_equal, true, sizeof(bool));
+
+    int block_dim = 256;
+    int num_blocks = (size + block_dim - 1) / block_dim;
+    compare_kernel<<<num_blocks, block_dim>>>(a, b, size, device_equal);
+
+    bool equal;
+    cudaMemcpy(&equal, device_equal, sizeof(bool), cudaMemcpyDeviceToHost);
+    cudaFree(device_equal);
+    return equal;
+}
\ No newline at end of file
@@ -1,48 +1,169 @@
-const CUDA_LIB_DIR: &str = "/workspaces/cuda-rust-example/cuda";
+use std::{env, process::Command};
+
+const CUDA_LIB_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../cuda");
+
+const JETSON_DRIVER_LIB_DIR: &str = "/usr/lib/aarch64-linux-gnu/tegra";
+
+const SOURCES: &[&str] = &[
+    "accumulation",
+    "batch_inverse",
+    "bit_reverse",
+    "blake2s",
+    "circle",
+    "compression",
+    "fri",
+    "gkr",
+    "inner_product",
+    "jit",
+    "logup",
+    "mask",
+    "mle",
+    "order",
+    "padding",
+    "point",
+    "preprocessed",
+    "query",
+    "quotient",
+    "row_constraints",
+    "scan",
+    "scratch",
+    "sort",
+    "sparse",
+    "stats",
+    "tuning",
+    "utils",
+];
+
+const HEADERS: &[&str] = &[
+    "accumulation",
+    "batch_inverse",
+    "bit_reverse",
+    "blake2s",
+    "circle",
+    "compression",
+    "fields",
+    "fri",
+    "gkr",
+    "inner_product",
+    "jit",
+    "logup",
+    "mask",
+    "mle",
+    "order",
+    "padding",
+    "point",
+    "preprocessed",
+    "query",
+    "quotient",
+    "reduce",
+    "row_constraints",
+    "scan",
+    "scratch",
+    "sort",
+    "sparse",
+    "stats",
+    "tuning",
+    "utils",
+];
 
 fn main() {
+    // Without the `cuda` feature the bindings are stubs, so there is nothing to build or link.
+    if env::var_os("CARGO_FEATURE_CUDA").is_none() {
+        return;
+    }
+
     // Rerun conditions
-    // Source files
-    println!(
-        "cargo:rerun-if-changed={}/src/batch_inverse.cu",
-        CUDA_LIB_DIR
-    );
-    println!("cargo:rerun-if-changed={}/src/bit_reverse.cu", CUDA_LIB_DIR);
-    println!("cargo:rerun-if-changed={}/src/circle.cu", CUDA_LIB_DIR);
-    println!("cargo:rerun-if-changed={}/src/utils.cu", CUDA_LIB_DIR);
-
-    // Header files
-    println!(
-        "cargo:rerun-if-changed={}/src/batch_inverse.cuh",
-        CUDA_LIB_DIR
-    );
-    println!(
-        "cargo:rerun-if-changed={}/src/bit_reverse.cuh",
-        CUDA_LIB_DIR
-    );
-    println!("cargo:rerun-if-changed={}/src/circle.cuh", CUDA_LIB_DIR);
-    println!("cargo:rerun-if-changed={}/src/fields.cuh", CUDA_LIB_DIR);
-    println!("cargo:rerun-if-changed={}/src/point.cuh", CUDA_LIB_DIR);
-    println!("cargo:rerun-if-changed={}/src/utils.cuh", CUDA_LIB_DIR);
+    for source in SOURCES {
+        println!("cargo:rerun-if-changed={}/src/{}.cu", CUDA_LIB_DIR, source);
+    }
+    for header in HEADERS {
+        println!(
+            "cargo:rerun-if-changed={}/include/{}.cuh",
+            CUDA_LIB_DIR, header
+        );
+    }
 
     // Build cuda code
+    println!("cargo:rerun-if-env-changed=CUDA_PATH");
     println!("cargo:rustc-link-search={}", CUDA_LIB_DIR);
-    let status = std::process::Command::new("nvcc")
-        .args([
-            "-arch=sm_50",
-            "-Xcompiler",
-            "-fPIC",
-            "-shared",
-            "-o",
-            &format!("{}/libgpubackend.so", CUDA_LIB_DIR),
-            &format!("{}/src/batch_inverse.cu", CUDA_LIB_DIR),
-            &format!("{}/src/bit_reverse.cu", CUDA_LIB_DIR),
-            &format!("{}/src/circle.cu", CUDA_LIB_DIR),
-            &format!("{}/src/utils.cu", CUDA_LIB_DIR),
-        ])
-        .status()
-        .expect("Failed to execute nvcc");
+    let is_windows = env::var("CARGO_CFG_TARGET_OS").unwrap() == "windows";
+    let is_aarch64 = env::var("CARGO_CFG_TARGET_ARCH").unwrap() == "aarch64";
+    let mut nvcc = Command::new(nvcc_path());
+    if is_aarch64 {
+        // Jetson boards: Xavier (sm_72) and Orin (sm_87). Other aarch64 hosts, e.g. Grace Hopper
+        // (sm_90), JIT compile the PTX of the oldest architecture supported on x86.
+        nvcc.args([
+            "-gencode=arch=compute_72,code=sm_72",
+            "-gencode=arch=compute_87,code=sm_87",
+            "-gencode=arch=compute_60,code=compute_60",
+        ]);
+    } else {
+        // Pascal (sm_60) is the oldest architecture with grid-wide synchronization, which the
+        // cooperative reductions use.
+        nvcc.arg("-arch=sm_60");
+    }
+    // `cudart-dynamic` takes precedence, so dependents can opt in without disabling the default
+    // features.
+    let dynamic_cudart = env::var_os("CARGO_FEATURE_CUDART_DYNAMIC").is_some();
+    nvcc.arg(if dynamic_cudart {
+        "--cudart=shared"
+    } else {
+        "--cudart=static"
+    });
+    if is_windows {
+        // Linking against a DLL requires an import library, which MSVC only produces for
+        // functions marked `dllexport`. A static library needs no annotations. `/MD` matches the
+        // C runtime used by rustc.
+        nvcc.args(["-lib", "-Xcompiler", "/MD", "-o"])
+            .arg(format!("{}/gpubackend.lib", CUDA_LIB_DIR));
+    } else {
+        nvcc.args(["-Xcompiler", "-fPIC", "-shared", "-o"])
+            .arg(format!("{}/libgpubackend.so", CUDA_LIB_DIR));
+    }
+    nvcc.args(
+        SOURCES
+            .iter()
+            .map(|source| format!("{}/src/{}.cu", CUDA_LIB_DIR, source)),
+    );
+    if is_windows {
+        // The dependencies of a static library are resolved when linking the final binary.
+        let cuda_path = env::var("CUDA_PATH").expect("CUDA_PATH is not set");
+        println!("cargo:rustc-link-search={}/lib/x64", cuda_path);
+        if dynamic_cudart {
+            println!("cargo:rustc-link-lib=cudart");
+        } else {
+            println!("cargo:rustc-link-lib=cudart_static");
+        }
+        println!("cargo:rustc-link-lib=nvrtc");
+        println!("cargo:rustc-link-lib=cuda");
+    } else {
+        if is_aarch64 {
+            // JetPack installs the driver library outside of the toolkit's library paths.
+            nvcc.arg(format!("-L{}", JETSON_DRIVER_LIB_DIR));
+        }
+        if dynamic_cudart {
+            // Let the loader find the runtime of the toolkit the kernels were built with.
+            nvcc.arg(format!("-Xlinker=-rpath={}/lib64", cuda_toolkit_dir()));
+        }
+        nvcc.args(["-lnvrtc", "-lcuda"]);
+    }
+
+    let status = nvcc.status().expect("Failed to execute nvcc");
     if !status.success() {
         panic!("nvcc failed with status: {}", status);
     }
 }
+
+/// nvcc from the CUDA toolkit pointed to by `CUDA_PATH` (always set by the Windows installer), or
+/// the one on the `PATH`.
+fn nvcc_path() -> String {
+    match env::var("CUDA_PATH") {
+        Ok(cuda_path) => format!("{}/bin/nvcc", cuda_path),
+        Err(_) => "nvcc".to_string(),
+    }
+}
+
+/// The CUDA toolkit pointed to by `CUDA_PATH`, or the default install location.
+fn cuda_toolkit_dir() -> String {
+    env::var("CUDA_PATH").unwrap_or_else(|_| "/usr/local/cuda".to_string())
+}
@@ -0,0 +1,27 @@
+//! Bit reverses columns of every power of two size, including those smaller than a block.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_wrapper::{cuda_available, BaseFieldVec, CudaBackend};
+use stwo_prover::core::{
+    backend::{Column, ColumnOps, CpuBackend},
+    fields::m31::BaseField,
+};
+
+fuzz_target!(|input: (u8, Vec<u32>)| {
+    if !cuda_available() {
+        return;
+    }
+    let (log_size, values) = input;
+    let size = 1 << (u32::from(log_size) % 21);
+    let values = (0..size)
+        .map(|i| BaseField::from(values.get(i % values.len().max(1)).copied().unwrap_or(0)))
+        .collect::<Vec<_>>();
+
+    let mut expected = values.clone();
+    CpuBackend::bit_reverse_column(&mut expected);
+    let mut column = BaseFieldVec::from_vec(values);
+    <CudaBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut column);
+
+    assert_eq!(column.to_cpu(), expected);
+});
@@ -0,0 +1,69 @@
+//! Folds line evaluations of any size, with twiddles precomputed for any larger coset, so the
+//! twiddle offsets cover every layer of the tree.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rust_wrapper::{cuda_available, BaseFieldVec, CudaBackend};
+use stwo_prover::core::{
+    backend::{Column, CpuBackend},
+    circle::Coset,
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+    fri::FriOps,
+    poly::{
+        circle::PolyOps,
+        line::{LineDomain, LineEvaluation},
+    },
+};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    log_size: u8,
+    extra_twiddle_layers: u8,
+    alpha: [u32; 4],
+    seed: u32,
+}
+
+fuzz_target!(|input: Input| {
+    if !cuda_available() {
+        return;
+    }
+    let log_size = 1 + u32::from(input.log_size) % 16;
+    let extra_twiddle_layers = u32::from(input.extra_twiddle_layers) % 4;
+    let root_coset = Coset::half_odds(log_size + extra_twiddle_layers);
+    let domain = LineDomain::new(root_coset.repeated_double(extra_twiddle_layers));
+    let alpha = SecureField::from_m31_array(input.alpha.map(BaseField::from));
+    let columns: [Vec<BaseField>; 4] = std::array::from_fn(|i| {
+        (0..1 << log_size)
+            .map(|j: u32| BaseField::from(input.seed ^ (j * 4 + i as u32)))
+            .collect()
+    });
+
+    let cpu_eval = LineEvaluation::<CpuBackend>::new(
+        domain,
+        SecureColumn {
+            columns: columns.clone(),
+        },
+    );
+    let expected = CpuBackend::fold_line(
+        &cpu_eval,
+        alpha,
+        &CpuBackend::precompute_twiddles(root_coset),
+    );
+    let gpu_eval = LineEvaluation::<CudaBackend>::new(
+        domain,
+        SecureColumn {
+            columns: columns.map(BaseFieldVec::from_vec),
+        },
+    );
+    let result = CudaBackend::fold_line(
+        &gpu_eval,
+        alpha,
+        &CudaBackend::precompute_twiddles(root_coset),
+    );
+
+    assert_eq!(
+        result.values.columns.map(|column| column.to_cpu()),
+        expected.values.columns
+    );
+});
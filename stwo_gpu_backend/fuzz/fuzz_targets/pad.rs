@@ -0,0 +1,38 @@
+//! Pads columns of arbitrary sizes, which are not multiples of the block size.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_wrapper::{cuda_available, pad, BaseFieldVec, Padding};
+use stwo_prover::core::{backend::Column, fields::m31::BaseField};
+
+fuzz_target!(|input: (Vec<u32>, u16, bool)| {
+    if !cuda_available() {
+        return;
+    }
+    let (values, extra, repeat) = input;
+    let values = values.into_iter().map(BaseField::from).collect::<Vec<_>>();
+    if repeat && values.is_empty() {
+        return;
+    }
+    let size = values.len() + usize::from(extra);
+    let padding = if repeat {
+        Padding::Repeat
+    } else {
+        Padding::Zeros
+    };
+
+    let expected = (0..size)
+        .map(|i| {
+            if i < values.len() {
+                values[i]
+            } else if repeat {
+                values[i % values.len()]
+            } else {
+                BaseField::from(0)
+            }
+        })
+        .collect::<Vec<_>>();
+    let result = pad(&BaseFieldVec::from_vec(values), size, padding);
+
+    assert_eq!(result.to_cpu(), expected);
+});
@@ -0,0 +1,62 @@
+#ifndef STWO_GPU_H
+#define STWO_GPU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define STWO_GPU_OK 0
+#define STWO_GPU_ERROR_NO_DEVICE 1
+#define STWO_GPU_ERROR_INSUFFICIENT_DRIVER 2
+#define STWO_GPU_ERROR_CUDA 3
+#define STWO_GPU_ERROR_INVALID_ARGUMENT 4
+#define STWO_GPU_ERROR_PANIC 5
+#define STWO_GPU_ERROR_CONTEXT_CORRUPTED 6
+#define STWO_GPU_ERROR_PROVING 7
+#define STWO_GPU_ERROR_INVALID_PROOF 8
+
+typedef struct StwoGpuColumn StwoGpuColumn;
+typedef struct StwoGpuCommitment StwoGpuCommitment;
+typedef struct StwoGpuProof StwoGpuProof;
+
+/* Bytes allocated by the library, released with stwo_gpu_bytes_free. */
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} StwoGpuBytes;
+
+int stwo_gpu_init(void);
+
+/* Returns NULL if a value is not a field element, i.e. not below 2^31 - 1. */
+StwoGpuColumn *stwo_gpu_column_upload(const uint32_t *values, size_t len);
+int stwo_gpu_column_len(const StwoGpuColumn *column, size_t *len);
+int stwo_gpu_column_download(const StwoGpuColumn *column, uint32_t *dst);
+void stwo_gpu_column_free(StwoGpuColumn *column);
+
+/* Columns are in natural order over canonic cosets of power of two sizes. Writes the 32 byte
+ * Merkle root to root. Returns NULL on invalid arguments. */
+StwoGpuCommitment *stwo_gpu_commit(const StwoGpuColumn *const *columns, size_t n_columns,
+                                   uint32_t log_blowup_factor, uint8_t *root);
+/* Positions are given in the largest extended columns. proof is overwritten without being
+ * released, so bytes from a previous call must be freed first with stwo_gpu_bytes_free. */
+int stwo_gpu_commitment_open(const StwoGpuCommitment *commitment, const size_t *positions,
+                             size_t n_positions, StwoGpuBytes *proof);
+void stwo_gpu_commitment_free(StwoGpuCommitment *commitment);
+
+/* Proves that a second column holds the squares of column, read in bit reversed order over the
+ * canonic coset of its power of two size. A smoke test of the whole prover on a device, not an
+ * entry point for other AIRs. Verifying consumes the proof, which must still be freed. */
+int stwo_gpu_prove_squares(const StwoGpuColumn *column, StwoGpuProof **proof);
+int stwo_gpu_proof_verify(StwoGpuProof *proof);
+void stwo_gpu_proof_free(StwoGpuProof *proof);
+
+void stwo_gpu_bytes_free(StwoGpuBytes *bytes);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // STWO_GPU_H
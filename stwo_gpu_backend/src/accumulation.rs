@@ -1,9 +1,121 @@
-use stwo_prover::core::{air::accumulation::AccumulationOps, fields::secure_column::SecureColumn};
+use stwo_prover::core::{
+    air::accumulation::AccumulationOps,
+    backend::Column,
+    fields::{qm31::SecureField, secure_column::SecureColumn},
+};
 
-use crate::backend::CudaBackend;
+use crate::{backend::CudaBackend, cuda, sparse::SparseColumn, watchdog::watch};
 
 impl AccumulationOps for CudaBackend {
-    fn accumulate(_column: &mut SecureColumn<Self>, _other: &SecureColumn<Self>) {
-        todo!()
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
+    fn accumulate(column: &mut SecureColumn<Self>, other: &SecureColumn<Self>) {
+        let _watch = watch("accumulate");
+        let size = column.len();
+        assert_eq!(other.len(), size);
+        unsafe {
+            cuda::bindings::accumulate(
+                cuda::secure_column_device_ptrs(column).as_ptr(),
+                cuda::secure_column_device_ptrs(other).as_ptr(),
+                size as u32,
+            );
+        }
+    }
+}
+
+impl CudaBackend {
+    /// Adds `coefficient` times `other` to `column`, only touching the rows where `other` has a
+    /// value, e.g. to combine a mostly zero witness column into a random linear combination.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_values = other.n_values()))
+    )]
+    pub fn accumulate_sparse(
+        column: &mut SecureColumn<Self>,
+        other: &SparseColumn,
+        coefficient: SecureField,
+    ) {
+        let _watch = watch("accumulate_sparse");
+        assert_eq!(other.len(), column.len());
+        unsafe {
+            cuda::bindings::accumulate_sparse(
+                cuda::secure_column_device_ptrs(column).as_ptr(),
+                other.rows().device_ptr(),
+                other.values().device_ptr(),
+                other.n_values() as u32,
+                coefficient,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        air::accumulation::AccumulationOps,
+        backend::{Column, CpuBackend},
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+    };
+
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec, sparse::SparseColumn};
+
+    fn cpu_secure_column(size: usize, offset: u32) -> SecureColumn<CpuBackend> {
+        SecureColumn {
+            columns: std::array::from_fn(|i| {
+                (0..size as u32)
+                    .map(|j| BaseField::from(offset + 4 * j + i as u32))
+                    .collect()
+            }),
+        }
+    }
+
+    fn to_device(column: &SecureColumn<CpuBackend>) -> SecureColumn<CudaBackend> {
+        SecureColumn {
+            columns: column.columns.clone().map(BaseFieldVec::from_vec),
+        }
+    }
+
+    #[test]
+    fn test_accumulate() {
+        require_gpu!();
+        let size = 1 << 12;
+        let mut cpu_column = cpu_secure_column(size, 1);
+        let cpu_other = cpu_secure_column(size, 5);
+        let mut column = to_device(&cpu_column);
+        let other = to_device(&cpu_other);
+
+        CpuBackend::accumulate(&mut cpu_column, &cpu_other);
+        CudaBackend::accumulate(&mut column, &other);
+
+        assert_eq!(
+            column.columns.map(|column| column.to_cpu()),
+            cpu_column.columns
+        );
+    }
+
+    #[test]
+    fn test_accumulate_sparse() {
+        require_gpu!();
+        let size = 1 << 12;
+        let coefficient = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut values = vec![BaseField::from(0); size];
+        for row in [1, 17, 2000, size - 1] {
+            values[row] = BaseField::from(row as u32 * 3 + 1);
+        }
+        let cpu_column = cpu_secure_column(size, 1);
+        let mut column = to_device(&cpu_column);
+
+        CudaBackend::accumulate_sparse(
+            &mut column,
+            &SparseColumn::from_dense(&values),
+            coefficient,
+        );
+
+        let expected_result = (0..size)
+            .map(|row| cpu_column.at(row) + coefficient * values[row])
+            .collect::<Vec<_>>();
+        assert_eq!(column.to_vec(), expected_result);
     }
 }
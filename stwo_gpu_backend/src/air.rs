@@ -0,0 +1,255 @@
+use stwo_prover::core::{
+    air::{
+        accumulation::{DomainEvaluationAccumulator, PointEvaluationAccumulator},
+        Air, AirProver, AirTraceGenerator, AirTraceVerifier, Component, ComponentProver,
+        ComponentTrace,
+    },
+    backend::Column,
+    channel::Blake2sChannel,
+    circle::CirclePoint,
+    constraints::coset_vanishing,
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+    pcs::TreeVec,
+    poly::{
+        circle::{CanonicCoset, CircleEvaluation, PolyOps},
+        BitReversedOrder,
+    },
+    prover::{commit_and_prove, commit_and_verify, ProvingError, StarkProof, VerificationError},
+    vcs::blake2_hash::Blake2sHash,
+    ColumnVec, InteractionElements, LookupValues,
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+    inner_product::mul_add,
+    row_constraints::{evaluate_row_constraints, MaskItem},
+    twiddles::cached_twiddles,
+    vanishing::inverse_coset_vanishing_evaluation,
+};
+
+/// The single constraint `b = a^2` of [`SquaresComponent`], read through this mask of columns
+/// `a` and `b`.
+const MASK: [MaskItem; 2] = [
+    MaskItem {
+        column: 0,
+        offset: 0,
+    },
+    MaskItem {
+        column: 1,
+        offset: 0,
+    },
+];
+
+/// A component of two columns `a` and `b` of size `2^log_size`, constrained by `b = a^2` on
+/// every row.
+///
+/// Small enough to be proved end to end on the device from tests and the C and Python
+/// bindings, with every step of the prover, constraint evaluation included, on [`CudaBackend`].
+#[derive(Clone, Debug)]
+pub struct SquaresComponent {
+    pub log_size: u32,
+}
+
+impl Component for SquaresComponent {
+    fn n_constraints(&self) -> usize {
+        1
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        // The constraint is of degree 2.
+        self.log_size + 1
+    }
+
+    fn n_interaction_phases(&self) -> u32 {
+        1
+    }
+
+    fn trace_log_degree_bounds(&self) -> TreeVec<ColumnVec<u32>> {
+        TreeVec::new(vec![vec![self.log_size; 2]])
+    }
+
+    fn mask_points(
+        &self,
+        point: CirclePoint<SecureField>,
+    ) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
+        TreeVec::new(vec![vec![vec![point]; 2]])
+    }
+
+    fn interaction_element_ids(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn evaluate_constraint_quotients_at_point(
+        &self,
+        point: CirclePoint<SecureField>,
+        mask: &TreeVec<ColumnVec<Vec<SecureField>>>,
+        evaluation_accumulator: &mut PointEvaluationAccumulator,
+        _interaction_elements: &InteractionElements,
+        _lookup_values: &LookupValues,
+    ) {
+        let (a, b) = (mask[0][0][0], mask[0][1][0]);
+        let denominator = coset_vanishing(CanonicCoset::new(self.log_size).coset(), point);
+        evaluation_accumulator.accumulate((b - a * a) / denominator);
+    }
+}
+
+impl ComponentProver<CudaBackend> for SquaresComponent {
+    fn evaluate_constraint_quotients_on_domain(
+        &self,
+        trace: &ComponentTrace<'_, CudaBackend>,
+        evaluation_accumulator: &mut DomainEvaluationAccumulator<CudaBackend>,
+        _interaction_elements: &InteractionElements,
+        _lookup_values: &LookupValues,
+    ) {
+        let log_size = self.max_constraint_log_degree_bound();
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let twiddles = cached_twiddles(domain.half_coset);
+        let [a, b] = [0, 1].map(|i| CudaBackend::evaluate(trace.polys[0][i], domain, &twiddles));
+
+        let [accumulator] = evaluation_accumulator.columns([(log_size, self.n_constraints())]);
+        let mut numerators = SecureColumn {
+            columns: std::array::from_fn(|_| BaseFieldVec::new_zeroes(domain.size())),
+        };
+        // The squares evaluator reads the coefficient of its single constraint.
+        unsafe {
+            evaluate_row_constraints(
+                cuda::bindings::squares_row_constraints,
+                &[&a.values, &b.values],
+                &MASK,
+                self.log_size,
+                &accumulator.random_coeff_powers,
+                &mut numerators,
+            )
+        };
+        let denominator_inverses =
+            inverse_coset_vanishing_evaluation(CanonicCoset::new(self.log_size).coset(), domain);
+        for (column, numerator) in accumulator.col.columns.iter_mut().zip(&numerators.columns) {
+            mul_add(column, numerator, &denominator_inverses.values);
+        }
+    }
+
+    fn lookup_values(&self, _trace: &ComponentTrace<'_, CudaBackend>) -> LookupValues {
+        LookupValues::default()
+    }
+}
+
+/// The AIR of a single [`SquaresComponent`].
+#[derive(Clone, Debug)]
+pub struct SquaresAir {
+    pub component: SquaresComponent,
+}
+
+impl SquaresAir {
+    pub fn new(log_size: u32) -> Self {
+        Self {
+            component: SquaresComponent { log_size },
+        }
+    }
+}
+
+impl Air for SquaresAir {
+    fn components(&self) -> Vec<&dyn Component> {
+        vec![&self.component]
+    }
+}
+
+impl AirTraceVerifier for SquaresAir {
+    fn interaction_elements(&self, _channel: &mut Blake2sChannel) -> InteractionElements {
+        InteractionElements::default()
+    }
+
+    fn verify_lookups(&self, _lookup_values: &LookupValues) -> Result<(), VerificationError> {
+        Ok(())
+    }
+}
+
+impl AirTraceGenerator<CudaBackend> for SquaresAir {
+    fn interact(
+        &self,
+        _trace: &ColumnVec<CircleEvaluation<CudaBackend, BaseField, BitReversedOrder>>,
+        _elements: &InteractionElements,
+    ) -> Vec<CircleEvaluation<CudaBackend, BaseField, BitReversedOrder>> {
+        vec![]
+    }
+
+    fn to_air_prover(&self) -> &impl AirProver<CudaBackend> {
+        self
+    }
+
+    fn composition_log_degree_bound(&self) -> u32 {
+        self.component.max_constraint_log_degree_bound()
+    }
+}
+
+impl AirProver<CudaBackend> for SquaresAir {
+    fn prover_components(&self) -> Vec<&dyn ComponentProver<CudaBackend>> {
+        vec![&self.component]
+    }
+}
+
+/// A proof of a [`SquaresAir`], with the size of its columns.
+#[derive(Debug)]
+pub struct SquaresProof {
+    pub log_size: u32,
+    pub proof: StarkProof,
+}
+
+impl SquaresProof {
+    /// Checks the proof with the verifier of stwo.
+    pub fn verify(self) -> Result<(), VerificationError> {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        commit_and_verify(self.proof, &SquaresAir::new(self.log_size), &mut channel)
+    }
+}
+
+/// Proves the [`SquaresAir`] whose column `a` is `values`, as an evaluation in bit reversed
+/// order over the canonic coset of its size, and whose column `b` holds their squares, computed
+/// on the device.
+///
+/// Mostly useful to exercise the whole prover on a trace of a given size, e.g. from the C and
+/// Python bindings.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = values.len()))
+)]
+pub fn prove_squares(values: &BaseFieldVec) -> Result<SquaresProof, ProvingError> {
+    assert!(
+        values.len().is_power_of_two(),
+        "column sizes must be powers of two"
+    );
+    let log_size = values.len().ilog2();
+    let a = values.clone();
+    let mut b = BaseFieldVec::new_zeroes(values.len());
+    mul_add(&mut b, values, values);
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let trace = [a, b]
+        .into_iter()
+        .map(|column| CircleEvaluation::new(domain, column))
+        .collect();
+    let mut channel = Blake2sChannel::new(Blake2sHash::default());
+    let proof = commit_and_prove(&SquaresAir::new(log_size), &mut channel, trace)?;
+    Ok(SquaresProof { log_size, proof })
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::prove_squares;
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_prove_squares() {
+        require_gpu!();
+        let values = (0..1 << 8)
+            .map(|i| BaseField::from(i * 7 + 1))
+            .collect::<Vec<_>>();
+
+        let proof = prove_squares(&BaseFieldVec::from_vec(values)).unwrap();
+
+        assert_eq!(proof.log_size, 8);
+        proof.verify().unwrap();
+    }
+}
@@ -0,0 +1,350 @@
+use std::{borrow::Cow, fmt};
+#[cfg(feature = "parquet")]
+use std::{fs::File, path::Path};
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Int32Type, Int64Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type},
+    Array, ArrowPrimitiveType, RecordBatch,
+};
+use arrow_schema::DataType;
+#[cfg(feature = "parquet")]
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ProjectionMask},
+    errors::ParquetError,
+};
+use stwo_prover::core::fields::m31::P;
+
+use crate::cuda::{self, BaseFieldVec};
+
+/// Why trace columns couldn't be read from Arrow data.
+#[derive(Debug)]
+pub enum ArrowTraceError {
+    MissingColumn(String),
+    /// Only integer columns can hold field elements.
+    UnsupportedType {
+        column: String,
+        data_type: DataType,
+    },
+    /// The column has null values.
+    Nulls {
+        column: String,
+    },
+    /// The value at `row` is negative or not below P.
+    OutOfRange {
+        column: String,
+        row: usize,
+    },
+    /// The batches of a Parquet file don't hold the number of rows of its metadata.
+    #[cfg(feature = "parquet")]
+    RowCount {
+        expected: usize,
+    },
+    #[cfg(feature = "parquet")]
+    Parquet(ParquetError),
+}
+
+impl fmt::Display for ArrowTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrowTraceError::MissingColumn(column) => write!(f, "no column named {column}"),
+            ArrowTraceError::UnsupportedType { column, data_type } => {
+                write!(f, "column {column} has unsupported type {data_type}")
+            }
+            ArrowTraceError::Nulls { column } => write!(f, "column {column} has null values"),
+            ArrowTraceError::OutOfRange { column, row } => {
+                write!(
+                    f,
+                    "value at row {row} of column {column} is not a field element"
+                )
+            }
+            #[cfg(feature = "parquet")]
+            ArrowTraceError::RowCount { expected } => {
+                write!(
+                    f,
+                    "parquet file does not hold the {expected} rows of its metadata"
+                )
+            }
+            #[cfg(feature = "parquet")]
+            ArrowTraceError::Parquet(error) => write!(f, "failed to read parquet file: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ArrowTraceError {}
+
+#[cfg(feature = "parquet")]
+impl From<ParquetError> for ArrowTraceError {
+    fn from(error: ParquetError) -> Self {
+        ArrowTraceError::Parquet(error)
+    }
+}
+
+/// Uploads the columns `names` of `batches`, the rows of each batch following those of the
+/// previous ones, to device columns.
+///
+/// Columns may be of any integer type, as long as every value is a field element, i.e. in
+/// `0..P`, and none is null. `UInt32` columns are uploaded without an intermediate copy.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_columns = names.len()))
+)]
+pub fn columns_from_record_batches(
+    batches: &[RecordBatch],
+    names: &[&str],
+) -> Result<Vec<BaseFieldVec>, ArrowTraceError> {
+    let n_rows = batches.iter().map(|batch| batch.num_rows()).sum();
+    let columns = new_columns(names.len(), n_rows);
+    let mut offset = 0;
+    for batch in batches {
+        upload_batch(batch, names, &columns, offset)?;
+        offset += batch.num_rows();
+    }
+    Ok(columns)
+}
+
+/// Same as [`columns_from_record_batches`], reading the columns `names` of the Parquet file at
+/// `path`. Batches are uploaded as they are decoded, so only one of them is held in host memory
+/// at a time.
+#[cfg(feature = "parquet")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_columns = names.len()))
+)]
+pub fn columns_from_parquet(
+    path: impl AsRef<Path>,
+    names: &[&str],
+) -> Result<Vec<BaseFieldVec>, ArrowTraceError> {
+    let file = File::open(path).map_err(|error| ArrowTraceError::Parquet(error.into()))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let n_rows = builder.metadata().file_metadata().num_rows() as usize;
+    let roots = names
+        .iter()
+        .map(|&name| {
+            builder
+                .schema()
+                .index_of(name)
+                .map_err(|_| ArrowTraceError::MissingColumn(name.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let projection = ProjectionMask::roots(builder.parquet_schema(), roots);
+    let reader = builder.with_projection(projection).build()?;
+
+    let columns = new_columns(names.len(), n_rows);
+    let mut offset = 0;
+    for batch in reader {
+        let batch = batch.map_err(|error| ArrowTraceError::Parquet(error.into()))?;
+        if offset + batch.num_rows() > n_rows {
+            return Err(ArrowTraceError::RowCount { expected: n_rows });
+        }
+        upload_batch(&batch, names, &columns, offset)?;
+        offset += batch.num_rows();
+    }
+    if offset != n_rows {
+        return Err(ArrowTraceError::RowCount { expected: n_rows });
+    }
+    Ok(columns)
+}
+
+fn new_columns(n_columns: usize, n_rows: usize) -> Vec<BaseFieldVec> {
+    (0..n_columns)
+        .map(|_| BaseFieldVec::new_uninitialized(n_rows))
+        .collect()
+}
+
+/// Copies the columns `names` of `batch` to `columns`, starting at row `offset`.
+fn upload_batch(
+    batch: &RecordBatch,
+    names: &[&str],
+    columns: &[BaseFieldVec],
+    offset: usize,
+) -> Result<(), ArrowTraceError> {
+    for (&name, column) in names.iter().zip(columns) {
+        let values = column_values(batch, name)?;
+        assert!(offset + values.len() <= column.size);
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_host_to_existing_device(
+                values.as_ptr(),
+                column.device_ptr().add(offset),
+                values.len() as u32,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The values of column `name` of `batch`, checked to be field elements.
+fn column_values<'a>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<Cow<'a, [u32]>, ArrowTraceError> {
+    let array = batch
+        .column_by_name(name)
+        .ok_or_else(|| ArrowTraceError::MissingColumn(name.to_string()))?;
+    if array.null_count() > 0 {
+        return Err(ArrowTraceError::Nulls {
+            column: name.to_string(),
+        });
+    }
+    let values = match array.data_type() {
+        DataType::UInt32 => Cow::Borrowed(&array.as_primitive::<UInt32Type>().values()[..]),
+        DataType::UInt8 => convert::<UInt8Type>(array, name)?,
+        DataType::UInt16 => convert::<UInt16Type>(array, name)?,
+        DataType::UInt64 => convert::<UInt64Type>(array, name)?,
+        DataType::Int32 => convert::<Int32Type>(array, name)?,
+        DataType::Int64 => convert::<Int64Type>(array, name)?,
+        data_type => {
+            return Err(ArrowTraceError::UnsupportedType {
+                column: name.to_string(),
+                data_type: data_type.clone(),
+            })
+        }
+    };
+    match values.iter().position(|&value| value >= P) {
+        Some(row) => Err(ArrowTraceError::OutOfRange {
+            column: name.to_string(),
+            row,
+        }),
+        None => Ok(values),
+    }
+}
+
+fn convert<T: ArrowPrimitiveType>(
+    array: &dyn Array,
+    name: &str,
+) -> Result<Cow<'static, [u32]>, ArrowTraceError>
+where
+    T::Native: TryInto<u32>,
+{
+    array
+        .as_primitive::<T>()
+        .values()
+        .iter()
+        .enumerate()
+        .map(|(row, &value)| {
+            value.try_into().map_err(|_| ArrowTraceError::OutOfRange {
+                column: name.to_string(),
+                row,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Cow::Owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray, UInt16Array, UInt32Array};
+    use stwo_prover::core::fields::m31::{BaseField, P};
+
+    use super::{columns_from_record_batches, ArrowTraceError};
+
+    fn batch(start: u32, len: u32) -> RecordBatch {
+        RecordBatch::try_from_iter([
+            (
+                "a",
+                Arc::new(UInt32Array::from_iter_values(start..start + len)) as ArrayRef,
+            ),
+            (
+                "b",
+                Arc::new(UInt16Array::from_iter_values(
+                    (start..start + len).map(|i| (i % 1000) as u16),
+                )),
+            ),
+            (
+                "c",
+                Arc::new(Int64Array::from_iter_values(
+                    (start..start + len).map(|i| i as i64 * 3),
+                )),
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_columns_from_record_batches() {
+        require_gpu!();
+        let batches = [batch(0, 1000), batch(1000, 24)];
+
+        let columns = columns_from_record_batches(&batches, &["c", "a", "b"]).unwrap();
+
+        assert_eq!(
+            columns[0].to_vec(),
+            (0..1024)
+                .map(|i| BaseField::from(3 * i))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            columns[1].to_vec(),
+            (0..1024).map(BaseField::from).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            columns[2].to_vec(),
+            (0..1024)
+                .map(|i| BaseField::from(i % 1000))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_columns_from_record_batches_errors() {
+        require_gpu!();
+        let invalid = RecordBatch::try_from_iter([
+            (
+                "negative",
+                Arc::new(Int64Array::from_iter_values([0, -1])) as ArrayRef,
+            ),
+            ("large", Arc::new(UInt32Array::from_iter_values([P, 0]))),
+            ("null", Arc::new(UInt32Array::from(vec![Some(1), None]))),
+            ("text", Arc::new(StringArray::from(vec!["1", "2"]))),
+        ])
+        .unwrap();
+        let batches = [invalid];
+
+        assert!(matches!(
+            columns_from_record_batches(&batches, &["missing"]),
+            Err(ArrowTraceError::MissingColumn(_))
+        ));
+        assert!(matches!(
+            columns_from_record_batches(&batches, &["negative"]),
+            Err(ArrowTraceError::OutOfRange { row: 1, .. })
+        ));
+        assert!(matches!(
+            columns_from_record_batches(&batches, &["large"]),
+            Err(ArrowTraceError::OutOfRange { row: 0, .. })
+        ));
+        assert!(matches!(
+            columns_from_record_batches(&batches, &["null"]),
+            Err(ArrowTraceError::Nulls { .. })
+        ));
+        assert!(matches!(
+            columns_from_record_batches(&batches, &["text"]),
+            Err(ArrowTraceError::UnsupportedType { .. })
+        ));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_columns_from_parquet() {
+        require_gpu!();
+        let path = std::env::temp_dir().join(format!("trace-{}.parquet", std::process::id()));
+        let batches = [batch(0, 1000), batch(1000, 24)];
+        let mut writer = parquet::arrow::ArrowWriter::try_new(
+            std::fs::File::create(&path).unwrap(),
+            batches[0].schema(),
+            None,
+        )
+        .unwrap();
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.close().unwrap();
+
+        let columns = super::columns_from_parquet(&path, &["b", "a"]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected = columns_from_record_batches(&batches, &["b", "a"]).unwrap();
+        assert_eq!(columns, expected);
+    }
+}
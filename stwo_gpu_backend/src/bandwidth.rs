@@ -0,0 +1,179 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cuda::{self, BaseFieldVec, PinnedBuffer},
+    stream::Event,
+};
+
+/// Words moved by each measured copy, 64 MiB, large enough for the link to reach its peak.
+const TRANSFER_WORDS: usize = 1 << 24;
+
+const REPETITIONS: usize = 5;
+
+/// Pinned host to device bandwidth, in GB/s, below which the link is most likely not a full
+/// x16 one. PCIe 3.0 x16 reaches about 12 GB/s and PCIe 4.0 x16 about 25 GB/s.
+const MIN_PINNED_GBPS: f64 = 6.0;
+
+/// Ratio of pinned to pageable bandwidth above which pageable transfers are worth avoiding.
+const MAX_PINNED_TO_PAGEABLE: f64 = 2.0;
+
+/// Device memory bandwidth, in GB/s, below which the device is unexpectedly slow for proving.
+const MIN_DEVICE_GBPS: f64 = 100.0;
+
+/// Bandwidths measured on this machine by [`measure_bandwidth`], in GB/s.
+///
+/// Its [`fmt::Display`] is a report with what to expect, e.g. to attach to performance issues.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BandwidthReport {
+    pub host_to_device_pageable: f64,
+    pub host_to_device_pinned: f64,
+    pub device_to_host_pageable: f64,
+    pub device_to_host_pinned: f64,
+    /// Copies between device vectors, counting the bytes both read and written.
+    pub device: f64,
+}
+
+/// Measures the bandwidth of transfers between the host and the device, from both pageable and
+/// pinned host memory, and of the device memory.
+///
+/// Slow proofs often come from the machine rather than the prover: GPUs on x1 PCIe risers or
+/// links negotiated at a lower generation, or traces uploaded from pageable memory, which goes
+/// through an extra copy. Takes about a second.
+pub fn measure_bandwidth() -> BandwidthReport {
+    let bytes = (4 * TRANSFER_WORDS) as f64;
+    let mut pageable = vec![0u32; TRANSFER_WORDS];
+    let pinned = PinnedBuffer::new(TRANSFER_WORDS);
+    let column = BaseFieldVec::new_zeroes(TRANSFER_WORDS);
+    let mut copy = BaseFieldVec::new_uninitialized(TRANSFER_WORDS);
+
+    let host_to_device = |host_ptr: *const u32| {
+        gbps(bytes, || unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_host_to_existing_device(
+                host_ptr,
+                column.device_ptr(),
+                TRANSFER_WORDS as u32,
+            )
+        })
+    };
+    let device_to_host = |host_ptr: *mut u32| {
+        gbps(bytes, || unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_device_to_host(
+                column.device_ptr(),
+                host_ptr,
+                TRANSFER_WORDS as u32,
+            )
+        })
+    };
+    BandwidthReport {
+        host_to_device_pageable: host_to_device(pageable.as_ptr()),
+        host_to_device_pinned: host_to_device(pinned.host_ptr),
+        device_to_host_pageable: device_to_host(pageable.as_mut_ptr()),
+        device_to_host_pinned: device_to_host(pinned.host_ptr as *mut u32),
+        device: gbps(2.0 * bytes, || {
+            copy.copy_from(&column);
+            // Copies between device buffers don't wait for the device.
+            Event::record_default_stream().synchronize();
+        }),
+    }
+}
+
+/// The bandwidth of the fastest of [`REPETITIONS`] runs of `copy`, after a warm-up one.
+fn gbps(bytes: f64, mut copy: impl FnMut()) -> f64 {
+    copy();
+    let fastest = (0..REPETITIONS)
+        .map(|_| {
+            let start = Instant::now();
+            copy();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    bytes / fastest.max(Duration::from_nanos(1)).as_secs_f64() / 1e9
+}
+
+impl fmt::Display for BandwidthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16}{:>12}{:>12}", "GB/s", "pageable", "pinned")?;
+        writeln!(
+            f,
+            "{:<16}{:>12.1}{:>12.1}",
+            "host to device", self.host_to_device_pageable, self.host_to_device_pinned
+        )?;
+        writeln!(
+            f,
+            "{:<16}{:>12.1}{:>12.1}",
+            "device to host", self.device_to_host_pageable, self.device_to_host_pinned
+        )?;
+        writeln!(f, "{:<16}{:>12.1}", "device memory", self.device)?;
+
+        let mut healthy = true;
+        if self.host_to_device_pinned.min(self.device_to_host_pinned) < MIN_PINNED_GBPS {
+            healthy = false;
+            writeln!(
+                f,
+                "- Pinned transfers are slow: expect about 12 GB/s on PCIe 3.0 x16 and 25 GB/s \
+                 on PCIe 4.0 x16. Check the GPU is not on an x1 riser or a narrow slot and that \
+                 the link runs at its full generation (nvidia-smi -q, section PCI)."
+            )?;
+        }
+        if self.host_to_device_pinned > MAX_PINNED_TO_PAGEABLE * self.host_to_device_pageable {
+            healthy = false;
+            writeln!(
+                f,
+                "- Uploads from pageable memory are much slower than from pinned memory: upload \
+                 large traces with BaseFieldVec::from_u32_slice_chunked, which stages them \
+                 through pinned buffers."
+            )?;
+        }
+        if self.device < MIN_DEVICE_GBPS {
+            healthy = false;
+            writeln!(
+                f,
+                "- Device memory is slow for a GPU used for proving: check the device isn't \
+                 shared with other work or throttled."
+            )?;
+        }
+        if healthy {
+            writeln!(f, "- Bandwidths look as expected.")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measure_bandwidth, BandwidthReport};
+
+    #[test]
+    fn test_report() {
+        let report = BandwidthReport {
+            host_to_device_pageable: 0.6,
+            host_to_device_pinned: 1.5,
+            device_to_host_pageable: 0.7,
+            device_to_host_pinned: 1.4,
+            device: 800.0,
+        }
+        .to_string();
+
+        assert!(report.starts_with(
+            "GB/s                pageable      pinned\n\
+             host to device           0.6         1.5\n"
+        ));
+        assert!(report.contains("x1 riser"));
+        assert!(report.contains("from_u32_slice_chunked"));
+        assert!(!report.contains("Device memory is slow"));
+        assert!(!report.contains("look as expected"));
+    }
+
+    #[test]
+    fn test_measure_bandwidth() {
+        require_gpu!();
+        let report = measure_bandwidth();
+
+        assert!(report.host_to_device_pinned > 0.0);
+        assert!(report.device > 0.0);
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, fmt};
+
+use stwo_prover::core::{
+    fields::{m31::BaseField, qm31::SecureField},
+    vcs::blake2_hash::Blake2sHash,
+};
+
+use crate::cuda;
+
+/// Proofs of a batch for which at least one check failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchVerificationFailure {
+    /// Indices of the failed proofs, in increasing order.
+    pub proofs: Vec<usize>,
+}
+
+impl fmt::Display for BatchVerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "proofs {:?} failed verification", self.proofs)
+    }
+}
+
+impl std::error::Error for BatchVerificationFailure {}
+
+/// Collects the data-parallel checks of many proofs, to run them all at once on the device.
+///
+/// The verifier of each proof replays its transcript on the host and, instead of hashing
+/// decommitments and folding queries itself, adds them here tagged with the index of the proof.
+/// [`BatchVerifier::verify`] then runs every check in a few launches, which pays off when
+/// verifying thousands of small proofs.
+#[derive(Debug, Default)]
+pub struct BatchVerifier {
+    /// Merkle paths, grouped by their number of leaf columns and depth.
+    merkle_paths: BTreeMap<(usize, usize), MerklePaths>,
+    fold_checks: FoldChecks,
+}
+
+#[derive(Debug, Default)]
+struct MerklePaths {
+    proofs: Vec<usize>,
+    leaf_values: Vec<BaseField>,
+    positions: Vec<u32>,
+    paths: Vec<u32>,
+    roots: Vec<Blake2sHash>,
+    root_indices: Vec<u32>,
+}
+
+#[derive(Debug, Default)]
+struct FoldChecks {
+    proofs: Vec<usize>,
+    f_x: Vec<SecureField>,
+    f_neg_x: Vec<SecureField>,
+    x: Vec<BaseField>,
+    alphas: Vec<SecureField>,
+    folded: Vec<SecureField>,
+}
+
+impl BatchVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks that the leaf at `position`, hashing `leaf_values`, leads to `root` through the
+    /// sibling hashes `path`, ordered from the leaves up. The tree must only have columns at
+    /// its leaves.
+    pub fn add_merkle_path(
+        &mut self,
+        proof: usize,
+        root: Blake2sHash,
+        position: usize,
+        leaf_values: &[BaseField],
+        path: &[Blake2sHash],
+    ) {
+        assert!(position >> path.len() == 0, "position out of the tree");
+        let paths = self
+            .merkle_paths
+            .entry((leaf_values.len(), path.len()))
+            .or_default();
+        // Paths of the same tree are usually added one after the other.
+        if paths.roots.last() != Some(&root) {
+            paths.roots.push(root);
+        }
+        paths.proofs.push(proof);
+        paths.leaf_values.extend_from_slice(leaf_values);
+        paths.positions.push(position as u32);
+        paths
+            .paths
+            .extend(path.iter().flat_map(cuda::hash_to_words));
+        paths.root_indices.push((paths.roots.len() - 1) as u32);
+    }
+
+    /// Checks that folding the values `f_x` and `f_neg_x` at the conjugate points `x` and `-x`
+    /// of a line domain with `alpha` gives `folded`, as a FRI query does at each layer.
+    pub fn add_fold_check(
+        &mut self,
+        proof: usize,
+        f_x: SecureField,
+        f_neg_x: SecureField,
+        x: BaseField,
+        alpha: SecureField,
+        folded: SecureField,
+    ) {
+        let checks = &mut self.fold_checks;
+        checks.proofs.push(proof);
+        checks.f_x.push(f_x);
+        checks.f_neg_x.push(f_neg_x);
+        checks.x.push(x);
+        checks.alphas.push(alpha);
+        checks.folded.push(folded);
+    }
+
+    /// Runs all the checks, returning the proofs that failed any of them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(n_fold_checks = self.fold_checks.proofs.len())
+        )
+    )]
+    pub fn verify(&self) -> Result<(), BatchVerificationFailure> {
+        let mut failed_proofs = Vec::new();
+
+        for (&(n_columns, depth), paths) in &self.merkle_paths {
+            let roots = paths
+                .roots
+                .iter()
+                .flat_map(cuda::hash_to_words)
+                .collect::<Vec<_>>();
+            let mut valid = vec![0i32; paths.proofs.len()];
+            unsafe {
+                cuda::bindings::verify_merkle_paths(
+                    paths.leaf_values.as_ptr(),
+                    n_columns as u32,
+                    paths.positions.as_ptr(),
+                    paths.paths.as_ptr(),
+                    depth as u32,
+                    roots.as_ptr(),
+                    paths.roots.len() as u32,
+                    paths.root_indices.as_ptr(),
+                    paths.proofs.len() as u32,
+                    valid.as_mut_ptr() as *const i32,
+                );
+            }
+            failed_proofs.extend(failures(&paths.proofs, &valid));
+        }
+
+        let checks = &self.fold_checks;
+        let mut valid = vec![0i32; checks.proofs.len()];
+        unsafe {
+            cuda::bindings::check_fold_pairs(
+                checks.f_x.as_ptr(),
+                checks.f_neg_x.as_ptr(),
+                checks.x.as_ptr(),
+                checks.alphas.as_ptr(),
+                checks.folded.as_ptr(),
+                checks.proofs.len() as u32,
+                valid.as_mut_ptr() as *const i32,
+            );
+        }
+        failed_proofs.extend(failures(&checks.proofs, &valid));
+
+        if failed_proofs.is_empty() {
+            return Ok(());
+        }
+        failed_proofs.sort_unstable();
+        failed_proofs.dedup();
+        Err(BatchVerificationFailure {
+            proofs: failed_proofs,
+        })
+    }
+}
+
+fn failures<'a>(proofs: &'a [usize], valid: &'a [i32]) -> impl Iterator<Item = usize> + 'a {
+    proofs
+        .iter()
+        .zip(valid)
+        .filter(|(_, &valid)| valid == 0)
+        .map(|(&proof, _)| proof)
+}
+
+/// Inner proof data shared by the tests of the batched verification kernels, here and in
+/// [`crate::recursion`].
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        circle::Coset,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+        fri::FriOps,
+        poly::{
+            circle::PolyOps,
+            line::{LineDomain, LineEvaluation},
+        },
+        utils::bit_reverse_index,
+        vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    pub(crate) type CpuMerkleTree = MerkleProver<CpuBackend, Blake2sMerkleHasher>;
+
+    /// Three columns of size `2^log_size` with values starting at `offset`, and their tree.
+    pub(crate) fn committed_columns(
+        log_size: usize,
+        offset: u32,
+    ) -> (Vec<Vec<BaseField>>, CpuMerkleTree) {
+        let columns = (0..3)
+            .map(|i| {
+                (0..1 << log_size)
+                    .map(|j| BaseField::from(offset + i * 1000 + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let tree = CpuMerkleTree::commit(columns.iter().collect());
+        (columns, tree)
+    }
+
+    /// The sibling hashes on the path from leaf `position` of `tree`, from the leaves up.
+    pub(crate) fn merkle_path(
+        tree: &CpuMerkleTree,
+        position: usize,
+        log_size: usize,
+    ) -> Vec<Blake2sHash> {
+        (0..log_size)
+            .map(|level| tree.layers[log_size - level][(position >> level) ^ 1])
+            .collect()
+    }
+
+    /// A line evaluation of size `2^log_size` folded once on the CPU.
+    pub(crate) struct LineFold {
+        pub alpha: SecureField,
+        pub domain: LineDomain,
+        pub values: SecureColumn<CpuBackend>,
+        pub folded: LineEvaluation<CpuBackend>,
+    }
+
+    impl LineFold {
+        pub(crate) fn new(log_size: u32) -> Self {
+            let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+            let domain = LineDomain::new(Coset::half_odds(log_size));
+            let values = SecureColumn::<CpuBackend> {
+                columns: std::array::from_fn(|i| {
+                    (0..1 << log_size)
+                        .map(|j| BaseField::from(4 * j + i as u32))
+                        .collect()
+                }),
+            };
+            let eval = LineEvaluation::new(domain, values.clone());
+            let twiddles = CpuBackend::precompute_twiddles(domain.coset());
+            let folded = CpuBackend::fold_line(&eval, alpha, &twiddles);
+            Self {
+                alpha,
+                domain,
+                values,
+                folded,
+            }
+        }
+
+        pub(crate) fn n_pairs(&self) -> usize {
+            self.domain.size() >> 1
+        }
+
+        /// The values `f(x)` and `f(-x)` folded into value `i`, and `x`.
+        pub(crate) fn pair(&self, i: usize) -> (SecureField, SecureField, BaseField) {
+            let x = self
+                .domain
+                .at(bit_reverse_index(i << 1, self.domain.log_size()));
+            (self.values.at(i << 1), self.values.at((i << 1) + 1), x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::{
+        fixtures::{committed_columns, merkle_path, LineFold},
+        BatchVerificationFailure, BatchVerifier,
+    };
+
+    #[test]
+    fn test_batch_verifier() {
+        require_gpu!();
+        let log_size = 6;
+        let mut verifier = BatchVerifier::new();
+
+        // Proofs 0 and 1 decommit from their own trees; proof 1 sends a wrong leaf value.
+        for proof in 0..2 {
+            let (columns, tree) = committed_columns(log_size, proof * 100000);
+            for position in [0, 5, 63] {
+                let mut leaf_values = columns
+                    .iter()
+                    .map(|column| column[position])
+                    .collect::<Vec<_>>();
+                if proof == 1 && position == 5 {
+                    leaf_values[2] += BaseField::from(1);
+                }
+                verifier.add_merkle_path(
+                    proof as usize,
+                    tree.root(),
+                    position,
+                    &leaf_values,
+                    &merkle_path(&tree, position, log_size),
+                );
+            }
+        }
+
+        // Proofs 0 and 2 fold a line evaluation; proof 2 sends a wrong folded value.
+        let fold = LineFold::new(log_size as u32);
+        for proof in [0, 2] {
+            for i in 0..fold.n_pairs() {
+                let (f_x, f_neg_x, x) = fold.pair(i);
+                let mut folded_value = fold.folded.values.at(i);
+                if proof == 2 && i == 7 {
+                    folded_value += fold.alpha;
+                }
+                verifier.add_fold_check(proof, f_x, f_neg_x, x, fold.alpha, folded_value);
+            }
+        }
+
+        assert_eq!(
+            verifier.verify(),
+            Err(BatchVerificationFailure { proofs: vec![1, 2] })
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Prints the bandwidths between the host and the device and of the device memory on this
+//! machine, with what to expect, to check the setup before investigating slow proofs.
+//!
+//! ```bash
+//! cargo run --release --bin bandwidth_check
+//! ```
+
+use rust_wrapper::{measure_bandwidth, try_init};
+
+fn main() {
+    if let Err(error) = try_init() {
+        eprintln!("no usable device: {error}");
+        std::process::exit(1);
+    }
+    print!("{}", measure_bandwidth());
+}
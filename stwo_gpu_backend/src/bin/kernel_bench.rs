@@ -0,0 +1,243 @@
+//! Measures the throughput of the main kernels across sizes and, for those with tunable launch
+//! parameters, block sizes, and prints a table, to pick [`KernelTuning`] on new GPU generations
+//! and catch bandwidth regressions.
+//!
+//! ```bash
+//! cargo run --release --bin kernel_bench -- [min_log_size] [max_log_size]
+//! ```
+//!
+//! Bandwidths count the bytes a kernel has to read and write at least once, so kernels making
+//! several passes over memory, such as the FFTs, show below the peak bandwidth of the device.
+
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use rust_wrapper::{
+    bit_reverse_range, kernel_tuning, mul_add, set_kernel_tuning, try_init, BaseFieldVec,
+    CudaBackend, KernelTuning,
+};
+use stwo_prover::core::{
+    air::accumulation::AccumulationOps,
+    backend::Column,
+    circle::Coset,
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn, FieldOps},
+    fri::FriOps,
+    poly::{
+        circle::{CanonicCoset, CirclePoly, PolyOps},
+        line::{LineDomain, LineEvaluation},
+    },
+    vcs::{blake2_merkle::Blake2sMerkleHasher, ops::MerkleOps},
+};
+
+const REPETITIONS: usize = 10;
+
+const BLOCK_DIMS: [u32; 4] = [128, 256, 512, 1024];
+
+/// The launch parameter of [`KernelTuning`] a kernel uses, if any.
+#[derive(Clone, Copy)]
+enum Tunable {
+    None,
+    Fft,
+    Merkle,
+    Fold,
+}
+
+struct Kernel {
+    name: &'static str,
+    tunable: Tunable,
+    /// Bytes read and written per element, at least.
+    bytes_per_element: usize,
+    /// Allocates the inputs for `2^log_size` elements and returns the launch to measure.
+    setup: fn(u32) -> Box<dyn FnMut()>,
+}
+
+fn random_secure_column(size: usize, seed: u64) -> SecureColumn<CudaBackend> {
+    SecureColumn {
+        columns: std::array::from_fn(|i| BaseFieldVec::random(size, seed + i as u64)),
+    }
+}
+
+const KERNELS: &[Kernel] = &[
+    Kernel {
+        name: "bit_reverse",
+        tunable: Tunable::None,
+        bytes_per_element: 8,
+        setup: |log_size| {
+            let size = 1 << log_size;
+            let mut column = BaseFieldVec::random(size, 0);
+            Box::new(move || bit_reverse_range(&mut column, 0..size))
+        },
+    },
+    Kernel {
+        name: "batch_inverse",
+        tunable: Tunable::None,
+        bytes_per_element: 8,
+        setup: |log_size| {
+            let column = BaseFieldVec::random(1 << log_size, 0);
+            let mut dst = BaseFieldVec::new_uninitialized(1 << log_size);
+            Box::new(move || <CudaBackend as FieldOps<BaseField>>::batch_inverse(&column, &mut dst))
+        },
+    },
+    Kernel {
+        name: "mul_add",
+        tunable: Tunable::None,
+        bytes_per_element: 16,
+        setup: |log_size| {
+            let mut dst = BaseFieldVec::random(1 << log_size, 0);
+            let a = BaseFieldVec::random(1 << log_size, 1);
+            let b = BaseFieldVec::random(1 << log_size, 2);
+            Box::new(move || mul_add(&mut dst, &a, &b))
+        },
+    },
+    Kernel {
+        name: "accumulate",
+        tunable: Tunable::None,
+        bytes_per_element: 48,
+        setup: |log_size| {
+            let mut column = random_secure_column(1 << log_size, 0);
+            let other = random_secure_column(1 << log_size, 4);
+            Box::new(move || CudaBackend::accumulate(&mut column, &other))
+        },
+    },
+    Kernel {
+        name: "sum_secure_column",
+        tunable: Tunable::None,
+        bytes_per_element: 16,
+        setup: |log_size| {
+            let column = random_secure_column(1 << log_size, 0);
+            Box::new(move || {
+                CudaBackend::sum_secure_column(&column);
+            })
+        },
+    },
+    Kernel {
+        name: "evaluate",
+        tunable: Tunable::Fft,
+        bytes_per_element: 8,
+        setup: |log_size| {
+            let domain = CanonicCoset::new(log_size).circle_domain();
+            let twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+            let poly = CirclePoly::<CudaBackend>::new(BaseFieldVec::random(1 << log_size, 0));
+            Box::new(move || {
+                CudaBackend::evaluate(&poly, domain, &twiddles);
+            })
+        },
+    },
+    Kernel {
+        name: "commit_on_layer",
+        tunable: Tunable::Merkle,
+        // Four columns read, one hash written.
+        bytes_per_element: 48,
+        setup: |log_size| {
+            let columns = random_secure_column(1 << log_size, 0);
+            Box::new(move || {
+                <CudaBackend as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                    log_size,
+                    None,
+                    &columns.columns.iter().collect::<Vec<_>>(),
+                );
+            })
+        },
+    },
+    Kernel {
+        name: "fold_line",
+        tunable: Tunable::Fold,
+        // The evaluation read, half of it written and half a twiddle per value read.
+        bytes_per_element: 26,
+        setup: |log_size| {
+            let root_coset = Coset::half_odds(log_size);
+            let twiddles = CudaBackend::precompute_twiddles(root_coset);
+            let eval = LineEvaluation::new(
+                LineDomain::new(root_coset),
+                random_secure_column(1 << log_size, 0),
+            );
+            let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+            Box::new(move || {
+                CudaBackend::fold_line(&eval, alpha, &twiddles);
+            })
+        },
+    },
+];
+
+/// `default` with the block size of `tunable` replaced by `block_dim`.
+fn with_block_dim(default: KernelTuning, tunable: Tunable, block_dim: u32) -> KernelTuning {
+    match tunable {
+        Tunable::None => default,
+        Tunable::Fft => KernelTuning {
+            fft_block_dim: block_dim,
+            ..default
+        },
+        Tunable::Merkle => KernelTuning {
+            merkle_block_dim: block_dim,
+            ..default
+        },
+        Tunable::Fold => KernelTuning {
+            fold_block_dim: block_dim,
+            ..default
+        },
+    }
+}
+
+/// The fastest of [`REPETITIONS`] launches, after a warm-up one.
+fn measure(launch: &mut dyn FnMut()) -> Duration {
+    launch();
+    (0..REPETITIONS)
+        .map(|_| {
+            let start = Instant::now();
+            launch();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let mut args = env::args().skip(1).map(|arg| {
+        arg.parse::<u32>()
+            .unwrap_or_else(|_| panic!("log sizes must be integers, got `{arg}`"))
+    });
+    let min_log_size = args.next().unwrap_or(16);
+    let max_log_size = args.next().unwrap_or(24);
+    assert!(
+        (1..31).contains(&min_log_size) && min_log_size <= max_log_size && max_log_size < 31,
+        "log sizes must be increasing, in 1..31"
+    );
+    if let Err(error) = try_init() {
+        eprintln!("no usable device: {error}");
+        std::process::exit(1);
+    }
+
+    let default = kernel_tuning();
+    println!(
+        "{:<20}{:>9}{:>7}{:>12}{:>10}{:>12}",
+        "kernel", "log_size", "block", "time (us)", "GB/s", "Melem/s"
+    );
+    for kernel in KERNELS {
+        let block_dims = match kernel.tunable {
+            Tunable::None => vec![None],
+            _ => BLOCK_DIMS.into_iter().map(Some).collect(),
+        };
+        for log_size in min_log_size..=max_log_size {
+            let mut launch = (kernel.setup)(log_size);
+            for &block_dim in &block_dims {
+                if let Some(block_dim) = block_dim {
+                    set_kernel_tuning(with_block_dim(default, kernel.tunable, block_dim));
+                }
+                let seconds = measure(&mut launch).as_secs_f64();
+                let size = (1u64 << log_size) as f64;
+                println!(
+                    "{:<20}{:>9}{:>7}{:>12.1}{:>10.1}{:>12.1}",
+                    kernel.name,
+                    log_size,
+                    block_dim.map_or("-".to_string(), |block_dim| block_dim.to_string()),
+                    seconds * 1e6,
+                    size * kernel.bytes_per_element as f64 / seconds / 1e9,
+                    size / seconds / 1e6,
+                );
+            }
+            set_kernel_tuning(default);
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! C interface to the GPU prover, declared in `include/stwo_gpu.h`. The library is only built on
+//! request, with the `capi` feature:
+//!
+//! ```bash
+//! cargo rustc --release --features capi --crate-type cdylib
+//! ```
+//!
+//! Objects are opaque handles owned by the caller and released with the matching `_free`
+//! function. Null handles are rejected with [`STWO_GPU_ERROR_INVALID_ARGUMENT`]. Functions never
+//! unwind into the caller: panics are reported as [`STWO_GPU_ERROR_PANIC`], or a null handle.
+
+use std::{
+    os::raw::c_int,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use stwo_prover::core::{
+    backend::Column,
+    fields::m31::{BaseField, P},
+    poly::circle::{CanonicCoset, CircleEvaluation},
+};
+
+use crate::{
+    air::{prove_squares, SquaresProof},
+    commitment::{commit_on_gpu, queries_per_log_size, GpuCommitment},
+    cuda::BaseFieldVec,
+    device::{try_init, InitError},
+};
+
+pub const STWO_GPU_OK: c_int = 0;
+pub const STWO_GPU_ERROR_NO_DEVICE: c_int = 1;
+pub const STWO_GPU_ERROR_INSUFFICIENT_DRIVER: c_int = 2;
+pub const STWO_GPU_ERROR_CUDA: c_int = 3;
+pub const STWO_GPU_ERROR_INVALID_ARGUMENT: c_int = 4;
+pub const STWO_GPU_ERROR_PANIC: c_int = 5;
+pub const STWO_GPU_ERROR_CONTEXT_CORRUPTED: c_int = 6;
+pub const STWO_GPU_ERROR_PROVING: c_int = 7;
+pub const STWO_GPU_ERROR_INVALID_PROOF: c_int = 8;
+
+/// A column of field elements on the device.
+pub struct StwoGpuColumn(BaseFieldVec);
+
+/// A commitment to columns, kept on the device until it is opened.
+pub struct StwoGpuCommitment(GpuCommitment);
+
+/// A proof of the squares AIR from [`stwo_gpu_prove_squares`], until it is verified.
+pub struct StwoGpuProof(Option<SquaresProof>);
+
+/// Bytes allocated by the library, released with [`stwo_gpu_bytes_free`].
+#[repr(C)]
+pub struct StwoGpuBytes {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+fn init_error_code(error: InitError) -> c_int {
+    match error {
+        InitError::NoDevice => STWO_GPU_ERROR_NO_DEVICE,
+        InitError::InsufficientDriver { .. } => STWO_GPU_ERROR_INSUFFICIENT_DRIVER,
+        InitError::Cuda(_) | InitError::AlreadyInitialized => STWO_GPU_ERROR_CUDA,
+        InitError::ContextCorrupted(_) => STWO_GPU_ERROR_CONTEXT_CORRUPTED,
+    }
+}
+
+/// Runs `f`, turning a panic into [`STWO_GPU_ERROR_PANIC`].
+fn guard(f: impl FnOnce() -> c_int) -> c_int {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(STWO_GPU_ERROR_PANIC)
+}
+
+/// Runs `f`, turning a panic into a null handle.
+fn guard_handle<T>(f: impl FnOnce() -> *mut T) -> *mut T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(ptr::null_mut())
+}
+
+/// Creates the CUDA context, see [`try_init`]. Every other function initializes it on first use
+/// anyway, but this reports why the device can't be used.
+#[no_mangle]
+pub extern "C" fn stwo_gpu_init() -> c_int {
+    guard(|| match try_init() {
+        Ok(_) => STWO_GPU_OK,
+        Err(error) => init_error_code(error),
+    })
+}
+
+/// Uploads the `len` values at `values`, which must be field elements. Returns null if one isn't.
+///
+/// # Safety
+///
+/// `values` must point to `len` readable values.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_column_upload(
+    values: *const u32,
+    len: usize,
+) -> *mut StwoGpuColumn {
+    guard_handle(|| {
+        if values.is_null() && len > 0 {
+            return ptr::null_mut();
+        }
+        let values = if len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(values, len)
+        };
+        if values.iter().any(|&value| value >= P) {
+            return ptr::null_mut();
+        }
+        // The values were checked to be reduced above.
+        Box::into_raw(Box::new(StwoGpuColumn(BaseFieldVec::from_u32_slice(
+            values,
+        ))))
+    })
+}
+
+/// Writes the number of values of `column` to `len`.
+///
+/// # Safety
+///
+/// `column` must be null or a live column handle, and `len` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_column_len(
+    column: *const StwoGpuColumn,
+    len: *mut usize,
+) -> c_int {
+    if column.is_null() || len.is_null() {
+        return STWO_GPU_ERROR_INVALID_ARGUMENT;
+    }
+    *len = (*column).0.len();
+    STWO_GPU_OK
+}
+
+/// Copies the values of `column` to `dst`, which must have room for all of them.
+///
+/// # Safety
+///
+/// `column` must be null or a live column handle and `dst` must point to
+/// [`stwo_gpu_column_len`] writable values.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_column_download(
+    column: *const StwoGpuColumn,
+    dst: *mut u32,
+) -> c_int {
+    guard(|| {
+        if column.is_null() {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        }
+        let column = &(*column).0;
+        if dst.is_null() && column.len() > 0 {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        }
+        let values = column.to_cpu();
+        ptr::copy_nonoverlapping(values.as_ptr() as *const u32, dst, values.len());
+        STWO_GPU_OK
+    })
+}
+
+/// # Safety
+///
+/// `column` must be null or a live column handle, which can't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_column_free(column: *mut StwoGpuColumn) {
+    if !column.is_null() {
+        drop(Box::from_raw(column));
+    }
+}
+
+/// Commits to the `n_columns` columns at `columns`, given in natural order over canonic cosets
+/// of power of two sizes, extending them by `2^log_blowup_factor`, see [`commit_on_gpu`]. The
+/// root is written to `root`. The columns are left untouched.
+///
+/// Returns null if a column size is not a power of two.
+///
+/// # Safety
+///
+/// `columns` must point to `n_columns` live column handles and `root` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_commit(
+    columns: *const *const StwoGpuColumn,
+    n_columns: usize,
+    log_blowup_factor: u32,
+    root: *mut u8,
+) -> *mut StwoGpuCommitment {
+    guard_handle(|| {
+        if columns.is_null() || root.is_null() {
+            return ptr::null_mut();
+        }
+        let columns = slice::from_raw_parts(columns, n_columns);
+        if columns
+            .iter()
+            .any(|&column| column.is_null() || !(*column).0.len().is_power_of_two())
+        {
+            return ptr::null_mut();
+        }
+
+        let evaluations = columns
+            .iter()
+            .map(|&column| {
+                let column = &(*column).0;
+                let mut values = BaseFieldVec::new_uninitialized(column.len());
+                values.copy_from(column);
+                let domain = CanonicCoset::new(column.len().ilog2()).circle_domain();
+                CircleEvaluation::new(domain, values)
+            })
+            .collect();
+        let (commitment_root, commitment) = commit_on_gpu(evaluations, log_blowup_factor);
+        ptr::copy_nonoverlapping(commitment_root.0.as_ptr(), root, 32);
+        Box::into_raw(Box::new(StwoGpuCommitment(commitment)))
+    })
+}
+
+/// Opens `commitment` at the `n_positions` positions at `positions`, given in the largest
+/// extended columns, and writes the serialized opening to `proof`.
+///
+/// Smaller columns are opened at the positions divided by their ratio to the largest ones, as
+/// FRI queries are. The opening holds, as little endian words: for each column, the number of
+/// its queried values and the values, then the number of hashes of the Merkle decommitment, its
+/// hashes as 32 bytes each, and the number of column values it needs and the values.
+///
+/// # Safety
+///
+/// `commitment` must be null or a live commitment handle, `positions` must point to
+/// `n_positions` readable positions and `proof` must be null or point to a writable
+/// [`StwoGpuBytes`]. `*proof` is overwritten without being released, so bytes it holds from a
+/// previous call must be freed first with [`stwo_gpu_bytes_free`].
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_commitment_open(
+    commitment: *const StwoGpuCommitment,
+    positions: *const usize,
+    n_positions: usize,
+    proof: *mut StwoGpuBytes,
+) -> c_int {
+    guard(|| {
+        if commitment.is_null() || proof.is_null() || (positions.is_null() && n_positions > 0) {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        }
+        let commitment = &(*commitment).0;
+        let positions = if n_positions == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(positions, n_positions)
+        };
+        let log_sizes = commitment.extended_log_sizes();
+        let max_log_size = log_sizes.iter().copied().max();
+        let Some(size) = max_log_size.and_then(|log_size| 1usize.checked_shl(log_size)) else {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        };
+        if positions.iter().any(|&position| position >= size) {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        }
+
+        let (queried_values, decommitment) =
+            commitment.decommit(queries_per_log_size(&log_sizes, positions));
+
+        let mut bytes = vec![];
+        for values in &queried_values {
+            write_field_elements(&mut bytes, values);
+        }
+        bytes.extend_from_slice(&(decommitment.hash_witness.len() as u32).to_le_bytes());
+        for hash in &decommitment.hash_witness {
+            bytes.extend_from_slice(&hash.0);
+        }
+        write_field_elements(&mut bytes, &decommitment.column_witness);
+
+        let bytes = bytes.into_boxed_slice();
+        *proof = StwoGpuBytes {
+            len: bytes.len(),
+            data: Box::into_raw(bytes) as *mut u8,
+        };
+        STWO_GPU_OK
+    })
+}
+
+fn write_field_elements(bytes: &mut Vec<u8>, values: &[BaseField]) {
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        bytes.extend_from_slice(&value.0.to_le_bytes());
+    }
+}
+
+/// # Safety
+///
+/// `commitment` must be null or a live commitment handle, which can't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_commitment_free(commitment: *mut StwoGpuCommitment) {
+    if !commitment.is_null() {
+        drop(Box::from_raw(commitment));
+    }
+}
+
+/// Proves, on the device, that a second column holds the squares of `column`, read as an
+/// evaluation in bit reversed order over the canonic coset of its size, see [`prove_squares`].
+/// The proof is written to `proof` and can be checked with [`stwo_gpu_proof_verify`].
+///
+/// A smoke test of the whole prover on a given device and trace size, not an entry point for
+/// other AIRs: the squares AIR is the only one proved through the C interface.
+///
+/// # Safety
+///
+/// `column` must be null or a live column handle, and `proof` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_prove_squares(
+    column: *const StwoGpuColumn,
+    proof: *mut *mut StwoGpuProof,
+) -> c_int {
+    guard(|| {
+        if column.is_null() || proof.is_null() || !(*column).0.len().is_power_of_two() {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        }
+        match prove_squares(&(*column).0) {
+            Ok(squares_proof) => {
+                *proof = Box::into_raw(Box::new(StwoGpuProof(Some(squares_proof))));
+                STWO_GPU_OK
+            }
+            Err(_) => STWO_GPU_ERROR_PROVING,
+        }
+    })
+}
+
+/// Checks `proof` with the verifier of stwo, returning [`STWO_GPU_ERROR_INVALID_PROOF`] if it is
+/// invalid. The verifier consumes the proof, so checking it again returns
+/// [`STWO_GPU_ERROR_INVALID_ARGUMENT`]; the handle must still be freed.
+///
+/// # Safety
+///
+/// `proof` must be null or a live proof handle.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_proof_verify(proof: *mut StwoGpuProof) -> c_int {
+    guard(|| {
+        let Some(proof) = proof.as_mut().and_then(|proof| proof.0.take()) else {
+            return STWO_GPU_ERROR_INVALID_ARGUMENT;
+        };
+        match proof.verify() {
+            Ok(()) => STWO_GPU_OK,
+            Err(_) => STWO_GPU_ERROR_INVALID_PROOF,
+        }
+    })
+}
+
+/// # Safety
+///
+/// `proof` must be null or a live proof handle, which can't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_proof_free(proof: *mut StwoGpuProof) {
+    if !proof.is_null() {
+        drop(Box::from_raw(proof));
+    }
+}
+
+/// Releases bytes returned by the library and resets `bytes` to empty.
+///
+/// # Safety
+///
+/// `bytes` must be null or point to bytes returned by the library, or to empty ones.
+#[no_mangle]
+pub unsafe extern "C" fn stwo_gpu_bytes_free(bytes: *mut StwoGpuBytes) {
+    let Some(bytes) = bytes.as_mut() else {
+        return;
+    };
+    if !bytes.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            bytes.data, bytes.len,
+        )));
+    }
+    *bytes = StwoGpuBytes {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::poly::NaturalOrder;
+
+    use super::*;
+    use crate::backend::CudaBackend;
+
+    #[test]
+    fn test_c_api_round_trip() {
+        require_gpu!();
+        assert_eq!(stwo_gpu_init(), STWO_GPU_OK);
+        let values = (0..1 << 8).map(|i| i * 3).collect::<Vec<u32>>();
+
+        unsafe {
+            assert!(stwo_gpu_column_upload([P].as_ptr(), 1).is_null());
+            let column = stwo_gpu_column_upload(values.as_ptr(), values.len());
+            assert!(!column.is_null());
+            let mut len = 0;
+            assert_eq!(stwo_gpu_column_len(column, &mut len), STWO_GPU_OK);
+            assert_eq!(len, values.len());
+            assert_eq!(
+                stwo_gpu_column_len(ptr::null(), &mut len),
+                STWO_GPU_ERROR_INVALID_ARGUMENT
+            );
+            let mut downloaded = vec![0; values.len()];
+            assert_eq!(
+                stwo_gpu_column_download(column, downloaded.as_mut_ptr()),
+                STWO_GPU_OK
+            );
+            assert_eq!(downloaded, values);
+
+            let mut root = [0u8; 32];
+            let commitment =
+                stwo_gpu_commit([column as *const _].as_ptr(), 1, 1, root.as_mut_ptr());
+            assert!(!commitment.is_null());
+            let evaluation = CircleEvaluation::<CudaBackend, _, NaturalOrder>::new(
+                CanonicCoset::new(8).circle_domain(),
+                BaseFieldVec::from_vec(values.iter().map(|&v| BaseField::from(v)).collect()),
+            );
+            let (expected_root, expected_commitment) = commit_on_gpu(vec![evaluation], 1);
+            assert_eq!(root, expected_root.0);
+
+            let positions = [3usize, 100, 511];
+            let mut proof = StwoGpuBytes {
+                data: ptr::null_mut(),
+                len: 0,
+            };
+            assert_eq!(
+                stwo_gpu_commitment_open(commitment, positions.as_ptr(), 3, &mut proof),
+                STWO_GPU_OK
+            );
+            let (queried_values, decommitment) =
+                expected_commitment.decommit([(9, positions.to_vec())].into_iter().collect());
+            let mut expected_proof = vec![];
+            write_field_elements(&mut expected_proof, &queried_values[0]);
+            expected_proof
+                .extend_from_slice(&(decommitment.hash_witness.len() as u32).to_le_bytes());
+            for hash in &decommitment.hash_witness {
+                expected_proof.extend_from_slice(&hash.0);
+            }
+            write_field_elements(&mut expected_proof, &decommitment.column_witness);
+            assert_eq!(slice::from_raw_parts(proof.data, proof.len), expected_proof);
+            assert_eq!(
+                stwo_gpu_commitment_open(commitment, [512usize].as_ptr(), 1, &mut proof),
+                STWO_GPU_ERROR_INVALID_ARGUMENT
+            );
+
+            assert_eq!(
+                stwo_gpu_commitment_open(ptr::null(), positions.as_ptr(), 3, &mut proof),
+                STWO_GPU_ERROR_INVALID_ARGUMENT
+            );
+
+            stwo_gpu_bytes_free(&mut proof);
+            assert!(proof.data.is_null());
+            stwo_gpu_commitment_free(commitment);
+            stwo_gpu_column_free(column);
+        }
+    }
+
+    #[test]
+    fn test_c_api_prove_squares() {
+        require_gpu!();
+        let values = (0..1 << 8).map(|i| i * 3).collect::<Vec<u32>>();
+
+        unsafe {
+            let column = stwo_gpu_column_upload(values.as_ptr(), values.len());
+            let mut proof = ptr::null_mut();
+            assert_eq!(stwo_gpu_prove_squares(column, &mut proof), STWO_GPU_OK);
+            assert_eq!(stwo_gpu_proof_verify(proof), STWO_GPU_OK);
+            assert_eq!(
+                stwo_gpu_proof_verify(proof),
+                STWO_GPU_ERROR_INVALID_ARGUMENT
+            );
+            assert_eq!(
+                stwo_gpu_prove_squares(ptr::null(), &mut proof),
+                STWO_GPU_ERROR_INVALID_ARGUMENT
+            );
+            stwo_gpu_proof_free(proof);
+            stwo_gpu_column_free(column);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Take, Write},
+    path::Path,
+};
+
+use stwo_prover::core::{
+    backend::Column,
+    circle::{CirclePointIndex, Coset},
+    fields::{m31::P, secure_column::SecureColumn},
+    poly::line::{LineDomain, LineEvaluation},
+    vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec, Blake2sHashVec},
+};
+
+/// Identifies checkpoint files written by [`save_checkpoint`].
+const MAGIC: [u8; 8] = *b"STWOGPU\0";
+/// Bumped whenever the encoding of any [`Checkpoint`] changes.
+const VERSION: u32 = 1;
+/// Number of words going through the host staging buffer at a time.
+const CHUNK_WORDS: usize = 1 << 20;
+
+/// Prover state that can be spilled to the host and written out, so that a preempted job can
+/// resume from it instead of restarting the proof.
+///
+/// Device vectors are streamed through a bounded host buffer in both directions, so checkpoints
+/// of huge columns don't need a host copy of the whole state. Everything is encoded as little
+/// endian words.
+pub trait Checkpoint: Sized {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()>;
+
+    /// Reads back a value written by [`Checkpoint::write_checkpoint`], uploading its vectors to
+    /// the device.
+    ///
+    /// `reader` is limited to the bytes of the checkpoint, and every length read from it is
+    /// checked against the bytes left before anything is allocated for it, so a corrupt file
+    /// fails with [`io::ErrorKind::InvalidData`] instead of exhausting memory.
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self>;
+}
+
+/// Writes `state` to the file at `path`, with a header identifying the format.
+///
+/// The state is first written to a temporary file next to `path`, which is renamed once
+/// complete, so a job preempted while saving leaves the previous checkpoint intact.
+pub fn save_checkpoint(path: impl AsRef<Path>, state: &impl Checkpoint) -> io::Result<()> {
+    let path = path.as_ref();
+    // Appended rather than substituted for the extension, so `a.bin` and `a.ckpt` don't share it.
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    writer.write_all(&MAGIC)?;
+    write_u32(&mut writer, VERSION)?;
+    state.write_checkpoint(&mut writer)?;
+    writer.into_inner()?.sync_all()?;
+    fs::rename(tmp_path, path)
+}
+
+/// Reads the state saved by [`save_checkpoint`] at `path`.
+pub fn load_checkpoint<T: Checkpoint>(path: impl AsRef<Path>) -> io::Result<T> {
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file).take(file_len);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid_data("not a checkpoint file"));
+    }
+    let version = read_u32(&mut reader)?;
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "unsupported checkpoint version {version}"
+        )));
+    }
+    T::read_checkpoint(&mut reader)
+}
+
+impl Checkpoint for BaseFieldVec {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        write_len(writer, self.size)?;
+        write_device_words(writer, self.device_ptr(), self.size)
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let size = read_len(reader, 4)?;
+        let vec = Self::new_uninitialized(size);
+        read_device_words(reader, vec.device_ptr(), size, |word| word < P)?;
+        Ok(vec)
+    }
+}
+
+impl Checkpoint for Blake2sHashVec {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        write_len(writer, self.size)?;
+        write_device_words(writer, self.device_ptr(), cuda::HASH_WORDS * self.size)
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let size = read_len(reader, 4 * cuda::HASH_WORDS)?;
+        let vec = Self::new_uninitialized(size);
+        read_device_words(reader, vec.device_ptr(), cuda::HASH_WORDS * size, |_| true)?;
+        Ok(vec)
+    }
+}
+
+impl<T: Checkpoint> Checkpoint for Vec<T> {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        write_len(writer, self.len())?;
+        self.iter()
+            .try_for_each(|value| value.write_checkpoint(writer))
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        // Every encoded value takes at least a byte.
+        let len = read_len(reader, 1)?;
+        (0..len).map(|_| T::read_checkpoint(reader)).collect()
+    }
+}
+
+impl Checkpoint for SecureColumn<CudaBackend> {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.columns
+            .iter()
+            .try_for_each(|column| column.write_checkpoint(writer))
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let columns = [
+            BaseFieldVec::read_checkpoint(reader)?,
+            BaseFieldVec::read_checkpoint(reader)?,
+            BaseFieldVec::read_checkpoint(reader)?,
+            BaseFieldVec::read_checkpoint(reader)?,
+        ];
+        if columns
+            .iter()
+            .any(|column| column.len() != columns[0].len())
+        {
+            return Err(invalid_data("secure column coordinates of different sizes"));
+        }
+        Ok(SecureColumn { columns })
+    }
+}
+
+/// A FRI layer, with its domain.
+impl Checkpoint for LineEvaluation<CudaBackend> {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        let coset = self.domain().coset();
+        write_len(writer, coset.initial_index.0)?;
+        write_u32(writer, coset.log_size)?;
+        self.values.write_checkpoint(writer)
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let initial_index = read_u64(reader)?;
+        let log_size = read_u32(reader)?;
+        if initial_index >= 1 << 31 {
+            return Err(invalid_data("line domain point index out of range"));
+        }
+        if log_size > 31 {
+            return Err(invalid_data("line domain too large"));
+        }
+        let coset = Coset::new(CirclePointIndex(initial_index as usize), log_size);
+        // `LineDomain::new` asserts the x-coordinates of the coset are distinct.
+        let has_distinct_xs = match coset.size() {
+            1 => true,
+            2 => coset.initial.x.0 != 0,
+            _ => coset.initial.log_order() >= coset.step.log_order() + 2,
+        };
+        if !has_distinct_xs {
+            return Err(invalid_data("line domain with repeated x-coordinates"));
+        }
+        let domain = LineDomain::new(coset);
+        let values = SecureColumn::read_checkpoint(reader)?;
+        if values.len() != domain.size() {
+            return Err(invalid_data("line evaluation of the wrong size"));
+        }
+        Ok(LineEvaluation::new(domain, values))
+    }
+}
+
+/// The layers of a committed tree, root first.
+impl Checkpoint for MerkleProver<CudaBackend, Blake2sMerkleHasher> {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.layers.write_checkpoint(writer)
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let layers = Vec::<Blake2sHashVec>::read_checkpoint(reader)?;
+        let is_complete = (layers.iter().enumerate())
+            .all(|(i, layer)| i < usize::BITS as usize && layer.len() == 1 << i);
+        if layers.is_empty() || !is_complete {
+            return Err(invalid_data("malformed Merkle tree layers"));
+        }
+        Ok(MerkleProver { layers })
+    }
+}
+
+/// Streams `n_words` words starting at `device_ptr` to `writer`.
+fn write_device_words(
+    writer: &mut impl Write,
+    device_ptr: *const u32,
+    n_words: usize,
+) -> io::Result<()> {
+    let mut buffer = vec![0u32; CHUNK_WORDS.min(n_words)];
+    for start in (0..n_words).step_by(CHUNK_WORDS) {
+        let chunk = &mut buffer[..CHUNK_WORDS.min(n_words - start)];
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_device_to_host(
+                device_ptr.add(start),
+                chunk.as_mut_ptr() as *const u32,
+                chunk.len() as u32,
+            );
+        }
+        let bytes = chunk
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        writer.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+/// Streams `n_words` words from `reader` to the device memory at `device_ptr`, failing if any of
+/// them is not `valid`.
+fn read_device_words(
+    reader: &mut impl Read,
+    device_ptr: *const u32,
+    n_words: usize,
+    valid: impl Fn(u32) -> bool,
+) -> io::Result<()> {
+    let mut bytes = vec![0u8; 4 * CHUNK_WORDS.min(n_words)];
+    for start in (0..n_words).step_by(CHUNK_WORDS) {
+        let len = CHUNK_WORDS.min(n_words - start);
+        reader.read_exact(&mut bytes[..4 * len])?;
+        let chunk = bytes[..4 * len]
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        if !chunk.iter().all(|&word| valid(word)) {
+            return Err(invalid_data("value out of range"));
+        }
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_host_to_existing_device(
+                chunk.as_ptr(),
+                device_ptr.add(start),
+                len as u32,
+            );
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_len(writer: &mut impl Write, len: usize) -> io::Result<()> {
+    writer.write_all(&(len as u64).to_le_bytes())
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads the length of a sequence of items encoded in `item_bytes` bytes each, failing if they
+/// don't fit in what is left of `reader`.
+fn read_len(reader: &mut Take<impl Read>, item_bytes: usize) -> io::Result<usize> {
+    let len = read_u64(reader)?;
+    match len.checked_mul(item_bytes as u64) {
+        Some(bytes) if bytes <= reader.limit() => Ok(len as usize),
+        _ => Err(invalid_data(format!(
+            "length {len} exceeds the {} bytes left",
+            reader.limit()
+        ))),
+    }
+}
+
+pub(crate) fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        circle::Coset,
+        fields::{m31::BaseField, secure_column::SecureColumn},
+        poly::line::{LineDomain, LineEvaluation},
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use std::io::{ErrorKind, Read};
+
+    use super::{load_checkpoint, save_checkpoint, Checkpoint};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    fn column(size: usize, offset: u32) -> Vec<BaseField> {
+        (0..size as u32)
+            .map(|i| BaseField::from(offset + i))
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        require_gpu!();
+        let log_size = 8;
+        let values = (0..3)
+            .map(|i| column(1 << log_size, i * 1000))
+            .collect::<Vec<_>>();
+        let columns = values
+            .iter()
+            .cloned()
+            .map(BaseFieldVec::from_vec)
+            .collect::<Vec<_>>();
+        let tree =
+            MerkleProver::<CudaBackend, Blake2sMerkleHasher>::commit(columns.iter().collect());
+        let cpu_tree =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(values.iter().collect());
+        let domain = LineDomain::new(Coset::half_odds(log_size - 2));
+        let layer = LineEvaluation::<CudaBackend>::new(
+            domain,
+            SecureColumn {
+                columns: std::array::from_fn(|i| {
+                    BaseFieldVec::from_vec(column(domain.size(), 10 * i as u32))
+                }),
+            },
+        );
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.bin", std::process::id()));
+
+        let mut bytes = vec![];
+        columns.write_checkpoint(&mut bytes).unwrap();
+        layer.write_checkpoint(&mut bytes).unwrap();
+        save_checkpoint(&path, &tree).unwrap();
+        let mut reader = bytes.as_slice().take(bytes.len() as u64);
+        let resumed_columns = Vec::<BaseFieldVec>::read_checkpoint(&mut reader).unwrap();
+        let resumed_layer = LineEvaluation::<CudaBackend>::read_checkpoint(&mut reader).unwrap();
+        let resumed_tree: MerkleProver<CudaBackend, Blake2sMerkleHasher> =
+            load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.limit(), 0);
+        assert_eq!(resumed_columns, columns);
+        assert_eq!(resumed_layer.domain().coset(), domain.coset());
+        assert_eq!(resumed_layer.values.columns, layer.values.columns);
+        assert_eq!(resumed_tree.root(), cpu_tree.root());
+        assert!(load_checkpoint::<BaseFieldVec>(&path).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_length() {
+        let mut bytes = (1u64 << 40).to_le_bytes().to_vec();
+        bytes.extend([0; 64]);
+
+        let error = BaseFieldVec::read_checkpoint(&mut bytes.as_slice().take(bytes.len() as u64))
+            .unwrap_err();
+        let truncated = Vec::<BaseFieldVec>::read_checkpoint(&mut bytes[..4].take(4)).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(truncated.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_corrupt_line_domain() {
+        // A coset starting at the identity holds two points at each x-coordinate.
+        let mut bytes = 0u64.to_le_bytes().to_vec();
+        bytes.extend(3u32.to_le_bytes());
+
+        let error = LineEvaluation::<CudaBackend>::read_checkpoint(&mut bytes.as_slice().take(12))
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -1,6 +1,7 @@
 use stwo_prover::core::{
     backend::{Column, ColumnOps},
     fields::{m31::BaseField, qm31::SecureField},
+    vcs::blake2_hash::Blake2sHash,
 };
 
 use crate::{backend::CudaBackend, cuda};
@@ -8,32 +9,45 @@ use crate::{backend::CudaBackend, cuda};
 impl ColumnOps<BaseField> for CudaBackend {
     type Column = cuda::BaseFieldVec;
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
     fn bit_reverse_column(column: &mut Self::Column) {
-        let size = column.len();
-        assert!(size.is_power_of_two() && size < u32::MAX as usize);
-
-        unsafe {
-            cuda::bindings::bit_reverse_base_field(column.device_ptr as *const u32, size);
-        }
+        // Lazy, so reversing a column back before using it launches nothing.
+        column.bit_reverse();
     }
 }
 
 impl ColumnOps<SecureField> for CudaBackend {
     type Column = cuda::SecureFieldVec;
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
     fn bit_reverse_column(column: &mut Self::Column) {
-        let size = column.len();
-        assert!(size.is_power_of_two() && size < u32::MAX as usize);
+        // Lazy, so reversing a column back before using it launches nothing.
+        column.bit_reverse();
+    }
+}
 
-        unsafe {
-            cuda::bindings::bit_reverse_secure_field(column.device_ptr as *const u32, size);
-        }
+impl ColumnOps<Blake2sHash> for CudaBackend {
+    type Column = cuda::Blake2sHashVec;
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
+    fn bit_reverse_column(column: &mut Self::Column) {
+        assert!(column.size.is_power_of_two() && column.size < u32::MAX as usize);
+        unsafe { cuda::bindings::bit_reverse_blake2s_hash(column.device_ptr(), column.size) };
     }
 }
 
 impl Column<BaseField> for cuda::BaseFieldVec {
-    fn zeros(_len: usize) -> Self {
-        todo!()
+    fn zeros(len: usize) -> Self {
+        Self::new_zeroes(len)
     }
 
     fn to_cpu(&self) -> Vec<BaseField> {
@@ -44,24 +58,24 @@ impl Column<BaseField> for cuda::BaseFieldVec {
         self.size
     }
 
-    fn at(&self, _index: usize) -> BaseField {
-        todo!()
+    fn at(&self, index: usize) -> BaseField {
+        self.value_at(index)
     }
 
-    fn set(&mut self, _index: usize, _value: BaseField) {
-        todo!()
+    fn set(&mut self, index: usize, value: BaseField) {
+        self.set_value(index, value);
     }
 }
 
 impl FromIterator<BaseField> for cuda::BaseFieldVec {
-    fn from_iter<T: IntoIterator<Item = BaseField>>(_iter: T) -> Self {
-        todo!()
+    fn from_iter<T: IntoIterator<Item = BaseField>>(iter: T) -> Self {
+        Self::from_vec(iter.into_iter().collect())
     }
 }
 
 impl Column<SecureField> for cuda::SecureFieldVec {
-    fn zeros(_len: usize) -> Self {
-        todo!()
+    fn zeros(len: usize) -> Self {
+        Self::new_zeroes(len)
     }
 
     fn to_cpu(&self) -> Vec<SecureField> {
@@ -72,48 +86,84 @@ impl Column<SecureField> for cuda::SecureFieldVec {
         self.size
     }
 
-    fn at(&self, _index: usize) -> SecureField {
-        todo!()
+    fn at(&self, index: usize) -> SecureField {
+        self.value_at(index)
     }
 
-    fn set(&mut self, _index: usize, _value: SecureField) {
-        todo!()
+    fn set(&mut self, index: usize, value: SecureField) {
+        self.set_value(index, value);
     }
 }
 
 impl FromIterator<SecureField> for cuda::SecureFieldVec {
-    fn from_iter<T: IntoIterator<Item = SecureField>>(_iter: T) -> Self {
-        todo!()
+    fn from_iter<T: IntoIterator<Item = SecureField>>(iter: T) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl Column<Blake2sHash> for cuda::Blake2sHashVec {
+    fn zeros(len: usize) -> Self {
+        Self::new_zeroes(len)
+    }
+
+    fn to_cpu(&self) -> Vec<Blake2sHash> {
+        self.to_vec()
+    }
+
+    fn len(&self) -> usize {
+        self.size
+    }
+
+    fn at(&self, index: usize) -> Blake2sHash {
+        cuda::words_to_hashes(&self.words(index, 1))[0]
+    }
+
+    fn set(&mut self, index: usize, value: Blake2sHash) {
+        assert!(index < self.size);
+        let words = cuda::hash_to_words(&value);
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_host_to_existing_device(
+                words.as_ptr(),
+                self.device_ptr().add(words.len() * index),
+                words.len() as u32,
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use stwo_prover::core::{
-        backend::{Column, ColumnOps, CpuBackend},
+        backend::{Col, Column, ColumnOps, CpuBackend},
         fields::{m31::BaseField, qm31::SecureField},
+        vcs::blake2_hash::Blake2sHash,
     };
 
     use crate::{
         backend::CudaBackend,
-        cuda::{BaseFieldVec, SecureFieldVec},
+        cuda::{BaseFieldVec, Blake2sHashVec, SecureFieldVec},
     };
 
     #[test]
     fn test_bit_reverse_base_field() {
-        let size: usize = 1 << 12;
-        let column_data = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
-        let mut expected_result = column_data.clone();
-        CpuBackend::bit_reverse_column(&mut expected_result);
+        require_gpu!();
+        // Small sizes don't fill the tiles of the vectorized kernel.
+        for log_size in [0, 3, 4, 5, 12] {
+            let size: usize = 1 << log_size;
+            let column_data = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
+            let mut expected_result = column_data.clone();
+            CpuBackend::bit_reverse_column(&mut expected_result);
 
-        let mut column = BaseFieldVec::from_vec(column_data);
-        <CudaBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut column);
+            let mut column = BaseFieldVec::from_vec(column_data);
+            <CudaBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut column);
 
-        assert_eq!(column.to_cpu(), expected_result);
+            assert_eq!(column.to_cpu(), expected_result);
+        }
     }
 
     #[test]
     fn test_bit_reverse_secure_field() {
+        require_gpu!();
         let size: usize = 1 << 12;
 
         let from_raw = (1..(size + 1) as u32).collect::<Vec<u32>>();
@@ -130,4 +180,120 @@ mod tests {
 
         assert_eq!(array.to_cpu(), array_expected);
     }
+
+    #[test]
+    fn test_bit_reverse_hashes() {
+        require_gpu!();
+        let hashes = (0..1 << 6)
+            .map(|i| Blake2sHash(std::array::from_fn(|j| (i + j) as u8)))
+            .collect::<Vec<_>>();
+        let mut expected_result = hashes.clone();
+        CpuBackend::bit_reverse_column(&mut expected_result);
+        let mut column = Blake2sHashVec::zeros(hashes.len());
+        for (i, &hash) in hashes.iter().enumerate() {
+            column.set(i, hash);
+        }
+
+        let copy = column.clone();
+        <CudaBackend as ColumnOps<Blake2sHash>>::bit_reverse_column(&mut column);
+
+        assert_eq!(column.to_cpu(), expected_result);
+        assert_eq!(copy.to_cpu(), hashes);
+    }
+
+    /// Generic code over the secure field columns of any backend.
+    fn reversed_secure_column<B: ColumnOps<SecureField>>(
+        values: &[SecureField],
+    ) -> Col<B, SecureField> {
+        let mut column = values.iter().copied().collect::<Col<B, SecureField>>();
+        B::bit_reverse_column(&mut column);
+        column
+    }
+
+    #[test]
+    fn test_generic_secure_field_column() {
+        require_gpu!();
+        let values = (0..1 << 8)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+
+        let column = reversed_secure_column::<CudaBackend>(&values);
+
+        assert_eq!(
+            column.to_cpu(),
+            reversed_secure_column::<CpuBackend>(&values)
+        );
+    }
+
+    #[test]
+    fn test_zeros_and_at() {
+        require_gpu!();
+        let size: usize = 1 << 10;
+        let column_data = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
+        let secure_column_data = (0..size as u32)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+
+        let column = BaseFieldVec::from_vec(column_data.clone());
+        let secure_column = SecureFieldVec::from_vec(secure_column_data.clone());
+
+        assert_eq!(
+            BaseFieldVec::zeros(size).to_cpu(),
+            vec![BaseField::from(0); size]
+        );
+        assert_eq!(
+            SecureFieldVec::zeros(size).to_cpu(),
+            vec![SecureField::from_u32_unchecked(0, 0, 0, 0); size]
+        );
+        for index in [0, 1, size - 1] {
+            assert_eq!(column.at(index), column_data[index]);
+            assert_eq!(secure_column.at(index), secure_column_data[index]);
+        }
+    }
+
+    #[test]
+    fn test_set_and_from_iter() {
+        require_gpu!();
+        let size: usize = 1 << 10;
+        let mut column_data = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
+        let mut secure_column_data = (0..size as u32)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+        let mut hashes = vec![Blake2sHash::default(); 4];
+
+        let mut column = column_data.iter().copied().collect::<BaseFieldVec>();
+        let mut secure_column = secure_column_data
+            .iter()
+            .copied()
+            .collect::<SecureFieldVec>();
+        let mut hash_column = Blake2sHashVec::zeros(hashes.len());
+        column_data[5] = BaseField::from(17);
+        secure_column_data[5] = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        hashes[2] = Blake2sHash(std::array::from_fn(|i| i as u8));
+        column.set(5, column_data[5]);
+        secure_column.set(5, secure_column_data[5]);
+        hash_column.set(2, hashes[2]);
+
+        assert_eq!(column.to_cpu(), column_data);
+        assert_eq!(secure_column.to_cpu(), secure_column_data);
+        assert_eq!(hash_column.to_cpu(), hashes);
+    }
+
+    #[test]
+    fn test_bit_reverse_small_sizes() {
+        require_gpu!();
+        // Up to twice the block size of the kernel.
+        for log_size in 1..=11 {
+            let column_data = (0..1u32 << log_size)
+                .map(BaseField::from)
+                .collect::<Vec<_>>();
+            let mut expected_result = column_data.clone();
+            CpuBackend::bit_reverse_column(&mut expected_result);
+
+            let mut column = BaseFieldVec::from_vec(column_data);
+            <CudaBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut column);
+
+            assert_eq!(column.to_cpu(), expected_result, "log_size = {log_size}");
+        }
+    }
 }
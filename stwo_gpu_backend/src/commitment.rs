@@ -0,0 +1,391 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Take, Write},
+};
+
+use stwo_prover::core::{
+    backend::{Col, Column, ColumnOps},
+    fields::m31::BaseField,
+    poly::{
+        circle::{CanonicCoset, CircleDomain, CircleEvaluation, CirclePoly, PolyOps},
+        BitReversedOrder, NaturalOrder,
+    },
+    vcs::{
+        blake2_hash::Blake2sHash,
+        blake2_merkle::Blake2sMerkleHasher,
+        prover::{MerkleDecommitment, MerkleProver},
+    },
+};
+
+use crate::{
+    backend::CudaBackend,
+    checkpoint::{invalid_data, read_u32, write_u32, Checkpoint},
+    memory::memory_phase,
+    query::{gather_capped_authentication_paths, merkle_cap},
+    twiddles::cached_twiddles,
+};
+
+/// Device-resident state of a commitment made with [`commit_on_gpu`] or
+/// [`commit_on_gpu_with_cap`].
+///
+/// Keeps the polynomials, their extensions and the Merkle tree on the device until the
+/// application asks for a decommitment.
+pub struct GpuCommitment {
+    polynomials: Vec<CirclePoly<CudaBackend>>,
+    evaluations: Vec<CircleEvaluation<CudaBackend, BaseField, BitReversedOrder>>,
+    tree: MerkleProver<CudaBackend, Blake2sMerkleHasher>,
+    cap_log_size: u32,
+}
+
+impl GpuCommitment {
+    pub fn root(&self) -> Blake2sHash {
+        self.tree.root()
+    }
+
+    /// The log size of the cap the commitment was made with, 0 for a plain root.
+    pub fn cap_log_size(&self) -> u32 {
+        self.cap_log_size
+    }
+
+    /// The `2^cap_log_size` hashes of the tree layer committed to.
+    pub fn cap(&self) -> Vec<Blake2sHash> {
+        merkle_cap(&self.tree, self.cap_log_size)
+    }
+
+    /// The interpolated columns, in the order they were committed.
+    pub fn polynomials(&self) -> &[CirclePoly<CudaBackend>] {
+        &self.polynomials
+    }
+
+    /// Returns the queried values of each extended column and the Merkle decommitment for them,
+    /// with `queries_per_log_size` indexed by the log size of the extended columns.
+    ///
+    /// The decommitment always goes up to the root, whatever the cap of the commitment.
+    pub fn decommit(
+        &self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+    ) -> (Vec<Vec<BaseField>>, MerkleDecommitment<Blake2sMerkleHasher>) {
+        self.tree
+            .decommit(queries_per_log_size, self.extended_columns())
+    }
+
+    /// Returns the authentication path of each leaf at `positions`, in the layer of the largest
+    /// extended columns. Paths stop below the cap.
+    pub fn authentication_paths(&self, positions: &[usize]) -> Vec<Vec<Blake2sHash>> {
+        gather_capped_authentication_paths(&self.tree, self.cap_log_size, positions)
+    }
+
+    /// The extended columns, in the order they were committed.
+    pub(crate) fn evaluations(
+        &self,
+    ) -> &[CircleEvaluation<CudaBackend, BaseField, BitReversedOrder>] {
+        &self.evaluations
+    }
+
+    /// The log sizes of the extended columns, in the order they were committed.
+    pub(crate) fn extended_log_sizes(&self) -> Vec<u32> {
+        self.evaluations
+            .iter()
+            .map(|evaluation| evaluation.domain.log_size())
+            .collect()
+    }
+
+    fn extended_columns(&self) -> Vec<&Col<CudaBackend, BaseField>> {
+        self.evaluations
+            .iter()
+            .map(|evaluation| &evaluation.values)
+            .collect()
+    }
+}
+
+/// Saves the polynomials, the extended columns and the tree, so a proof can resume from the
+/// commitment without recomputing it.
+impl Checkpoint for GpuCommitment {
+    fn write_checkpoint(&self, writer: &mut impl Write) -> io::Result<()> {
+        write_u32(writer, self.cap_log_size)?;
+        write_u32(writer, self.polynomials.len() as u32)?;
+        for (polynomial, evaluation) in self.polynomials.iter().zip(&self.evaluations) {
+            write_u32(writer, evaluation.domain.log_size())?;
+            polynomial.coeffs.write_checkpoint(writer)?;
+            evaluation.values.write_checkpoint(writer)?;
+        }
+        self.tree.write_checkpoint(writer)
+    }
+
+    fn read_checkpoint(reader: &mut Take<impl Read>) -> io::Result<Self> {
+        let cap_log_size = read_u32(reader)?;
+        let n_columns = read_u32(reader)?;
+        let mut polynomials = vec![];
+        let mut evaluations = vec![];
+        for _ in 0..n_columns {
+            let extended_log_size = read_u32(reader)?;
+            let coeffs = Col::<CudaBackend, BaseField>::read_checkpoint(reader)?;
+            let values = Col::<CudaBackend, BaseField>::read_checkpoint(reader)?;
+            if !coeffs.len().is_power_of_two()
+                || extended_log_size > 31
+                || values.len() != 1 << extended_log_size
+            {
+                return Err(invalid_data("malformed committed column"));
+            }
+            polynomials.push(CirclePoly::new(coeffs));
+            evaluations.push(CircleEvaluation::new(
+                circle_domain(extended_log_size),
+                values,
+            ));
+        }
+        let tree = MerkleProver::read_checkpoint(reader)?;
+        if cap_log_size as usize >= tree.layers.len() {
+            return Err(invalid_data("cap larger than the tree"));
+        }
+        Ok(Self {
+            polynomials,
+            evaluations,
+            tree,
+            cap_log_size,
+        })
+    }
+}
+
+/// Commits to `columns`, given in natural order over canonic cosets.
+///
+/// Bit reverses and interpolates each column, evaluates it on a domain `2^log_blowup_factor`
+/// times larger and builds the Merkle tree of the extended columns, all on the device. Only the
+/// root is copied back to the host. Twiddles come from the shared cache of [`cached_twiddles`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(n_columns = columns.len(), log_blowup_factor = log_blowup_factor)
+    )
+)]
+pub fn commit_on_gpu(
+    columns: Vec<CircleEvaluation<CudaBackend, BaseField, NaturalOrder>>,
+    log_blowup_factor: u32,
+) -> (Blake2sHash, GpuCommitment) {
+    let commitment = commit(columns, log_blowup_factor, 0);
+    (commitment.root(), commitment)
+}
+
+/// Like [`commit_on_gpu`], but commits to the `2^cap_log_size` hashes of the tree layer
+/// `cap_log_size` levels below the root instead of the root itself. Authentication paths given by
+/// the commitment are `cap_log_size` hashes shorter.
+///
+/// `cap_log_size` must not exceed the log size of the largest extended column.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            n_columns = columns.len(),
+            log_blowup_factor = log_blowup_factor,
+            cap_log_size = cap_log_size
+        )
+    )
+)]
+pub fn commit_on_gpu_with_cap(
+    columns: Vec<CircleEvaluation<CudaBackend, BaseField, NaturalOrder>>,
+    log_blowup_factor: u32,
+    cap_log_size: u32,
+) -> (Vec<Blake2sHash>, GpuCommitment) {
+    let commitment = commit(columns, log_blowup_factor, cap_log_size);
+    (commitment.cap(), commitment)
+}
+
+/// Spreads `positions`, given in the largest of the extended columns of `log_sizes`, to every
+/// log size as FRI queries are: shifted right by the difference of log sizes and deduplicated.
+pub(crate) fn queries_per_log_size(
+    log_sizes: &[u32],
+    positions: &[usize],
+) -> BTreeMap<u32, Vec<usize>> {
+    let max_log_size = log_sizes.iter().copied().max().unwrap_or(0);
+    log_sizes
+        .iter()
+        .map(|&log_size| {
+            let mut queries = positions
+                .iter()
+                .map(|&position| position >> (max_log_size - log_size))
+                .collect::<Vec<_>>();
+            queries.sort_unstable();
+            queries.dedup();
+            (log_size, queries)
+        })
+        .collect()
+}
+
+fn commit(
+    columns: Vec<CircleEvaluation<CudaBackend, BaseField, NaturalOrder>>,
+    log_blowup_factor: u32,
+    cap_log_size: u32,
+) -> GpuCommitment {
+    let _phase = memory_phase("commit");
+    let (polynomials, evaluations): (Vec<_>, Vec<_>) = columns
+        .into_iter()
+        .map(|column| {
+            let log_size = column.domain.log_size();
+            assert_eq!(column.domain, circle_domain(log_size));
+            let mut values = column.values;
+            <CudaBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut values);
+            let polynomial = CudaBackend::interpolate(
+                CircleEvaluation::new(column.domain, values),
+                &cached_twiddles(circle_domain(log_size).half_coset),
+            );
+
+            let extended_log_size = log_size + log_blowup_factor;
+            let evaluation = CudaBackend::evaluate(
+                &polynomial,
+                circle_domain(extended_log_size),
+                &cached_twiddles(circle_domain(extended_log_size).half_coset),
+            );
+            (polynomial, evaluation)
+        })
+        .unzip();
+
+    let tree = MerkleProver::commit(
+        evaluations
+            .iter()
+            .map(|evaluation| &evaluation.values)
+            .collect(),
+    );
+    assert!(
+        (cap_log_size as usize) < tree.layers.len(),
+        "cap larger than the tree"
+    );
+    GpuCommitment {
+        polynomials,
+        evaluations,
+        tree,
+        cap_log_size,
+    }
+}
+
+fn circle_domain(log_size: u32) -> CircleDomain {
+    CanonicCoset::new(log_size).circle_domain()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use stwo_prover::core::{
+        backend::{Column, ColumnOps, CpuBackend},
+        fields::m31::BaseField,
+        poly::circle::{CanonicCoset, CircleEvaluation, PolyOps},
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{commit_on_gpu, commit_on_gpu_with_cap, queries_per_log_size};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_commit_on_gpu() {
+        require_gpu!();
+        let log_blowup_factor = 2;
+        let columns = [(5, 0), (5, 1), (7, 2)]
+            .map(|(log_size, seed): (u32, u32)| {
+                let values = (0..1u32 << log_size)
+                    .map(|i| BaseField::from((i + seed).wrapping_mul(2654435761) % (1 << 30)))
+                    .collect::<Vec<_>>();
+                (log_size, values)
+            })
+            .to_vec();
+
+        let cpu_evaluations = columns
+            .iter()
+            .map(|(log_size, values)| {
+                let domain = CanonicCoset::new(*log_size).circle_domain();
+                let mut values = values.clone();
+                CpuBackend::bit_reverse_column(&mut values);
+                let polynomial = CpuBackend::interpolate(
+                    CircleEvaluation::new(domain, values),
+                    &CpuBackend::precompute_twiddles(domain.half_coset),
+                );
+                let extended_domain =
+                    CanonicCoset::new(log_size + log_blowup_factor).circle_domain();
+                CpuBackend::evaluate(
+                    &polynomial,
+                    extended_domain,
+                    &CpuBackend::precompute_twiddles(extended_domain.half_coset),
+                )
+                .values
+            })
+            .collect::<Vec<_>>();
+        let cpu_tree = MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(
+            cpu_evaluations.iter().collect(),
+        );
+
+        let (root, commitment) = commit_on_gpu(
+            columns
+                .into_iter()
+                .map(|(log_size, values)| {
+                    CircleEvaluation::new(
+                        CanonicCoset::new(log_size).circle_domain(),
+                        BaseFieldVec::from_vec(values),
+                    )
+                })
+                .collect(),
+            log_blowup_factor,
+        );
+        assert_eq!(root, cpu_tree.root());
+
+        let queries = BTreeMap::from([(7, vec![1, 200]), (9, vec![0, 17, 511])]);
+        let (values, decommitment) = commitment.decommit(queries.clone());
+        let (expected_values, expected_decommitment) =
+            cpu_tree.decommit(queries, cpu_evaluations.iter().collect());
+        assert_eq!(values, expected_values);
+        assert_eq!(
+            decommitment.hash_witness,
+            expected_decommitment.hash_witness
+        );
+        assert_eq!(
+            decommitment.column_witness,
+            expected_decommitment.column_witness
+        );
+    }
+
+    #[test]
+    fn test_commit_on_gpu_with_cap() {
+        require_gpu!();
+        let log_size = 6;
+        let log_blowup_factor = 1;
+        let cap_log_size = 3;
+        let columns = || {
+            (0..3u32)
+                .map(|seed| {
+                    CircleEvaluation::new(
+                        CanonicCoset::new(log_size).circle_domain(),
+                        BaseFieldVec::from_vec(
+                            (0..1u32 << log_size)
+                                .map(|i| BaseField::from(i * 31 + seed))
+                                .collect(),
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let positions = [0, 5, 77, (1 << (log_size + log_blowup_factor)) - 1];
+
+        let (_, commitment) = commit_on_gpu(columns(), log_blowup_factor);
+        let (cap, capped_commitment) =
+            commit_on_gpu_with_cap(columns(), log_blowup_factor, cap_log_size);
+
+        assert_eq!(cap.len(), 1 << cap_log_size);
+        assert_eq!(cap, commitment.tree.layers[cap_log_size as usize].to_cpu());
+        assert_eq!(capped_commitment.root(), commitment.root());
+        let paths = commitment.authentication_paths(&positions);
+        let capped_paths = capped_commitment.authentication_paths(&positions);
+        for (path, capped_path) in paths.iter().zip(&capped_paths) {
+            let capped_len = (log_size + log_blowup_factor - cap_log_size) as usize;
+            assert_eq!(capped_path[..], path[..capped_len]);
+        }
+    }
+
+    #[test]
+    fn test_queries_per_log_size() {
+        assert_eq!(
+            queries_per_log_size(&[6, 4, 6], &[63, 2, 3, 40]),
+            BTreeMap::from([(4, vec![0, 10, 15]), (6, vec![2, 3, 40, 63])])
+        );
+    }
+}
@@ -0,0 +1,1019 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    str::FromStr,
+    sync::atomic::{AtomicU32, AtomicU8, Ordering},
+};
+
+use stwo_prover::core::{
+    air::accumulation::AccumulationOps,
+    backend::{Backend, Col, Column, ColumnOps, CpuBackend},
+    circle::{CirclePoint, Coset},
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn, Field, FieldOps},
+    fri::FriOps,
+    lookups::{
+        gkr_prover::{EqEvals, GkrMultivariatePolyOracle, GkrOps, Layer},
+        mle::{Mle, MleOps},
+        utils::UnivariatePoly,
+    },
+    pcs::quotients::{ColumnSampleBatch, QuotientOps},
+    poly::{
+        circle::{
+            CanonicCoset, CircleDomain, CircleEvaluation, CirclePoly, PolyOps, SecureEvaluation,
+        },
+        line::LineEvaluation,
+        twiddles::TwiddleTree,
+        BitReversedOrder,
+    },
+    vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, ops::MerkleOps},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{Blake2sHashVec, DeviceVec, Pod},
+    device::{cuda_available, try_init, InitError, MigInstance},
+};
+
+const MIXED_BACKENDS: &str = "columns of the CPU and CUDA backends can't be mixed";
+
+/// The backend [`CpuOrCuda`] creates new columns on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    #[default]
+    Cpu,
+    Cuda,
+}
+
+impl BackendKind {
+    /// The backend suiting columns of `1 << log_size` values, e.g. to pick the backend of each
+    /// tree of a [`crate::HybridCommitmentScheme`]: the CPU below [`cpu_fallback_log_size`] or if
+    /// no device is usable, as launches and transfers would dominate, CUDA otherwise.
+    pub fn for_log_size(log_size: u32) -> Self {
+        if log_size < cpu_fallback_log_size() || !cuda_available() {
+            BackendKind::Cpu
+        } else {
+            BackendKind::Cuda
+        }
+    }
+}
+
+/// Log size from which [`BackendKind::for_log_size`] picks CUDA on a whole GPU.
+const CPU_FALLBACK_LOG_SIZE: u32 = 16;
+
+/// The log size set with [`set_cpu_fallback_log_size`], `u32::MAX` if unset.
+static CPU_FALLBACK_OVERRIDE: AtomicU32 = AtomicU32::new(u32::MAX);
+
+/// Log size from which [`BackendKind::for_log_size`] picks CUDA. Unless set with
+/// [`set_cpu_fallback_log_size`], 16 on a whole GPU and higher on a MIG slice of the current
+/// device, whose share of the SMs only beats the CPU on larger columns, e.g. 19 on a 1g slice.
+pub fn cpu_fallback_log_size() -> u32 {
+    match CPU_FALLBACK_OVERRIDE.load(Ordering::Relaxed) {
+        u32::MAX => scaled_cpu_fallback_log_size(
+            CudaBackend::current_device()
+                .and_then(|device| device.mig)
+                .as_ref(),
+        ),
+        log_size => log_size,
+    }
+}
+
+/// Overrides [`cpu_fallback_log_size`], or restores the default with `None`.
+pub fn set_cpu_fallback_log_size(log_size: Option<u32>) {
+    CPU_FALLBACK_OVERRIDE.store(log_size.unwrap_or(u32::MAX), Ordering::Relaxed);
+}
+
+/// The default of [`cpu_fallback_log_size`] on `mig`, or on a whole GPU if `None`: one more per
+/// halving of the compute.
+fn scaled_cpu_fallback_log_size(mig: Option<&MigInstance>) -> u32 {
+    let Some(mig) = mig else {
+        return CPU_FALLBACK_LOG_SIZE;
+    };
+    CPU_FALLBACK_LOG_SIZE + (1.0 / mig.compute_share()).log2().round() as u32
+}
+
+impl FromStr for BackendKind {
+    type Err = ParseBackendKindError;
+
+    /// Parses `"cpu"` or `"cuda"`, ignoring case, e.g. from a config file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(BackendKind::Cpu),
+            "cuda" => Ok(BackendKind::Cuda),
+            _ => Err(ParseBackendKindError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBackendKindError(String);
+
+impl fmt::Display for ParseBackendKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown backend {:?}, expected \"cpu\" or \"cuda\"",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseBackendKindError {}
+
+static SELECTED: AtomicU8 = AtomicU8::new(BackendKind::Cpu as u8);
+
+/// Backend choosing between [`CpuBackend`] and [`CudaBackend`] at runtime, so that a prover
+/// generic over its backend is only compiled once and the backend can come from a config file.
+///
+/// Columns are created on the backend selected with [`CpuOrCuda::select`], the CPU by default.
+/// Every other operation runs on the backend its inputs live on: columns of both backends can't
+/// be mixed in a single operation, which panics if they are. Select the backend once, before
+/// generating the trace.
+#[derive(Clone, Copy, Debug)]
+pub struct CpuOrCuda;
+
+impl CpuOrCuda {
+    /// Selects the backend new columns are created on. Selecting [`BackendKind::Cuda`] fails,
+    /// keeping the current selection, if no device is usable.
+    pub fn select(kind: BackendKind) -> Result<(), InitError> {
+        if kind == BackendKind::Cuda {
+            try_init()?;
+        }
+        SELECTED.store(kind as u8, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn selected() -> BackendKind {
+        match SELECTED.load(Ordering::Relaxed) {
+            0 => BackendKind::Cpu,
+            _ => BackendKind::Cuda,
+        }
+    }
+}
+
+impl Backend for CpuOrCuda {}
+
+/// Column of [`CpuOrCuda`], holding the column of the backend it was created on.
+#[derive(Debug)]
+pub enum DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    Cpu(Vec<T>),
+    Cuda(Col<CudaBackend, T>),
+}
+
+impl<T> DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            DynColumn::Cpu(_) => BackendKind::Cpu,
+            DynColumn::Cuda(_) => BackendKind::Cuda,
+        }
+    }
+}
+
+/// Copies the values, on the backend they are on.
+impl<T: Clone> Clone for DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn clone(&self) -> Self {
+        match self {
+            DynColumn::Cpu(column) => DynColumn::Cpu(column.clone()),
+            DynColumn::Cuda(column) => DynColumn::Cuda(column.clone()),
+        }
+    }
+}
+
+impl<T> Default for DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn default() -> Self {
+        DynColumn::Cpu(Vec::new())
+    }
+}
+
+impl<T: Debug + Clone + Default> Column<T> for DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn zeros(len: usize) -> Self {
+        match CpuOrCuda::selected() {
+            BackendKind::Cpu => DynColumn::Cpu(Column::zeros(len)),
+            BackendKind::Cuda => DynColumn::Cuda(Column::zeros(len)),
+        }
+    }
+
+    fn to_cpu(&self) -> Vec<T> {
+        match self {
+            DynColumn::Cpu(column) => column.clone(),
+            DynColumn::Cuda(column) => column.to_cpu(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            DynColumn::Cpu(column) => column.len(),
+            DynColumn::Cuda(column) => column.len(),
+        }
+    }
+
+    fn at(&self, index: usize) -> T {
+        match self {
+            DynColumn::Cpu(column) => column.at(index),
+            DynColumn::Cuda(column) => column.at(index),
+        }
+    }
+
+    fn set(&mut self, index: usize, value: T) {
+        match self {
+            DynColumn::Cpu(column) => column.set(index, value),
+            DynColumn::Cuda(column) => column.set(index, value),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DynColumn<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        match CpuOrCuda::selected() {
+            BackendKind::Cpu => DynColumn::Cpu(iter.into_iter().collect()),
+            BackendKind::Cuda => DynColumn::Cuda(iter.into_iter().collect()),
+        }
+    }
+}
+
+/// Calls the function `$f`, generic over a [`Side`], with the backend of `$kind`.
+macro_rules! dispatch {
+    ($kind:expr, $f:ident($($arg:expr),* $(,)?)) => {
+        match $kind {
+            BackendKind::Cpu => $f::<CpuBackend>($($arg),*),
+            BackendKind::Cuda => $f::<CudaBackend>($($arg),*),
+        }
+    };
+}
+
+/// Columns standing for borrowed columns of [`CpuOrCuda`] in the ops of the underlying backend.
+/// Device columns are aliased, without copying them: the alias shares the memory of the column,
+/// so it must be forgotten rather than dropped. Host columns are copied, as two `Vec`s can't own
+/// the same memory. [`Borrowed`] drops or forgets them as needed.
+trait Alias {
+    unsafe fn alias(&self) -> Self;
+}
+
+impl<T: Clone> Alias for Vec<T> {
+    unsafe fn alias(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl<T: Pod> Alias for DeviceVec<T> {
+    unsafe fn alias(&self) -> Self {
+        DeviceVec::new(self.device_ptr(), self.size)
+    }
+}
+
+impl Alias for Blake2sHashVec {
+    unsafe fn alias(&self) -> Self {
+        Blake2sHashVec::new(self.device_ptr(), self.size)
+    }
+}
+
+/// Conversions between the columns of [`CpuOrCuda`] and those of one of the backends it
+/// dispatches to. Conversions of columns of the other backend panic.
+trait Side<T>: ColumnOps<T>
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn into_dyn(column: Col<Self, T>) -> DynColumn<T>;
+
+    fn from_dyn(column: DynColumn<T>) -> Col<Self, T>;
+
+    /// See [`Alias`].
+    unsafe fn alias_dyn(column: &DynColumn<T>) -> Col<Self, T>;
+}
+
+impl<T: Debug + Clone + Default> Side<T> for CpuBackend
+where
+    CudaBackend: ColumnOps<T>,
+{
+    fn into_dyn(column: Vec<T>) -> DynColumn<T> {
+        DynColumn::Cpu(column)
+    }
+
+    fn from_dyn(column: DynColumn<T>) -> Vec<T> {
+        match column {
+            DynColumn::Cpu(column) => column,
+            DynColumn::Cuda(_) => panic!("{MIXED_BACKENDS}"),
+        }
+    }
+
+    unsafe fn alias_dyn(column: &DynColumn<T>) -> Vec<T> {
+        match column {
+            DynColumn::Cpu(column) => column.alias(),
+            DynColumn::Cuda(_) => panic!("{MIXED_BACKENDS}"),
+        }
+    }
+}
+
+impl<T> Side<T> for CudaBackend
+where
+    CudaBackend: ColumnOps<T>,
+    Col<CudaBackend, T>: Alias,
+{
+    fn into_dyn(column: Col<Self, T>) -> DynColumn<T> {
+        DynColumn::Cuda(column)
+    }
+
+    fn from_dyn(column: DynColumn<T>) -> Col<Self, T> {
+        match column {
+            DynColumn::Cuda(column) => column,
+            DynColumn::Cpu(_) => panic!("{MIXED_BACKENDS}"),
+        }
+    }
+
+    unsafe fn alias_dyn(column: &DynColumn<T>) -> Col<Self, T> {
+        match column {
+            DynColumn::Cuda(column) => column.alias(),
+            DynColumn::Cpu(_) => panic!("{MIXED_BACKENDS}"),
+        }
+    }
+}
+
+/// A value of the backend `B` made of columns from [`Side::alias_dyn`], standing for borrowed
+/// values of [`CpuOrCuda`]. Dropping it forgets the aliases of device columns and drops the
+/// copies of host columns.
+struct Borrowed<B: SideBackend, T> {
+    value: ManuallyDrop<T>,
+    _backend: PhantomData<B>,
+}
+
+impl<B: SideBackend, T> Borrowed<B, T> {
+    fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            _backend: PhantomData,
+        }
+    }
+}
+
+impl<B: SideBackend, T> Deref for Borrowed<B, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<B: SideBackend, T> Drop for Borrowed<B, T> {
+    fn drop(&mut self) {
+        if B::COPIES_BORROWED {
+            unsafe { ManuallyDrop::drop(&mut self.value) };
+        }
+    }
+}
+
+/// A backend [`CpuOrCuda`] dispatches to.
+trait SideBackend:
+    Backend
+    + GkrOps
+    + MerkleOps<Blake2sMerkleHasher>
+    + Side<BaseField>
+    + Side<SecureField>
+    + Side<Blake2sHash>
+{
+    /// Whether [`Alias`] copies the columns of the backend rather than aliasing them.
+    const COPIES_BORROWED: bool;
+
+    fn twiddles_into_dyn(twiddles: Self::Twiddles) -> DynColumn<BaseField>;
+
+    /// See [`Alias`].
+    unsafe fn alias_twiddles(twiddles: &DynColumn<BaseField>) -> Self::Twiddles;
+
+    /// Calls `f` with the [`EqEvals`] of `y` on this backend.
+    ///
+    /// Those of [`CpuOrCuda`] can't be aliased, as their fields are private, so they are
+    /// generated again. The last ones are cached, since they stay the same through all the
+    /// sumcheck rounds of a layer.
+    fn with_eq_evals<R>(y: &[SecureField], f: impl FnOnce(&EqEvals<Self>) -> R) -> R;
+}
+
+thread_local! {
+    static CPU_EQ_EVALS: RefCell<Option<EqEvals<CpuBackend>>> = const { RefCell::new(None) };
+    static CUDA_EQ_EVALS: RefCell<Option<EqEvals<CudaBackend>>> = const { RefCell::new(None) };
+}
+
+impl SideBackend for CpuBackend {
+    const COPIES_BORROWED: bool = true;
+
+    fn twiddles_into_dyn(twiddles: Vec<BaseField>) -> DynColumn<BaseField> {
+        Self::into_dyn(twiddles)
+    }
+
+    unsafe fn alias_twiddles(twiddles: &DynColumn<BaseField>) -> Vec<BaseField> {
+        Self::alias_dyn(twiddles)
+    }
+
+    fn with_eq_evals<R>(y: &[SecureField], f: impl FnOnce(&EqEvals<Self>) -> R) -> R {
+        CPU_EQ_EVALS.with(|cached| {
+            let mut cached = cached.borrow_mut();
+            if cached.as_ref().map_or(true, |eq_evals| eq_evals.y() != y) {
+                *cached = Some(EqEvals::generate(y));
+            }
+            f(cached.as_ref().unwrap())
+        })
+    }
+}
+
+impl SideBackend for CudaBackend {
+    const COPIES_BORROWED: bool = false;
+
+    fn twiddles_into_dyn(twiddles: Self::Twiddles) -> DynColumn<BaseField> {
+        Self::into_dyn(twiddles)
+    }
+
+    unsafe fn alias_twiddles(twiddles: &DynColumn<BaseField>) -> Self::Twiddles {
+        Self::alias_dyn(twiddles)
+    }
+
+    fn with_eq_evals<R>(y: &[SecureField], f: impl FnOnce(&EqEvals<Self>) -> R) -> R {
+        CUDA_EQ_EVALS.with(|cached| {
+            let mut cached = cached.borrow_mut();
+            if cached.as_ref().map_or(true, |eq_evals| eq_evals.y() != y) {
+                *cached = Some(EqEvals::generate(y));
+            }
+            f(cached.as_ref().unwrap())
+        })
+    }
+}
+
+/// Runs `f` on the column of `B` held by `column`, which is moved out for the call.
+fn with_mut<B: Side<T>, T, R>(column: &mut DynColumn<T>, f: impl FnOnce(&mut Col<B, T>) -> R) -> R
+where
+    CudaBackend: ColumnOps<T>,
+{
+    let mut inner = B::from_dyn(mem::take(column));
+    let result = f(&mut inner);
+    *column = B::into_dyn(inner);
+    result
+}
+
+fn secure_into_dyn<B: SideBackend>(column: SecureColumn<B>) -> SecureColumn<CpuOrCuda> {
+    SecureColumn {
+        columns: column.columns.map(B::into_dyn),
+    }
+}
+
+fn secure_from_dyn<B: SideBackend>(column: SecureColumn<CpuOrCuda>) -> SecureColumn<B> {
+    SecureColumn {
+        columns: column.columns.map(B::from_dyn),
+    }
+}
+
+unsafe fn alias_secure<B: SideBackend>(column: &SecureColumn<CpuOrCuda>) -> SecureColumn<B> {
+    SecureColumn {
+        columns: std::array::from_fn(|i| B::alias_dyn(&column.columns[i])),
+    }
+}
+
+fn secure_kind(column: &SecureColumn<CpuOrCuda>) -> BackendKind {
+    column.columns[0].kind()
+}
+
+unsafe fn alias_poly<B: SideBackend>(poly: &CirclePoly<CpuOrCuda>) -> Borrowed<B, CirclePoly<B>> {
+    Borrowed::new(CirclePoly::new(B::alias_dyn(&poly.coeffs)))
+}
+
+unsafe fn alias_evaluation<B: SideBackend>(
+    eval: &CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder>,
+) -> Borrowed<B, CircleEvaluation<B, BaseField, BitReversedOrder>> {
+    Borrowed::new(CircleEvaluation::new(
+        eval.domain,
+        B::alias_dyn(&eval.values),
+    ))
+}
+
+unsafe fn alias_secure_evaluation<B: SideBackend>(
+    eval: &SecureEvaluation<CpuOrCuda>,
+) -> Borrowed<B, SecureEvaluation<B>> {
+    Borrowed::new(SecureEvaluation {
+        domain: eval.domain,
+        values: alias_secure(&eval.values),
+    })
+}
+
+unsafe fn alias_twiddle_tree<B: SideBackend>(
+    twiddles: &TwiddleTree<CpuOrCuda>,
+) -> Borrowed<B, TwiddleTree<B>> {
+    Borrowed::new(TwiddleTree {
+        root_coset: twiddles.root_coset,
+        twiddles: B::alias_twiddles(&twiddles.twiddles),
+        itwiddles: B::alias_twiddles(&twiddles.itwiddles),
+    })
+}
+
+fn evaluation_into_dyn<B: SideBackend>(
+    eval: CircleEvaluation<B, BaseField, BitReversedOrder>,
+) -> CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder> {
+    CircleEvaluation::new(eval.domain, B::into_dyn(eval.values))
+}
+
+fn secure_evaluation_into_dyn<B: SideBackend>(
+    eval: SecureEvaluation<B>,
+) -> SecureEvaluation<CpuOrCuda> {
+    SecureEvaluation {
+        domain: eval.domain,
+        values: secure_into_dyn(eval.values),
+    }
+}
+
+impl<T: Debug + Clone + Default> ColumnOps<T> for CpuOrCuda
+where
+    CudaBackend: ColumnOps<T>,
+{
+    type Column = DynColumn<T>;
+
+    fn bit_reverse_column(column: &mut Self::Column) {
+        match column {
+            DynColumn::Cpu(column) => <CpuBackend as ColumnOps<T>>::bit_reverse_column(column),
+            DynColumn::Cuda(column) => <CudaBackend as ColumnOps<T>>::bit_reverse_column(column),
+        }
+    }
+}
+
+fn batch_inverse<B: SideBackend + FieldOps<F> + Side<F>, F: Field>(
+    column: &DynColumn<F>,
+    dst: &mut DynColumn<F>,
+) where
+    CudaBackend: ColumnOps<F>,
+{
+    let column = Borrowed::<B, _>::new(unsafe { <B as Side<F>>::alias_dyn(column) });
+    with_mut::<B, F, _>(dst, |dst| <B as FieldOps<F>>::batch_inverse(&column, dst));
+}
+
+impl FieldOps<BaseField> for CpuOrCuda {
+    fn batch_inverse(column: &Self::Column, dst: &mut Self::Column) {
+        match column.kind() {
+            BackendKind::Cpu => batch_inverse::<CpuBackend, BaseField>(column, dst),
+            BackendKind::Cuda => batch_inverse::<CudaBackend, BaseField>(column, dst),
+        }
+    }
+}
+
+impl FieldOps<SecureField> for CpuOrCuda {
+    fn batch_inverse(column: &Self::Column, dst: &mut Self::Column) {
+        match column.kind() {
+            BackendKind::Cpu => batch_inverse::<CpuBackend, SecureField>(column, dst),
+            BackendKind::Cuda => batch_inverse::<CudaBackend, SecureField>(column, dst),
+        }
+    }
+}
+
+fn new_canonical_ordered<B: SideBackend>(
+    coset: CanonicCoset,
+    values: DynColumn<BaseField>,
+) -> CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder> {
+    evaluation_into_dyn(B::new_canonical_ordered(coset, B::from_dyn(values)))
+}
+
+fn interpolate<B: SideBackend>(
+    eval: CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder>,
+    twiddles: &TwiddleTree<CpuOrCuda>,
+) -> CirclePoly<CpuOrCuda> {
+    let eval = CircleEvaluation::new(eval.domain, B::from_dyn(eval.values));
+    let twiddles = unsafe { alias_twiddle_tree::<B>(twiddles) };
+    CirclePoly::new(B::into_dyn(B::interpolate(eval, &twiddles).coeffs))
+}
+
+fn eval_at_point<B: SideBackend>(
+    poly: &CirclePoly<CpuOrCuda>,
+    point: CirclePoint<SecureField>,
+) -> SecureField {
+    B::eval_at_point(&unsafe { alias_poly::<B>(poly) }, point)
+}
+
+fn extend<B: SideBackend>(poly: &CirclePoly<CpuOrCuda>, log_size: u32) -> CirclePoly<CpuOrCuda> {
+    let poly = unsafe { alias_poly::<B>(poly) };
+    CirclePoly::new(B::into_dyn(B::extend(&poly, log_size).coeffs))
+}
+
+fn evaluate<B: SideBackend>(
+    poly: &CirclePoly<CpuOrCuda>,
+    domain: CircleDomain,
+    twiddles: &TwiddleTree<CpuOrCuda>,
+) -> CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder> {
+    let poly = unsafe { alias_poly::<B>(poly) };
+    let twiddles = unsafe { alias_twiddle_tree::<B>(twiddles) };
+    evaluation_into_dyn(B::evaluate(&poly, domain, &twiddles))
+}
+
+fn precompute_twiddles<B: SideBackend>(coset: Coset) -> TwiddleTree<CpuOrCuda> {
+    let twiddles = B::precompute_twiddles(coset);
+    TwiddleTree {
+        root_coset: twiddles.root_coset,
+        twiddles: B::twiddles_into_dyn(twiddles.twiddles),
+        itwiddles: B::twiddles_into_dyn(twiddles.itwiddles),
+    }
+}
+
+impl PolyOps for CpuOrCuda {
+    /// The twiddles of the selected backend.
+    type Twiddles = DynColumn<BaseField>;
+
+    fn new_canonical_ordered(
+        coset: CanonicCoset,
+        values: Col<Self, BaseField>,
+    ) -> CircleEvaluation<Self, BaseField, BitReversedOrder> {
+        dispatch!(values.kind(), new_canonical_ordered(coset, values))
+    }
+
+    fn interpolate(
+        eval: CircleEvaluation<Self, BaseField, BitReversedOrder>,
+        twiddles: &TwiddleTree<Self>,
+    ) -> CirclePoly<Self> {
+        dispatch!(eval.values.kind(), interpolate(eval, twiddles))
+    }
+
+    fn eval_at_point(poly: &CirclePoly<Self>, point: CirclePoint<SecureField>) -> SecureField {
+        dispatch!(poly.coeffs.kind(), eval_at_point(poly, point))
+    }
+
+    fn extend(poly: &CirclePoly<Self>, log_size: u32) -> CirclePoly<Self> {
+        dispatch!(poly.coeffs.kind(), extend(poly, log_size))
+    }
+
+    fn evaluate(
+        poly: &CirclePoly<Self>,
+        domain: CircleDomain,
+        twiddles: &TwiddleTree<Self>,
+    ) -> CircleEvaluation<Self, BaseField, BitReversedOrder> {
+        dispatch!(poly.coeffs.kind(), evaluate(poly, domain, twiddles))
+    }
+
+    fn precompute_twiddles(coset: Coset) -> TwiddleTree<Self> {
+        dispatch!(CpuOrCuda::selected(), precompute_twiddles(coset))
+    }
+}
+
+fn accumulate_quotients<B: SideBackend>(
+    domain: CircleDomain,
+    columns: &[&CircleEvaluation<CpuOrCuda, BaseField, BitReversedOrder>],
+    random_coeff: SecureField,
+    sample_batches: &[ColumnSampleBatch],
+) -> SecureEvaluation<CpuOrCuda> {
+    let columns = columns
+        .iter()
+        .map(|column| unsafe { alias_evaluation::<B>(column) })
+        .collect::<Vec<_>>();
+    let columns = columns.iter().map(|column| &**column).collect::<Vec<_>>();
+    secure_evaluation_into_dyn(B::accumulate_quotients(
+        domain,
+        &columns,
+        random_coeff,
+        sample_batches,
+    ))
+}
+
+impl QuotientOps for CpuOrCuda {
+    fn accumulate_quotients(
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[ColumnSampleBatch],
+    ) -> SecureEvaluation<Self> {
+        let kind = columns
+            .first()
+            .map_or(CpuOrCuda::selected(), |column| column.values.kind());
+        dispatch!(
+            kind,
+            accumulate_quotients(domain, columns, random_coeff, sample_batches)
+        )
+    }
+}
+
+fn fold_line<B: SideBackend>(
+    eval: &LineEvaluation<CpuOrCuda>,
+    alpha: SecureField,
+    twiddles: &TwiddleTree<CpuOrCuda>,
+) -> LineEvaluation<CpuOrCuda> {
+    let eval = Borrowed::<B, _>::new(LineEvaluation::<B>::new(eval.domain(), unsafe {
+        alias_secure(&eval.values)
+    }));
+    let twiddles = unsafe { alias_twiddle_tree::<B>(twiddles) };
+    let folded = B::fold_line(&eval, alpha, &twiddles);
+    LineEvaluation::new(folded.domain(), secure_into_dyn(folded.values))
+}
+
+fn fold_circle_into_line<B: SideBackend>(
+    dst: &mut LineEvaluation<CpuOrCuda>,
+    src: &SecureEvaluation<CpuOrCuda>,
+    alpha: SecureField,
+    twiddles: &TwiddleTree<CpuOrCuda>,
+) {
+    let values = SecureColumn {
+        columns: mem::take(&mut dst.values.columns),
+    };
+    let mut line = LineEvaluation::<B>::new(dst.domain(), secure_from_dyn(values));
+    let src = unsafe { alias_secure_evaluation::<B>(src) };
+    let twiddles = unsafe { alias_twiddle_tree::<B>(twiddles) };
+    B::fold_circle_into_line(&mut line, &src, alpha, &twiddles);
+    dst.values = secure_into_dyn(line.values);
+}
+
+fn decompose<B: SideBackend>(
+    eval: &SecureEvaluation<CpuOrCuda>,
+) -> (SecureEvaluation<CpuOrCuda>, SecureField) {
+    let eval = unsafe { alias_secure_evaluation::<B>(eval) };
+    let (g, lambda) = B::decompose(&eval);
+    (secure_evaluation_into_dyn(g), lambda)
+}
+
+impl FriOps for CpuOrCuda {
+    fn fold_line(
+        eval: &LineEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &TwiddleTree<Self>,
+    ) -> LineEvaluation<Self> {
+        dispatch!(secure_kind(&eval.values), fold_line(eval, alpha, twiddles))
+    }
+
+    fn fold_circle_into_line(
+        dst: &mut LineEvaluation<Self>,
+        src: &SecureEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &TwiddleTree<Self>,
+    ) {
+        dispatch!(
+            secure_kind(&src.values),
+            fold_circle_into_line(dst, src, alpha, twiddles)
+        )
+    }
+
+    fn decompose(eval: &SecureEvaluation<Self>) -> (SecureEvaluation<Self>, SecureField) {
+        dispatch!(secure_kind(&eval.values), decompose(eval))
+    }
+}
+
+fn accumulate<B: SideBackend>(
+    column: &mut SecureColumn<CpuOrCuda>,
+    other: &SecureColumn<CpuOrCuda>,
+) {
+    let mut inner = secure_from_dyn::<B>(SecureColumn {
+        columns: mem::take(&mut column.columns),
+    });
+    let other = Borrowed::<B, _>::new(unsafe { alias_secure::<B>(other) });
+    B::accumulate(&mut inner, &other);
+    *column = secure_into_dyn(inner);
+}
+
+impl AccumulationOps for CpuOrCuda {
+    fn accumulate(column: &mut SecureColumn<Self>, other: &SecureColumn<Self>) {
+        dispatch!(secure_kind(column), accumulate(column, other))
+    }
+}
+
+fn commit_on_layer<B: SideBackend>(
+    log_size: u32,
+    prev_layer: Option<&DynColumn<Blake2sHash>>,
+    columns: &[&DynColumn<BaseField>],
+) -> DynColumn<Blake2sHash> {
+    let prev_layer = prev_layer.map(|layer| Borrowed::<B, _>::new(unsafe { B::alias_dyn(layer) }));
+    let columns = columns
+        .iter()
+        .map(|column| Borrowed::<B, _>::new(unsafe { B::alias_dyn(*column) }))
+        .collect::<Vec<_>>();
+    let columns = columns.iter().map(|column| &**column).collect::<Vec<_>>();
+    B::into_dyn(B::commit_on_layer(
+        log_size,
+        prev_layer.as_deref(),
+        &columns,
+    ))
+}
+
+impl MerkleOps<Blake2sMerkleHasher> for CpuOrCuda {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Col<Self, Blake2sHash>>,
+        columns: &[&Col<Self, BaseField>],
+    ) -> Col<Self, Blake2sHash> {
+        let kind = columns
+            .first()
+            .map(|column| column.kind())
+            .or(prev_layer.map(DynColumn::kind))
+            .unwrap_or(CpuOrCuda::selected());
+        dispatch!(kind, commit_on_layer(log_size, prev_layer, columns))
+    }
+}
+
+fn mle_into_dyn<B: SideBackend>(mle: Mle<B, SecureField>) -> Mle<CpuOrCuda, SecureField> {
+    Mle::new(B::into_dyn(mle.into_evals()))
+}
+
+fn fix_first_variable<B: SideBackend + MleOps<F> + Side<F>, F: Field>(
+    mle: Mle<CpuOrCuda, F>,
+    assignment: SecureField,
+) -> Mle<CpuOrCuda, SecureField>
+where
+    CudaBackend: ColumnOps<F>,
+{
+    let mle = Mle::<B, F>::new(<B as Side<F>>::from_dyn(mle.into_evals()));
+    mle_into_dyn(<B as MleOps<F>>::fix_first_variable(mle, assignment))
+}
+
+impl MleOps<BaseField> for CpuOrCuda {
+    fn fix_first_variable(
+        mle: Mle<Self, BaseField>,
+        assignment: SecureField,
+    ) -> Mle<Self, SecureField> {
+        match mle.kind() {
+            BackendKind::Cpu => fix_first_variable::<CpuBackend, BaseField>(mle, assignment),
+            BackendKind::Cuda => fix_first_variable::<CudaBackend, BaseField>(mle, assignment),
+        }
+    }
+}
+
+impl MleOps<SecureField> for CpuOrCuda {
+    fn fix_first_variable(
+        mle: Mle<Self, SecureField>,
+        assignment: SecureField,
+    ) -> Mle<Self, SecureField> {
+        match mle.kind() {
+            BackendKind::Cpu => fix_first_variable::<CpuBackend, SecureField>(mle, assignment),
+            BackendKind::Cuda => fix_first_variable::<CudaBackend, SecureField>(mle, assignment),
+        }
+    }
+}
+
+fn layer_kind(layer: &Layer<CpuOrCuda>) -> BackendKind {
+    match layer {
+        Layer::GrandProduct(mle) => mle.kind(),
+        Layer::LogUpGeneric { denominators, .. }
+        | Layer::LogUpMultiplicities { denominators, .. }
+        | Layer::LogUpSingles { denominators } => denominators.kind(),
+    }
+}
+
+unsafe fn alias_layer<B: SideBackend>(layer: &Layer<CpuOrCuda>) -> Layer<B> {
+    match layer {
+        Layer::GrandProduct(mle) => Layer::GrandProduct(Mle::new(B::alias_dyn(&**mle))),
+        Layer::LogUpGeneric {
+            numerators,
+            denominators,
+        } => Layer::LogUpGeneric {
+            numerators: Mle::new(B::alias_dyn(&**numerators)),
+            denominators: Mle::new(B::alias_dyn(&**denominators)),
+        },
+        Layer::LogUpMultiplicities {
+            numerators,
+            denominators,
+        } => Layer::LogUpMultiplicities {
+            numerators: Mle::new(B::alias_dyn(&**numerators)),
+            denominators: Mle::new(B::alias_dyn(&**denominators)),
+        },
+        Layer::LogUpSingles { denominators } => Layer::LogUpSingles {
+            denominators: Mle::new(B::alias_dyn(&**denominators)),
+        },
+    }
+}
+
+fn layer_into_dyn<B: SideBackend>(layer: Layer<B>) -> Layer<CpuOrCuda> {
+    match layer {
+        Layer::GrandProduct(mle) => Layer::GrandProduct(mle_into_dyn(mle)),
+        Layer::LogUpGeneric {
+            numerators,
+            denominators,
+        } => Layer::LogUpGeneric {
+            numerators: mle_into_dyn(numerators),
+            denominators: mle_into_dyn(denominators),
+        },
+        Layer::LogUpMultiplicities {
+            numerators,
+            denominators,
+        } => Layer::LogUpMultiplicities {
+            numerators: Mle::new(B::into_dyn(numerators.into_evals())),
+            denominators: mle_into_dyn(denominators),
+        },
+        Layer::LogUpSingles { denominators } => Layer::LogUpSingles {
+            denominators: mle_into_dyn(denominators),
+        },
+    }
+}
+
+fn gen_eq_evals<B: SideBackend>(y: &[SecureField], v: SecureField) -> Mle<CpuOrCuda, SecureField> {
+    mle_into_dyn(B::gen_eq_evals(y, v))
+}
+
+fn next_layer<B: SideBackend>(layer: &Layer<CpuOrCuda>) -> Layer<CpuOrCuda> {
+    let layer = Borrowed::<B, _>::new(unsafe { alias_layer::<B>(layer) });
+    layer_into_dyn(B::next_layer(&layer))
+}
+
+fn sum_as_poly_in_first_variable<B: SideBackend>(
+    h: &GkrMultivariatePolyOracle<'_, CpuOrCuda>,
+    claim: SecureField,
+) -> UnivariatePoly<SecureField> {
+    B::with_eq_evals(h.eq_evals.y(), |eq_evals| {
+        let h = Borrowed::<B, _>::new(GkrMultivariatePolyOracle {
+            eq_evals,
+            input_layer: unsafe { alias_layer::<B>(&h.input_layer) },
+            eq_fixed_var_correction: h.eq_fixed_var_correction,
+            lambda: h.lambda,
+        });
+        B::sum_as_poly_in_first_variable(&h, claim)
+    })
+}
+
+impl GkrOps for CpuOrCuda {
+    fn gen_eq_evals(y: &[SecureField], v: SecureField) -> Mle<Self, SecureField> {
+        dispatch!(CpuOrCuda::selected(), gen_eq_evals(y, v))
+    }
+
+    fn next_layer(layer: &Layer<Self>) -> Layer<Self> {
+        dispatch!(layer_kind(layer), next_layer(layer))
+    }
+
+    fn sum_as_poly_in_first_variable(
+        h: &GkrMultivariatePolyOracle<'_, Self>,
+        claim: SecureField,
+    ) -> UnivariatePoly<SecureField> {
+        dispatch!(
+            layer_kind(&h.input_layer),
+            sum_as_poly_in_first_variable(h, claim)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        circle::SECURE_FIELD_CIRCLE_GEN,
+        fields::m31::BaseField,
+        poly::circle::{CanonicCoset, PolyOps},
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{scaled_cpu_fallback_log_size, BackendKind, CpuOrCuda};
+    use crate::device::MigInstance;
+
+    #[test]
+    fn test_scaled_cpu_fallback_log_size() {
+        let slice = |compute_slices| MigInstance {
+            profile: format!("{compute_slices}g.10gb"),
+            compute_slices,
+        };
+
+        assert_eq!(scaled_cpu_fallback_log_size(None), 16);
+        assert_eq!(scaled_cpu_fallback_log_size(Some(&slice(7))), 16);
+        assert_eq!(scaled_cpu_fallback_log_size(Some(&slice(3))), 17);
+        assert_eq!(scaled_cpu_fallback_log_size(Some(&slice(1))), 19);
+    }
+
+    #[test]
+    fn test_cpu_or_cuda() {
+        require_gpu!();
+        let log_size = 8;
+        let values = (0..1 << log_size)
+            .map(|i| BaseField::from(i * 7 + 1))
+            .collect::<Vec<_>>();
+
+        let prove = |kind: BackendKind| {
+            CpuOrCuda::select(kind).unwrap();
+            let extended_domain = CanonicCoset::new(log_size + 1).circle_domain();
+            let twiddles = CpuOrCuda::precompute_twiddles(extended_domain.half_coset);
+            let eval = CpuOrCuda::new_canonical_ordered(
+                CanonicCoset::new(log_size),
+                values.iter().copied().collect(),
+            );
+            let poly = CpuOrCuda::interpolate(eval, &twiddles);
+            let extended = CpuOrCuda::evaluate(&poly, extended_domain, &twiddles);
+            let tree =
+                MerkleProver::<CpuOrCuda, Blake2sMerkleHasher>::commit(vec![&extended.values]);
+            (
+                extended.values.kind(),
+                poly.coeffs.to_cpu(),
+                CpuOrCuda::eval_at_point(&poly, SECURE_FIELD_CIRCLE_GEN),
+                tree.root(),
+            )
+        };
+        let (cpu_kind, cpu_coeffs, cpu_value, cpu_root) = prove(BackendKind::Cpu);
+        let (cuda_kind, cuda_coeffs, cuda_value, cuda_root) = prove(BackendKind::Cuda);
+        CpuOrCuda::select(BackendKind::Cpu).unwrap();
+
+        assert_eq!(cpu_kind, BackendKind::Cpu);
+        assert_eq!(cuda_kind, BackendKind::Cuda);
+        assert_eq!(cuda_coeffs, cpu_coeffs);
+        assert_eq!(cuda_value, cpu_value);
+        assert_eq!(cuda_root, cpu_root);
+        assert_eq!("CUDA".parse(), Ok(BackendKind::Cuda));
+        assert!("tpu".parse::<BackendKind>().is_err());
+    }
+}
@@ -1,68 +1,214 @@
-use stwo_prover::core::fields::m31::BaseField;
+use std::io::{self, Read};
 
-use super::bindings;
+use stwo_prover::core::fields::m31::{BaseField, P};
 
-#[derive(Clone, Debug)]
-pub struct BaseFieldVec {
-    pub(crate) device_ptr: *const u32,
-    pub(crate) size: usize,
-}
+use super::{bindings, device_vec::words, DeviceVec};
+use crate::{
+    memory::memory_phase,
+    stream::{Event, Stream},
+};
+
+pub type BaseFieldVec = DeviceVec<BaseField>;
 
 impl BaseFieldVec {
-    pub fn new(device_ptr: *const u32, size: usize) -> Self {
-        Self { device_ptr, size }
+    /// Same as [`BaseFieldVec::from_slice`] for raw values.
+    ///
+    /// # Safety
+    ///
+    /// Every value must already be reduced modulo P, which is only checked in debug builds.
+    /// Unchecked values from outside of the prover go through
+    /// [`BaseFieldVec::from_u32_slice_chunked`] instead.
+    pub unsafe fn from_u32_slice(host_array: &[u32]) -> Self {
+        debug_assert!(host_array.iter().all(|&value| value < P));
+        let _phase = memory_phase("upload");
+        let device_ptr = bindings::copy_uint32_t_vec_from_host_to_device(
+            host_array.as_ptr(),
+            words::<BaseField>(host_array.len()),
+        );
+        Self::new(device_ptr, host_array.len())
     }
 
-    pub fn from_vec(host_array: Vec<BaseField>) -> Self {
-        let device_ptr = unsafe {
-            bindings::copy_uint32_t_vec_from_host_to_device(
-                host_array.as_ptr() as *const u32,
-                host_array.len() as u32,
-            )
-        };
-        let size = host_array.len();
-        Self::new(device_ptr, size)
+    /// Same as [`BaseFieldVec::from_u32_slice`], streaming the values through two pinned host
+    /// buffers of `chunk_size` values, e.g. a trace memory-mapped from the file of a separate
+    /// witness generator.
+    ///
+    /// Each chunk is copied to a buffer while the previous one is transferred, so the pages of
+    /// `host_array` are read once, in order, and never need to be resident all at once, and
+    /// `host_array` is never page-locked.
+    ///
+    /// As with [`BaseFieldVec::from_reader`], the values come from outside of the prover, so
+    /// any of them not reduced modulo P fails with [`io::ErrorKind::InvalidData`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = host_array.len()))
+    )]
+    pub fn from_u32_slice_chunked(host_array: &[u32], chunk_size: usize) -> io::Result<Self> {
+        assert!(chunk_size > 0);
+        let _phase = memory_phase("upload");
+        let result = Self::new_uninitialized(host_array.len());
+        let mut uploader = ChunkedUpload::new(&result, chunk_size);
+        for chunk in host_array.chunks(chunk_size) {
+            if chunk.iter().any(|&value| value >= P) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "value out of range",
+                ));
+            }
+            uploader.push(
+                |buffer| buffer[..chunk.len()].copy_from_slice(chunk),
+                chunk.len(),
+            );
+        }
+        drop(uploader);
+        Ok(result)
     }
 
-    pub fn new_uninitialized(size: usize) -> Self {
-        Self::new(unsafe { bindings::cuda_malloc_uint32_t(size as u32) }, size)
+    /// Reads `size` values, encoded as little endian words, from `reader` to a new vector, e.g.
+    /// a trace file too large to be held in host memory. Goes through two pinned host buffers of
+    /// `chunk_size` values, so reading a chunk overlaps with the transfer of the previous one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = size))
+    )]
+    pub fn from_reader(reader: &mut impl Read, size: usize, chunk_size: usize) -> io::Result<Self> {
+        assert!(chunk_size > 0);
+        let _phase = memory_phase("upload");
+        let result = Self::new_uninitialized(size);
+        let mut uploader = ChunkedUpload::new(&result, chunk_size);
+        for start in (0..size).step_by(chunk_size) {
+            let len = chunk_size.min(size - start);
+            let mut outcome = Ok(());
+            uploader.push(
+                |buffer| {
+                    let words = &mut buffer[..len];
+                    let bytes = unsafe {
+                        std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, 4 * len)
+                    };
+                    outcome = reader.read_exact(bytes).and_then(|()| {
+                        for word in words.iter_mut() {
+                            *word = u32::from_le(*word);
+                            if *word >= P {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    "value out of range",
+                                ));
+                            }
+                        }
+                        Ok(())
+                    });
+                },
+                len,
+            );
+            outcome?;
+        }
+        drop(uploader);
+        Ok(result)
     }
 
-    pub fn new_zeroes(size: usize) -> Self {
-        Self::new(
-            unsafe { bindings::cuda_alloc_zeroes_uint32_t(size as u32) },
-            size,
-        )
+    /// Sets every value of the vector to `value`, without any host to device transfer.
+    pub fn fill(&mut self, value: BaseField) {
+        unsafe { bindings::fill_base_field(self.device_ptr(), value, self.size as u32) };
     }
 
-    pub fn copy_from(&mut self, other: &Self) {
-        assert!(self.size >= other.size);
-        unsafe {
-            bindings::copy_uint32_t_vec_from_device_to_device(
-                other.device_ptr,
-                self.device_ptr,
-                other.size as u32,
-            );
+    /// Downloads the vector in chunks of at most `chunk_size` values, calling `f` on each of
+    /// them in order.
+    ///
+    /// Chunks go through a single pinned host buffer, so huge columns can be hashed or written
+    /// to disk as they stream without holding a host copy of the whole column.
+    pub fn for_each_chunk(&self, chunk_size: usize, mut f: impl FnMut(&[BaseField])) {
+        assert!(chunk_size > 0);
+        let buffer = PinnedBuffer::new(chunk_size.min(self.size));
+        for start in (0..self.size).step_by(chunk_size) {
+            let len = chunk_size.min(self.size - start);
+            unsafe {
+                bindings::copy_uint32_t_vec_from_device_to_host(
+                    self.device_ptr().add(start),
+                    buffer.host_ptr,
+                    len as u32,
+                );
+                f(std::slice::from_raw_parts(
+                    buffer.host_ptr as *const BaseField,
+                    len,
+                ));
+            }
         }
     }
+}
+
+/// Page-locked host memory used as a staging buffer for downloads.
+pub(crate) struct PinnedBuffer {
+    pub(crate) host_ptr: *const u32,
+}
+
+impl PinnedBuffer {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            host_ptr: unsafe { bindings::cuda_malloc_host_uint32_t(size as u32) },
+        }
+    }
+}
 
-    pub fn to_vec(&self) -> Vec<BaseField> {
-        let mut host_data: Vec<BaseField> = Vec::with_capacity(self.size);
+impl Drop for PinnedBuffer {
+    fn drop(&mut self) {
+        unsafe { bindings::free_host_uint32_t_vec(self.host_ptr) };
+    }
+}
+
+/// Fills a device vector chunk by chunk through two pinned buffers, alternating between them so
+/// the host fills one while the other is being transferred. Dropping it waits for the transfers
+/// queued so far.
+struct ChunkedUpload<'a> {
+    dst: &'a BaseFieldVec,
+    chunk_size: usize,
+    offset: usize,
+    stream: Stream,
+    buffers: [PinnedBuffer; 2],
+    /// Reached once the transfer out of each buffer is done.
+    transfers: [Option<Event>; 2],
+}
+
+impl<'a> ChunkedUpload<'a> {
+    fn new(dst: &'a BaseFieldVec, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.min(dst.size).max(1);
+        Self {
+            dst,
+            chunk_size,
+            offset: 0,
+            stream: Stream::new(),
+            buffers: [PinnedBuffer::new(chunk_size), PinnedBuffer::new(chunk_size)],
+            transfers: [None, None],
+        }
+    }
+
+    /// Queues the upload of the next `len` values, which `fill` writes to the start of a
+    /// staging buffer.
+    fn push(&mut self, fill: impl FnOnce(&mut [u32]), len: usize) {
+        assert!(len <= self.chunk_size && self.offset + len <= self.dst.size);
+        let slot = (self.offset / self.chunk_size) % 2;
+        // The buffer is still being read by the transfer queued two chunks ago.
+        if let Some(transfer) = self.transfers[slot].take() {
+            transfer.synchronize();
+        }
+        let buffer = &self.buffers[slot];
+        fill(unsafe {
+            std::slice::from_raw_parts_mut(buffer.host_ptr as *mut u32, self.chunk_size)
+        });
         unsafe {
-            host_data.set_len(self.size.try_into().unwrap());
-            bindings::copy_uint32_t_vec_from_device_to_host(
-                self.device_ptr,
-                host_data.as_mut_ptr() as *const u32,
-                self.size as u32,
+            bindings::copy_uint32_t_vec_from_host_to_device_async(
+                buffer.host_ptr,
+                self.dst.device_ptr().add(self.offset),
+                len as u32,
+                self.stream.ptr,
             );
         }
-        host_data
+        self.transfers[slot] = Some(self.stream.record());
+        self.offset += len;
     }
 }
 
-impl Drop for BaseFieldVec {
+impl Drop for ChunkedUpload<'_> {
     fn drop(&mut self) {
-        unsafe { bindings::free_uint32_t_vec(self.device_ptr) };
+        self.stream.synchronize();
     }
 }
 
@@ -73,10 +219,110 @@ mod tests {
 
     #[test]
     fn test_constructor() {
+        require_gpu!();
         let size = 1 << 25;
         let host_data = (0..size).map(BaseField::from).collect::<Vec<_>>();
         let base_field_vec = BaseFieldVec::from_vec(host_data.clone());
         assert_eq!(base_field_vec.to_vec(), host_data);
         assert_eq!(base_field_vec.size, host_data.len());
     }
+
+    #[test]
+    fn test_fill() {
+        require_gpu!();
+        let size = 1 << 12;
+        let mut base_field_vec = BaseFieldVec::new_uninitialized(size);
+
+        base_field_vec.fill(BaseField::from(7));
+
+        assert_eq!(base_field_vec.to_vec(), vec![BaseField::from(7); size]);
+    }
+
+    #[test]
+    fn test_for_each_chunk() {
+        require_gpu!();
+        let size = (1 << 20) + 3;
+        let host_data = (0..size).map(BaseField::from).collect::<Vec<_>>();
+        let base_field_vec = BaseFieldVec::from_vec(host_data.clone());
+
+        let mut chunks = vec![];
+        base_field_vec.for_each_chunk(1 << 16, |chunk| chunks.push(chunk.to_vec()));
+
+        assert_eq!(chunks.len(), 17);
+        assert!(chunks[..16].iter().all(|chunk| chunk.len() == 1 << 16));
+        assert_eq!(chunks.concat(), host_data);
+    }
+
+    #[test]
+    fn test_eq() {
+        require_gpu!();
+        let size = 1 << 12;
+        let host_data = (0..size).map(BaseField::from).collect::<Vec<_>>();
+        let mut other_data = host_data.clone();
+        other_data[size - 1] += BaseField::from(1);
+
+        let base_field_vec = BaseFieldVec::from_vec(host_data.clone());
+
+        assert_eq!(base_field_vec, BaseFieldVec::from_vec(host_data.clone()));
+        assert_ne!(base_field_vec, BaseFieldVec::from_vec(other_data));
+        assert_ne!(
+            base_field_vec,
+            BaseFieldVec::from_vec(host_data[1..].to_vec())
+        );
+    }
+
+    #[test]
+    fn test_from_slices() {
+        require_gpu!();
+        let raw_data = (0..1 << 12).collect::<Vec<u32>>();
+        let host_data = raw_data
+            .iter()
+            .map(|&value| BaseField::from_u32_unchecked(value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(BaseFieldVec::from_slice(&host_data).to_vec(), host_data);
+        assert_eq!(
+            unsafe { BaseFieldVec::from_u32_slice(&raw_data) }.to_vec(),
+            host_data
+        );
+    }
+
+    #[test]
+    fn test_from_u32_slice_chunked() {
+        require_gpu!();
+        let raw_data = (0..(1 << 16) + 5).collect::<Vec<u32>>();
+
+        let base_field_vec = BaseFieldVec::from_u32_slice_chunked(&raw_data, 1000).unwrap();
+        assert_eq!(base_field_vec, unsafe {
+            BaseFieldVec::from_u32_slice(&raw_data)
+        });
+
+        let mut out_of_range = raw_data.clone();
+        out_of_range[3000] = P;
+        let error = BaseFieldVec::from_u32_slice_chunked(&out_of_range, 1000).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        require_gpu!();
+        let raw_data = (0..(1 << 16) + 5).collect::<Vec<u32>>();
+        let bytes = raw_data
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let base_field_vec =
+            BaseFieldVec::from_reader(&mut bytes.as_slice(), raw_data.len(), 1000).unwrap();
+        assert_eq!(base_field_vec, unsafe {
+            BaseFieldVec::from_u32_slice(&raw_data)
+        });
+
+        let mut out_of_range = bytes.clone();
+        out_of_range[4000..4004].copy_from_slice(&P.to_le_bytes());
+        assert!(
+            BaseFieldVec::from_reader(&mut out_of_range.as_slice(), raw_data.len(), 1000).is_err()
+        );
+        assert!(BaseFieldVec::from_reader(&mut &bytes[..100], raw_data.len(), 1000).is_err());
+    }
 }
@@ -1,115 +1,874 @@
+//! Raw bindings to the kernels of `libgpubackend`, public with the `unstable-ffi` feature.
+//!
+//! These are not covered by semver and change whenever the kernels do.
+//!
+//! # Safety
+//!
+//! Nothing is checked on this side of the boundary. Callers must pass device pointers to live
+//! allocations with at least the number of `u32` words the function reads or writes (4 per
+//! `SecureField`, 8 per hash), which must be reduced field elements where values are expected.
+//! Host wrappers synchronize the device before returning, unless they take a stream. Memory
+//! allocated by `cuda_malloc_uint32_t` must be freed with `free_uint32_t_vec`, as it may come
+//! from the arena or the memory pool.
+
+use std::ffi::{c_char, c_void};
+
 use stwo_prover::core::{
     circle::CirclePoint,
     fields::{m31::BaseField, qm31::SecureField},
 };
 
-#[link(name = "gpubackend")]
-extern "C" {
+use crate::row_constraints::MaskItem;
+
+/// Declares the functions exported by `libgpubackend`. Without the `cuda` feature, each of them
+/// is replaced by a stub that panics, so the crate builds without the CUDA toolchain.
+///
+/// Functions declared `extern "C"`, e.g. row constraint launchers passed around as function
+/// pointers, get stubs of the C ABI so they keep their type. Those abort instead, as a panic
+/// can't unwind through them.
+macro_rules! cuda_bindings {
+    ($(pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)*) => {
+        $(
+            #[cfg(feature = "cuda")]
+            #[link(name = "gpubackend")]
+            extern "C" {
+                pub fn $name($($arg: $ty),*) $(-> $ret)?;
+            }
+
+            #[cfg(not(feature = "cuda"))]
+            #[allow(unused_variables, clippy::too_many_arguments, clippy::missing_safety_doc)]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                unimplemented!(concat!(stringify!($name), " requires the `cuda` feature"))
+            }
+        )*
+    };
+    ($($(#[$attr:meta])* pub extern "C" fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)*) => {
+        $(
+            #[cfg(feature = "cuda")]
+            #[link(name = "gpubackend")]
+            extern "C" {
+                $(#[$attr])*
+                pub fn $name($($arg: $ty),*) $(-> $ret)?;
+            }
+
+            #[cfg(not(feature = "cuda"))]
+            $(#[$attr])*
+            #[allow(unused_variables, clippy::too_many_arguments, clippy::missing_safety_doc)]
+            pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+                unimplemented!(concat!(stringify!($name), " requires the `cuda` feature"))
+            }
+        )*
+    };
+}
+
+// This is needed since `CirclePoint<BaseField>` is not FFI safe.
+#[repr(C)]
+pub struct CirclePointBaseField {
+    x: BaseField,
+    y: BaseField,
+}
+
+impl From<CirclePoint<BaseField>> for CirclePointBaseField {
+    fn from(value: CirclePoint<BaseField>) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+/// Mirrors `device_properties` in `utils.cuh`.
+#[repr(C)]
+pub struct DeviceProperties {
+    pub name: [c_char; 256],
+    pub total_memory: usize,
+    pub free_memory: usize,
+    pub major: i32,
+    pub minor: i32,
+    pub sm_count: i32,
+    pub uuid: [u8; 16],
+    pub pci_bus_id: [c_char; 32],
+}
+
+/// Mirrors `kernel_tuning` in `tuning.cuh`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct KernelTuning {
+    pub fft_block_dim: i32,
+    pub fft_butterflies_per_thread: i32,
+    pub merkle_block_dim: i32,
+    pub fold_block_dim: i32,
+}
+
+/// Mirrors `column_stats` in `stats.cuh`.
+#[repr(C)]
+#[derive(Default)]
+pub struct ColumnStats {
+    pub min: u32,
+    pub max: u32,
+    pub n_zeros: u64,
+    pub n_out_of_range: u64,
+}
+
+cuda_bindings! {
+    pub fn set_managed_allocations(enabled: bool);
+
+    pub fn reserve_memory_pool(bytes: usize) -> i32;
+
+    pub fn trim_memory_pool(bytes_to_keep: usize) -> i32;
+
+    pub fn reserve_arena(bytes: usize) -> i32;
+
+    pub fn reset_arena();
+
+    pub fn release_arena();
+
+    pub fn arena_used() -> usize;
+
+    pub fn clear_scratch_buffers();
+
+    pub fn scratch_buffers_size() -> usize;
+
     pub fn copy_uint32_t_vec_from_device_to_host(
         device_ptr: *const u32,
         host_ptr: *const u32,
         size: u32,
     );
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn copy_uint32_t_vec_from_device_to_host_checked(
+        device_ptr: *const u32,
+        host_ptr: *const u32,
+        size: u32,
+    ) -> i32;
+
+    pub fn copy_uint32_t_vec_from_device_to_host_async(
+        device_ptr: *const u32,
+        host_ptr: *const u32,
+        size: u32,
+        stream: *mut c_void,
+    );
+
+    pub fn copy_uint32_t_vec_from_host_to_device_async(
+        host_ptr: *const u32,
+        device_ptr: *const u32,
+        size: u32,
+        stream: *mut c_void,
+    );
+
+    pub fn create_stream() -> *mut c_void;
+
+    pub fn create_non_blocking_stream() -> *mut c_void;
+
+    pub fn stream_wait_default_stream(stream: *mut c_void);
+
+    pub fn register_host_memory(host_ptr: *const u32, bytes: usize) -> i32;
+
+    pub fn unregister_host_memory(host_ptr: *const u32);
+
+    pub fn create_event() -> *mut c_void;
+
+    pub fn record_event(event: *mut c_void, stream: *mut c_void);
+
+    pub fn stream_wait_event(stream: *mut c_void, event: *mut c_void);
+
+    pub fn synchronize_event(event: *mut c_void);
+
+    pub fn query_event(event: *mut c_void) -> bool;
+
+    pub fn destroy_event(event: *mut c_void);
+
+    pub fn synchronize_stream(stream: *mut c_void);
+
+    pub fn launch_host_callback(
+        stream: *mut c_void,
+        callback: extern "C" fn(*mut c_void),
+        data: *mut c_void,
+    );
+
+    pub fn destroy_stream(stream: *mut c_void);
+
     pub fn copy_uint32_t_vec_from_host_to_device(host_ptr: *const u32, size: u32) -> *const u32;
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn copy_uint32_t_vec_from_host_to_existing_device(
+        host_ptr: *const u32,
+        device_ptr: *const u32,
+        size: u32,
+    );
+
     pub fn copy_uint32_t_vec_from_device_to_device(
         from: *const u32,
         dst: *const u32,
         size: u32,
     ) -> *const u32;
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn retain_memory_tracking();
+
+    pub fn release_memory_tracking();
+
+    pub fn device_memory_in_use() -> usize;
+
+    pub fn allocation_mark() -> u64;
+
+    pub fn free_allocations_since(mark: u64) -> i32;
+
+    pub fn reset_device() -> i32;
+
+    pub fn recover_device() -> i32;
+
+    pub fn clear_last_error() -> i32;
+
+    pub fn context_generation() -> i32;
+
+    pub fn begin_memory_peak() -> i32;
+
+    pub fn end_memory_peak(id: i32) -> usize;
+
     pub fn cuda_malloc_uint32_t(size: u32) -> *const u32;
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn cuda_alloc_zeroes_uint32_t(size: u32) -> *const u32;
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn free_uint32_t_vec(device_ptr: *const u32);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn free_uint32_t_vecs(device_ptrs: *const *const u32, count: u32);
+
+    pub fn free_uint32_t_vec_after_events(
+        device_ptr: *const u32,
+        events: *const *mut c_void,
+        n_events: u32,
+    );
+
+    pub fn cuda_malloc_host_uint32_t(size: u32) -> *const u32;
+
+    pub fn free_host_uint32_t_vec(host_ptr: *const u32);
+
+    pub fn probe_cuda_device(
+        device_count: *mut i32,
+        driver_version: *mut i32,
+        runtime_version: *mut i32,
+    ) -> i32;
+
+    pub fn get_device_count(device_count: *mut i32) -> i32;
+
+    pub fn get_device_properties(device: i32, properties: *mut DeviceProperties) -> i32;
+
+    pub fn get_current_device() -> i32;
+
+    pub fn set_current_device(device: i32) -> i32;
+
+    pub fn kernel_tuning_for_architecture(major: i32, minor: i32) -> KernelTuning;
+
+    pub fn kernel_tuning_for_device(major: i32, minor: i32, sm_count: i32) -> KernelTuning;
+
+    pub fn get_kernel_tuning() -> KernelTuning;
+
+    pub fn set_kernel_tuning(value: KernelTuning);
+
     pub fn bit_reverse_base_field(array: *const u32, size: usize);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn bit_reverse_secure_field(array: *const u32, size: usize);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn bit_reverse_blake2s_hash(array: *const u32, size: usize);
+
     pub fn batch_inverse_base_field(from: *const u32, dst: *const u32, size: usize);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn batch_inverse_secure_field(from: *const u32, dst: *const u32, size: usize);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn sort_values_and_permute_with_bit_reverse_order(
         from: *const u32,
         size: usize,
     ) -> *const u32;
-}
 
-// This is needed since `CirclePoint<BaseField>` is not FFI safe.
-#[repr(C)]
-pub(crate) struct CirclePointBaseField {
-    x: BaseField,
-    y: BaseField,
-}
-
-impl From<CirclePoint<BaseField>> for CirclePointBaseField {
-    fn from(value: CirclePoint<BaseField>) -> Self {
-        Self {
-            x: value.x,
-            y: value.y,
-        }
-    }
-}
-
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn precompute_twiddles(
         initial: CirclePointBaseField,
         step: CirclePointBaseField,
         total_size: usize,
     ) -> *const u32;
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn interpolate(values: *const u32, inverse_twiddles_tree: *const u32, values_size: u32);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
     pub fn evaluate(values: *const u32, inverse_twiddles_tree: *const u32, values_size: u32);
-}
 
-#[link(name = "gpubackend")]
-extern "C" {
+    pub fn interpolate_batch(
+        values: *const u32,
+        inverse_twiddles_tree: *const u32,
+        values_size: u32,
+        n_instances: u32,
+    );
+
+    pub fn evaluate_batch(
+        values: *const u32,
+        inverse_twiddles_tree: *const u32,
+        values_size: u32,
+        n_instances: u32,
+    );
+
+    pub fn extend_batch(
+        coeffs: *const u32,
+        size: u32,
+        dst: *const u32,
+        extended_size: u32,
+        n_instances: u32,
+    );
+
     pub fn eval_at_point(
         coeffs: *const u32,
         coeffs_size: u32,
         point_x: SecureField,
         point_y: SecureField,
     ) -> SecureField;
+
+    pub fn fix_first_variable_base_field(
+        evals: *const u32,
+        evals_size: u32,
+        assignment: SecureField,
+        dst: *const u32,
+    );
+
+    pub fn fix_first_variable_secure_field(
+        evals: *const u32,
+        evals_size: u32,
+        assignment: SecureField,
+        dst: *const u32,
+    );
+
+    pub fn gen_eq_evals(y: *const u32, y_size: u32, v: SecureField, dst: *const u32);
+
+    pub fn gkr_grand_product_sum(
+        eq_evals: *const u32,
+        input: *const u32,
+        n_terms: u32,
+        result: *mut SecureField,
+    );
+
+    pub fn gkr_logup_sum_base_field(
+        eq_evals: *const u32,
+        numerators: *const u32,
+        denominators: *const u32,
+        n_terms: u32,
+        lambda: SecureField,
+        result: *mut SecureField,
+    );
+
+    pub fn gkr_logup_sum_secure_field(
+        eq_evals: *const u32,
+        numerators: *const u32,
+        denominators: *const u32,
+        n_terms: u32,
+        lambda: SecureField,
+        result: *mut SecureField,
+    );
+
+    pub fn gkr_next_grand_product_layer(layer: *const u32, size: u32, dst: *const u32);
+
+    pub fn gkr_next_logup_layer_base_field(
+        numerators: *const u32,
+        denominators: *const u32,
+        size: u32,
+        dst_numerators: *const u32,
+        dst_denominators: *const u32,
+    );
+
+    pub fn gkr_next_logup_layer_secure_field(
+        numerators: *const u32,
+        denominators: *const u32,
+        size: u32,
+        dst_numerators: *const u32,
+        dst_denominators: *const u32,
+    );
+
+    pub fn add_fractions(
+        numerators_a: *const u32,
+        denominators_a: *const u32,
+        numerators_b: *const u32,
+        denominators_b: *const u32,
+        dst_numerators: *const u32,
+        dst_denominators: *const u32,
+        size: u32,
+    );
+
+    pub fn fractions_cumulative_sum(numerators: *const u32, denominators: *const u32, size: u32);
+
+    pub fn cumulative_sum_base_field(values: *const u32, size: u32);
+
+    pub fn cumulative_sum_secure_field(values: *const u32, size: u32);
+
+    pub fn cumulative_product_base_field(values: *const u32, size: u32);
+
+    pub fn cumulative_product_secure_field(values: *const u32, size: u32);
+
+    pub fn sort_base_field(values: *const u32, size: u32);
+
+    pub fn sort_base_field_by_key(keys: *const u32, values: *const u32, size: u32);
+
+    pub fn unique_base_field(values: *const u32, size: u32, dst: *const u32) -> u32;
+
+    pub fn accumulate_multiplicities(
+        column: *const u32,
+        size: u32,
+        multiplicities: *const u32,
+        table_size: u32,
+    );
+
+    pub fn base_field_column_stats(
+        column: *const u32,
+        size: u32,
+        range_start: u32,
+        range_end: u32,
+        result: *mut ColumnStats,
+    );
+
+    pub fn last_nonzero_index(column: *const u32, size: u32) -> i32;
+
+    pub fn gather_mask_base_field(
+        column: *const u32,
+        dst: *const u32,
+        trace_log_size: u32,
+        eval_log_size: u32,
+        offset: i32,
+    );
+
+    pub fn natural_to_circle_domain_order(column: *const u32, dst: *const u32, log_size: u32);
+
+    pub fn circle_domain_to_natural_order(column: *const u32, dst: *const u32, log_size: u32);
+
+    pub fn gather_base_field(column: *const u32, indices: *const u32, size: u32, dst: *const u32);
+
+    pub fn gather_secure_field(
+        column: *const *const u32,
+        indices: *const u32,
+        size: u32,
+        dst: *const *const u32,
+    );
+
+    pub fn scatter_base_field(column: *const u32, indices: *const u32, size: u32, dst: *const u32);
+
+    pub fn scatter_secure_field(
+        column: *const *const u32,
+        indices: *const u32,
+        size: u32,
+        dst: *const *const u32,
+    );
+
+    pub fn densify(
+        indices: *const u32,
+        values: *const u32,
+        n_values: u32,
+        dst: *const u32,
+        size: u32,
+    );
+
+    pub fn tile_base_field(pattern: *const u32, pattern_size: u32, dst: *const u32, size: u32);
+
+    pub fn gen_step_selector(dst: *const u32, log_size: u32, step: u32, offset: u32);
+
+    pub fn gen_row_bits(dst: *const u32, log_size: u32, shift: u32, mask: u32);
+
+    pub fn jit_target_architecture() -> u32;
+
+    pub fn jit_compile(
+        source: *const c_char,
+        fields_header: *const c_char,
+        ptx: *mut *mut c_char,
+        log: *mut *mut c_char,
+    ) -> i32;
+
+    pub fn jit_free(ptr: *mut c_char);
+
+    pub fn jit_load_module(ptx: *const c_char, module: *mut *const c_void) -> i32;
+
+    pub fn jit_get_function(
+        module: *const c_void,
+        name: *const c_char,
+        function: *mut *const c_void,
+    ) -> i32;
+
+    pub fn jit_unload_module(module: *const c_void);
+
+    pub fn jit_launch_constraint_kernel(
+        function: *const c_void,
+        columns: *const *const u32,
+        n_columns: u32,
+        random_coeff_powers: *const u32,
+        accumulator: *const *const u32,
+        size: u32,
+    ) -> i32;
+
+    pub fn jit_launch_check_kernel(
+        function: *const c_void,
+        columns: *const *const u32,
+        n_columns: u32,
+        size: u32,
+        first_failure: *mut u64,
+    ) -> i32;
+
+    pub fn fold_line(
+        eval: *const *const u32,
+        folded: *const *const u32,
+        eval_size: u32,
+        itwiddles: *const u32,
+        twiddle_offset: u32,
+        alpha: SecureField,
+    );
+
+    pub fn fold_line_on_stream(
+        eval: *const *const u32,
+        folded: *const *const u32,
+        eval_size: u32,
+        itwiddles: *const u32,
+        twiddle_offset: u32,
+        alpha: SecureField,
+        stream: *mut c_void,
+    );
+
+    pub fn fold_line_and_hash(
+        eval: *const *const u32,
+        folded: *const *const u32,
+        eval_size: u32,
+        itwiddles: *const u32,
+        twiddle_offset: u32,
+        alpha: SecureField,
+        leaves: *const u32,
+    );
+
+    pub fn fold_circle_into_line(
+        dst: *const *const u32,
+        src: *const *const u32,
+        dst_size: u32,
+        itwiddles: *const u32,
+        twiddle_offset: u32,
+        alpha: SecureField,
+    );
+
+    pub fn fold_line_by(
+        eval: *const *const u32,
+        folded: *const *const u32,
+        eval_size: u32,
+        log_fold_factor: u32,
+        itwiddles: *const u32,
+        twiddle_offsets: *const u32,
+        alpha: SecureField,
+    );
+
+    pub fn fold_lines(
+        evals: *const *const u32,
+        folded: *const *const u32,
+        n_evals: u32,
+        eval_size: u32,
+        itwiddles: *const u32,
+        twiddle_offset: u32,
+        alphas: *const SecureField,
+    );
+
+    pub fn inner_product_base_field(a: *const u32, b: *const u32, size: u32) -> BaseField;
+
+    pub fn inner_product_secure_field(
+        a: *const *const u32,
+        b: *const *const u32,
+        size: u32,
+    ) -> SecureField;
+
+    pub fn mul_add_base_field(dst: *const u32, a: *const u32, b: *const u32, size: u32);
+
+    pub fn mul_add_secure_field(
+        dst: *const *const u32,
+        a: *const *const u32,
+        b: *const *const u32,
+        size: u32,
+    );
+
+    pub fn sum_secure_column(column: *const *const u32, size: u32, result: *mut SecureField)
+        -> i32;
+
+    pub fn compute_g_values(
+        f_values: *const *const u32,
+        dst: *const *const u32,
+        size: u32,
+        lambda: SecureField,
+    );
+
+    pub fn decompose(
+        f_values: *const *const u32,
+        dst: *const *const u32,
+        size: u32,
+        result: *mut SecureField,
+    ) -> i32;
+
+    pub fn check_fold_pairs(
+        f_x: *const SecureField,
+        f_neg_x: *const SecureField,
+        x: *const BaseField,
+        alphas: *const SecureField,
+        folded: *const SecureField,
+        n_checks: u32,
+        valid: *const i32,
+    );
+
+    pub fn fold_pairs(
+        f_x: *const u32,
+        f_neg_x: *const u32,
+        x: *const u32,
+        alphas: *const u32,
+        n_pairs: u32,
+        folded: *const u32,
+    );
+
+    pub fn commit_on_layer(
+        log_size: u32,
+        prev_layer: *const u32,
+        columns: *const *const u32,
+        n_columns: u32,
+        dst: *const u32,
+    );
+
+    pub fn commit_on_layer_on_stream(
+        log_size: u32,
+        prev_layer: *const u32,
+        columns: *const *const u32,
+        n_columns: u32,
+        dst: *const u32,
+        stream: *mut c_void,
+    );
+
+    pub fn commit_on_layer_batch(
+        log_size: u32,
+        n_instances: u32,
+        prev_layer: *const u32,
+        columns: *const *const u32,
+        n_columns: u32,
+        dst: *const u32,
+    );
+
+    pub fn verify_merkle_paths(
+        leaf_values: *const BaseField,
+        n_columns: u32,
+        positions: *const u32,
+        paths: *const u32,
+        depth: u32,
+        roots: *const u32,
+        n_roots: u32,
+        root_indices: *const u32,
+        n_paths: u32,
+        valid: *const i32,
+    );
+
+    pub fn merkle_path_nodes(
+        leaf_values: *const u32,
+        n_columns: u32,
+        positions: *const u32,
+        paths: *const u32,
+        depth: u32,
+        n_paths: u32,
+        nodes: *const u32,
+    );
+
+    pub fn gather_query_values(
+        columns: *const *const u32,
+        n_columns: u32,
+        positions: *const u32,
+        n_positions: u32,
+        result: *const u32,
+    );
+
+    pub fn gather_authentication_paths(
+        layers: *const *const u32,
+        n_layers: u32,
+        positions: *const u32,
+        n_positions: u32,
+        result: *const u32,
+    );
+
+    pub fn eval_polys_at_points(
+        coeffs: *const *const u32,
+        log_sizes: *const u32,
+        n_polys: u32,
+        points_x: *const SecureField,
+        points_y: *const SecureField,
+        n_points: u32,
+        result: *mut SecureField,
+    );
+
+    pub fn eval_samples(
+        coeffs: *const *const u32,
+        log_sizes: *const u32,
+        n_polys: u32,
+        points_x: *const SecureField,
+        points_y: *const SecureField,
+        n_points: u32,
+        sample_polys: *const u32,
+        sample_points: *const u32,
+        n_samples: u32,
+        result: *mut SecureField,
+    );
+
+    pub fn add_points(
+        lhs_x: *const u32,
+        lhs_y: *const u32,
+        rhs_x: *const u32,
+        rhs_y: *const u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn double_points(
+        x: *const u32,
+        y: *const u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn mul_points(
+        x: *const u32,
+        y: *const u32,
+        scalar: u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn add_secure_points(
+        lhs_x: *const u32,
+        lhs_y: *const u32,
+        rhs_x: *const u32,
+        rhs_y: *const u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn double_secure_points(
+        x: *const u32,
+        y: *const u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn mul_secure_points(
+        x: *const u32,
+        y: *const u32,
+        scalar: u32,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn coset_points(
+        initial: CirclePointBaseField,
+        step: CirclePointBaseField,
+        dst_x: *const u32,
+        dst_y: *const u32,
+        size: u32,
+    );
+
+    pub fn coset_vanishing(
+        half_coset_initial: CirclePointBaseField,
+        half_coset_step: CirclePointBaseField,
+        log_size: u32,
+        shift: CirclePointBaseField,
+        log_coset_size: u32,
+        dst: *const u32,
+    );
+
+    pub fn pair_vanishing_denominators(
+        half_coset_initial: CirclePointBaseField,
+        half_coset_step: CirclePointBaseField,
+        log_size: u32,
+        sample_x: SecureField,
+        sample_y: SecureField,
+        dst_a: *const u32,
+        dst_b: *const u32,
+    );
+
+    pub fn pair_vanishing_denominator_inverses(
+        half_coset_initial: CirclePointBaseField,
+        half_coset_step: CirclePointBaseField,
+        log_size: u32,
+        sample_x: SecureField,
+        sample_y: SecureField,
+        dst_a: *const u32,
+        dst_b: *const u32,
+    );
+
+    pub fn pad_base_field(
+        column: *const u32,
+        size: u32,
+        dst: *const u32,
+        dst_size: u32,
+        repeat: bool,
+    );
+
+    pub fn accumulate(column: *const *const u32, other: *const *const u32, size: u32);
+
+    pub fn accumulate_sparse(
+        column: *const *const u32,
+        indices: *const u32,
+        values: *const u32,
+        n_values: u32,
+        coefficient: SecureField,
+    );
+
+    pub fn accumulate_quotients(
+        half_coset_x: *const u32,
+        half_coset_y: *const u32,
+        log_size: u32,
+        columns: *const *const u32,
+        n_columns: u32,
+        sample_points_x: *const SecureField,
+        sample_points_y: *const SecureField,
+        batch_random_coeffs: *const SecureField,
+        batch_sizes: *const u32,
+        n_batches: u32,
+        column_indices: *const u32,
+        line_coeffs: *const SecureField,
+        n_samples: u32,
+        result: *const *const u32,
+    );
+
+    pub fn pack_base_field(column: *const u32, size: u32, dst: *const u32, packed_size: u32);
+
+    pub fn unpack_base_field(packed: *const u32, packed_size: u32, dst: *const u32, size: u32);
+
+    pub fn fill_base_field(dst: *const u32, value: BaseField, size: u32);
+
+    pub fn fill_secure_field(dst: *const u32, value: SecureField, size: u32);
+
+    pub fn fill_random_base_field(dst: *const u32, size: u32, seed: u64);
+
+    pub fn fill_powers_secure_field(dst: *const u32, x: SecureField, size: u32);
+
+    pub fn embed_base_field(src: *const u32, dst: *const u32, size: u32);
+
+    pub fn embed_base_field_into_secure_column(src: *const u32, dst: *const *const u32, size: u32);
+
+    pub fn uint32_t_vec_equal(a: *const u32, b: *const u32, size: u32) -> bool;
+}
+
+// Row constraint launchers, passed to `evaluate_row_constraints`.
+cuda_bindings! {
+    #[cfg(test)]
+    pub extern "C" fn example_row_constraints(
+        columns: *const *const u32,
+        n_columns: u32,
+        mask: *const MaskItem,
+        n_mask_items: u32,
+        trace_log_size: u32,
+        eval_log_size: u32,
+        random_coeff_powers: *const SecureField,
+        n_constraints: u32,
+        accumulator: *const *const u32,
+    ) -> i32;
+
+    pub extern "C" fn squares_row_constraints(
+        columns: *const *const u32,
+        n_columns: u32,
+        mask: *const MaskItem,
+        n_mask_items: u32,
+        trace_log_size: u32,
+        eval_log_size: u32,
+        random_coeff_powers: *const SecureField,
+        n_constraints: u32,
+        accumulator: *const *const u32,
+    ) -> i32;
 }
@@ -0,0 +1,141 @@
+use stwo_prover::core::vcs::blake2_hash::Blake2sHash;
+
+use super::{bindings, free, DownloadError};
+
+/// Number of `u32` words in a [`Blake2sHash`].
+pub(crate) const HASH_WORDS: usize = 8;
+
+#[derive(Debug)]
+pub struct Blake2sHashVec {
+    device_ptr: *const u32,
+    pub(crate) size: usize,
+    /// The context the memory was allocated in, see [`crate::reset_device`].
+    context: u32,
+}
+
+// Device memory is shared by all the threads of the process.
+unsafe impl Send for Blake2sHashVec {}
+
+unsafe impl Sync for Blake2sHashVec {}
+
+impl Blake2sHashVec {
+    pub fn new(device_ptr: *const u32, size: usize) -> Self {
+        Self {
+            device_ptr,
+            size,
+            context: free::context(),
+        }
+    }
+
+    /// Pointer to the first word of the vector, for passing it to [`crate::bindings`]. The
+    /// vector keeps ownership of the memory.
+    #[cfg(feature = "unstable-ffi")]
+    pub fn device_ptr(&self) -> *const u32 {
+        self.device_ptr
+    }
+
+    /// Pointer to the first word of the vector, for passing it to the bindings.
+    #[cfg(not(feature = "unstable-ffi"))]
+    pub(crate) fn device_ptr(&self) -> *const u32 {
+        self.device_ptr
+    }
+
+    pub fn new_uninitialized(size: usize) -> Self {
+        Self::new(
+            unsafe { bindings::cuda_malloc_uint32_t((HASH_WORDS * size) as u32) },
+            size,
+        )
+    }
+
+    pub fn new_zeroes(size: usize) -> Self {
+        Self::new(
+            unsafe { bindings::cuda_alloc_zeroes_uint32_t((HASH_WORDS * size) as u32) },
+            size,
+        )
+    }
+
+    /// Copies the hashes to the host, once the device is known to be done writing them.
+    pub fn try_to_vec(&self) -> Result<Vec<Blake2sHash>, DownloadError> {
+        Ok(words_to_hashes(&self.try_words(0, self.size)?))
+    }
+
+    /// [`Blake2sHashVec::try_to_vec`], panicking if the copy fails.
+    pub fn to_vec(&self) -> Vec<Blake2sHash> {
+        self.try_to_vec().unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Copies the words of the hashes in positions `start..start + len` to the host.
+    pub(crate) fn words(&self, start: usize, len: usize) -> Vec<u32> {
+        self.try_words(start, len)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    fn try_words(&self, start: usize, len: usize) -> Result<Vec<u32>, DownloadError> {
+        assert!(start + len <= self.size);
+        let mut host_data = vec![0u32; HASH_WORDS * len];
+        match unsafe {
+            bindings::copy_uint32_t_vec_from_device_to_host_checked(
+                self.device_ptr.add(HASH_WORDS * start),
+                host_data.as_mut_ptr() as *const u32,
+                (HASH_WORDS * len) as u32,
+            )
+        } {
+            0 => Ok(host_data),
+            code => Err(DownloadError(code)),
+        }
+    }
+}
+
+pub(crate) fn words_to_hashes(words: &[u32]) -> Vec<Blake2sHash> {
+    words
+        .chunks(HASH_WORDS)
+        .map(|hash_words| {
+            let mut bytes = [0u8; 32];
+            for (chunk, word) in bytes.chunks_mut(4).zip(hash_words) {
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+            Blake2sHash(bytes)
+        })
+        .collect()
+}
+
+pub(crate) fn hash_to_words(hash: &Blake2sHash) -> [u32; HASH_WORDS] {
+    std::array::from_fn(|i| u32::from_le_bytes(hash.0[4 * i..4 * i + 4].try_into().unwrap()))
+}
+
+impl PartialEq for Blake2sHashVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && (self.device_ptr == other.device_ptr
+                || unsafe {
+                    bindings::uint32_t_vec_equal(
+                        self.device_ptr,
+                        other.device_ptr,
+                        (HASH_WORDS * self.size) as u32,
+                    )
+                })
+    }
+}
+
+impl Eq for Blake2sHashVec {}
+
+/// Copies the hashes to new device memory.
+impl Clone for Blake2sHashVec {
+    fn clone(&self) -> Self {
+        let result = Self::new_uninitialized(self.size);
+        unsafe {
+            bindings::copy_uint32_t_vec_from_device_to_device(
+                self.device_ptr,
+                result.device_ptr,
+                (HASH_WORDS * self.size) as u32,
+            );
+        }
+        result
+    }
+}
+
+impl Drop for Blake2sHashVec {
+    fn drop(&mut self) {
+        free::free_device_ptr(self.device_ptr, self.context);
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    error::Error,
+    fmt,
+    marker::PhantomData,
+    mem::size_of,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
+
+use super::{bindings, free};
+use crate::memory::memory_phase;
+
+/// Values that can be stored in a [`DeviceVec`], which copies them to and from the device as
+/// they are laid out on the host.
+///
+/// # Safety
+///
+/// Implementors must consist of `u32` words only, without padding, and every word pattern the
+/// kernels write must be a valid value.
+pub unsafe trait Pod: Copy {
+    /// Number of `u32` words of a value.
+    const WORDS: usize = size_of::<Self>() / size_of::<u32>();
+}
+
+unsafe impl Pod for u32 {}
+
+unsafe impl Pod for BaseField {}
+
+unsafe impl Pod for SecureField {}
+
+/// A copy of device memory to the host that failed, or the kernels it waited for did, with the
+/// CUDA error code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DownloadError(pub i32);
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "copy from the device failed with CUDA error {}", self.0)
+    }
+}
+
+impl Error for DownloadError {}
+
+/// Serializes applying pending bit reversals, which vectors do behind shared references.
+static REORDER: Mutex<()> = Mutex::new(());
+
+/// A vector of values in device memory, freed on drop.
+///
+/// Sizes are in values: the conversion to the `u32` words the kernels deal with only happens
+/// here.
+#[derive(Debug)]
+pub struct DeviceVec<T: Pod> {
+    device_ptr: *const u32,
+    pub(crate) size: usize,
+    /// The context the memory was allocated in, see [`crate::reset_device`].
+    context: u32,
+    /// Whether the values are stored in the bit reversed order of the one they are in, see
+    /// [`DeviceVec::bit_reverse`].
+    bit_reversal_pending: AtomicBool,
+    _values: PhantomData<T>,
+}
+
+// Device memory is shared by all the threads of the process.
+unsafe impl<T: Pod> Send for DeviceVec<T> {}
+
+unsafe impl<T: Pod> Sync for DeviceVec<T> {}
+
+impl<T: Pod> DeviceVec<T> {
+    /// Takes ownership of `size` values at `device_ptr`, which must come from
+    /// `cuda_malloc_uint32_t` as the vector frees it on drop.
+    pub fn new(device_ptr: *const u32, size: usize) -> Self {
+        Self {
+            device_ptr,
+            size,
+            context: free::context(),
+            bit_reversal_pending: AtomicBool::new(false),
+            _values: PhantomData,
+        }
+    }
+
+    /// Pointer to the first word of the vector, for passing it to [`crate::bindings`]. The
+    /// vector keeps ownership of the memory.
+    #[cfg(feature = "unstable-ffi")]
+    pub fn device_ptr(&self) -> *const u32 {
+        self.ordered_ptr()
+    }
+
+    /// Pointer to the first word of the vector, for passing it to the bindings.
+    #[cfg(not(feature = "unstable-ffi"))]
+    pub(crate) fn device_ptr(&self) -> *const u32 {
+        self.ordered_ptr()
+    }
+
+    /// Bit reverses the order of the values, lazily: the permutation only runs when the values
+    /// are next used, so reversing the vector back before then, e.g. after handing it in the other
+    /// order to code that didn't touch it, costs nothing.
+    ///
+    /// Only vectors of base or secure field elements can be bit reversed.
+    pub(crate) fn bit_reverse(&mut self) {
+        assert!(self.size.is_power_of_two() && self.size < u32::MAX as usize);
+        assert!(
+            matches!(T::WORDS, 1 | 4),
+            "no bit reversal of values of {} words",
+            T::WORDS
+        );
+        *self.bit_reversal_pending.get_mut() ^= true;
+    }
+
+    /// The pointer to the values, applying the pending bit reversal first.
+    fn ordered_ptr(&self) -> *const u32 {
+        if self.bit_reversal_pending.load(Ordering::Acquire) {
+            let _reorder = REORDER.lock().unwrap();
+            // Another thread may have applied it while this one waited.
+            if self.bit_reversal_pending.load(Ordering::Acquire) {
+                unsafe {
+                    match T::WORDS {
+                        1 => bindings::bit_reverse_base_field(self.device_ptr, self.size),
+                        _ => bindings::bit_reverse_secure_field(self.device_ptr, self.size),
+                    }
+                }
+                self.bit_reversal_pending.store(false, Ordering::Release);
+            }
+        }
+        self.device_ptr
+    }
+
+    pub fn from_vec(host_array: Vec<T>) -> Self {
+        Self::from_slice(&host_array)
+    }
+
+    /// Uploads borrowed values, e.g. from a memory-mapped file, without copying them first.
+    pub fn from_slice(host_array: &[T]) -> Self {
+        let _phase = memory_phase("upload");
+        let device_ptr = unsafe {
+            bindings::copy_uint32_t_vec_from_host_to_device(
+                host_array.as_ptr() as *const u32,
+                words::<T>(host_array.len()),
+            )
+        };
+        Self::new(device_ptr, host_array.len())
+    }
+
+    pub fn new_uninitialized(size: usize) -> Self {
+        Self::new(
+            unsafe { bindings::cuda_malloc_uint32_t(words::<T>(size)) },
+            size,
+        )
+    }
+
+    pub fn new_zeroes(size: usize) -> Self {
+        Self::new(
+            unsafe { bindings::cuda_alloc_zeroes_uint32_t(words::<T>(size)) },
+            size,
+        )
+    }
+
+    /// Returns a vector of pseudorandom values derived from `seed`, generated on the device so
+    /// large inputs for tests and benchmarks don't have to be uploaded.
+    ///
+    /// The values only depend on `seed` and `size`, but are not suitable for cryptographic use.
+    pub fn random(size: usize, seed: u64) -> Self {
+        let result = Self::new_uninitialized(size);
+        // Random field elements are valid values of every `Pod` type.
+        unsafe { bindings::fill_random_base_field(result.device_ptr(), words::<T>(size), seed) };
+        result
+    }
+
+    pub fn copy_from(&mut self, other: &Self) {
+        assert!(self.size >= other.size);
+        unsafe {
+            bindings::copy_uint32_t_vec_from_device_to_device(
+                other.device_ptr(),
+                self.device_ptr(),
+                words::<T>(other.size),
+            );
+        }
+    }
+
+    /// Copies the values to the host, once the device is known to be done writing them.
+    ///
+    /// Fails if the copy does, or any kernel queued before it, whose results it would return.
+    pub fn try_to_vec(&self) -> Result<Vec<T>, DownloadError> {
+        let mut host_data: Vec<T> = Vec::with_capacity(self.size);
+        download(
+            self.device_ptr(),
+            &mut host_data.spare_capacity_mut()[..self.size],
+        )?;
+        // The copy wrote every value.
+        unsafe { host_data.set_len(self.size) };
+        Ok(host_data)
+    }
+
+    /// [`DeviceVec::try_to_vec`], panicking if the copy fails.
+    pub fn to_vec(&self) -> Vec<T> {
+        self.try_to_vec().unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Copies the value at `index` to the host.
+    pub(crate) fn value_at(&self, index: usize) -> T {
+        assert!(index < self.size);
+        let mut value = [std::mem::MaybeUninit::<T>::uninit()];
+        let device_ptr = unsafe { self.device_ptr().add(T::WORDS * index) };
+        download(device_ptr, &mut value).unwrap_or_else(|error| panic!("{error}"));
+        unsafe { value[0].assume_init() }
+    }
+
+    /// Overwrites the value at `index` with `value`.
+    pub(crate) fn set_value(&mut self, index: usize, value: T) {
+        assert!(index < self.size);
+        unsafe {
+            bindings::copy_uint32_t_vec_from_host_to_existing_device(
+                &value as *const T as *const u32,
+                self.device_ptr().add(T::WORDS * index),
+                words::<T>(1),
+            );
+        }
+    }
+}
+
+/// Fills `host_data` with the values at `device_ptr`, checking that the copy is done.
+fn download<T: Pod>(
+    device_ptr: *const u32,
+    host_data: &mut [std::mem::MaybeUninit<T>],
+) -> Result<(), DownloadError> {
+    match unsafe {
+        bindings::copy_uint32_t_vec_from_device_to_host_checked(
+            device_ptr,
+            host_data.as_mut_ptr() as *const u32,
+            words::<T>(host_data.len()),
+        )
+    } {
+        0 => Ok(()),
+        code => Err(DownloadError(code)),
+    }
+}
+
+/// Number of `u32` words of `size` values of type `T`, as taken by the bindings.
+pub(super) fn words<T: Pod>(size: usize) -> u32 {
+    (T::WORDS * size).try_into().unwrap()
+}
+
+/// Compares the values on the device, without copying them to the host.
+impl<T: Pod> PartialEq for DeviceVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && (self.device_ptr() == other.device_ptr()
+                || unsafe {
+                    bindings::uint32_t_vec_equal(
+                        self.device_ptr(),
+                        other.device_ptr(),
+                        words::<T>(self.size),
+                    )
+                })
+    }
+}
+
+impl<T: Pod> Eq for DeviceVec<T> {}
+
+/// Copies the values to new device memory, in the order they are in.
+impl<T: Pod> Clone for DeviceVec<T> {
+    fn clone(&self) -> Self {
+        let mut result = Self::new_uninitialized(self.size);
+        result.copy_from(self);
+        result
+    }
+}
+
+impl<T: Pod> Drop for DeviceVec<T> {
+    fn drop(&mut self) {
+        free::free_device_ptr(self.device_ptr, self.context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
+
+    use std::sync::atomic::Ordering;
+
+    use super::{DeviceVec, Pod};
+
+    #[test]
+    fn test_words() {
+        assert_eq!(BaseField::WORDS, 1);
+        assert_eq!(SecureField::WORDS, 4);
+    }
+
+    #[test]
+    fn test_random() {
+        require_gpu!();
+        let size = 1 << 16;
+        let splitmix64 = |mut z: u64| {
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+        let expected_result = (0..size as u64)
+            .map(|i| {
+                let hash = splitmix64(7u64.wrapping_add((i + 1).wrapping_mul(0x9E3779B97F4A7C15)));
+                BaseField::from((hash >> 33) as u32)
+            })
+            .collect::<Vec<_>>();
+
+        let column = DeviceVec::<BaseField>::random(size, 7);
+        let secure_column = DeviceVec::<SecureField>::random(size / 4, 7);
+
+        assert_eq!(column.to_vec(), expected_result);
+        assert_eq!(
+            secure_column
+                .to_vec()
+                .iter()
+                .flat_map(|value| value.to_m31_array())
+                .collect::<Vec<_>>(),
+            expected_result
+        );
+        assert_ne!(DeviceVec::<BaseField>::random(size, 8), column);
+    }
+
+    #[test]
+    fn test_try_to_vec() {
+        require_gpu!();
+        let host_data = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let vec = DeviceVec::from_slice(&host_data);
+
+        assert_eq!(vec.try_to_vec(), Ok(host_data));
+        assert_eq!(
+            DeviceVec::<SecureField>::new_zeroes(0).try_to_vec(),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_value_at() {
+        require_gpu!();
+        let host_data = (0..1 << 10)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+        let mut vec = DeviceVec::from_slice(&host_data);
+        let value = SecureField::from_u32_unchecked(5, 6, 7, 8);
+
+        vec.set_value(513, value);
+
+        assert_eq!(vec.value_at(512), host_data[512]);
+        assert_eq!(vec.value_at(513), value);
+        assert_eq!(vec.value_at(514), host_data[514]);
+    }
+
+    #[test]
+    fn test_lazy_bit_reverse() {
+        require_gpu!();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let mut vec = DeviceVec::from_slice(&values);
+
+        vec.bit_reverse();
+        vec.bit_reverse();
+        assert!(!vec.bit_reversal_pending.load(Ordering::Relaxed));
+        assert_eq!(vec.to_vec(), values);
+
+        vec.bit_reverse();
+        assert!(vec.bit_reversal_pending.load(Ordering::Relaxed));
+        assert_eq!(vec.value_at(1), values[1 << 9]);
+        assert!(!vec.bit_reversal_pending.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_clone() {
+        require_gpu!();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let mut vec = DeviceVec::from_slice(&values);
+        vec.bit_reverse();
+
+        let mut clone = vec.clone();
+        clone.bit_reverse();
+
+        assert_ne!(clone.device_ptr(), vec.device_ptr());
+        assert_eq!(clone.to_vec(), values);
+        assert_eq!(vec.value_at(1), values[1 << 9]);
+    }
+}
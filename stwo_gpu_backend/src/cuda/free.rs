@@ -0,0 +1,178 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    marker::PhantomData,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::bindings;
+use crate::stream::{Event, Stream};
+
+thread_local! {
+    /// Number of live [`FreeBatch`] guards of the thread.
+    static BATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// Vectors dropped by the thread while a batch is open, with their context.
+    static DEFERRED: RefCell<Vec<(*const u32, u32)>> = const { RefCell::new(vec![]) };
+}
+
+/// Events after which each vector used by work queued on a stream can be freed, by device
+/// pointer, see [`free_after`].
+static PENDING_USES: Mutex<BTreeMap<usize, Vec<Arc<Event>>>> = Mutex::new(BTreeMap::new());
+
+/// Defers freeing the device vectors dropped by the current thread until the returned guard is
+/// dropped, then frees all of them with a single call, e.g. around the teardown of a proof's
+/// worth of columns.
+///
+/// Individual frees synchronize the device one by one. The batch synchronizes it once, and
+/// returns memory from the pool in bulk. Batches nest: only the outermost one frees.
+#[must_use = "vectors are freed when the batch is dropped"]
+pub fn batch_frees() -> FreeBatch {
+    BATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    FreeBatch {
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard returned by [`batch_frees`].
+pub struct FreeBatch {
+    // The deferred vectors belong to the thread that opened the batch.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for FreeBatch {
+    fn drop(&mut self) {
+        let depth = BATCH_DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        if depth == 0 {
+            let current = context();
+            let device_ptrs = DEFERRED
+                .with(|deferred| deferred.take())
+                .into_iter()
+                .filter(|&(_, context)| context == current)
+                .map(|(device_ptr, _)| device_ptr)
+                .collect::<Vec<_>>();
+            if !device_ptrs.is_empty() {
+                unsafe {
+                    bindings::free_uint32_t_vecs(device_ptrs.as_ptr(), device_ptrs.len() as u32)
+                };
+            }
+        }
+    }
+}
+
+/// The generation of the current CUDA context, bumped by [`crate::reset_device`], which vectors
+/// record when they are allocated. The memory of vectors allocated in an older one went with it,
+/// so they must not free it.
+pub(crate) fn context() -> u32 {
+    unsafe { bindings::context_generation() as u32 }
+}
+
+/// Forgets the pending uses of the vectors of the previous context, once it was reset.
+pub(crate) fn forget_context() {
+    // The events went with the context, so they can't even be destroyed.
+    mem::forget(mem::take(&mut *PENDING_USES.lock().unwrap()));
+}
+
+/// Orders the frees of the vectors at `device_ptrs` after the work queued on `stream` so far, so
+/// dropping them while that work still reads or writes them is safe.
+pub(crate) fn free_after(device_ptrs: impl IntoIterator<Item = *const u32>, stream: &Stream) {
+    let event = Arc::new(stream.record());
+    let mut pending_uses = PENDING_USES.lock().unwrap();
+    for device_ptr in device_ptrs {
+        let events = pending_uses.entry(device_ptr as usize).or_default();
+        // Keeps the list of a long-lived vector short.
+        events.retain(|event| !event.is_done());
+        events.push(event.clone());
+    }
+}
+
+/// Frees a vector allocated by `cuda_malloc_uint32_t` in `context`, or defers it to the open
+/// batch of the thread.
+///
+/// Vectors used by work queued on a stream, see [`free_after`], are freed in stream order once
+/// that work is done instead.
+pub(crate) fn free_device_ptr(device_ptr: *const u32, context: u32) {
+    if context != self::context() {
+        return;
+    }
+    let events = PENDING_USES
+        .lock()
+        .unwrap()
+        .remove(&(device_ptr as usize))
+        .unwrap_or_default();
+    let event_ptrs = events
+        .iter()
+        .filter(|event| !event.is_done())
+        .map(|event| event.ptr)
+        .collect::<Vec<_>>();
+    if !event_ptrs.is_empty() {
+        // The events may be destroyed right away: CUDA keeps them until the free is done.
+        unsafe {
+            bindings::free_uint32_t_vec_after_events(
+                device_ptr,
+                event_ptrs.as_ptr(),
+                event_ptrs.len() as u32,
+            )
+        };
+    } else if BATCH_DEPTH.with(Cell::get) > 0 {
+        DEFERRED.with(|deferred| deferred.borrow_mut().push((device_ptr, context)));
+    } else {
+        unsafe { bindings::free_uint32_t_vec(device_ptr) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::{batch_frees, BATCH_DEPTH, DEFERRED, PENDING_USES};
+    use crate::{cuda::BaseFieldVec, stream::Stream};
+
+    fn n_deferred() -> usize {
+        DEFERRED.with(|deferred| deferred.borrow().len())
+    }
+
+    #[test]
+    fn test_batch_frees() {
+        require_gpu!();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let columns = (0..100)
+            .map(|_| BaseFieldVec::from_vec(values.clone()))
+            .collect::<Vec<_>>();
+
+        let batch = batch_frees();
+        let inner_batch = batch_frees();
+        drop(columns);
+        drop(inner_batch);
+        assert_eq!(n_deferred(), 100);
+        drop(batch);
+        assert_eq!(n_deferred(), 0);
+        assert_eq!(BATCH_DEPTH.with(|depth| depth.get()), 0);
+
+        // Freed memory is handed out again.
+        let column = BaseFieldVec::from_vec(values.clone());
+        assert_eq!(column.to_vec(), values);
+    }
+
+    #[test]
+    fn test_free_after() {
+        require_gpu!();
+        let values = (0..1 << 20).map(BaseField::from).collect::<Vec<_>>();
+        let stream = Stream::non_blocking();
+        let column = BaseFieldVec::from_vec(values.clone());
+        let device_ptr = column.device_ptr() as usize;
+
+        let mut copied = vec![BaseField::from(0); values.len()];
+        // The copy may still run when the column is dropped. `copied` is only read once the
+        // stream is synchronized.
+        std::mem::forget(unsafe { column.copy_to_slice_async(&mut copied, &stream) });
+        drop(column);
+        assert!(!PENDING_USES.lock().unwrap().contains_key(&device_ptr));
+
+        stream.synchronize();
+        assert_eq!(copied, values);
+    }
+}
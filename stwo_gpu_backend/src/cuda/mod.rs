@@ -1,6 +1,23 @@
 mod base_field_vec;
+#[cfg(feature = "unstable-ffi")]
+pub mod bindings;
+#[cfg(not(feature = "unstable-ffi"))]
 pub(crate) mod bindings;
+mod blake2s_hash_vec;
+mod device_vec;
+mod free;
+mod secure_column;
 mod secure_field_vec;
 
-pub(crate) use crate::cuda::base_field_vec::BaseFieldVec;
-pub(crate) use crate::cuda::secure_field_vec::SecureFieldVec;
+pub use crate::cuda::base_field_vec::BaseFieldVec;
+pub(crate) use crate::cuda::base_field_vec::PinnedBuffer;
+pub use crate::cuda::blake2s_hash_vec::Blake2sHashVec;
+pub(crate) use crate::cuda::blake2s_hash_vec::{hash_to_words, words_to_hashes, HASH_WORDS};
+pub use crate::cuda::device_vec::{DeviceVec, DownloadError, Pod};
+pub use crate::cuda::free::{batch_frees, FreeBatch};
+pub(crate) use crate::cuda::free::{forget_context, free_after};
+pub use crate::cuda::secure_column::secure_column_from_base_field;
+pub(crate) use crate::cuda::secure_column::{
+    new_uninitialized_secure_column, secure_column_device_ptrs,
+};
+pub use crate::cuda::secure_field_vec::SecureFieldVec;
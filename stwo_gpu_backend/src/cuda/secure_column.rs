@@ -0,0 +1,61 @@
+use stwo_prover::core::fields::secure_column::SecureColumn;
+
+use super::{bindings, BaseFieldVec};
+use crate::backend::CudaBackend;
+
+/// Device pointers of the coordinates of `column`, in the layout expected by kernels taking a
+/// `m31 **` secure column.
+pub(crate) fn secure_column_device_ptrs(column: &SecureColumn<CudaBackend>) -> [*const u32; 4] {
+    let [a, b, c, d] = &column.columns;
+    [
+        a.device_ptr(),
+        b.device_ptr(),
+        c.device_ptr(),
+        d.device_ptr(),
+    ]
+}
+
+pub(crate) fn new_uninitialized_secure_column(size: usize) -> SecureColumn<CudaBackend> {
+    SecureColumn {
+        columns: std::array::from_fn(|_| BaseFieldVec::new_uninitialized(size)),
+    }
+}
+
+/// Lifts `values` to a secure column, with `values` as the first coordinate and zeros in the
+/// others, e.g. to add a base field column to a secure field accumulator without going through
+/// the host.
+pub fn secure_column_from_base_field(values: &BaseFieldVec) -> SecureColumn<CudaBackend> {
+    let column = new_uninitialized_secure_column(values.size);
+    unsafe {
+        bindings::embed_base_field_into_secure_column(
+            values.device_ptr(),
+            secure_column_device_ptrs(&column).as_ptr(),
+            values.size as u32,
+        );
+    }
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
+
+    use super::secure_column_from_base_field;
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_secure_column_from_base_field() {
+        require_gpu!();
+        let values = (0..(1 << 10) + 3).map(BaseField::from).collect::<Vec<_>>();
+
+        let column = secure_column_from_base_field(&BaseFieldVec::from_vec(values.clone()));
+
+        assert_eq!(
+            column.to_vec(),
+            values
+                .into_iter()
+                .map(SecureField::from)
+                .collect::<Vec<_>>()
+        );
+    }
+}
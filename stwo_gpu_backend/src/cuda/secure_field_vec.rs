@@ -1,55 +1,45 @@
 use stwo_prover::core::fields::qm31::SecureField;
 
-use super::bindings;
+use super::{bindings, BaseFieldVec, DeviceVec};
 
-#[derive(Clone, Debug)]
-pub struct SecureFieldVec {
-    pub(crate) device_ptr: *const u32,
-    pub(crate) size: usize,
-}
+pub type SecureFieldVec = DeviceVec<SecureField>;
 
 impl SecureFieldVec {
-    pub fn new(device_ptr: *const u32, size: usize) -> Self {
-        Self { device_ptr, size }
-    }
-    pub fn from_vec(host_array: Vec<SecureField>) -> Self {
-        let device_ptr = unsafe {
-            bindings::copy_uint32_t_vec_from_host_to_device(
-                host_array.as_ptr() as *const u32,
-                4 * host_array.len() as u32,
-            )
-        };
-        let size = host_array.len();
-        Self::new(device_ptr, size)
+    /// Sets every value of the vector to `value`, without any host to device transfer.
+    pub fn fill(&mut self, value: SecureField) {
+        unsafe { bindings::fill_secure_field(self.device_ptr(), value, self.size as u32) };
     }
 
-    pub fn to_vec(&self) -> Vec<SecureField> {
-        let mut host_data: Vec<SecureField> = Vec::with_capacity(self.size);
-        unsafe {
-            host_data.set_len(self.size.try_into().unwrap());
-            bindings::copy_uint32_t_vec_from_device_to_host(
-                self.device_ptr,
-                host_data.as_mut_ptr() as *const u32,
-                4 * self.size as u32,
-            );
-        }
-        host_data
+    /// Returns the vector `1, x, x^2, ..., x^(size - 1)`, as used to combine quotients and
+    /// constraints with powers of a random coefficient.
+    ///
+    /// Each value is computed from the squares of `x` independently, rather than by a serial
+    /// chain of multiplications.
+    pub fn powers(x: SecureField, size: usize) -> Self {
+        let result = Self::new_uninitialized(size);
+        unsafe { bindings::fill_powers_secure_field(result.device_ptr(), x, size as u32) };
+        result
     }
-}
 
-impl Drop for SecureFieldVec {
-    fn drop(&mut self) {
-        unsafe { bindings::free_uint32_t_vec(self.device_ptr) };
+    /// Lifts `values` to the secure field, with each value as the first coordinate and zeros in
+    /// the others, without going through the host.
+    pub fn from_base_field(values: &BaseFieldVec) -> Self {
+        let result = Self::new_uninitialized(values.size);
+        unsafe {
+            bindings::embed_base_field(values.device_ptr(), result.device_ptr(), values.size as u32)
+        };
+        result
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use stwo_prover::core::fields::qm31::SecureField;
+    use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
 
     #[test]
     fn test_constructor() {
+        require_gpu!();
         let size = 1 << 5;
         let from_raw = (1..(size + 1) as u32).collect::<Vec<u32>>();
         let host_data = from_raw
@@ -61,4 +51,64 @@ mod tests {
         assert_eq!(secure_field_vec.to_vec(), host_data);
         assert_eq!(secure_field_vec.size, host_data.len());
     }
+
+    #[test]
+    fn test_fill() {
+        require_gpu!();
+        let size = 1 << 12;
+        let value = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let mut secure_field_vec = SecureFieldVec::new_uninitialized(size);
+
+        secure_field_vec.fill(value);
+
+        assert_eq!(secure_field_vec.to_vec(), vec![value; size]);
+    }
+
+    #[test]
+    fn test_eq() {
+        require_gpu!();
+        let host_data = (0..1 << 10)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+        let mut other_data = host_data.clone();
+        other_data[7] = SecureField::from_u32_unchecked(7, 8, 9, 11);
+
+        let secure_field_vec = SecureFieldVec::from_vec(host_data.clone());
+
+        assert_eq!(secure_field_vec, SecureFieldVec::from_vec(host_data));
+        assert_ne!(secure_field_vec, SecureFieldVec::from_vec(other_data));
+    }
+
+    #[test]
+    fn test_powers() {
+        require_gpu!();
+        let size = (1 << 12) + 3;
+        let x = SecureField::from_u32_unchecked(5, 6, 7, 8);
+        let expected_result = (0..size)
+            .scan(SecureField::from_u32_unchecked(1, 0, 0, 0), |power, _| {
+                let value = *power;
+                *power *= x;
+                Some(value)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(SecureFieldVec::powers(x, size).to_vec(), expected_result);
+    }
+
+    #[test]
+    fn test_from_base_field() {
+        require_gpu!();
+        let values = (0..(1 << 10) + 3).map(BaseField::from).collect::<Vec<_>>();
+
+        let secure_field_vec =
+            SecureFieldVec::from_base_field(&BaseFieldVec::from_vec(values.clone()));
+
+        assert_eq!(
+            secure_field_vec.to_vec(),
+            values
+                .into_iter()
+                .map(SecureField::from)
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use stwo_prover::core::{
+    backend::Column,
+    poly::circle::{CircleEvaluation, PolyOps, SecureEvaluation},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+    twiddles::cached_twiddles,
+};
+
+/// A composition polynomial of higher degree than its bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DegreeBoundViolation {
+    /// Index of the offending coordinate polynomial, in `0..4`.
+    pub coordinate: usize,
+    /// Degree of the coordinate, as the index of its last nonzero coefficient.
+    pub degree: usize,
+    pub log_degree_bound: u32,
+}
+
+impl fmt::Display for DegreeBoundViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "coordinate {} of the composition polynomial has degree {}, above the bound 2^{}",
+            self.coordinate, self.degree, self.log_degree_bound
+        )
+    }
+}
+
+impl std::error::Error for DegreeBoundViolation {}
+
+/// Debug helper checking that the composition polynomial evaluated in `evaluation` has fewer than
+/// `2^log_degree_bound` coefficients, as the verifier will expect.
+///
+/// Each coordinate is interpolated on the device and only the position of its last nonzero
+/// coefficient is copied back. A violation usually means the AIR has a constraint of higher
+/// degree than declared, which would otherwise only show up as a failed proof.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(size = evaluation.values.len(), log_degree_bound = log_degree_bound)
+    )
+)]
+pub fn check_degree_bound(
+    evaluation: &SecureEvaluation<CudaBackend>,
+    log_degree_bound: u32,
+) -> Result<(), DegreeBoundViolation> {
+    let domain = evaluation.domain;
+    let twiddles = cached_twiddles(domain.half_coset);
+    for (coordinate, column) in evaluation.values.columns.iter().enumerate() {
+        // Interpolation is in place, so it works on a copy of the evaluation.
+        let mut values = BaseFieldVec::new_uninitialized(column.len());
+        values.copy_from(column);
+        let poly = CudaBackend::interpolate(CircleEvaluation::new(domain, values), &twiddles);
+
+        let last_nonzero = unsafe {
+            cuda::bindings::last_nonzero_index(poly.coeffs.device_ptr(), poly.coeffs.len() as u32)
+        };
+        if last_nonzero >= 1 << log_degree_bound {
+            return Err(DegreeBoundViolation {
+                coordinate,
+                degree: last_nonzero as usize,
+                log_degree_bound,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        fields::{m31::BaseField, secure_column::SecureColumn},
+        poly::circle::{CanonicCoset, CirclePoly, PolyOps, SecureEvaluation},
+    };
+
+    use super::{check_degree_bound, DegreeBoundViolation};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    #[test]
+    fn test_check_degree_bound() {
+        require_gpu!();
+        let log_size = 8;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+        let evaluation = SecureEvaluation {
+            domain,
+            values: SecureColumn {
+                columns: std::array::from_fn(|coordinate| {
+                    let mut coeffs = (0..1u32 << 5)
+                        .map(|i| BaseField::from(i * 3 + coordinate as u32 + 1))
+                        .collect::<Vec<_>>();
+                    coeffs.resize(1 << 6, BaseField::from(0));
+                    if coordinate == 2 {
+                        coeffs[35] = BaseField::from(1);
+                    }
+                    let poly = CirclePoly::<CudaBackend>::new(BaseFieldVec::from_vec(coeffs));
+                    CudaBackend::evaluate(&poly, domain, &twiddles).values
+                }),
+            },
+        };
+
+        assert_eq!(
+            check_degree_bound(&evaluation, 5),
+            Err(DegreeBoundViolation {
+                coordinate: 2,
+                degree: 35,
+                log_degree_bound: 5,
+            })
+        );
+        assert_eq!(check_degree_bound(&evaluation, 6), Ok(()));
+    }
+}
@@ -0,0 +1,766 @@
+use std::{env, error::Error, ffi::CStr, fmt, sync::OnceLock};
+
+use crate::{backend::CudaBackend, cuda};
+
+#[cfg(feature = "cuda")]
+const CUDA_ERROR_INSUFFICIENT_DRIVER: i32 = 35;
+#[cfg(feature = "cuda")]
+const CUDA_ERROR_NO_DEVICE: i32 = 100;
+
+/// Description of the CUDA setup found by [`try_init`].
+///
+/// Versions are encoded as `1000 * major + 10 * minor`, as reported by CUDA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub device_count: u32,
+    pub driver_version: u32,
+    pub runtime_version: u32,
+}
+
+/// Properties of a GPU, as returned by [`CudaBackend::list_devices`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Device {
+    /// Index to pass to CUDA to select the device, e.g. with [`CudaBackend::with_device`]. It is
+    /// logical: CUDA only numbers the devices `CUDA_VISIBLE_DEVICES` leaves visible.
+    pub index: u32,
+    /// The entry of `CUDA_VISIBLE_DEVICES` that made the device visible, e.g. the physical index
+    /// or UUID a scheduler such as Slurm or Kubernetes assigned, or `None` when the variable is
+    /// not set.
+    pub visible_as: Option<String>,
+    /// Identifies the device whatever devices are visible, formatted as nvidia-smi does, e.g.
+    /// `GPU-8f4c1a2e-...`, or `MIG-...` for a MIG instance.
+    pub uuid: String,
+    /// PCI address of the device, e.g. `0000:65:00.0`.
+    pub pci_bus_id: String,
+    pub name: String,
+    /// Memory sizes, in bytes.
+    pub total_memory: usize,
+    pub free_memory: usize,
+    /// Compute capability, as `(major, minor)`.
+    pub compute_capability: (u32, u32),
+    pub sm_count: u32,
+    /// The MIG instance the device is, if it is a slice of a GPU rather than a whole one. Its
+    /// memory and SM count are then those of the slice.
+    pub mig: Option<MigInstance>,
+}
+
+/// Compute slices a GPU supporting MIG, e.g. an A100 or H100, is divided into.
+const MIG_COMPUTE_SLICES: u32 = 7;
+
+/// A MIG instance: a slice of a GPU with its own share of the SMs and memory, which CUDA shows as
+/// a device of its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigInstance {
+    /// The profile of the instance, e.g. `1g.10gb`.
+    pub profile: String,
+    /// Compute slices of the instance, out of the 7 of the whole GPU.
+    pub compute_slices: u32,
+}
+
+impl MigInstance {
+    /// Share of the compute of the whole GPU the instance has.
+    pub fn compute_share(&self) -> f64 {
+        self.compute_slices as f64 / MIG_COMPUTE_SLICES as f64
+    }
+}
+
+/// The MIG instance of the device named `name`. CUDA names instances after the GPU and their
+/// profile, e.g. `NVIDIA A100-SXM4-40GB MIG 1g.5gb`, or `... MIG 1c.3g.20gb` for a compute
+/// instance using part of a GPU instance.
+fn mig_instance(name: &str) -> Option<MigInstance> {
+    let (_, profile) = name.rsplit_once(" MIG ")?;
+    let profile = profile.trim();
+    let mut gpu_slices = None;
+    let mut compute_slices = None;
+    for part in profile.split('.') {
+        if let Some(slices) = part.strip_suffix('c') {
+            compute_slices = slices.parse().ok();
+        } else if let Some(slices) = part.strip_suffix('g') {
+            gpu_slices = slices.parse().ok();
+        }
+    }
+    Some(MigInstance {
+        profile: profile.to_string(),
+        compute_slices: compute_slices.or(gpu_slices)?,
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitError {
+    NoDevice,
+    /// The installed driver is older than the CUDA runtime the crate was built with.
+    InsufficientDriver {
+        driver_version: u32,
+        runtime_version: u32,
+    },
+    /// Any other CUDA error, with its error code.
+    Cuda(i32),
+    /// The CUDA context was already created, so settings that only apply to a new context can't
+    /// be changed.
+    AlreadyInitialized,
+    /// A sticky error, such as an illegal memory access, with its error code, left the context
+    /// unusable until [`crate::reset_device`].
+    ContextCorrupted(i32),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::NoDevice => write!(f, "no CUDA device found"),
+            InitError::InsufficientDriver {
+                driver_version,
+                runtime_version,
+            } => write!(
+                f,
+                "CUDA driver version {driver_version} is older than runtime version \
+                 {runtime_version}"
+            ),
+            InitError::Cuda(code) => write!(f, "CUDA initialization failed with error {code}"),
+            InitError::AlreadyInitialized => write!(f, "CUDA is already initialized"),
+            InitError::ContextCorrupted(code) => {
+                write!(f, "CUDA context corrupted by error {code}")
+            }
+        }
+    }
+}
+
+impl Error for InitError {}
+
+/// Probes for a usable device and driver, creating the CUDA context on success.
+///
+/// The result is computed once and cached, so this is cheap to call before every GPU operation.
+pub fn try_init() -> Result<DeviceInfo, InitError> {
+    *INIT_RESULT.get_or_init(probe)
+}
+
+static INIT_RESULT: OnceLock<Result<DeviceInfo, InitError>> = OnceLock::new();
+
+/// Settings for running several prover processes on one GPU through CUDA MPS, see
+/// [`MpsConfig::environment`]. Fields left as `None` keep the limits set by the MPS control
+/// daemon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MpsConfig {
+    /// Percentage of the SMs of each device that kernels of this process may run on, in 1..=100.
+    pub active_thread_percentage: Option<u32>,
+    /// Device memory this process may allocate on each device, in bytes, rounded down to MiB.
+    pub device_memory_limit: Option<usize>,
+}
+
+impl MpsConfig {
+    /// The environment variables applying this config, to set on each prover process when
+    /// spawning it, e.g. with [`std::process::Command::envs`]. CUDA only reads them when the
+    /// process creates its context, and setting them from a running process races with the
+    /// other threads reading the environment, so they must be set before the process starts.
+    ///
+    /// The crate only resets the device when asked to with [`crate::reset_device`], and only
+    /// synchronizes its own work, so processes sharing a GPU through MPS don't disturb each
+    /// other. Keep each of them within its share of the memory with [`CudaBackend::warm_up`],
+    /// whose pool is returned to the driver beyond the reserved size, and [`trim_memory_pool`].
+    ///
+    /// # Panics
+    ///
+    /// If `active_thread_percentage` is not in 1..=100.
+    pub fn environment(&self) -> Result<Vec<(&'static str, String)>, InitError> {
+        let mut environment = vec![];
+        if let Some(percentage) = self.active_thread_percentage {
+            assert!(
+                (1..=100).contains(&percentage),
+                "active thread percentage must be in 1..=100, got {percentage}"
+            );
+            environment.push(("CUDA_MPS_ACTIVE_THREAD_PERCENTAGE", percentage.to_string()));
+        }
+        if let Some(bytes) = self.device_memory_limit {
+            // The limit is given per device ordinal, e.g. "0=512M,1=512M".
+            let mut device_count = 0;
+            match unsafe { cuda::bindings::get_device_count(&mut device_count) } {
+                0 => {}
+                code => return Err(InitError::Cuda(code)),
+            }
+            let limit = (0..device_count)
+                .map(|device| format!("{device}={}M", bytes >> 20))
+                .collect::<Vec<_>>()
+                .join(",");
+            environment.push(("CUDA_MPS_PINNED_DEVICE_MEM_LIMIT", limit));
+        }
+        Ok(environment)
+    }
+}
+
+/// Returns the memory held by the device's memory pool beyond `bytes_to_keep` to the driver,
+/// e.g. so other processes sharing the GPU can use it between proofs.
+pub fn trim_memory_pool(bytes_to_keep: usize) -> Result<(), InitError> {
+    match unsafe { cuda::bindings::trim_memory_pool(bytes_to_keep) } {
+        0 => Ok(()),
+        code => Err(InitError::Cuda(code)),
+    }
+}
+
+impl CudaBackend {
+    /// Pays the one-off costs of the first GPU operation upfront: creates the context, and loads
+    /// the kernels if the process was started with `CUDA_MODULE_LOADING=EAGER`. Otherwise CUDA
+    /// loads each kernel on its first launch. If `memory_pool_bytes` is given, that much memory
+    /// is reserved in the memory pool of the current device, which columns on that device are
+    /// then allocated from.
+    pub fn warm_up(memory_pool_bytes: Option<usize>) -> Result<DeviceInfo, InitError> {
+        let info = try_init()?;
+        if let Some(bytes) = memory_pool_bytes {
+            match unsafe { cuda::bindings::reserve_memory_pool(bytes) } {
+                0 => {}
+                code => return Err(InitError::Cuda(code)),
+            }
+        }
+        Ok(info)
+    }
+
+    /// Lists the available GPUs, i.e. those `CUDA_VISIBLE_DEVICES` leaves visible, by logical
+    /// index. Empty if CUDA cannot be initialized.
+    pub fn list_devices() -> Vec<Device> {
+        let Ok(info) = try_init() else {
+            return vec![];
+        };
+        let visible = env::var("CUDA_VISIBLE_DEVICES")
+            .ok()
+            .map(|value| visible_devices(&value));
+        (0..info.device_count)
+            .filter_map(|index| {
+                let mut properties = cuda::bindings::DeviceProperties {
+                    name: [0; 256],
+                    total_memory: 0,
+                    free_memory: 0,
+                    major: 0,
+                    minor: 0,
+                    sm_count: 0,
+                    uuid: [0; 16],
+                    pci_bus_id: [0; 32],
+                };
+                let code =
+                    unsafe { cuda::bindings::get_device_properties(index as i32, &mut properties) };
+                if code != 0 {
+                    return None;
+                }
+                let name = unsafe { CStr::from_ptr(properties.name.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned();
+                let pci_bus_id = unsafe { CStr::from_ptr(properties.pci_bus_id.as_ptr()) };
+                let mig = mig_instance(&name);
+                let mut uuid = format_uuid(properties.uuid);
+                if mig.is_some() {
+                    uuid = uuid.replacen("GPU-", "MIG-", 1);
+                }
+                Some(Device {
+                    index,
+                    visible_as: visible
+                        .as_ref()
+                        .and_then(|visible| visible.get(index as usize).cloned()),
+                    uuid,
+                    pci_bus_id: pci_bus_id.to_string_lossy().into_owned(),
+                    name,
+                    total_memory: properties.total_memory,
+                    free_memory: properties.free_memory,
+                    compute_capability: (properties.major as u32, properties.minor as u32),
+                    sm_count: properties.sm_count as u32,
+                    mig,
+                })
+            })
+            .collect()
+    }
+
+    /// The device selected on the current thread, e.g. with [`CudaBackend::with_device`]. `None`
+    /// if CUDA cannot be initialized.
+    pub fn current_device() -> Option<Device> {
+        try_init().ok()?;
+        let index = unsafe { cuda::bindings::get_current_device() };
+        Self::list_devices()
+            .into_iter()
+            .find(|device| device.index as i32 == index)
+    }
+
+    /// Finds the visible device with the physical identity `id`, as found in
+    /// `CUDA_VISIBLE_DEVICES` or reported by nvidia-smi: its UUID or a prefix of it, its PCI
+    /// address, or the entry of `CUDA_VISIBLE_DEVICES` that made it visible.
+    pub fn find_device(id: &str) -> Option<Device> {
+        let id = id.trim();
+        Self::list_devices().into_iter().find(|device| {
+            device.visible_as.as_deref() == Some(id)
+                || device.pci_bus_id.eq_ignore_ascii_case(id)
+                || (id.len() > "GPU-".len() && device.uuid.starts_with(id))
+        })
+    }
+
+    /// Runs `f` with the device of logical index `index`, as in [`CudaBackend::list_devices`],
+    /// selected on the current thread, then selects the previous device again.
+    ///
+    /// The scratch buffers of the current thread aren't kept per device, so they are emptied when
+    /// switching devices. The arena of the thread stays on the device it was reserved on, and
+    /// columns allocated on other devices bypass it, see [`reserve_arena`]. The twiddle cache
+    /// keeps the trees of each device apart. Vectors must only be used on the device they were
+    /// allocated on.
+    pub fn with_device<T>(index: u32, f: impl FnOnce() -> T) -> Result<T, InitError> {
+        try_init()?;
+        let previous = unsafe { cuda::bindings::get_current_device() };
+        if previous == index as i32 {
+            return Ok(f());
+        }
+        match unsafe { cuda::bindings::set_current_device(index as i32) } {
+            0 => {}
+            code => return Err(InitError::Cuda(code)),
+        }
+        clear_scratch_buffers();
+        let _restore = RestoreDevice(previous);
+        Ok(f())
+    }
+}
+
+/// Selects the device again when dropped, even if [`CudaBackend::with_device`] unwinds.
+struct RestoreDevice(i32);
+
+impl Drop for RestoreDevice {
+    fn drop(&mut self) {
+        clear_scratch_buffers();
+        unsafe { cuda::bindings::set_current_device(self.0) };
+    }
+}
+
+/// The entries of a `CUDA_VISIBLE_DEVICES` value, in the order CUDA numbers the devices. CUDA
+/// ignores the entries after an empty one.
+fn visible_devices(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .take_while(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn format_uuid(bytes: [u8; 16]) -> String {
+    let hex = |range: std::ops::Range<usize>| {
+        bytes[range]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+    };
+    format!(
+        "GPU-{}-{}-{}-{}-{}",
+        hex(0..4),
+        hex(4..6),
+        hex(6..8),
+        hex(8..10),
+        hex(10..16)
+    )
+}
+
+/// Where column allocations live, see [`set_memory_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MemoryMode {
+    /// Plain device memory.
+    #[default]
+    Device,
+    /// Managed memory, which the driver can page between host and device. This lowers the
+    /// device memory needed by large traces at some performance cost, and is the recommended
+    /// mode on boards with little memory shared by the CPU and GPU, such as Jetson devices.
+    Managed,
+}
+
+/// Selects how columns allocated from now on are backed. Existing columns are not moved.
+pub fn set_memory_mode(mode: MemoryMode) {
+    unsafe { cuda::bindings::set_managed_allocations(mode == MemoryMode::Managed) };
+}
+
+/// Launch parameters of the hot kernels, see [`set_kernel_tuning`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KernelTuning {
+    /// Threads per block of the FFT layers.
+    pub fft_block_dim: u32,
+    /// Butterflies computed by each thread of the FFT layers, one of 1, 2 or 4.
+    pub fft_butterflies_per_thread: u32,
+    /// Threads per block hashing Merkle layers.
+    pub merkle_block_dim: u32,
+    /// Threads per block of the FRI folds.
+    pub fold_block_dim: u32,
+}
+
+impl KernelTuning {
+    /// The variant picked for devices of compute capability `major.minor`.
+    pub fn for_architecture(major: u32, minor: u32) -> Self {
+        unsafe { cuda::bindings::kernel_tuning_for_architecture(major as i32, minor as i32) }.into()
+    }
+
+    /// The variant picked for `device`: that of its architecture, with smaller blocks on devices
+    /// with few SMs, such as the small MIG slices.
+    pub fn for_device(device: &Device) -> Self {
+        let (major, minor) = device.compute_capability;
+        unsafe {
+            cuda::bindings::kernel_tuning_for_device(
+                major as i32,
+                minor as i32,
+                device.sm_count as i32,
+            )
+        }
+        .into()
+    }
+}
+
+impl From<cuda::bindings::KernelTuning> for KernelTuning {
+    fn from(value: cuda::bindings::KernelTuning) -> Self {
+        Self {
+            fft_block_dim: value.fft_block_dim as u32,
+            fft_butterflies_per_thread: value.fft_butterflies_per_thread as u32,
+            merkle_block_dim: value.merkle_block_dim as u32,
+            fold_block_dim: value.fold_block_dim as u32,
+        }
+    }
+}
+
+/// The launch parameters of the hot kernels on the current device. Unless set with
+/// [`set_kernel_tuning`], they are picked on the first use of each device as
+/// [`KernelTuning::for_device`] does, as the best block sizes and unroll factors differ between
+/// e.g. sm_80 and sm_90, and between a whole GPU and a MIG slice.
+pub fn kernel_tuning() -> KernelTuning {
+    unsafe { cuda::bindings::get_kernel_tuning() }.into()
+}
+
+/// Overrides the launch parameters of the hot kernels on the current device, e.g. to try variants
+/// on a new device. Kernels launched from now on on that device use them; results don't depend on
+/// them.
+///
+/// # Panics
+///
+/// If a block size is not a multiple of 32 in 32..=1024, or the butterflies per thread are not
+/// 1, 2 or 4.
+pub fn set_kernel_tuning(tuning: KernelTuning) {
+    for block_dim in [
+        tuning.fft_block_dim,
+        tuning.merkle_block_dim,
+        tuning.fold_block_dim,
+    ] {
+        assert!(
+            block_dim % 32 == 0 && (32..=1024).contains(&block_dim),
+            "block size must be a multiple of 32 in 32..=1024, got {block_dim}"
+        );
+    }
+    assert!(
+        [1, 2, 4].contains(&tuning.fft_butterflies_per_thread),
+        "butterflies per thread must be 1, 2 or 4, got {}",
+        tuning.fft_butterflies_per_thread
+    );
+    unsafe {
+        cuda::bindings::set_kernel_tuning(cuda::bindings::KernelTuning {
+            fft_block_dim: tuning.fft_block_dim as i32,
+            fft_butterflies_per_thread: tuning.fft_butterflies_per_thread as i32,
+            merkle_block_dim: tuning.merkle_block_dim as i32,
+            fold_block_dim: tuning.fold_block_dim as i32,
+        })
+    };
+}
+
+/// Reserves `bytes` of memory on the current device that the columns allocated from now on by
+/// the current thread are carved from, replacing any previous arena of the thread. Columns
+/// allocated while another device is current, e.g. in [`CudaBackend::with_device`], bypass it.
+///
+/// Freeing a column from the arena does nothing: all of them are reclaimed at once by
+/// [`reset_arena`], typically at the end of each proof. This saves a `cudaFree` per temporary
+/// and keeps successive proofs from fragmenting device memory. Once the arena is full, columns
+/// are allocated as usual.
+pub fn reserve_arena(bytes: usize) -> Result<(), InitError> {
+    match unsafe { cuda::bindings::reserve_arena(bytes) } {
+        0 => Ok(()),
+        code => Err(InitError::Cuda(code)),
+    }
+}
+
+/// Bytes of the current thread's arena in use since it was reserved or last reset.
+pub fn arena_used() -> usize {
+    unsafe { cuda::bindings::arena_used() }
+}
+
+/// Makes the whole arena of the current thread available again, keeping it reserved.
+///
+/// # Safety
+///
+/// Columns allocated by this thread since the arena was reserved or last reset must not be used
+/// afterwards, as their memory will be handed out again.
+pub unsafe fn reset_arena() {
+    cuda::bindings::reset_arena();
+}
+
+/// Returns the arena of the current thread to the driver and goes back to allocating columns
+/// as usual.
+///
+/// # Safety
+///
+/// Same as [`reset_arena`].
+pub unsafe fn release_arena() {
+    cuda::bindings::release_arena();
+}
+
+/// Frees the scratch buffers kernels of the current thread keep between calls, e.g. under memory
+/// pressure. They are allocated again on the next use.
+///
+/// Temporaries of predictable size, such as those of FRI sums and evaluations at a point, are
+/// requested by name and log size from a registry instead of being allocated on every call.
+pub fn clear_scratch_buffers() {
+    unsafe { cuda::bindings::clear_scratch_buffers() };
+}
+
+/// Bytes of device memory held by the scratch buffers of the current thread.
+pub fn scratch_buffers_size() -> usize {
+    unsafe { cuda::bindings::scratch_buffers_size() }
+}
+
+/// Whether a device can be used. Note the CUDA driver library must still be installed for this
+/// crate to load at all.
+pub fn cuda_available() -> bool {
+    try_init().is_ok()
+}
+
+#[cfg(not(feature = "cuda"))]
+fn probe() -> Result<DeviceInfo, InitError> {
+    Err(InitError::NoDevice)
+}
+
+#[cfg(feature = "cuda")]
+fn probe() -> Result<DeviceInfo, InitError> {
+    let mut device_count = 0;
+    let mut driver_version = 0;
+    let mut runtime_version = 0;
+    let code = unsafe {
+        cuda::bindings::probe_cuda_device(
+            &mut device_count,
+            &mut driver_version,
+            &mut runtime_version,
+        )
+    };
+
+    let info = DeviceInfo {
+        device_count: device_count as u32,
+        driver_version: driver_version as u32,
+        runtime_version: runtime_version as u32,
+    };
+    match code {
+        0 if info.device_count > 0 => Ok(info),
+        0 | CUDA_ERROR_NO_DEVICE => Err(InitError::NoDevice),
+        CUDA_ERROR_INSUFFICIENT_DRIVER => Err(InitError::InsufficientDriver {
+            driver_version: info.driver_version,
+            runtime_version: info.runtime_version,
+        }),
+        code => Err(InitError::Cuda(code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        circle::SECURE_FIELD_CIRCLE_GEN,
+        fields::m31::BaseField,
+        poly::circle::{CanonicCoset, CircleEvaluation, CirclePoly, PolyOps},
+    };
+
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    #[test]
+    fn test_list_devices() {
+        require_gpu!();
+        let devices = CudaBackend::list_devices();
+
+        assert_eq!(
+            devices.len(),
+            crate::try_init().unwrap().device_count as usize
+        );
+        for device in devices {
+            assert!(!device.name.is_empty());
+            assert!(device.free_memory <= device.total_memory);
+            assert!(device.sm_count > 0);
+            assert!(device.uuid.starts_with("GPU-") || device.uuid.starts_with("MIG-"));
+            assert!(!device.pci_bus_id.is_empty());
+            assert_eq!(device.uuid.starts_with("MIG-"), device.mig.is_some());
+        }
+    }
+
+    #[test]
+    fn test_mig_instance() {
+        let instance = super::mig_instance("NVIDIA A100-SXM4-40GB MIG 2g.10gb").unwrap();
+        let compute_instance = super::mig_instance("NVIDIA H100 80GB HBM3 MIG 1c.3g.40gb").unwrap();
+
+        assert_eq!(instance.profile, "2g.10gb");
+        assert_eq!(instance.compute_slices, 2);
+        assert_eq!(compute_instance.compute_slices, 1);
+        assert_eq!(super::mig_instance("NVIDIA A100-SXM4-40GB"), None);
+    }
+
+    #[test]
+    fn test_visible_devices() {
+        assert_eq!(
+            super::visible_devices("2, GPU-8f4c1a2e,MIG-1"),
+            ["2", "GPU-8f4c1a2e", "MIG-1"]
+        );
+        assert_eq!(super::visible_devices("1,,3"), ["1"]);
+        assert!(super::visible_devices("").is_empty());
+    }
+
+    #[test]
+    fn test_format_uuid() {
+        let bytes = std::array::from_fn(|i| 0x10 * i as u8 + 1);
+
+        assert_eq!(
+            super::format_uuid(bytes),
+            "GPU-01112131-4151-6171-8191-a1b1c1d1e1f1"
+        );
+    }
+
+    #[test]
+    fn test_with_device() {
+        require_gpu!();
+        let device = CudaBackend::list_devices().remove(0);
+        let values = (0..1024).map(BaseField::from).collect::<Vec<_>>();
+
+        let result = CudaBackend::with_device(device.index, || {
+            BaseFieldVec::from_vec(values.clone()).to_vec()
+        });
+
+        assert_eq!(result, Ok(values));
+        assert_eq!(CudaBackend::find_device(&device.uuid), Some(device.clone()));
+        assert_eq!(CudaBackend::find_device(&device.pci_bus_id), Some(device));
+        let device_count = crate::try_init().unwrap().device_count;
+        assert!(CudaBackend::with_device(device_count, || ()).is_err());
+    }
+
+    #[test]
+    fn test_arena() {
+        require_gpu!();
+        super::reserve_arena(1 << 20).unwrap();
+
+        let values = (0..1024).map(BaseField::from).collect::<Vec<_>>();
+        let column = BaseFieldVec::from_vec(values.clone());
+        assert_eq!(super::arena_used(), 4096);
+        drop(column);
+        assert_eq!(super::arena_used(), 4096);
+
+        unsafe { super::reset_arena() };
+        assert_eq!(super::arena_used(), 0);
+        let column = BaseFieldVec::from_vec(values.clone());
+        // Larger than what is left of the arena.
+        let large_column = BaseFieldVec::new_zeroes(1 << 20);
+        assert_eq!(column.to_vec(), values);
+        assert_eq!(large_column.to_vec(), vec![BaseField::from(0); 1 << 20]);
+        assert_eq!(super::arena_used(), 4096);
+
+        drop(column);
+        drop(large_column);
+        unsafe { super::release_arena() };
+        assert_eq!(super::arena_used(), 0);
+    }
+
+    #[test]
+    fn test_arena_column_dropped_on_another_thread() {
+        require_gpu!();
+        std::thread::spawn(|| {
+            super::reserve_arena(1 << 20).unwrap();
+            let values = (0..1024).map(BaseField::from).collect::<Vec<_>>();
+            let column = BaseFieldVec::from_vec(values.clone());
+
+            std::thread::spawn(move || drop(column)).join().unwrap();
+            let column = BaseFieldVec::from_vec(values.clone());
+
+            assert_eq!(super::arena_used(), 8192);
+            assert_eq!(column.to_vec(), values);
+            drop(column);
+            unsafe { super::release_arena() };
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_scratch_buffers() {
+        require_gpu!();
+        let coeffs = (0..1 << 12).map(BaseField::from).collect::<Vec<_>>();
+        let cpu_poly = CirclePoly::<CpuBackend>::new(coeffs.clone());
+        let poly = CirclePoly::<CudaBackend>::new(BaseFieldVec::from_vec(coeffs));
+        let point = SECURE_FIELD_CIRCLE_GEN.mul(7);
+        super::clear_scratch_buffers();
+
+        assert_eq!(poly.eval_at_point(point), cpu_poly.eval_at_point(point));
+        let size = super::scratch_buffers_size();
+        assert!(size > 0);
+        // The same buffers are reused by later calls of the same size.
+        assert_eq!(poly.eval_at_point(point), cpu_poly.eval_at_point(point));
+        assert_eq!(super::scratch_buffers_size(), size);
+
+        super::clear_scratch_buffers();
+        assert_eq!(super::scratch_buffers_size(), 0);
+    }
+
+    #[test]
+    fn test_mps_environment() {
+        let config = super::MpsConfig {
+            active_thread_percentage: Some(50),
+            device_memory_limit: None,
+        };
+
+        assert_eq!(
+            config.environment().unwrap(),
+            vec![("CUDA_MPS_ACTIVE_THREAD_PERCENTAGE", "50".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_trim_memory_pool() {
+        require_gpu!();
+        CudaBackend::warm_up(Some(1 << 20)).unwrap();
+
+        super::trim_memory_pool(0).unwrap();
+        let column = BaseFieldVec::from_vec((0..1024).map(BaseField::from).collect());
+        assert_eq!(
+            column.to_vec(),
+            (0..1024).map(BaseField::from).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_kernel_tuning_variants() {
+        require_gpu!();
+        let log_size = 12;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let values = (0..1 << log_size).map(BaseField::from).collect::<Vec<_>>();
+        let cpu_poly = CpuBackend::interpolate(
+            CircleEvaluation::new(domain, values.clone()),
+            &CpuBackend::precompute_twiddles(domain.half_coset),
+        );
+        let twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+        let selected = super::kernel_tuning();
+
+        for (major, minor) in [(7, 5), (8, 0), (8, 9), (9, 0)] {
+            super::set_kernel_tuning(super::KernelTuning::for_architecture(major, minor));
+            let poly = CudaBackend::interpolate(
+                CircleEvaluation::new(domain, BaseFieldVec::from_vec(values.clone())),
+                &twiddles,
+            );
+            assert_eq!(poly.coeffs.to_vec(), cpu_poly.coeffs);
+            let evaluation = CudaBackend::evaluate(&poly, domain, &twiddles);
+            assert_eq!(evaluation.values.to_vec(), values);
+        }
+        super::set_kernel_tuning(selected);
+        assert_eq!(super::kernel_tuning(), selected);
+        // As a 1g slice of an A100.
+        let mut slice = CudaBackend::current_device().unwrap();
+        slice.compute_capability = (8, 0);
+        slice.sm_count = 14;
+        assert!(
+            super::KernelTuning::for_device(&slice).merkle_block_dim
+                < super::KernelTuning::for_architecture(8, 0).merkle_block_dim
+        );
+    }
+
+    #[test]
+    fn test_warm_up() {
+        require_gpu!();
+        CudaBackend::warm_up(Some(1 << 20)).unwrap();
+
+        let column = BaseFieldVec::from_vec((0..1024).map(BaseField::from).collect());
+        assert_eq!(
+            column.to_vec(),
+            (0..1024).map(BaseField::from).collect::<Vec<_>>()
+        );
+    }
+}
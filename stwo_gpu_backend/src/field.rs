@@ -6,11 +6,15 @@ use stwo_prover::core::{
 use crate::{backend::CudaBackend, cuda};
 
 impl FieldOps<BaseField> for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
     fn batch_inverse(column: &Self::Column, dst: &mut Self::Column) {
         unsafe {
             cuda::bindings::batch_inverse_base_field(
-                column.device_ptr,
-                dst.device_ptr,
+                column.device_ptr(),
+                dst.device_ptr(),
                 column.len(),
             );
         }
@@ -18,11 +22,15 @@ impl FieldOps<BaseField> for CudaBackend {
 }
 
 impl FieldOps<SecureField> for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+    )]
     fn batch_inverse(column: &Self::Column, dst: &mut Self::Column) {
         unsafe {
             cuda::bindings::batch_inverse_secure_field(
-                column.device_ptr,
-                dst.device_ptr,
+                column.device_ptr(),
+                dst.device_ptr(),
                 column.len(),
             );
         }
@@ -40,6 +48,7 @@ mod tests {
 
     #[test]
     fn test_batch_inverse_basefield() {
+        require_gpu!();
         let size: usize = 1 << 25;
         let from = (1..(size + 1) as u32)
             .map(BaseField::from)
@@ -57,6 +66,7 @@ mod tests {
 
     #[test]
     fn test_batch_inverse_secure_field() {
+        require_gpu!();
         let size: usize = 1 << 25;
 
         let from_raw = (1..(size + 1) as u32).collect::<Vec<u32>>();
@@ -76,4 +86,41 @@ mod tests {
 
         assert_eq!(dst_device.to_cpu(), dst_expected_cpu);
     }
+
+    #[test]
+    fn test_batch_inverse_small_sizes() {
+        require_gpu!();
+        // Up to twice the number of values inverted by a block.
+        for log_size in 1..=11 {
+            let size: usize = 1 << log_size;
+            let from = (1..(size + 1) as u32)
+                .map(BaseField::from)
+                .collect::<Vec<_>>();
+            let secure_from = from
+                .iter()
+                .map(|&value| SecureField::from_m31_array([value, value + value, value, value]))
+                .collect::<Vec<_>>();
+            let mut expected = from.clone();
+            let mut secure_expected = secure_from.clone();
+            CpuBackend::batch_inverse(&from, &mut expected);
+            CpuBackend::batch_inverse(&secure_from, &mut secure_expected);
+
+            let from_device = cuda::BaseFieldVec::from_vec(from);
+            let secure_from_device = cuda::SecureFieldVec::from_vec(secure_from);
+            let mut dst_device = cuda::BaseFieldVec::new_uninitialized(size);
+            let mut secure_dst_device = cuda::SecureFieldVec::new_uninitialized(size);
+            <CudaBackend as FieldOps<BaseField>>::batch_inverse(&from_device, &mut dst_device);
+            <CudaBackend as FieldOps<SecureField>>::batch_inverse(
+                &secure_from_device,
+                &mut secure_dst_device,
+            );
+
+            assert_eq!(dst_device.to_cpu(), expected, "log_size = {log_size}");
+            assert_eq!(
+                secure_dst_device.to_cpu(),
+                secure_expected,
+                "log_size = {log_size}"
+            );
+        }
+    }
 }
@@ -1,30 +1,759 @@
+use std::ops::Range;
+
 use stwo_prover::core::{
-    fields::qm31::SecureField,
-    fri::FriOps,
-    poly::{circle::SecureEvaluation, line::LineEvaluation, twiddles::TwiddleTree},
+    backend::Column,
+    fields::{qm31::SecureField, secure_column::SecureColumn},
+    fri::{FriOps, FriProver},
+    poly::{
+        circle::SecureEvaluation,
+        line::{LineDomain, LineEvaluation},
+        twiddles::TwiddleTree,
+    },
+    vcs::{blake2_merkle::Blake2sMerkleHasher, ops::MerkleOps, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend, cuda, line_twiddles::LineTwiddleLayers, memory::memory_phase,
+    watchdog::watch,
 };
 
-use crate::backend::CudaBackend;
+/// FRI prover running every layer on the device.
+///
+/// Folds, decompositions and layer commitments never leave the GPU; only the last layer
+/// (to interpolate the last layer polynomial) and the values and hashes at the queried
+/// positions are copied back to the host.
+pub type CudaFriProver = FriProver<CudaBackend, Blake2sMerkleHasher>;
+
+/// Largest number of halvings [`CudaBackend::fold_line_by`] can do in one launch. FRI steps of
+/// [`CudaFriProver`] always halve the evaluation once.
+pub const MAX_LOG_FOLD_FACTOR: u32 = 3;
 
 impl FriOps for CudaBackend {
     fn fold_line(
-        _eval: &LineEvaluation<Self>,
-        _alpha: SecureField,
-        _twiddles: &TwiddleTree<Self>,
+        eval: &LineEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &TwiddleTree<Self>,
     ) -> LineEvaluation<Self> {
-        todo!()
+        Self::fold_line_with(eval, alpha, twiddles)
     }
 
     fn fold_circle_into_line(
-        _dst: &mut LineEvaluation<Self>,
-        _src: &SecureEvaluation<Self>,
-        _alpha: SecureField,
-        _twiddles: &TwiddleTree<Self>,
+        dst: &mut LineEvaluation<Self>,
+        src: &SecureEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &TwiddleTree<Self>,
+    ) {
+        Self::fold_circle_into_line_with(dst, src, alpha, twiddles)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = eval.len()))
+    )]
+    fn decompose(eval: &SecureEvaluation<Self>) -> (SecureEvaluation<Self>, SecureField) {
+        let _watch = watch("decompose");
+        let _phase = memory_phase("fri");
+        // g = f - lambda * v_n, with lambda = (sum of the first half - sum of the second half) /
+        // domain_size, reduced and applied by a single cooperative launch where supported.
+        let size = eval.len();
+        let g_values = cuda::new_uninitialized_secure_column(size);
+        let mut lambda = SecureField::from_u32_unchecked(0, 0, 0, 0);
+        let code = unsafe {
+            cuda::bindings::decompose(
+                cuda::secure_column_device_ptrs(&eval.values).as_ptr(),
+                cuda::secure_column_device_ptrs(&g_values).as_ptr(),
+                size as u32,
+                &mut lambda,
+            )
+        };
+        assert_eq!(code, 0, "decompose launch failed with CUDA error {code}");
+
+        let g = SecureEvaluation {
+            domain: eval.domain,
+            values: g_values,
+        };
+        (g, lambda)
+    }
+}
+
+impl CudaBackend {
+    /// Sum of all the values of `column`, e.g. the claimed sum of a logup column.
+    pub fn sum_secure_column(column: &SecureColumn<Self>) -> SecureField {
+        Self::sum_secure_column_range(column, 0..column.len())
+    }
+
+    /// Sum of the values of `column` in `range`, reducing the four coordinates in a single pass.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = range.len()))
+    )]
+    pub fn sum_secure_column_range(
+        column: &SecureColumn<Self>,
+        range: Range<usize>,
+    ) -> SecureField {
+        assert!(range.start <= range.end && range.end <= column.len());
+        let device_ptrs =
+            cuda::secure_column_device_ptrs(column).map(|ptr| unsafe { ptr.add(range.start) });
+        let mut sum = SecureField::from_u32_unchecked(0, 0, 0, 0);
+        let code = unsafe {
+            cuda::bindings::sum_secure_column(device_ptrs.as_ptr(), range.len() as u32, &mut sum)
+        };
+        assert_eq!(code, 0, "sum launch failed with CUDA error {code}");
+        sum
+    }
+
+    /// Subtracts `lambda` from the first half of `values` and adds it to the second half, i.e.
+    /// returns `values - lambda * v_n`, where `v_n` is the vanishing-like polynomial that is 1 on
+    /// the first half of a bit reversed circle domain and -1 on the second.
+    ///
+    /// This is the correction applied by [`FriOps::decompose`], which computes `lambda` itself.
+    /// `values` must have a positive even length.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = values.len()))
+    )]
+    pub fn compute_g_values(
+        values: &SecureColumn<Self>,
+        lambda: SecureField,
+    ) -> SecureColumn<Self> {
+        let size = values.len();
+        assert!(size >= 2 && size % 2 == 0, "odd number of values");
+        let g_values = cuda::new_uninitialized_secure_column(size);
+        unsafe {
+            cuda::bindings::compute_g_values(
+                cuda::secure_column_device_ptrs(values).as_ptr(),
+                cuda::secure_column_device_ptrs(&g_values).as_ptr(),
+                size as u32,
+                lambda,
+            );
+        }
+        g_values
+    }
+
+    /// [`FriOps::fold_line`] with the twiddles of any line domains, e.g. [`crate::LineTwiddles`] of
+    /// domains that don't come from a circle commitment.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = eval.len()))
+    )]
+    pub fn fold_line_with(
+        eval: &LineEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &impl LineTwiddleLayers,
+    ) -> LineEvaluation<Self> {
+        let _watch = watch("fold_line");
+        let _phase = memory_phase("fri");
+        let n = eval.len();
+        assert!(n >= 2, "Evaluation too small");
+        let domain = eval.domain();
+
+        let folded_values = cuda::new_uninitialized_secure_column(n >> 1);
+        unsafe {
+            cuda::bindings::fold_line(
+                cuda::secure_column_device_ptrs(&eval.values).as_ptr(),
+                cuda::secure_column_device_ptrs(&folded_values).as_ptr(),
+                n as u32,
+                twiddles.itwiddles().device_ptr(),
+                twiddles.layer_offset(domain),
+                alpha,
+            );
+        }
+        LineEvaluation::new(domain.double(), folded_values)
+    }
+
+    /// Folds `eval` `log_fold_factor` times in a single launch, as many [`FriOps::fold_line`]
+    /// with `alpha`, `alpha^2`, `alpha^4`, ... would, without writing the intermediate layers.
+    ///
+    /// This is a standalone helper: [`CudaFriProver`] doesn't call it and still folds by 2 and
+    /// commits to every layer, since stwo's FRI protocol draws each alpha after committing the
+    /// previous layer. `log_fold_factor` is between 1 and [`MAX_LOG_FOLD_FACTOR`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(size = eval.len(), log_fold_factor)
+        )
+    )]
+    pub fn fold_line_by(
+        eval: &LineEvaluation<Self>,
+        alpha: SecureField,
+        log_fold_factor: u32,
+        twiddles: &impl LineTwiddleLayers,
+    ) -> LineEvaluation<Self> {
+        let _watch = watch("fold_line_by");
+        let _phase = memory_phase("fri");
+        let n = eval.len();
+        assert!(
+            (1..=MAX_LOG_FOLD_FACTOR).contains(&log_fold_factor),
+            "Unsupported fold factor 2^{log_fold_factor}"
+        );
+        assert!(n >> log_fold_factor >= 1, "Evaluation too small");
+
+        let mut domain = eval.domain();
+        let twiddle_offsets = (0..log_fold_factor)
+            .map(|_| {
+                let offset = twiddles.layer_offset(domain);
+                domain = domain.double();
+                offset
+            })
+            .collect::<Vec<_>>();
+        let folded_values = cuda::new_uninitialized_secure_column(n >> log_fold_factor);
+        unsafe {
+            cuda::bindings::fold_line_by(
+                cuda::secure_column_device_ptrs(&eval.values).as_ptr(),
+                cuda::secure_column_device_ptrs(&folded_values).as_ptr(),
+                n as u32,
+                log_fold_factor,
+                twiddles.itwiddles().device_ptr(),
+                twiddle_offsets.as_ptr(),
+                alpha,
+            );
+        }
+        LineEvaluation::new(domain, folded_values)
+    }
+
+    /// Performs [`FriOps::fold_line`] and commits to the folded evaluation as
+    /// [`MerkleProver::commit`] of its coordinates would, hashing the leaves right after the
+    /// fold, while the folded values are still in L2.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = eval.len()))
+    )]
+    pub fn fold_line_and_commit(
+        eval: &LineEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &impl LineTwiddleLayers,
+    ) -> (
+        LineEvaluation<Self>,
+        MerkleProver<Self, Blake2sMerkleHasher>,
     ) {
-        todo!()
+        let _watch = watch("fold_line_and_commit");
+        let _phase = memory_phase("fri");
+        let n = eval.len();
+        assert!(n >= 2, "Evaluation too small");
+        let domain = eval.domain();
+        let log_size = (n >> 1).ilog2();
+
+        let folded_values = cuda::new_uninitialized_secure_column(n >> 1);
+        let leaves = cuda::Blake2sHashVec::new_uninitialized(n >> 1);
+        unsafe {
+            cuda::bindings::fold_line_and_hash(
+                cuda::secure_column_device_ptrs(&eval.values).as_ptr(),
+                cuda::secure_column_device_ptrs(&folded_values).as_ptr(),
+                n as u32,
+                twiddles.itwiddles().device_ptr(),
+                twiddles.layer_offset(domain),
+                alpha,
+                leaves.device_ptr(),
+            );
+        }
+
+        let mut layers = vec![leaves];
+        for log_size in (0..log_size).rev() {
+            let layer = <Self as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                log_size,
+                layers.last(),
+                &[],
+            );
+            layers.push(layer);
+        }
+        layers.reverse();
+        (
+            LineEvaluation::new(domain.double(), folded_values),
+            MerkleProver { layers },
+        )
+    }
+
+    /// [`FriOps::fold_circle_into_line`] with the twiddles of any line domains, see
+    /// [`CudaBackend::fold_line_with`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = src.len()))
+    )]
+    pub fn fold_circle_into_line_with(
+        dst: &mut LineEvaluation<Self>,
+        src: &SecureEvaluation<Self>,
+        alpha: SecureField,
+        twiddles: &impl LineTwiddleLayers,
+    ) {
+        let _watch = watch("fold_circle_into_line");
+        let _phase = memory_phase("fri");
+        assert_eq!(src.len() >> 1, dst.len());
+
+        unsafe {
+            cuda::bindings::fold_circle_into_line(
+                cuda::secure_column_device_ptrs(&dst.values).as_ptr(),
+                cuda::secure_column_device_ptrs(&src.values).as_ptr(),
+                dst.len() as u32,
+                twiddles.itwiddles().device_ptr(),
+                // Circle twiddles are derived from the line twiddles of the half coset.
+                twiddles.layer_offset(LineDomain::new(src.domain.half_coset)),
+                alpha,
+            );
+        }
+    }
+
+    /// Performs [`FriOps::fold_line`] of each of `evals` with the corresponding alpha in a
+    /// single launch.
+    ///
+    /// All the evaluations must be over the same domain, as happens for the FRI instances of
+    /// components of the same size. Batching them keeps the GPU busy for medium sizes, where a
+    /// single fold does not have enough work to fill the device.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_evals = evals.len()))
+    )]
+    pub fn fold_lines(
+        evals: &[&LineEvaluation<Self>],
+        alphas: &[SecureField],
+        twiddles: &impl LineTwiddleLayers,
+    ) -> Vec<LineEvaluation<Self>> {
+        assert_eq!(evals.len(), alphas.len());
+        let Some(first) = evals.first() else {
+            return vec![];
+        };
+        let domain = first.domain();
+        assert!(evals.iter().all(|eval| eval.domain() == domain));
+        let n = domain.size();
+        assert!(n >= 2, "Evaluation too small");
+
+        let folded = evals
+            .iter()
+            .map(|_| {
+                LineEvaluation::new(
+                    domain.double(),
+                    cuda::new_uninitialized_secure_column(n >> 1),
+                )
+            })
+            .collect::<Vec<_>>();
+        let eval_ptrs = evals
+            .iter()
+            .flat_map(|eval| cuda::secure_column_device_ptrs(&eval.values))
+            .collect::<Vec<_>>();
+        let folded_ptrs = folded
+            .iter()
+            .flat_map(|eval| cuda::secure_column_device_ptrs(&eval.values))
+            .collect::<Vec<_>>();
+        let twiddle_offset = twiddles.layer_offset(domain);
+        unsafe {
+            cuda::bindings::fold_lines(
+                eval_ptrs.as_ptr(),
+                folded_ptrs.as_ptr(),
+                evals.len() as u32,
+                n as u32,
+                twiddles.itwiddles().device_ptr(),
+                twiddle_offset,
+                alphas.as_ptr(),
+            );
+        }
+        folded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        channel::{Blake2sChannel, Channel},
+        circle::Coset,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+        fri::{FriConfig, FriOps, FriProof, FriProver},
+        poly::{
+            circle::{CanonicCoset, CirclePoly, PolyOps, SecureEvaluation},
+            line::{LineDomain, LineEvaluation},
+        },
+        vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{CudaFriProver, MAX_LOG_FOLD_FACTOR};
+    use crate::{backend::CudaBackend, cuda};
+
+    fn cpu_secure_column(size: usize, offset: u32) -> SecureColumn<CpuBackend> {
+        SecureColumn {
+            columns: std::array::from_fn(|i| {
+                (0..size as u32)
+                    .map(|j| BaseField::from(offset + 4 * j + i as u32))
+                    .collect()
+            }),
+        }
+    }
+
+    fn to_device(column: &SecureColumn<CpuBackend>) -> SecureColumn<CudaBackend> {
+        SecureColumn {
+            columns: column.columns.clone().map(cuda::BaseFieldVec::from_vec),
+        }
+    }
+
+    fn to_host(column: &SecureColumn<CudaBackend>) -> Vec<Vec<BaseField>> {
+        column
+            .columns
+            .iter()
+            .map(|column| column.to_cpu())
+            .collect()
+    }
+
+    #[test]
+    fn test_fold_line() {
+        require_gpu!();
+        let root_log_size = 12;
+        let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let root_coset = Coset::half_odds(root_log_size);
+        let cpu_twiddles = CpuBackend::precompute_twiddles(root_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(root_coset);
+
+        for n_doublings in [0, 3] {
+            let domain = LineDomain::new(root_coset.repeated_double(n_doublings));
+            let values = cpu_secure_column(domain.size(), 1);
+            let cpu_eval = LineEvaluation::<CpuBackend>::new(domain, values.clone());
+            let gpu_eval = LineEvaluation::<CudaBackend>::new(domain, to_device(&values));
+
+            let expected_result = CpuBackend::fold_line(&cpu_eval, alpha, &cpu_twiddles);
+            let result = CudaBackend::fold_line(&gpu_eval, alpha, &gpu_twiddles);
+
+            assert_eq!(
+                to_host(&result.values),
+                expected_result.values.columns.to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_fold_circle_into_line() {
+        require_gpu!();
+        let log_size = 12;
+        let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let line_domain = LineDomain::new(domain.half_coset);
+        let cpu_twiddles = CpuBackend::precompute_twiddles(domain.half_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+        let src_values = cpu_secure_column(1 << log_size, 1);
+        let dst_values = cpu_secure_column(1 << (log_size - 1), 7);
+
+        let cpu_src = SecureEvaluation::<CpuBackend> {
+            domain,
+            values: src_values.clone(),
+        };
+        let mut cpu_dst = LineEvaluation::<CpuBackend>::new(line_domain, dst_values.clone());
+        CpuBackend::fold_circle_into_line(&mut cpu_dst, &cpu_src, alpha, &cpu_twiddles);
+
+        let gpu_src = SecureEvaluation::<CudaBackend> {
+            domain,
+            values: to_device(&src_values),
+        };
+        let mut gpu_dst = LineEvaluation::<CudaBackend>::new(line_domain, to_device(&dst_values));
+        CudaBackend::fold_circle_into_line(&mut gpu_dst, &gpu_src, alpha, &gpu_twiddles);
+
+        assert_eq!(to_host(&gpu_dst.values), cpu_dst.values.columns.to_vec());
     }
 
-    fn decompose(_eval: &SecureEvaluation<Self>) -> (SecureEvaluation<Self>, SecureField) {
-        todo!()
+    #[test]
+    fn test_decompose() {
+        require_gpu!();
+        let log_size = 12;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let values = cpu_secure_column(1 << log_size, 1);
+
+        let (expected_g, expected_lambda) = CpuBackend::decompose(&SecureEvaluation {
+            domain,
+            values: values.clone(),
+        });
+        let (g, lambda) = CudaBackend::decompose(&SecureEvaluation {
+            domain,
+            values: to_device(&values),
+        });
+
+        assert_eq!(lambda, expected_lambda);
+        assert_eq!(to_host(&g.values), expected_g.values.columns.to_vec());
+    }
+
+    #[test]
+    fn test_decompose_more_values_than_resident_threads() {
+        require_gpu!();
+        // Each thread of the single cooperative launch handles several values.
+        let log_size = 20;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let values = cpu_secure_column(1 << log_size, 2);
+
+        let (expected_g, expected_lambda) = CpuBackend::decompose(&SecureEvaluation {
+            domain,
+            values: values.clone(),
+        });
+        let (g, lambda) = CudaBackend::decompose(&SecureEvaluation {
+            domain,
+            values: to_device(&values),
+        });
+
+        assert_eq!(lambda, expected_lambda);
+        assert_eq!(to_host(&g.values), expected_g.values.columns.to_vec());
+    }
+
+    #[test]
+    fn test_sum_secure_column() {
+        require_gpu!();
+        let values = cpu_secure_column((1 << 16) + 5, 3);
+        let expected = values.to_vec().into_iter().reduce(|a, b| a + b).unwrap();
+
+        assert_eq!(
+            CudaBackend::sum_secure_column(&to_device(&values)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_sum_secure_column_range() {
+        require_gpu!();
+        let values = cpu_secure_column(1000, 3);
+        let expected = values.to_vec()[100..356]
+            .iter()
+            .copied()
+            .reduce(|a, b| a + b)
+            .unwrap();
+
+        let result = CudaBackend::sum_secure_column_range(&to_device(&values), 100..356);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_compute_g_values() {
+        require_gpu!();
+        let log_size = 10;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let values = cpu_secure_column(1 << log_size, 1);
+        let (expected_g, lambda) = CpuBackend::decompose(&SecureEvaluation {
+            domain,
+            values: values.clone(),
+        });
+
+        let g = CudaBackend::compute_g_values(&to_device(&values), lambda);
+
+        assert_eq!(to_host(&g), expected_g.values.columns.to_vec());
+    }
+
+    #[test]
+    fn test_fri_prover() {
+        require_gpu!();
+        let log_degree = 10;
+        let log_blowup_factor = 1;
+        let config = FriConfig::new(2, log_blowup_factor, 3);
+        let domain = CanonicCoset::new(log_degree + log_blowup_factor).circle_domain();
+        let cpu_twiddles = CpuBackend::precompute_twiddles(domain.half_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+        let values = SecureColumn::<CpuBackend> {
+            columns: std::array::from_fn(|i| {
+                let coeffs = (0..1 << log_degree)
+                    .map(|j| BaseField::from(4 * j + i as u32))
+                    .collect();
+                CpuBackend::evaluate(&CirclePoly::new(coeffs), domain, &cpu_twiddles).values
+            }),
+        };
+
+        let mut cpu_channel = Blake2sChannel::new(Blake2sHash::default());
+        let cpu_prover = FriProver::<CpuBackend, Blake2sMerkleHasher>::commit(
+            &mut cpu_channel,
+            config,
+            &[SecureEvaluation {
+                domain,
+                values: values.clone(),
+            }],
+            &cpu_twiddles,
+        );
+        let (expected_proof, _) = cpu_prover.decommit(&mut cpu_channel);
+
+        let mut gpu_channel = Blake2sChannel::new(Blake2sHash::default());
+        let gpu_prover = CudaFriProver::commit(
+            &mut gpu_channel,
+            config,
+            &[SecureEvaluation {
+                domain,
+                values: to_device(&values),
+            }],
+            &gpu_twiddles,
+        );
+        let (proof, _) = gpu_prover.decommit(&mut gpu_channel);
+
+        let commitments = |proof: &FriProof<Blake2sMerkleHasher>| {
+            proof
+                .inner_layers
+                .iter()
+                .map(|layer| layer.commitment)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(commitments(&proof), commitments(&expected_proof));
+        assert_eq!(gpu_channel.draw_felt(), cpu_channel.draw_felt());
+    }
+
+    #[test]
+    fn test_fold_lines() {
+        require_gpu!();
+        let log_size = 10;
+        // More than the evaluations folded by a single launch.
+        let n_evals = 37;
+        let root_coset = Coset::half_odds(log_size + 1);
+        let cpu_twiddles = CpuBackend::precompute_twiddles(root_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(root_coset);
+        let domain = LineDomain::new(root_coset.double());
+        let columns = (0..n_evals)
+            .map(|i| cpu_secure_column(domain.size(), 7 * i))
+            .collect::<Vec<_>>();
+        let alphas = (0..n_evals)
+            .map(|i| SecureField::from_u32_unchecked(i + 1, i + 2, i + 3, i + 4))
+            .collect::<Vec<_>>();
+
+        let expected = columns
+            .iter()
+            .zip(&alphas)
+            .map(|(values, &alpha)| {
+                let eval = LineEvaluation::<CpuBackend>::new(domain, values.clone());
+                CpuBackend::fold_line(&eval, alpha, &cpu_twiddles)
+                    .values
+                    .columns
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+
+        let gpu_evals = columns
+            .iter()
+            .map(|values| LineEvaluation::<CudaBackend>::new(domain, to_device(values)))
+            .collect::<Vec<_>>();
+        let folded = CudaBackend::fold_lines(
+            &gpu_evals.iter().collect::<Vec<_>>(),
+            &alphas,
+            &gpu_twiddles,
+        );
+
+        assert_eq!(
+            folded
+                .iter()
+                .map(|eval| to_host(&eval.values))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_fold_line_by() {
+        require_gpu!();
+        let log_size = 10;
+        let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let root_coset = Coset::half_odds(log_size + 1);
+        let cpu_twiddles = CpuBackend::precompute_twiddles(root_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(root_coset);
+        let domain = LineDomain::new(root_coset.double());
+        let values = cpu_secure_column(domain.size(), 1);
+        let gpu_eval = LineEvaluation::<CudaBackend>::new(domain, to_device(&values));
+
+        for log_fold_factor in 1..=MAX_LOG_FOLD_FACTOR {
+            let mut expected_result = LineEvaluation::<CpuBackend>::new(domain, values.clone());
+            let mut layer_alpha = alpha;
+            for _ in 0..log_fold_factor {
+                expected_result =
+                    CpuBackend::fold_line(&expected_result, layer_alpha, &cpu_twiddles);
+                layer_alpha = layer_alpha * layer_alpha;
+            }
+
+            let result =
+                CudaBackend::fold_line_by(&gpu_eval, alpha, log_fold_factor, &gpu_twiddles);
+
+            assert_eq!(result.domain(), expected_result.domain());
+            assert_eq!(
+                to_host(&result.values),
+                expected_result.values.columns.to_vec(),
+                "2^{log_fold_factor}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fold_line_and_commit() {
+        require_gpu!();
+        let log_size = 10;
+        let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let root_coset = Coset::half_odds(log_size);
+        let twiddles = CudaBackend::precompute_twiddles(root_coset);
+        let domain = LineDomain::new(root_coset);
+        let eval = LineEvaluation::<CudaBackend>::new(
+            domain,
+            to_device(&cpu_secure_column(1 << log_size, 1)),
+        );
+
+        let expected_layer = CudaBackend::fold_line(&eval, alpha, &twiddles);
+        let expected_tree = MerkleProver::<CudaBackend, Blake2sMerkleHasher>::commit(
+            expected_layer.values.columns.iter().collect(),
+        );
+        let (layer, tree) = CudaBackend::fold_line_and_commit(&eval, alpha, &twiddles);
+
+        assert_eq!(to_host(&layer.values), to_host(&expected_layer.values));
+        assert_eq!(tree.root(), expected_tree.root());
+        assert_eq!(
+            tree.layers
+                .iter()
+                .map(|layer| layer.to_cpu())
+                .collect::<Vec<_>>(),
+            expected_tree
+                .layers
+                .iter()
+                .map(|layer| layer.to_cpu())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_fold_small_sizes() {
+        require_gpu!();
+        let root_log_size = 12;
+        let alpha = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let root_coset = Coset::half_odds(root_log_size);
+        let cpu_twiddles = CpuBackend::precompute_twiddles(root_coset);
+        let gpu_twiddles = CudaBackend::precompute_twiddles(root_coset);
+
+        // Line evaluations of 2 to 1024 values, i.e. up to twice the values folded by a block.
+        for n_doublings in 2..root_log_size {
+            let domain = LineDomain::new(root_coset.repeated_double(n_doublings));
+            let values = cpu_secure_column(domain.size(), 1);
+            let cpu_eval = LineEvaluation::<CpuBackend>::new(domain, values.clone());
+            let gpu_eval = LineEvaluation::<CudaBackend>::new(domain, to_device(&values));
+
+            let expected_result = CpuBackend::fold_line(&cpu_eval, alpha, &cpu_twiddles);
+            let result = CudaBackend::fold_line(&gpu_eval, alpha, &gpu_twiddles);
+
+            assert_eq!(
+                to_host(&result.values),
+                expected_result.values.columns.to_vec(),
+                "size = {}",
+                domain.size()
+            );
+        }
+
+        for log_size in 2..=10 {
+            let domain = CanonicCoset::new(log_size).circle_domain();
+            let line_domain = LineDomain::new(domain.half_coset);
+            let cpu_twiddles = CpuBackend::precompute_twiddles(domain.half_coset);
+            let gpu_twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+            let src_values = cpu_secure_column(1 << log_size, 1);
+            let dst_values = cpu_secure_column(1 << (log_size - 1), 7);
+
+            let cpu_src = SecureEvaluation::<CpuBackend> {
+                domain,
+                values: src_values.clone(),
+            };
+            let mut cpu_dst = LineEvaluation::<CpuBackend>::new(line_domain, dst_values.clone());
+            CpuBackend::fold_circle_into_line(&mut cpu_dst, &cpu_src, alpha, &cpu_twiddles);
+
+            let gpu_src = SecureEvaluation::<CudaBackend> {
+                domain,
+                values: to_device(&src_values),
+            };
+            let mut gpu_dst =
+                LineEvaluation::<CudaBackend>::new(line_domain, to_device(&dst_values));
+            CudaBackend::fold_circle_into_line(&mut gpu_dst, &gpu_src, alpha, &gpu_twiddles);
+
+            assert_eq!(
+                to_host(&gpu_dst.values),
+                cpu_dst.values.columns.to_vec(),
+                "log_size = {log_size}"
+            );
+        }
     }
 }
@@ -0,0 +1,213 @@
+use std::{
+    ffi::c_void,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use stwo_prover::core::{
+    fields::m31::BaseField,
+    vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec, Blake2sHashVec, HASH_WORDS},
+    pipeline::commit_on_stream,
+    stream::{prefetch_columns, Pending, Stream},
+};
+
+/// Lets a [`Pending`] be awaited instead of waited for, so the task yields to the executor while
+/// the device works rather than blocking its thread.
+impl<'a, T> IntoFuture for Pending<'a, T> {
+    type Output = T;
+    type IntoFuture = PendingFuture<'a, T>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        PendingFuture {
+            pending: Some(self),
+            notification: None,
+        }
+    }
+}
+
+/// Future of a [`Pending`], resolved once the stream reaches the work.
+///
+/// The first poll queues a host callback on the stream behind the work, which wakes the task when
+/// it runs. Works with any executor, e.g. tokio. Dropping the future before it resolves blocks
+/// until the work is done, like dropping the [`Pending`].
+#[must_use = "futures do nothing unless polled"]
+pub struct PendingFuture<'a, T> {
+    pending: Option<Pending<'a, T>>,
+    notification: Option<Arc<Notification>>,
+}
+
+#[derive(Default)]
+struct Notification {
+    done: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Called by the driver with a reference to a [`Notification`] leaked by
+/// [`PendingFuture::poll`].
+extern "C" fn notify(data: *mut c_void) {
+    let notification = unsafe { Arc::from_raw(data as *const Notification) };
+    notification.done.store(true, Ordering::Release);
+    if let Some(waker) = notification.waker.lock().unwrap().take() {
+        waker.wake();
+    }
+}
+
+impl<T> Future for PendingFuture<'_, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let pending = this
+            .pending
+            .as_ref()
+            .expect("future polled after completion");
+        let notification = this.notification.get_or_insert_with(|| {
+            let notification = Arc::new(Notification::default());
+            unsafe {
+                cuda::bindings::launch_host_callback(
+                    pending.stream().ptr,
+                    notify,
+                    Arc::into_raw(notification.clone()) as *mut c_void,
+                );
+            }
+            notification
+        });
+
+        if !notification.done.load(Ordering::Acquire) {
+            *notification.waker.lock().unwrap() = Some(cx.waker().clone());
+            // The callback may have run before the waker was stored.
+            if !notification.done.load(Ordering::Acquire) {
+                return Poll::Pending;
+            }
+        }
+        // The stream is past the work, so this only releases the resources of the transfers.
+        Poll::Ready(this.pending.take().unwrap().wait())
+    }
+}
+
+/// Same as [`prefetch_columns`], resolving once the columns are on the device.
+pub async fn upload_trace(columns: &[&[BaseField]], stream: &Stream) -> Vec<BaseFieldVec> {
+    prefetch_columns(columns, stream).await
+}
+
+/// Same as [`commit_on_stream`], resolving once the tree is hashed.
+pub async fn commit_async(
+    columns: &[&BaseFieldVec],
+    stream: &Stream,
+) -> MerkleProver<CudaBackend, Blake2sMerkleHasher> {
+    commit_on_stream(columns, stream).await
+}
+
+/// Copies `column` to the host, e.g. the values of the proof, through `stream`.
+pub async fn download_column(column: &BaseFieldVec, stream: &Stream) -> Vec<BaseField> {
+    let mut host_column = vec![BaseField::from(0); column.size];
+    // Dropping the future while the copy runs drops the `Pending`, which waits for it, before
+    // `host_column`. Once polled, the future is pinned, so it can't be forgotten without leaking
+    // `host_column` along with it.
+    unsafe { column.copy_to_slice_async(&mut host_column, stream) }.await;
+    host_column
+}
+
+/// Copies `hashes` to the host, e.g. the decommitments of the proof, through `stream`.
+pub async fn download_hashes(hashes: &Blake2sHashVec, stream: &Stream) -> Vec<Blake2sHash> {
+    let mut words = vec![0u32; HASH_WORDS * hashes.size];
+    // `words` outlives the copy as `host_column` does in `download_column`.
+    unsafe {
+        cuda::bindings::stream_wait_default_stream(stream.ptr);
+        cuda::bindings::copy_uint32_t_vec_from_device_to_host_async(
+            hashes.device_ptr(),
+            words.as_mut_ptr() as *const u32,
+            words.len() as u32,
+            stream.ptr,
+        );
+    }
+    hashes.free_after(stream);
+    Pending::new((), stream).await;
+    cuda::words_to_hashes(&words)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake},
+        thread::{self, Thread},
+    };
+
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        fields::m31::BaseField,
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{commit_async, download_column, download_hashes, upload_trace};
+    use crate::stream::Stream;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor, parking the thread until the future is woken.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_async_proof_phases() {
+        require_gpu!();
+        let columns = (0..3)
+            .map(|i| {
+                (0..1 << 12)
+                    .map(|j| BaseField::from(i + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let stream = Stream::non_blocking();
+
+        let (trace, tree) = block_on(async {
+            let trace = upload_trace(
+                &columns.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                &stream,
+            )
+            .await;
+            let tree = commit_async(&trace.iter().collect::<Vec<_>>(), &stream).await;
+            (trace, tree)
+        });
+        let (column, leaves) = block_on(async {
+            (
+                download_column(&trace[1], &stream).await,
+                download_hashes(&tree.layers[12], &stream).await,
+            )
+        });
+
+        let cpu_tree =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect());
+        assert_eq!(column, columns[1]);
+        assert_eq!(leaves, cpu_tree.layers[12]);
+        assert_eq!(tree.root(), cpu_tree.root());
+    }
+}
@@ -0,0 +1,339 @@
+use stwo_prover::core::{
+    fields::qm31::SecureField,
+    lookups::{
+        gkr_prover::{
+            correct_sum_as_poly_in_first_variable, GkrMultivariatePolyOracle, GkrOps, Layer,
+        },
+        mle::Mle,
+        sumcheck::MultivariatePolyOracle,
+        utils::UnivariatePoly,
+    },
+};
+
+use crate::{backend::CudaBackend, cuda};
+
+impl GkrOps for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_variables = y.len()))
+    )]
+    fn gen_eq_evals(y: &[SecureField], v: SecureField) -> Mle<Self, SecureField> {
+        let device_y = cuda::SecureFieldVec::from_vec(y.to_vec());
+        let result = cuda::SecureFieldVec::new_uninitialized(1 << y.len());
+        unsafe {
+            cuda::bindings::gen_eq_evals(
+                device_y.device_ptr(),
+                y.len() as u32,
+                v,
+                result.device_ptr(),
+            );
+        }
+        Mle::new(result)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_variables = layer.n_variables()))
+    )]
+    fn next_layer(layer: &Layer<Self>) -> Layer<Self> {
+        let size = 1 << (layer.n_variables() - 1);
+        match layer {
+            Layer::GrandProduct(input) => {
+                let result = cuda::SecureFieldVec::new_uninitialized(size);
+                unsafe {
+                    cuda::bindings::gkr_next_grand_product_layer(
+                        input.device_ptr(),
+                        size as u32,
+                        result.device_ptr(),
+                    );
+                }
+                Layer::GrandProduct(Mle::new(result))
+            }
+            Layer::LogUpGeneric {
+                numerators,
+                denominators,
+            } => next_logup_layer(size, |dst_numerators, dst_denominators| unsafe {
+                cuda::bindings::gkr_next_logup_layer_secure_field(
+                    numerators.device_ptr(),
+                    denominators.device_ptr(),
+                    size as u32,
+                    dst_numerators,
+                    dst_denominators,
+                )
+            }),
+            Layer::LogUpMultiplicities {
+                numerators,
+                denominators,
+            } => next_logup_layer(size, |dst_numerators, dst_denominators| unsafe {
+                cuda::bindings::gkr_next_logup_layer_base_field(
+                    numerators.device_ptr(),
+                    denominators.device_ptr(),
+                    size as u32,
+                    dst_numerators,
+                    dst_denominators,
+                )
+            }),
+            // A null numerators pointer is interpreted as a column of ones.
+            Layer::LogUpSingles { denominators } => {
+                next_logup_layer(size, |dst_numerators, dst_denominators| unsafe {
+                    cuda::bindings::gkr_next_logup_layer_base_field(
+                        std::ptr::null(),
+                        denominators.device_ptr(),
+                        size as u32,
+                        dst_numerators,
+                        dst_denominators,
+                    )
+                })
+            }
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_variables = h.n_variables()))
+    )]
+    fn sum_as_poly_in_first_variable(
+        h: &GkrMultivariatePolyOracle<'_, Self>,
+        claim: SecureField,
+    ) -> UnivariatePoly<SecureField> {
+        let n_variables = h.n_variables();
+        assert_ne!(n_variables, 0);
+        let n_terms = 1 << (n_variables - 1);
+        let eq_evals = h.eq_evals;
+
+        // Only `eval_at_0` and `eval_at_2` are brought back to the host.
+        let mut evals = [SecureField::from_u32_unchecked(0, 0, 0, 0); 2];
+        unsafe {
+            match &h.input_layer {
+                Layer::GrandProduct(input) => cuda::bindings::gkr_grand_product_sum(
+                    eq_evals.device_ptr(),
+                    input.device_ptr(),
+                    n_terms,
+                    evals.as_mut_ptr(),
+                ),
+                Layer::LogUpGeneric {
+                    numerators,
+                    denominators,
+                } => cuda::bindings::gkr_logup_sum_secure_field(
+                    eq_evals.device_ptr(),
+                    numerators.device_ptr(),
+                    denominators.device_ptr(),
+                    n_terms,
+                    h.lambda,
+                    evals.as_mut_ptr(),
+                ),
+                Layer::LogUpMultiplicities {
+                    numerators,
+                    denominators,
+                } => cuda::bindings::gkr_logup_sum_base_field(
+                    eq_evals.device_ptr(),
+                    numerators.device_ptr(),
+                    denominators.device_ptr(),
+                    n_terms,
+                    h.lambda,
+                    evals.as_mut_ptr(),
+                ),
+                // A null numerators pointer is interpreted as a column of ones.
+                Layer::LogUpSingles { denominators } => cuda::bindings::gkr_logup_sum_base_field(
+                    eq_evals.device_ptr(),
+                    std::ptr::null(),
+                    denominators.device_ptr(),
+                    n_terms,
+                    h.lambda,
+                    evals.as_mut_ptr(),
+                ),
+            }
+        }
+
+        let [eval_at_0, eval_at_2] = evals.map(|eval| eval * h.eq_fixed_var_correction);
+        correct_sum_as_poly_in_first_variable(
+            eval_at_0,
+            eval_at_2,
+            claim,
+            eq_evals.y(),
+            n_variables,
+        )
+    }
+}
+
+/// Allocates the `size` fractions of the next layer and lets `launch` fill their numerators and
+/// denominators.
+fn next_logup_layer(
+    size: usize,
+    launch: impl FnOnce(*const u32, *const u32),
+) -> Layer<CudaBackend> {
+    let numerators = cuda::SecureFieldVec::new_uninitialized(size);
+    let denominators = cuda::SecureFieldVec::new_uninitialized(size);
+    launch(numerators.device_ptr(), denominators.device_ptr());
+    Layer::LogUpGeneric {
+        numerators: Mle::new(numerators),
+        denominators: Mle::new(denominators),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        lookups::{
+            gkr_prover::{EqEvals, GkrMultivariatePolyOracle, GkrOps, Layer},
+            mle::Mle,
+            sumcheck::MultivariatePolyOracle,
+        },
+    };
+
+    use crate::{backend::CudaBackend, cuda};
+
+    fn secure_field_values(size: usize, offset: u32) -> Vec<SecureField> {
+        (offset..offset + 4 * size as u32)
+            .collect::<Vec<u32>>()
+            .chunks(4)
+            .map(|a| SecureField::from_u32_unchecked(a[0], a[1], a[2], a[3]))
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn test_gen_eq_evals() {
+        require_gpu!();
+        let y = secure_field_values(10, 1);
+        let v = SecureField::from_u32_unchecked(4, 3, 2, 1);
+
+        let expected_result = CpuBackend::gen_eq_evals(&y, v);
+        let result = CudaBackend::gen_eq_evals(&y, v);
+
+        assert_eq!(result.into_evals().to_cpu(), expected_result.into_evals());
+    }
+
+    #[test]
+    fn test_sum_as_poly_in_first_variable_grand_product() {
+        require_gpu!();
+        let log_size = 12;
+        let y = secure_field_values(log_size - 1, 1);
+        let lambda = SecureField::from_u32_unchecked(5, 6, 7, 8);
+        let claim = SecureField::from_u32_unchecked(8, 7, 6, 5);
+        let values = secure_field_values(1 << log_size, 100);
+
+        let cpu_eq_evals = EqEvals::<CpuBackend>::generate(&y);
+        let cpu_oracle = GkrMultivariatePolyOracle {
+            eq_evals: &cpu_eq_evals,
+            input_layer: Layer::GrandProduct(Mle::new(values.clone())),
+            eq_fixed_var_correction: lambda,
+            lambda,
+        };
+        let expected_result = cpu_oracle.sum_as_poly_in_first_variable(claim);
+
+        let gpu_eq_evals = EqEvals::<CudaBackend>::generate(&y);
+        let gpu_oracle = GkrMultivariatePolyOracle {
+            eq_evals: &gpu_eq_evals,
+            input_layer: Layer::GrandProduct(Mle::new(cuda::SecureFieldVec::from_vec(values))),
+            eq_fixed_var_correction: lambda,
+            lambda,
+        };
+        let result = gpu_oracle.sum_as_poly_in_first_variable(claim);
+
+        for x in secure_field_values(4, 1000) {
+            assert_eq!(result.eval_at_point(x), expected_result.eval_at_point(x));
+        }
+    }
+
+    #[test]
+    fn test_sum_as_poly_in_first_variable_logup_multiplicities() {
+        require_gpu!();
+        let log_size = 12;
+        let y = secure_field_values(log_size - 1, 1);
+        let lambda = SecureField::from_u32_unchecked(5, 6, 7, 8);
+        let claim = SecureField::from_u32_unchecked(8, 7, 6, 5);
+        let numerators = (0..(1 << log_size) as u32)
+            .map(BaseField::from)
+            .collect::<Vec<_>>();
+        let denominators = secure_field_values(1 << log_size, 100);
+
+        let cpu_eq_evals = EqEvals::<CpuBackend>::generate(&y);
+        let cpu_oracle = GkrMultivariatePolyOracle {
+            eq_evals: &cpu_eq_evals,
+            input_layer: Layer::LogUpMultiplicities {
+                numerators: Mle::new(numerators.clone()),
+                denominators: Mle::new(denominators.clone()),
+            },
+            eq_fixed_var_correction: lambda,
+            lambda,
+        };
+        let expected_result = cpu_oracle.sum_as_poly_in_first_variable(claim);
+
+        let gpu_eq_evals = EqEvals::<CudaBackend>::generate(&y);
+        let gpu_oracle = GkrMultivariatePolyOracle {
+            eq_evals: &gpu_eq_evals,
+            input_layer: Layer::LogUpMultiplicities {
+                numerators: Mle::new(cuda::BaseFieldVec::from_vec(numerators)),
+                denominators: Mle::new(cuda::SecureFieldVec::from_vec(denominators)),
+            },
+            eq_fixed_var_correction: lambda,
+            lambda,
+        };
+        let result = gpu_oracle.sum_as_poly_in_first_variable(claim);
+
+        for x in secure_field_values(4, 1000) {
+            assert_eq!(result.eval_at_point(x), expected_result.eval_at_point(x));
+        }
+    }
+
+    #[test]
+    fn test_next_layer_grand_product() {
+        require_gpu!();
+        let values = secure_field_values(1 << 12, 1);
+
+        let Layer::GrandProduct(expected_result) =
+            CpuBackend::next_layer(&Layer::GrandProduct(Mle::new(values.clone())))
+        else {
+            panic!("Expected a grand product layer");
+        };
+        let Layer::GrandProduct(result) = CudaBackend::next_layer(&Layer::GrandProduct(Mle::new(
+            cuda::SecureFieldVec::from_vec(values),
+        ))) else {
+            panic!("Expected a grand product layer");
+        };
+
+        assert_eq!(result.into_evals().to_cpu(), expected_result.into_evals());
+    }
+
+    #[test]
+    fn test_next_layer_logup_multiplicities() {
+        require_gpu!();
+        let log_size = 12;
+        let numerators = (0..(1 << log_size) as u32)
+            .map(BaseField::from)
+            .collect::<Vec<_>>();
+        let denominators = secure_field_values(1 << log_size, 100);
+
+        let Layer::LogUpGeneric {
+            numerators: expected_numerators,
+            denominators: expected_denominators,
+        } = CpuBackend::next_layer(&Layer::LogUpMultiplicities {
+            numerators: Mle::new(numerators.clone()),
+            denominators: Mle::new(denominators.clone()),
+        })
+        else {
+            panic!("Expected a generic LogUp layer");
+        };
+        let Layer::LogUpGeneric {
+            numerators: result_numerators,
+            denominators: result_denominators,
+        } = CudaBackend::next_layer(&Layer::LogUpMultiplicities {
+            numerators: Mle::new(cuda::BaseFieldVec::from_vec(numerators)),
+            denominators: Mle::new(cuda::SecureFieldVec::from_vec(denominators)),
+        })
+        else {
+            panic!("Expected a generic LogUp layer");
+        };
+
+        assert_eq!(
+            result_numerators.into_evals().to_cpu(),
+            expected_numerators.into_evals()
+        );
+        assert_eq!(
+            result_denominators.into_evals().to_cpu(),
+            expected_denominators.into_evals()
+        );
+    }
+}
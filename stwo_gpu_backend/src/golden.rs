@@ -0,0 +1,151 @@
+//! Golden tests of single kernel bindings.
+//!
+//! [`check_golden`] runs a binding on small inputs given by the test and compares every buffer
+//! it touched with the vectors recorded in `golden/<name>.golden`. Running the tests with
+//! `UPDATE_GOLDEN=1` records the current results instead, so a regression test for a kernel is
+//! one call plus a reviewed golden file.
+//!
+//! Golden files hold one buffer per line, as space separated decimal words: the inputs as left by
+//! the call, which shows in-place updates, followed by the outputs. Lines starting with `#` are
+//! comments.
+
+use std::{env, fs, path::PathBuf};
+
+use crate::cuda::bindings;
+
+/// Uploads `inputs`, allocates zeroed outputs of `output_sizes` words, calls `run` with the
+/// device pointers of both, then checks all of them against the golden file `name`.
+pub(crate) fn check_golden(
+    name: &str,
+    inputs: &[&[u32]],
+    output_sizes: &[usize],
+    run: impl FnOnce(&[*const u32], &[*const u32]),
+) {
+    let input_ptrs = inputs
+        .iter()
+        .map(|input| unsafe {
+            bindings::copy_uint32_t_vec_from_host_to_device(input.as_ptr(), input.len() as u32)
+        })
+        .collect::<Vec<_>>();
+    let output_ptrs = output_sizes
+        .iter()
+        .map(|&size| unsafe { bindings::cuda_alloc_zeroes_uint32_t(size as u32) })
+        .collect::<Vec<_>>();
+
+    run(&input_ptrs, &output_ptrs);
+
+    let sizes = inputs
+        .iter()
+        .map(|input| input.len())
+        .chain(output_sizes.iter().copied());
+    let buffers = input_ptrs
+        .iter()
+        .chain(&output_ptrs)
+        .zip(sizes)
+        .map(|(&device_ptr, size)| {
+            let mut buffer = vec![0u32; size];
+            unsafe {
+                bindings::copy_uint32_t_vec_from_device_to_host(
+                    device_ptr,
+                    buffer.as_mut_ptr() as *const u32,
+                    size as u32,
+                );
+                bindings::free_uint32_t_vec(device_ptr);
+            }
+            buffer
+        })
+        .collect::<Vec<_>>();
+
+    let path = golden_path(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, format_golden(name, &buffers)).unwrap();
+        return;
+    }
+    let golden = fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!(
+            "can't read {}: {error}. Run with UPDATE_GOLDEN=1 to record it.",
+            path.display()
+        )
+    });
+    let expected = parse_golden(&golden);
+    assert_eq!(buffers.len(), expected.len(), "number of buffers of {name}");
+    for (i, (buffer, expected)) in buffers.iter().zip(&expected).enumerate() {
+        assert_eq!(buffer, expected, "buffer {i} of {name}");
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{name}.golden"))
+}
+
+fn format_golden(name: &str, buffers: &[Vec<u32>]) -> String {
+    let mut golden = format!("# {name}\n");
+    for buffer in buffers {
+        let words = buffer.iter().map(u32::to_string).collect::<Vec<_>>();
+        golden.push_str(&words.join(" "));
+        golden.push('\n');
+    }
+    golden
+}
+
+fn parse_golden(golden: &str) -> Vec<Vec<u32>> {
+    golden
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            line.split_whitespace()
+                .map(|word| word.parse().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_golden;
+    use crate::cuda::bindings;
+
+    #[test]
+    fn test_golden_bit_reverse_base_field() {
+        require_gpu!();
+        let values = (0..16).collect::<Vec<u32>>();
+        check_golden(
+            "bit_reverse_base_field",
+            &[&values],
+            &[],
+            |inputs, _| unsafe {
+                bindings::bit_reverse_base_field(inputs[0], values.len());
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_batch_inverse_base_field() {
+        require_gpu!();
+        let values = [1, 2, 3, 7, 1 << 20, 2147483646, 12345, 99];
+        check_golden(
+            "batch_inverse_base_field",
+            &[&values],
+            &[values.len()],
+            |inputs, outputs| unsafe {
+                bindings::batch_inverse_base_field(inputs[0], outputs[0], values.len());
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_cumulative_product_base_field() {
+        require_gpu!();
+        let values = [3, 5, 2147483646, 1 << 30, 7, 1, 11, 13, 17, 19];
+        check_golden(
+            "cumulative_product_base_field",
+            &[&values],
+            &[],
+            |inputs, _| unsafe {
+                bindings::cumulative_product_base_field(inputs[0], values.len() as u32);
+            },
+        );
+    }
+}
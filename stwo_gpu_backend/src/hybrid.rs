@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+
+use stwo_prover::core::{
+    backend::{simd::SimdBackend, Col, Column, ColumnOps},
+    circle::CirclePoint,
+    fields::{m31::BaseField, qm31::SecureField},
+    pcs::quotients::{ColumnSampleBatch, QuotientOps},
+    poly::{
+        circle::{
+            CanonicCoset, CircleDomain, CircleEvaluation, CirclePoly, PolyOps, SecureEvaluation,
+        },
+        BitReversedOrder, NaturalOrder,
+    },
+    vcs::{
+        blake2_hash::Blake2sHash,
+        blake2_merkle::Blake2sMerkleHasher,
+        prover::{MerkleDecommitment, MerkleProver},
+    },
+};
+
+use crate::{
+    backend::CudaBackend,
+    commitment::{commit_on_gpu, GpuCommitment},
+    cpu_or_cuda::BackendKind,
+    cuda::BaseFieldVec,
+};
+
+/// Commitment scheme whose trees are each committed on the backend that suits them: small
+/// trees, e.g. preprocessed columns, on the CPU with [`SimdBackend`], so their columns don't
+/// cross PCIe nor take the device from the large trace trees committed with [`CudaBackend`].
+///
+/// The pieces of the proof where trees meet are converted here: out of domain samples are
+/// evaluated on the backend of each tree and returned to the host, and the FRI quotients of all
+/// the trees are accumulated on the device, where FRI runs.
+pub struct HybridCommitmentScheme {
+    log_blowup_factor: u32,
+    trees: Vec<HybridTree>,
+}
+
+enum HybridTree {
+    Cpu(CpuTree),
+    Cuda(GpuCommitment),
+}
+
+/// A tree committed with [`SimdBackend`], laid out as a [`GpuCommitment`].
+struct CpuTree {
+    polynomials: Vec<CirclePoly<SimdBackend>>,
+    evaluations: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    tree: MerkleProver<SimdBackend, Blake2sMerkleHasher>,
+}
+
+impl HybridCommitmentScheme {
+    pub fn new(log_blowup_factor: u32) -> Self {
+        Self {
+            log_blowup_factor,
+            trees: vec![],
+        }
+    }
+
+    /// Commits to `columns`, given in natural order over canonic cosets of power of two sizes,
+    /// as a new tree on the backend of `kind`. Returns the root of the tree.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(n_columns = columns.len(), kind = ?kind)
+        )
+    )]
+    pub fn commit(&mut self, columns: Vec<Vec<BaseField>>, kind: BackendKind) -> Blake2sHash {
+        for column in &columns {
+            assert!(
+                column.len().is_power_of_two(),
+                "column sizes must be powers of two"
+            );
+        }
+        match kind {
+            BackendKind::Cpu => self.commit_on_cpu(columns),
+            BackendKind::Cuda => self.commit_on_gpu(
+                columns
+                    .into_iter()
+                    .map(|values| {
+                        CircleEvaluation::new(
+                            circle_domain(values.len().ilog2()),
+                            BaseFieldVec::from_vec(values),
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Commits to columns already on the device as a new tree, see [`commit_on_gpu`].
+    pub fn commit_on_gpu(
+        &mut self,
+        columns: Vec<CircleEvaluation<CudaBackend, BaseField, NaturalOrder>>,
+    ) -> Blake2sHash {
+        let (root, commitment) = commit_on_gpu(columns, self.log_blowup_factor);
+        self.trees.push(HybridTree::Cuda(commitment));
+        root
+    }
+
+    fn commit_on_cpu(&mut self, columns: Vec<Vec<BaseField>>) -> Blake2sHash {
+        let twiddles = columns
+            .iter()
+            .flat_map(|values| {
+                let log_size = values.len().ilog2();
+                [log_size, log_size + self.log_blowup_factor]
+            })
+            .map(|log_size| {
+                let half_coset = circle_domain(log_size).half_coset;
+                (log_size, SimdBackend::precompute_twiddles(half_coset))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let (polynomials, evaluations): (Vec<_>, Vec<_>) = columns
+            .into_iter()
+            .map(|values| {
+                let log_size = values.len().ilog2();
+                let mut values = values.into_iter().collect::<Col<SimdBackend, BaseField>>();
+                <SimdBackend as ColumnOps<BaseField>>::bit_reverse_column(&mut values);
+                let polynomial = SimdBackend::interpolate(
+                    CircleEvaluation::new(circle_domain(log_size), values),
+                    &twiddles[&log_size],
+                );
+                let extended_log_size = log_size + self.log_blowup_factor;
+                let evaluation = SimdBackend::evaluate(
+                    &polynomial,
+                    circle_domain(extended_log_size),
+                    &twiddles[&extended_log_size],
+                );
+                (polynomial, evaluation)
+            })
+            .unzip();
+        let tree = MerkleProver::commit(
+            evaluations
+                .iter()
+                .map(|evaluation| &evaluation.values)
+                .collect(),
+        );
+        let root = tree.root();
+        self.trees.push(HybridTree::Cpu(CpuTree {
+            polynomials,
+            evaluations,
+            tree,
+        }));
+        root
+    }
+
+    /// The backend each tree was committed on, in the order they were committed.
+    pub fn tree_backends(&self) -> Vec<BackendKind> {
+        self.trees
+            .iter()
+            .map(|tree| match tree {
+                HybridTree::Cpu(_) => BackendKind::Cpu,
+                HybridTree::Cuda(_) => BackendKind::Cuda,
+            })
+            .collect()
+    }
+
+    pub fn roots(&self) -> Vec<Blake2sHash> {
+        self.trees
+            .iter()
+            .map(|tree| match tree {
+                HybridTree::Cpu(tree) => tree.tree.root(),
+                HybridTree::Cuda(commitment) => commitment.root(),
+            })
+            .collect()
+    }
+
+    /// Evaluates column `j` of tree `i` at each of `points[i][j]`, e.g. the out of domain
+    /// samples, on the backend of the tree.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn sampled_values(
+        &self,
+        points: &[Vec<Vec<CirclePoint<SecureField>>>],
+    ) -> Vec<Vec<Vec<SecureField>>> {
+        assert_eq!(points.len(), self.trees.len());
+        self.trees
+            .iter()
+            .zip(points)
+            .map(|(tree, points)| match tree {
+                HybridTree::Cpu(tree) => sample(&tree.polynomials, points),
+                HybridTree::Cuda(commitment) => sample(commitment.polynomials(), points),
+            })
+            .collect()
+    }
+
+    /// Accumulates the quotients of every column by its samples, `points` and the `values` of
+    /// [`HybridCommitmentScheme::sampled_values`] there, into one column per log size, on the
+    /// device and from the largest to the smallest, as FRI commits to them.
+    ///
+    /// The extended columns of the CPU trees are uploaded for this: they are the small ones.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn fri_quotients(
+        &self,
+        points: &[Vec<Vec<CirclePoint<SecureField>>>],
+        values: &[Vec<Vec<SecureField>>],
+        random_coeff: SecureField,
+    ) -> Vec<SecureEvaluation<CudaBackend>> {
+        assert_eq!(points.len(), self.trees.len());
+        assert_eq!(values.len(), self.trees.len());
+        let uploaded = self
+            .trees
+            .iter()
+            .map(|tree| match tree {
+                HybridTree::Cpu(tree) => tree
+                    .evaluations
+                    .iter()
+                    .map(|evaluation| {
+                        CircleEvaluation::new(
+                            evaluation.domain,
+                            BaseFieldVec::from_vec(evaluation.values.to_cpu()),
+                        )
+                    })
+                    .collect(),
+                HybridTree::Cuda(_) => vec![],
+            })
+            .collect::<Vec<Vec<_>>>();
+
+        // The columns of each log size, with their samples.
+        let mut samples_per_log_size = BTreeMap::<u32, Vec<_>>::new();
+        for (i, tree) in self.trees.iter().enumerate() {
+            let evaluations = match tree {
+                HybridTree::Cpu(_) => &uploaded[i],
+                HybridTree::Cuda(commitment) => commitment.evaluations(),
+            };
+            assert_eq!(points[i].len(), evaluations.len());
+            for (j, evaluation) in evaluations.iter().enumerate() {
+                assert_eq!(points[i][j].len(), values[i][j].len());
+                samples_per_log_size
+                    .entry(evaluation.domain.log_size())
+                    .or_default()
+                    .push((evaluation, &points[i][j], &values[i][j]));
+            }
+        }
+
+        samples_per_log_size
+            .into_iter()
+            .rev()
+            .map(|(log_size, samples)| {
+                let mut sample_batches: Vec<ColumnSampleBatch> = vec![];
+                for (index, (_, points, values)) in samples.iter().enumerate() {
+                    for (&point, &value) in points.iter().zip(values.iter()) {
+                        match sample_batches.iter_mut().find(|batch| batch.point == point) {
+                            Some(batch) => batch.columns_and_values.push((index, value)),
+                            None => sample_batches.push(ColumnSampleBatch {
+                                point,
+                                columns_and_values: vec![(index, value)],
+                            }),
+                        }
+                    }
+                }
+                let columns = samples
+                    .iter()
+                    .map(|&(evaluation, ..)| evaluation)
+                    .collect::<Vec<_>>();
+                CudaBackend::accumulate_quotients(
+                    circle_domain(log_size),
+                    &columns,
+                    random_coeff,
+                    &sample_batches,
+                )
+            })
+            .collect()
+    }
+
+    /// Decommits tree `tree` as [`GpuCommitment::decommit`] does, whatever its backend.
+    pub fn decommit(
+        &self,
+        tree: usize,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+    ) -> (Vec<Vec<BaseField>>, MerkleDecommitment<Blake2sMerkleHasher>) {
+        match &self.trees[tree] {
+            HybridTree::Cpu(tree) => tree.tree.decommit(
+                queries_per_log_size,
+                tree.evaluations
+                    .iter()
+                    .map(|evaluation| &evaluation.values)
+                    .collect(),
+            ),
+            HybridTree::Cuda(commitment) => commitment.decommit(queries_per_log_size),
+        }
+    }
+}
+
+fn sample<B: PolyOps>(
+    polynomials: &[CirclePoly<B>],
+    points: &[Vec<CirclePoint<SecureField>>],
+) -> Vec<Vec<SecureField>> {
+    assert_eq!(points.len(), polynomials.len());
+    polynomials
+        .iter()
+        .zip(points)
+        .map(|(polynomial, points)| {
+            points
+                .iter()
+                .map(|&point| B::eval_at_point(polynomial, point))
+                .collect()
+        })
+        .collect()
+}
+
+fn circle_domain(log_size: u32) -> CircleDomain {
+    CanonicCoset::new(log_size).circle_domain()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use stwo_prover::core::{
+        backend::Column,
+        circle::SECURE_FIELD_CIRCLE_GEN,
+        fields::{m31::BaseField, qm31::SecureField},
+    };
+
+    use super::HybridCommitmentScheme;
+    use crate::cpu_or_cuda::BackendKind;
+
+    #[test]
+    fn test_hybrid_commitment_scheme() {
+        require_gpu!();
+        let trees = [vec![4, 5], vec![6, 6, 4]].map(|log_sizes: Vec<u32>| {
+            log_sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &log_size)| {
+                    (0..1 << log_size)
+                        .map(|row: u32| BaseField::from(row * 13 + i as u32))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        });
+        let mut hybrid = HybridCommitmentScheme::new(1);
+        let mut on_gpu = HybridCommitmentScheme::new(1);
+        for (tree, kind) in trees.iter().zip([BackendKind::Cpu, BackendKind::Cuda]) {
+            hybrid.commit(tree.clone(), kind);
+            on_gpu.commit(tree.clone(), BackendKind::Cuda);
+        }
+        let points = trees
+            .iter()
+            .map(|tree| {
+                (0..tree.len())
+                    .map(|i| {
+                        vec![
+                            SECURE_FIELD_CIRCLE_GEN.mul(3),
+                            SECURE_FIELD_CIRCLE_GEN.mul(i as u128 + 5),
+                        ]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let random_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+
+        let values = hybrid.sampled_values(&points);
+        let quotients = hybrid.fri_quotients(&points, &values, random_coeff);
+
+        assert_eq!(
+            hybrid.tree_backends(),
+            [BackendKind::Cpu, BackendKind::Cuda]
+        );
+        assert_eq!(hybrid.roots(), on_gpu.roots());
+        assert_eq!(values, on_gpu.sampled_values(&points));
+        let expected_quotients = on_gpu.fri_quotients(&points, &values, random_coeff);
+        assert_eq!(quotients.len(), 3);
+        for (quotient, expected_quotient) in quotients.iter().zip(&expected_quotients) {
+            assert_eq!(quotient.domain, expected_quotient.domain);
+            assert_eq!(
+                quotient
+                    .values
+                    .columns
+                    .iter()
+                    .map(|column| column.to_cpu())
+                    .collect::<Vec<_>>(),
+                expected_quotient
+                    .values
+                    .columns
+                    .iter()
+                    .map(|column| column.to_cpu())
+                    .collect::<Vec<_>>()
+            );
+        }
+        let queries = BTreeMap::from([(6, vec![1, 17]), (5, vec![0, 8])]);
+        assert_eq!(
+            hybrid.decommit(0, queries.clone()).0,
+            on_gpu.decommit(0, queries).0
+        );
+    }
+}
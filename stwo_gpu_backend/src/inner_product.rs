@@ -0,0 +1,189 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+};
+
+/// Computes `Σ a[i] * b[i]` on the device, multiplying and reducing in a single pass.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = a.len()))
+)]
+pub fn inner_product(a: &BaseFieldVec, b: &BaseFieldVec) -> BaseField {
+    assert_eq!(a.len(), b.len());
+    unsafe {
+        cuda::bindings::inner_product_base_field(a.device_ptr(), b.device_ptr(), a.len() as u32)
+    }
+}
+
+/// Same as [`inner_product`] for secure columns.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = a.len()))
+)]
+pub fn secure_inner_product(
+    a: &SecureColumn<CudaBackend>,
+    b: &SecureColumn<CudaBackend>,
+) -> SecureField {
+    assert_eq!(a.len(), b.len());
+    unsafe {
+        cuda::bindings::inner_product_secure_field(
+            cuda::secure_column_device_ptrs(a).as_ptr(),
+            cuda::secure_column_device_ptrs(b).as_ptr(),
+            a.len() as u32,
+        )
+    }
+}
+
+/// Computes `dst[i] += a[i] * b[i]` on the device, e.g. to add a product of columns to a
+/// composition or quotient numerator without separate multiplication and addition passes.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = dst.len()))
+)]
+pub fn mul_add(dst: &mut BaseFieldVec, a: &BaseFieldVec, b: &BaseFieldVec) {
+    assert_eq!(a.len(), dst.len());
+    assert_eq!(b.len(), dst.len());
+    unsafe {
+        cuda::bindings::mul_add_base_field(
+            dst.device_ptr(),
+            a.device_ptr(),
+            b.device_ptr(),
+            dst.len() as u32,
+        );
+    }
+}
+
+/// Same as [`mul_add`] for secure columns.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = dst.len()))
+)]
+pub fn secure_mul_add(
+    dst: &mut SecureColumn<CudaBackend>,
+    a: &SecureColumn<CudaBackend>,
+    b: &SecureColumn<CudaBackend>,
+) {
+    assert_eq!(a.len(), dst.len());
+    assert_eq!(b.len(), dst.len());
+    unsafe {
+        cuda::bindings::mul_add_secure_field(
+            cuda::secure_column_device_ptrs(dst).as_ptr(),
+            cuda::secure_column_device_ptrs(a).as_ptr(),
+            cuda::secure_column_device_ptrs(b).as_ptr(),
+            dst.len() as u32,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        fields::{m31::BaseField, secure_column::SecureColumn},
+    };
+
+    use super::{inner_product, mul_add, secure_inner_product, secure_mul_add};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_inner_product() {
+        require_gpu!();
+        let size = (1 << 18) + 3;
+        let a = (0..size).map(BaseField::from).collect::<Vec<_>>();
+        let b = (0..size)
+            .map(|i| BaseField::from(3 * i + 1))
+            .collect::<Vec<_>>();
+        let expected = a
+            .iter()
+            .zip(&b)
+            .map(|(&a, &b)| a * b)
+            .reduce(|x, y| x + y)
+            .unwrap();
+
+        let result = inner_product(&BaseFieldVec::from_vec(a), &BaseFieldVec::from_vec(b));
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_secure_inner_product() {
+        require_gpu!();
+        let size = 1 << 12;
+        let column = |offset: u32| SecureColumn::<CpuBackend> {
+            columns: std::array::from_fn(|i| {
+                (0..size as u32)
+                    .map(|j| BaseField::from(offset + 4 * j + i as u32))
+                    .collect()
+            }),
+        };
+        let (a, b) = (column(1), column(7));
+        let expected = a
+            .to_vec()
+            .into_iter()
+            .zip(b.to_vec())
+            .map(|(a, b)| a * b)
+            .reduce(|x, y| x + y)
+            .unwrap();
+
+        let to_device = |column: SecureColumn<CpuBackend>| SecureColumn {
+            columns: column.columns.map(BaseFieldVec::from_vec),
+        };
+        let result = secure_inner_product(&to_device(a), &to_device(b));
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_mul_add() {
+        require_gpu!();
+        let size = (1 << 12) + 3;
+        let column = |factor: u32, offset: u32| {
+            (0..size)
+                .map(|i| BaseField::from(factor * i + offset))
+                .collect::<Vec<_>>()
+        };
+        let (dst, a, b) = (column(1, 0), column(2, 5), column(3, 1));
+        let expected_result = (0..size as usize)
+            .map(|i| dst[i] + a[i] * b[i])
+            .collect::<Vec<_>>();
+
+        let mut result = BaseFieldVec::from_vec(dst);
+        mul_add(
+            &mut result,
+            &BaseFieldVec::from_vec(a),
+            &BaseFieldVec::from_vec(b),
+        );
+
+        assert_eq!(result.to_vec(), expected_result);
+    }
+
+    #[test]
+    fn test_secure_mul_add() {
+        require_gpu!();
+        let size = 1 << 12;
+        let column = |offset: u32| SecureColumn::<CpuBackend> {
+            columns: std::array::from_fn(|i| {
+                (0..size as u32)
+                    .map(|j| BaseField::from(offset + 4 * j + i as u32))
+                    .collect()
+            }),
+        };
+        let (dst, a, b) = (column(3), column(1), column(7));
+        let expected_result = (0..size)
+            .map(|i| dst.at(i) + a.at(i) * b.at(i))
+            .collect::<Vec<_>>();
+
+        let to_device = |column: SecureColumn<CpuBackend>| SecureColumn {
+            columns: column.columns.map(BaseFieldVec::from_vec),
+        };
+        let mut result = to_device(dst);
+        secure_mul_add(&mut result, &to_device(a), &to_device(b));
+
+        assert_eq!(result.to_vec(), expected_result);
+    }
+}
@@ -0,0 +1,360 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::m31::BaseField,
+    poly::circle::{CanonicCoset, CircleDomain},
+    vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec, Blake2sHashVec, HASH_WORDS},
+    twiddles::cached_twiddles,
+};
+
+/// Most instances a single FFT launch covers, the limit of the second dimension of a CUDA grid.
+const MAX_INSTANCES_PER_LAUNCH: usize = 65535;
+
+/// Many instances of a statement with the same shape, packed for proving them together.
+///
+/// Column `i` of every instance is stored in a single wide column, the instances one after the
+/// other. Interpolation, extension and commitment then run on all the instances with the launches
+/// of a single one, which amortizes the fixed costs dominating the proofs of tiny statements.
+/// No prover consumes an [`InstanceBatch`] yet, so proving its instances still takes a proof each.
+pub struct InstanceBatch {
+    n_instances: usize,
+    log_sizes: Vec<u32>,
+    columns: Vec<BaseFieldVec>,
+}
+
+impl InstanceBatch {
+    /// Packs `instances`, each given by its columns. Every instance must have the same number of
+    /// columns, with the same power of two sizes.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_instances = instances.len()))
+    )]
+    pub fn new(instances: &[Vec<Vec<BaseField>>]) -> Self {
+        assert!(!instances.is_empty(), "a batch needs at least one instance");
+        let log_sizes = instances[0]
+            .iter()
+            .map(|column| {
+                assert!(
+                    column.len().is_power_of_two(),
+                    "column sizes must be powers of two"
+                );
+                column.len().ilog2()
+            })
+            .collect::<Vec<_>>();
+        for instance in instances {
+            assert!(
+                instance
+                    .iter()
+                    .map(|column| column.len())
+                    .eq(log_sizes.iter().map(|&log_size| 1 << log_size)),
+                "instances must have the same shape"
+            );
+        }
+
+        let columns = (0..log_sizes.len())
+            .map(|i| {
+                BaseFieldVec::from_vec(
+                    instances
+                        .iter()
+                        .flat_map(|instance| instance[i].iter().copied())
+                        .collect(),
+                )
+            })
+            .collect();
+        Self {
+            n_instances: instances.len(),
+            log_sizes,
+            columns,
+        }
+    }
+
+    pub fn n_instances(&self) -> usize {
+        self.n_instances
+    }
+
+    /// The log sizes of the columns of one instance.
+    pub fn log_sizes(&self) -> &[u32] {
+        &self.log_sizes
+    }
+
+    /// The wide column holding column `i` of every instance.
+    pub fn column(&self, i: usize) -> &BaseFieldVec {
+        &self.columns[i]
+    }
+
+    /// Copies column `column` of instance `instance` to the host.
+    pub fn instance_column(&self, instance: usize, column: usize) -> Vec<BaseField> {
+        assert!(instance < self.n_instances);
+        let size = 1 << self.log_sizes[column];
+        let mut values = vec![BaseField::from(0); size];
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_device_to_host(
+                self.columns[column].device_ptr().add(instance * size),
+                values.as_mut_ptr() as *const u32,
+                size as u32,
+            );
+        }
+        values
+    }
+
+    /// Interpolates every column of every instance in place, from its evaluation on the canonic
+    /// domain of its size in bit reversed order to the coefficients of its polynomial.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_instances = self.n_instances))
+    )]
+    pub fn interpolate(&mut self) {
+        for (column, &log_size) in self.columns.iter().zip(&self.log_sizes) {
+            let twiddles = cached_twiddles(domain(log_size).half_coset);
+            for_each_launch(
+                column,
+                log_size,
+                self.n_instances,
+                |values, n_instances| unsafe {
+                    cuda::bindings::interpolate_batch(
+                        values,
+                        twiddles.itwiddles.device_ptr(),
+                        1 << log_size,
+                        n_instances,
+                    );
+                },
+            );
+        }
+    }
+
+    /// Evaluates the polynomials of every instance, given by their coefficients, on the canonic
+    /// domains `2^log_blowup_factor` times larger than their columns, e.g. to commit to them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(n_instances = self.n_instances, log_blowup_factor = log_blowup_factor)
+        )
+    )]
+    pub fn evaluate(&self, log_blowup_factor: u32) -> Self {
+        let log_sizes = self
+            .log_sizes
+            .iter()
+            .map(|log_size| log_size + log_blowup_factor)
+            .collect::<Vec<_>>();
+        let columns = self
+            .columns
+            .iter()
+            .zip(&self.log_sizes)
+            .zip(&log_sizes)
+            .map(|((coeffs, &log_size), &extended_log_size)| {
+                let extended =
+                    BaseFieldVec::new_uninitialized(self.n_instances << extended_log_size);
+                unsafe {
+                    cuda::bindings::extend_batch(
+                        coeffs.device_ptr(),
+                        1 << log_size,
+                        extended.device_ptr(),
+                        1 << extended_log_size,
+                        self.n_instances as u32,
+                    );
+                }
+                let twiddles = cached_twiddles(domain(extended_log_size).half_coset);
+                for_each_launch(
+                    &extended,
+                    extended_log_size,
+                    self.n_instances,
+                    |values, n_instances| unsafe {
+                        cuda::bindings::evaluate_batch(
+                            values,
+                            twiddles.twiddles.device_ptr(),
+                            1 << extended_log_size,
+                            n_instances,
+                        );
+                    },
+                );
+                extended
+            })
+            .collect();
+        Self {
+            n_instances: self.n_instances,
+            log_sizes,
+            columns,
+        }
+    }
+
+    /// Commits to the columns of each instance in a Merkle tree of its own, laid out as by
+    /// [`MerkleProver::commit`]. The same layer of all the trees is hashed by one launch.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_instances = self.n_instances))
+    )]
+    pub fn commit(&self) -> BatchCommitment {
+        let columns = self.columns.iter().zip(&self.log_sizes).collect::<Vec<_>>();
+        let max_log_size = self.log_sizes.iter().copied().max().unwrap_or(0);
+
+        let mut layers: Vec<Blake2sHashVec> = vec![];
+        for log_size in (0..=max_log_size).rev() {
+            let column_ptrs = columns
+                .iter()
+                .filter(|&&(_, &column_log_size)| column_log_size == log_size)
+                .map(|(column, _)| column.device_ptr())
+                .collect::<Vec<_>>();
+            let layer = Blake2sHashVec::new_uninitialized(self.n_instances << log_size);
+            unsafe {
+                cuda::bindings::commit_on_layer_batch(
+                    log_size,
+                    self.n_instances as u32,
+                    layers
+                        .last()
+                        .map_or(std::ptr::null(), |prev_layer| prev_layer.device_ptr()),
+                    column_ptrs.as_ptr(),
+                    column_ptrs.len() as u32,
+                    layer.device_ptr(),
+                );
+            }
+            layers.push(layer);
+        }
+        layers.reverse();
+        BatchCommitment {
+            n_instances: self.n_instances,
+            layers,
+        }
+    }
+}
+
+/// The Merkle trees of the instances of an [`InstanceBatch`], stored layer by layer: layer `i`
+/// holds the `2^i` nodes of each tree, one tree after the other.
+pub struct BatchCommitment {
+    n_instances: usize,
+    layers: Vec<Blake2sHashVec>,
+}
+
+impl BatchCommitment {
+    /// The root of each instance, copied with a single transfer.
+    pub fn roots(&self) -> Vec<Blake2sHash> {
+        self.layers[0].to_vec()
+    }
+
+    /// Copies the tree of `instance` out of the batch, e.g. to decommit its queries.
+    pub fn tree(&self, instance: usize) -> MerkleProver<CudaBackend, Blake2sMerkleHasher> {
+        assert!(instance < self.n_instances);
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(log_size, layer)| {
+                let size = 1 << log_size;
+                let instance_layer = Blake2sHashVec::new_uninitialized(size);
+                unsafe {
+                    cuda::bindings::copy_uint32_t_vec_from_device_to_device(
+                        layer.device_ptr().add(HASH_WORDS * size * instance),
+                        instance_layer.device_ptr(),
+                        (HASH_WORDS * size) as u32,
+                    );
+                }
+                instance_layer
+            })
+            .collect();
+        MerkleProver { layers }
+    }
+}
+
+fn domain(log_size: u32) -> CircleDomain {
+    CanonicCoset::new(log_size).circle_domain()
+}
+
+/// Calls `launch` with the first value and the number of instances of each group of at most
+/// [`MAX_INSTANCES_PER_LAUNCH`] instances of `column`.
+fn for_each_launch(
+    column: &BaseFieldVec,
+    log_size: u32,
+    n_instances: usize,
+    mut launch: impl FnMut(*const u32, u32),
+) {
+    for first in (0..n_instances).step_by(MAX_INSTANCES_PER_LAUNCH) {
+        let n_launch_instances = MAX_INSTANCES_PER_LAUNCH.min(n_instances - first);
+        launch(
+            unsafe { column.device_ptr().add(first << log_size) },
+            n_launch_instances as u32,
+        );
+    }
+    debug_assert_eq!(column.len(), n_instances << log_size);
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        fields::m31::BaseField,
+        poly::{
+            circle::{CanonicCoset, CircleEvaluation, PolyOps},
+            BitReversedOrder,
+        },
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::InstanceBatch;
+
+    #[test]
+    fn test_instance_batch() {
+        require_gpu!();
+        let log_blowup_factor = 1;
+        let instances = (0..5u32)
+            .map(|instance| {
+                [4, 6, 4]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &log_size)| {
+                        (0..1 << log_size)
+                            .map(|j| BaseField::from(instance * 1000 + i as u32 * 100 + j))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut batch = InstanceBatch::new(&instances);
+        batch.interpolate();
+        let extended = batch.evaluate(log_blowup_factor);
+        let commitment = extended.commit();
+
+        let roots = commitment.roots();
+        for (instance, columns) in instances.iter().enumerate() {
+            let extended_columns = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let log_size = column.len().ilog2();
+                    let domain = CanonicCoset::new(log_size).circle_domain();
+                    let poly = CpuBackend::interpolate(
+                        CircleEvaluation::<CpuBackend, _, BitReversedOrder>::new(
+                            domain,
+                            column.clone(),
+                        ),
+                        &CpuBackend::precompute_twiddles(domain.half_coset),
+                    );
+                    assert_eq!(batch.instance_column(instance, i), poly.coeffs);
+
+                    let extended_domain =
+                        CanonicCoset::new(log_size + log_blowup_factor).circle_domain();
+                    CpuBackend::evaluate(
+                        &poly,
+                        extended_domain,
+                        &CpuBackend::precompute_twiddles(extended_domain.half_coset),
+                    )
+                    .values
+                })
+                .collect::<Vec<_>>();
+            for (i, values) in extended_columns.iter().enumerate() {
+                assert_eq!(&extended.instance_column(instance, i), values);
+            }
+
+            let cpu_tree = MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(
+                extended_columns.iter().collect(),
+            );
+            assert_eq!(roots[instance], cpu_tree.root());
+            assert_eq!(commitment.tree(instance).root(), cpu_tree.root());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    env,
+    ffi::CString,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use super::{compile_to_ptx, JitError, FIELDS_HEADER};
+use crate::cuda;
+
+const PTX_CACHE_DIR_ENV_VAR: &str = "STWO_GPU_PTX_CACHE_DIR";
+
+/// Directory where compiled constraint kernels are cached. Defaults to a directory inside the
+/// system's temporary directory, and can be overridden with `STWO_GPU_PTX_CACHE_DIR`.
+pub fn ptx_cache_dir() -> PathBuf {
+    env::var_os(PTX_CACHE_DIR_ENV_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("stwo-gpu-backend-ptx"))
+}
+
+/// FNV-1a hash of the generated source, the `fields.cuh` it includes and the target architecture.
+/// Unlike `DefaultHasher`, it is stable across Rust versions, so the cache survives toolchain
+/// updates, while changes to the field arithmetic of the crate invalidate it.
+fn cache_key(source: &str, fields_header: &str, architecture: u32) -> u64 {
+    source
+        .as_bytes()
+        .iter()
+        .chain(&(source.len() as u64).to_le_bytes())
+        .chain(fields_header.as_bytes())
+        .chain(&architecture.to_le_bytes())
+        .fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+pub(crate) fn load_or_compile(source: &str) -> Result<CString, JitError> {
+    load_or_compile_in(&ptx_cache_dir(), source)
+}
+
+fn load_or_compile_in(cache_dir: &Path, source: &str) -> Result<CString, JitError> {
+    let architecture = unsafe { cuda::bindings::jit_target_architecture() };
+    let path = cache_dir.join(format!(
+        "{:016x}_compute_{architecture}.ptx",
+        cache_key(source, FIELDS_HEADER, architecture)
+    ));
+
+    if let Some(ptx) = fs::read(&path).ok().and_then(|ptx| CString::new(ptx).ok()) {
+        return Ok(ptx);
+    }
+
+    let ptx = compile_to_ptx(source)?;
+    // Write to a temporary file first so that concurrent provers never read a partial file.
+    // Failing to populate the cache is not an error.
+    let temporary_path = path.with_extension(format!("{}.tmp", process::id()));
+    let _ = fs::create_dir_all(cache_dir)
+        .and_then(|_| fs::write(&temporary_path, ptx.as_bytes()))
+        .and_then(|_| fs::rename(&temporary_path, &path));
+    Ok(ptx)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::{cache_key, load_or_compile_in};
+    use crate::jit::{generate_source, Expr};
+
+    #[test]
+    fn test_cache_key() {
+        let source = generate_source(&[Expr::Column(0) * Expr::Column(1)]);
+        let other_source = generate_source(&[Expr::Column(0) + Expr::Column(1)]);
+
+        let header = "struct m31 {};";
+        let other_header = "struct m31 { unsigned int value; };";
+
+        assert_eq!(
+            cache_key(&source, header, 80),
+            cache_key(&source, header, 80)
+        );
+        assert_ne!(
+            cache_key(&source, header, 80),
+            cache_key(&source, header, 90)
+        );
+        assert_ne!(
+            cache_key(&source, header, 80),
+            cache_key(&other_source, header, 80)
+        );
+        assert_ne!(
+            cache_key(&source, header, 80),
+            cache_key(&source, other_header, 80)
+        );
+    }
+
+    #[test]
+    fn test_load_or_compile() {
+        require_gpu!();
+        let cache_dir = env::temp_dir().join(format!("stwo-gpu-ptx-cache-test-{}", process::id()));
+        let source = generate_source(&[Expr::Column(0) * Expr::Column(1) - Expr::Column(2)]);
+
+        let ptx = load_or_compile_in(&cache_dir, &source).unwrap();
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+        let cached_ptx = load_or_compile_in(&cache_dir, &source).unwrap();
+        assert_eq!(cached_ptx, ptx);
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
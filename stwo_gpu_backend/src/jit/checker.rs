@@ -0,0 +1,185 @@
+use std::{
+    ffi::c_void,
+    fmt::{self, Write},
+};
+
+use stwo_prover::core::backend::Column;
+
+use super::{cache, check_cuda, load_function, Expr, JitError};
+use crate::cuda::{self, BaseFieldVec};
+
+const KERNEL_NAME: &str = "check_constraints";
+
+/// Generates the source of a kernel that evaluates all `constraints` at each row and records the
+/// first (row, constraint) pair that doesn't vanish.
+fn generate_source(constraints: &[Expr]) -> String {
+    let n_constraints = constraints.len();
+    let mut body = String::new();
+    let mut n_variables = 0;
+    for (i, constraint) in constraints.iter().enumerate() {
+        let value = constraint.emit(&mut body, &mut n_variables);
+        writeln!(
+            body,
+            "    if ({value} != 0) {{ atomicMin(first_failure, (uint64_t) row * {n_constraints} + {i}); }}"
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"#include "fields.cuh"
+
+extern "C" __global__ void {KERNEL_NAME}(m31 **columns, uint64_t *first_failure, int size) {{
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+    if (row >= size) {{
+        return;
+    }}
+
+{body}}}
+"#
+    )
+}
+
+/// A constraint that doesn't hold on a trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure {
+    /// Index of the row in the trace columns.
+    pub row: usize,
+    /// Index of the constraint in the list the checker was compiled with.
+    pub constraint: usize,
+}
+
+impl fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} does not hold at row {}",
+            self.constraint, self.row
+        )
+    }
+}
+
+impl std::error::Error for ConstraintFailure {}
+
+/// Debug helper evaluating the constraints of a component over a whole trace on the device,
+/// without downloading it.
+///
+/// Unlike [`super::ConstraintKernel`], the constraints are checked row by row, so it is meant
+/// to catch witness bugs before wasting a full proof on an invalid trace.
+#[derive(Debug)]
+pub struct ConstraintChecker {
+    module: *const c_void,
+    function: *const c_void,
+    n_columns: usize,
+    n_constraints: usize,
+}
+
+impl ConstraintChecker {
+    pub fn compile(constraints: &[Expr]) -> Result<Self, JitError> {
+        let ptx = cache::load_or_compile(&generate_source(constraints))?;
+        let (module, function) = load_function(&ptx, KERNEL_NAME)?;
+        Ok(Self {
+            module,
+            function,
+            n_columns: constraints.iter().map(Expr::n_columns).max().unwrap_or(0),
+            n_constraints: constraints.len(),
+        })
+    }
+
+    /// Returns the first row where a constraint fails, together with the first constraint
+    /// failing at that row, or `None` if all the constraints hold.
+    pub fn check(&self, columns: &[&BaseFieldVec]) -> Result<Option<ConstraintFailure>, JitError> {
+        assert!(columns.len() >= self.n_columns);
+        let Some(size) = columns.first().map(|column| column.len()) else {
+            return Ok(None);
+        };
+        assert!(columns.iter().all(|column| column.len() == size));
+
+        let column_ptrs = columns
+            .iter()
+            .map(|column| column.device_ptr())
+            .collect::<Vec<_>>();
+        let mut first_failure = u64::MAX;
+        check_cuda(unsafe {
+            cuda::bindings::jit_launch_check_kernel(
+                self.function,
+                column_ptrs.as_ptr(),
+                column_ptrs.len() as u32,
+                size as u32,
+                &mut first_failure,
+            )
+        })?;
+        if first_failure == u64::MAX {
+            return Ok(None);
+        }
+        let n_constraints = self.n_constraints as u64;
+        Ok(Some(ConstraintFailure {
+            row: (first_failure / n_constraints) as usize,
+            constraint: (first_failure % n_constraints) as usize,
+        }))
+    }
+}
+
+impl Drop for ConstraintChecker {
+    fn drop(&mut self) {
+        unsafe { cuda::bindings::jit_unload_module(self.module) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::{ConstraintChecker, ConstraintFailure};
+    use crate::{cuda::BaseFieldVec, jit::Expr};
+
+    #[test]
+    fn test_constraint_checker() {
+        require_gpu!();
+        let size = 1 << 12;
+        let a = || Expr::Column(0);
+        let b = || Expr::Column(1);
+        let c = || Expr::Column(2);
+        // c = a * b and b = a + 1.
+        let constraints = vec![c() - a() * b(), b() - a() - Expr::from(BaseField::from(1))];
+        let mut columns = [
+            (0..size).map(BaseField::from).collect::<Vec<_>>(),
+            (0..size).map(|row| BaseField::from(row + 1)).collect(),
+            (0..size)
+                .map(|row| BaseField::from(row * (row + 1)))
+                .collect(),
+        ];
+        let checker = ConstraintChecker::compile(&constraints).unwrap();
+        let check = |columns: &[Vec<BaseField>]| {
+            let device_columns = columns
+                .iter()
+                .map(|column| BaseFieldVec::from_vec(column.clone()))
+                .collect::<Vec<_>>();
+            checker
+                .check(&device_columns.iter().collect::<Vec<_>>())
+                .unwrap()
+        };
+
+        assert_eq!(check(&columns), None);
+
+        // Only the second constraint fails at row 3000.
+        columns[1][3000] += BaseField::from(1);
+        columns[2][3000] = columns[0][3000] * columns[1][3000];
+        columns[2][2000] += BaseField::from(1);
+        assert_eq!(
+            check(&columns),
+            Some(ConstraintFailure {
+                row: 2000,
+                constraint: 0
+            })
+        );
+
+        columns[2][2000] -= BaseField::from(1);
+        assert_eq!(
+            check(&columns),
+            Some(ConstraintFailure {
+                row: 3000,
+                constraint: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fmt::Write,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use stwo_prover::core::fields::m31::BaseField;
+
+/// Arithmetic expression over the base field columns of a component, evaluated row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    /// Value of the column at the given index in the current row.
+    Column(usize),
+    Const(BaseField),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+}
+
+impl Expr {
+    /// Returns the number of columns referenced by the expression, i.e. the largest column
+    /// index plus one.
+    pub fn n_columns(&self) -> usize {
+        match self {
+            Expr::Column(index) => index + 1,
+            Expr::Const(_) => 0,
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => a.n_columns().max(b.n_columns()),
+            Expr::Neg(a) => a.n_columns(),
+        }
+    }
+
+    /// Appends to `body` the CUDA statements computing the expression, and returns the name of
+    /// the variable holding its value.
+    pub(crate) fn emit(&self, body: &mut String, n_variables: &mut usize) -> String {
+        let value = match self {
+            Expr::Column(index) => format!("columns[{index}][row]"),
+            Expr::Const(value) => format!("m31{{ {} }}", value.0),
+            Expr::Add(a, b) => format!(
+                "add({}, {})",
+                a.emit(body, n_variables),
+                b.emit(body, n_variables)
+            ),
+            Expr::Sub(a, b) => format!(
+                "sub({}, {})",
+                a.emit(body, n_variables),
+                b.emit(body, n_variables)
+            ),
+            Expr::Mul(a, b) => format!(
+                "mul({}, {})",
+                a.emit(body, n_variables),
+                b.emit(body, n_variables)
+            ),
+            Expr::Neg(a) => format!("neg({})", a.emit(body, n_variables)),
+        };
+        let variable = format!("v{n_variables}");
+        *n_variables += 1;
+        writeln!(body, "    m31 {variable} = {value};").unwrap();
+        variable
+    }
+}
+
+impl From<BaseField> for Expr {
+    fn from(value: BaseField) -> Self {
+        Expr::Const(value)
+    }
+}
+
+impl Add for Expr {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Expr::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Sub for Expr {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Expr::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Mul for Expr {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Expr::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Neg for Expr {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Expr::Neg(Box::new(self))
+    }
+}
@@ -0,0 +1,292 @@
+mod cache;
+#[cfg(feature = "debug-constraints")]
+mod checker;
+mod expr;
+
+use std::{
+    error::Error,
+    ffi::{c_char, c_void, CStr, CString},
+    fmt::{self, Write},
+    ptr,
+};
+
+pub use cache::ptx_cache_dir;
+#[cfg(feature = "debug-constraints")]
+pub use checker::{ConstraintChecker, ConstraintFailure};
+pub use expr::Expr;
+use stwo_prover::core::{
+    backend::Column,
+    fields::{qm31::SecureField, secure_column::SecureColumn},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec, SecureFieldVec},
+};
+
+const FIELDS_HEADER: &str = include_str!("../../../cuda/include/fields.cuh");
+const KERNEL_NAME: &str = "evaluate_constraints";
+
+/// Failure to compile, load or run a runtime-compiled kernel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JitError {
+    /// NVRTC rejected the generated source, with its error code and compilation log.
+    Compile { code: i32, log: String },
+    /// A CUDA driver call failed, with its error code.
+    Cuda(i32),
+}
+
+impl fmt::Display for JitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JitError::Compile { code, log } => {
+                write!(f, "NVRTC compilation failed with error {code}: {log}")
+            }
+            JitError::Cuda(code) => write!(f, "CUDA driver call failed with error {code}"),
+        }
+    }
+}
+
+impl Error for JitError {}
+
+fn check_cuda(code: i32) -> Result<(), JitError> {
+    match code {
+        0 => Ok(()),
+        code => Err(JitError::Cuda(code)),
+    }
+}
+
+/// Generates the source of a kernel that, for each row, evaluates all `constraints` and adds
+/// their random linear combination to the accumulator.
+pub(crate) fn generate_source(constraints: &[Expr]) -> String {
+    let mut body = String::new();
+    let mut n_variables = 0;
+    for (i, constraint) in constraints.iter().enumerate() {
+        let value = constraint.emit(&mut body, &mut n_variables);
+        writeln!(
+            body,
+            "    acc = add(acc, mul(random_coeff_powers[{i}], {value}));"
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"#include "fields.cuh"
+
+extern "C" __global__ void {KERNEL_NAME}(m31 **columns, qm31 *random_coeff_powers, m31 **accumulator, int size) {{
+    int row = blockIdx.x * blockDim.x + threadIdx.x;
+    if (row >= size) {{
+        return;
+    }}
+
+    qm31 acc = {{{{accumulator[0][row], accumulator[1][row]}}, {{accumulator[2][row], accumulator[3][row]}}}};
+{body}
+    accumulator[0][row] = acc.a.a;
+    accumulator[1][row] = acc.a.b;
+    accumulator[2][row] = acc.b.a;
+    accumulator[3][row] = acc.b.b;
+}}
+"#
+    )
+}
+
+/// Compiles `source` to PTX with NVRTC, for the architecture of the current device.
+pub(crate) fn compile_to_ptx(source: &str) -> Result<CString, JitError> {
+    let source = CString::new(source).unwrap();
+    let fields_header = CString::new(FIELDS_HEADER).unwrap();
+    let (mut ptx, mut log) = (ptr::null_mut(), ptr::null_mut());
+    let code = unsafe {
+        cuda::bindings::jit_compile(source.as_ptr(), fields_header.as_ptr(), &mut ptx, &mut log)
+    };
+    if code != 0 {
+        let log = if log.is_null() {
+            String::new()
+        } else {
+            unsafe { take_string(log) }.to_string_lossy().into_owned()
+        };
+        return Err(JitError::Compile { code, log });
+    }
+    Ok(unsafe { take_string(ptx) })
+}
+
+/// Copies and frees a string allocated by the JIT functions.
+unsafe fn take_string(string: *mut c_char) -> CString {
+    let result = CStr::from_ptr(string).to_owned();
+    cuda::bindings::jit_free(string);
+    result
+}
+
+/// Loads `ptx` as a module and looks up its kernel `name`.
+pub(crate) fn load_function(
+    ptx: &CStr,
+    name: &str,
+) -> Result<(*const c_void, *const c_void), JitError> {
+    let name = CString::new(name).unwrap();
+    let mut module = ptr::null();
+    check_cuda(unsafe { cuda::bindings::jit_load_module(ptx.as_ptr(), &mut module) })?;
+    let mut function = ptr::null();
+    let code = unsafe { cuda::bindings::jit_get_function(module, name.as_ptr(), &mut function) };
+    if let Err(error) = check_cuda(code) {
+        unsafe { cuda::bindings::jit_unload_module(module) };
+        return Err(error);
+    }
+    Ok((module, function))
+}
+
+/// A single fused kernel evaluating all the constraints of a component, compiled at runtime.
+#[derive(Debug)]
+pub struct ConstraintKernel {
+    module: *const c_void,
+    function: *const c_void,
+    n_columns: usize,
+    n_constraints: usize,
+}
+
+impl ConstraintKernel {
+    /// Compiles the kernel, or loads it from the on-disk PTX cache if the same constraints were
+    /// already compiled for this architecture.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_constraints = constraints.len()))
+    )]
+    pub fn compile(constraints: &[Expr]) -> Result<Self, JitError> {
+        let ptx = cache::load_or_compile(&generate_source(constraints))?;
+        Self::load(&ptx, constraints)
+    }
+
+    pub(crate) fn load(ptx: &CStr, constraints: &[Expr]) -> Result<Self, JitError> {
+        let (module, function) = load_function(ptx, KERNEL_NAME)?;
+        Ok(Self {
+            module,
+            function,
+            n_columns: constraints.iter().map(Expr::n_columns).max().unwrap_or(0),
+            n_constraints: constraints.len(),
+        })
+    }
+
+    /// Adds `sum_i random_coeff_powers[i] * constraints[i](row)` to each row of `accumulator`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = accumulator.len()))
+    )]
+    pub fn evaluate(
+        &self,
+        columns: &[&BaseFieldVec],
+        random_coeff_powers: &[SecureField],
+        accumulator: &mut SecureColumn<CudaBackend>,
+    ) -> Result<(), JitError> {
+        assert!(columns.len() >= self.n_columns);
+        assert_eq!(random_coeff_powers.len(), self.n_constraints);
+        let size = accumulator.len();
+        assert!(columns.iter().all(|column| column.len() == size));
+
+        let column_ptrs = columns
+            .iter()
+            .map(|column| column.device_ptr())
+            .collect::<Vec<_>>();
+        let accumulator_ptrs = accumulator
+            .columns
+            .iter()
+            .map(|column| column.device_ptr())
+            .collect::<Vec<_>>();
+        let random_coeff_powers = SecureFieldVec::from_vec(random_coeff_powers.to_vec());
+        check_cuda(unsafe {
+            cuda::bindings::jit_launch_constraint_kernel(
+                self.function,
+                column_ptrs.as_ptr(),
+                column_ptrs.len() as u32,
+                random_coeff_powers.device_ptr(),
+                accumulator_ptrs.as_ptr(),
+                size as u32,
+            )
+        })
+    }
+}
+
+impl Drop for ConstraintKernel {
+    fn drop(&mut self) {
+        unsafe { cuda::bindings::jit_unload_module(self.module) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+    };
+
+    use super::{ConstraintKernel, Expr};
+    use crate::cuda::BaseFieldVec;
+
+    fn eval(expr: &Expr, row: &[BaseField]) -> BaseField {
+        match expr {
+            Expr::Column(index) => row[*index],
+            Expr::Const(value) => *value,
+            Expr::Add(a, b) => eval(a, row) + eval(b, row),
+            Expr::Sub(a, b) => eval(a, row) - eval(b, row),
+            Expr::Mul(a, b) => eval(a, row) * eval(b, row),
+            Expr::Neg(a) => -eval(a, row),
+        }
+    }
+
+    #[test]
+    fn test_constraint_kernel() {
+        require_gpu!();
+        let size = 1 << 12;
+        let a = || Expr::Column(0);
+        let b = || Expr::Column(1);
+        let c = || Expr::Column(2);
+        let constraints = vec![
+            c() - (a() * a() + b() * b()),
+            a() * b() * c() + Expr::from(BaseField::from(7)),
+            -(a() - c()),
+        ];
+        let random_coeff_powers = vec![
+            SecureField::from_u32_unchecked(1, 0, 0, 0),
+            SecureField::from_u32_unchecked(1, 2, 3, 4),
+            SecureField::from_u32_unchecked(5, 6, 7, 8),
+        ];
+        let columns = (0..3)
+            .map(|i| {
+                (0..size as u32)
+                    .map(|row| BaseField::from(row * 3 + i))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let expected_result = (0..size)
+            .map(|row| {
+                let row_values = columns.iter().map(|column| column[row]).collect::<Vec<_>>();
+                constraints.iter().zip(&random_coeff_powers).fold(
+                    SecureField::from_u32_unchecked(0, 0, 0, 0),
+                    |acc, (constraint, &coeff)| acc + coeff * eval(constraint, &row_values),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let kernel = ConstraintKernel::compile(&constraints).unwrap();
+        let device_columns = columns
+            .into_iter()
+            .map(BaseFieldVec::from_vec)
+            .collect::<Vec<_>>();
+        let mut accumulator = SecureColumn {
+            columns: std::array::from_fn(|_| {
+                BaseFieldVec::from_vec(vec![BaseField::from(0); size])
+            }),
+        };
+        kernel
+            .evaluate(
+                &device_columns.iter().collect::<Vec<_>>(),
+                &random_coeff_powers,
+                &mut accumulator,
+            )
+            .unwrap();
+
+        let [a, b, c, d] = &accumulator.columns;
+        let (a, b, c, d) = (a.to_cpu(), b.to_cpu(), c.to_cpu(), d.to_cpu());
+        let result = (0..size)
+            .map(|row| SecureField::from_m31_array([a[row], b[row], c[row], d[row]]))
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
+}
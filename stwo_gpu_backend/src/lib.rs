@@ -1,8 +1,141 @@
+/// Returns early from a test when no GPU is available, so the suite can run on machines
+/// without a card.
+#[cfg(test)]
+macro_rules! require_gpu {
+    () => {
+        if !$crate::cuda_available() {
+            eprintln!("skipping test: {}", $crate::try_init().unwrap_err());
+            return;
+        }
+    };
+}
+
 mod accumulation;
+mod air;
+#[cfg(feature = "arrow")]
+mod arrow;
 mod backend;
+mod bandwidth;
+mod batch_verify;
+#[cfg(feature = "capi")]
+mod capi;
+mod checkpoint;
 mod column;
+mod commitment;
+mod compression;
+mod cpu_or_cuda;
 mod cuda;
+#[cfg(feature = "debug-constraints")]
+mod degree_bound;
+mod device;
 mod field;
 mod fri;
+#[cfg(feature = "async")]
+mod future;
+mod gkr;
+#[cfg(test)]
+mod golden;
+mod hybrid;
+mod inner_product;
+mod instance_batch;
+mod jit;
+mod line_twiddles;
+mod logup;
+mod mask;
+mod memory;
+mod merkle;
+mod mle;
+mod order;
+mod padding;
+mod pipeline;
+mod point;
 mod poly;
+mod preprocessed;
+#[cfg(feature = "python")]
+mod python;
+mod query;
 mod quotient;
+mod range;
+mod recovery;
+mod recursion;
+mod row_constraints;
+mod scan;
+mod sort;
+mod sparse;
+mod stats;
+mod stream;
+mod twiddles;
+mod vanishing;
+mod watchdog;
+
+pub use air::{prove_squares, SquaresAir, SquaresComponent, SquaresProof};
+#[cfg(feature = "parquet")]
+pub use arrow::columns_from_parquet;
+#[cfg(feature = "arrow")]
+pub use arrow::{columns_from_record_batches, ArrowTraceError};
+pub use backend::CudaBackend;
+pub use bandwidth::{measure_bandwidth, BandwidthReport};
+pub use batch_verify::{BatchVerificationFailure, BatchVerifier};
+pub use checkpoint::{load_checkpoint, save_checkpoint, Checkpoint};
+pub use commitment::{commit_on_gpu, commit_on_gpu_with_cap, GpuCommitment};
+pub use compression::TransferCompression;
+pub use cpu_or_cuda::{
+    cpu_fallback_log_size, set_cpu_fallback_log_size, BackendKind, CpuOrCuda, DynColumn,
+    ParseBackendKindError,
+};
+#[cfg(feature = "unstable-ffi")]
+pub use cuda::bindings;
+pub use cuda::{
+    batch_frees, secure_column_from_base_field, BaseFieldVec, Blake2sHashVec, DeviceVec,
+    DownloadError, FreeBatch, Pod, SecureFieldVec,
+};
+#[cfg(feature = "debug-constraints")]
+pub use degree_bound::{check_degree_bound, DegreeBoundViolation};
+pub use device::{
+    arena_used, clear_scratch_buffers, cuda_available, kernel_tuning, release_arena, reserve_arena,
+    reset_arena, scratch_buffers_size, set_kernel_tuning, set_memory_mode, trim_memory_pool,
+    try_init, Device, DeviceInfo, InitError, KernelTuning, MemoryMode, MigInstance, MpsConfig,
+};
+pub use fri::{CudaFriProver, MAX_LOG_FOLD_FACTOR};
+#[cfg(feature = "async")]
+pub use future::{commit_async, download_column, download_hashes, upload_trace, PendingFuture};
+pub use hybrid::HybridCommitmentScheme;
+pub use inner_product::{inner_product, mul_add, secure_inner_product, secure_mul_add};
+pub use instance_batch::{BatchCommitment, InstanceBatch};
+pub use jit::{ptx_cache_dir, ConstraintKernel, Expr, JitError};
+#[cfg(feature = "debug-constraints")]
+pub use jit::{ConstraintChecker, ConstraintFailure};
+pub use line_twiddles::{LineTwiddleLayers, LineTwiddles};
+pub use logup::{multiplicities, FractionVec};
+pub use mask::gather_mask;
+pub use memory::{
+    device_memory_in_use, memory_phase, memory_report, reset_memory_report, set_memory_tracking,
+    MemoryPhase, MemoryReport, PhaseMemory,
+};
+pub use order::{to_circle_domain_order, to_natural_order, Permute};
+pub use padding::{pad, pad_to_power_of_two, Padding};
+pub use pipeline::{commit_on_stream, fold_line_on_stream, PhasePipeline};
+pub use point::CirclePointVec;
+pub use preprocessed::{
+    gen_is_first, gen_is_last, gen_is_step_with_offset, gen_row_bit, gen_row_limb, gen_seq,
+    periodic_column, periodic_column_from_host,
+};
+pub use query::{
+    gather_authentication_paths, gather_capped_authentication_paths, gather_query_values,
+    merkle_cap,
+};
+pub use range::{batch_inverse_range, bit_reverse_range, fold_line_range, sum_range};
+pub use recovery::{install_panic_hook, recover_device, reset_device, run_on_device};
+pub use recursion::{fold_pairs, merkle_path_nodes};
+pub use row_constraints::{evaluate_row_constraints, MaskItem, RowConstraintsLauncher};
+pub use scan::CumulativeScan;
+pub use sort::DeviceSort;
+pub use sparse::SparseColumn;
+pub use stats::{column_stats, ColumnStats};
+pub use stream::{prefetch_columns, Event, Pending, Stream};
+pub use twiddles::{cached_twiddles, clear_twiddle_cache, set_twiddle_cache_capacity};
+pub use vanishing::{
+    coset_vanishing_evaluation, inverse_coset_vanishing_evaluation,
+    pair_vanishing_denominator_inverses, pair_vanishing_denominators,
+};
+pub use watchdog::set_watchdog;
@@ -0,0 +1,136 @@
+use stwo_prover::core::{
+    circle::Coset,
+    poly::{circle::PolyOps, line::LineDomain, twiddles::TwiddleTree},
+};
+
+use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+/// Inverse twiddles of the line domains obtained by doubling a root coset, stored on the device
+/// one layer per domain, from the largest to the smallest, as folds read them.
+///
+/// Both the [`TwiddleTree`] of a circle commitment and [`LineTwiddles`] provide them.
+pub trait LineTwiddleLayers {
+    fn root_coset(&self) -> Coset;
+
+    /// The layers of inverse twiddles, one after the other.
+    fn itwiddles(&self) -> &BaseFieldVec;
+
+    /// Position of the layer of `domain` in [`LineTwiddleLayers::itwiddles`]. Panics if `domain`
+    /// is not a doubling of the root coset.
+    fn layer_offset(&self, domain: LineDomain) -> u32;
+}
+
+impl LineTwiddleLayers for TwiddleTree<CudaBackend> {
+    fn root_coset(&self) -> Coset {
+        self.root_coset
+    }
+
+    fn itwiddles(&self) -> &BaseFieldVec {
+        &self.itwiddles
+    }
+
+    fn layer_offset(&self, domain: LineDomain) -> u32 {
+        // The layer of a domain comes after those of all the larger domains.
+        let root_size = self.root_coset.size();
+        (root_size - (root_size >> n_doublings(self.root_coset, domain))) as u32
+    }
+}
+
+/// [`LineTwiddleLayers`] of line domains generated on their own, e.g. for FRI over a line
+/// evaluation that doesn't come from a circle commitment, with the offset of every layer
+/// computed once.
+pub struct LineTwiddles {
+    root_coset: Coset,
+    itwiddles: BaseFieldVec,
+    /// The offset of the layer of the domain doubled `i` times, at index `i`.
+    layer_offsets: Vec<u32>,
+}
+
+impl LineTwiddles {
+    /// Computes the inverse twiddles of the line domain over `root_coset` and of all its
+    /// doublings.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(log_size = root_coset.log_size()))
+    )]
+    pub fn new(root_coset: Coset) -> Self {
+        let TwiddleTree { itwiddles, .. } = CudaBackend::precompute_twiddles(root_coset);
+        let layer_offsets = (0..=root_coset.log_size())
+            .map(|n_doublings| (root_coset.size() - (root_coset.size() >> n_doublings)) as u32)
+            .collect();
+        Self {
+            root_coset,
+            itwiddles,
+            layer_offsets,
+        }
+    }
+
+    /// The line domain of the largest layer.
+    pub fn root_domain(&self) -> LineDomain {
+        LineDomain::new(self.root_coset)
+    }
+}
+
+impl LineTwiddleLayers for LineTwiddles {
+    fn root_coset(&self) -> Coset {
+        self.root_coset
+    }
+
+    fn itwiddles(&self) -> &BaseFieldVec {
+        &self.itwiddles
+    }
+
+    fn layer_offset(&self, domain: LineDomain) -> u32 {
+        self.layer_offsets[n_doublings(self.root_coset, domain)]
+    }
+}
+
+/// Number of doublings of `root_coset` giving the coset of `domain`.
+fn n_doublings(root_coset: Coset, domain: LineDomain) -> usize {
+    let n_doublings = root_coset
+        .log_size()
+        .checked_sub(domain.log_size())
+        .expect("line domain larger than the twiddles");
+    assert!(
+        domain.coset() == root_coset.repeated_double(n_doublings),
+        "line domain not a doubling of the root coset of the twiddles"
+    );
+    n_doublings as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        circle::Coset,
+        poly::{circle::PolyOps, line::LineDomain},
+    };
+
+    use super::{n_doublings, LineTwiddleLayers, LineTwiddles};
+    use crate::backend::CudaBackend;
+
+    #[test]
+    fn test_layer_offsets() {
+        require_gpu!();
+        let root_coset = Coset::half_odds(8);
+        let twiddles = LineTwiddles::new(root_coset);
+        let tree = CudaBackend::precompute_twiddles(root_coset);
+
+        for n_doublings in 0..8 {
+            let domain = LineDomain::new(root_coset.repeated_double(n_doublings));
+            assert_eq!(twiddles.layer_offset(domain), tree.layer_offset(domain));
+        }
+        assert_eq!(twiddles.layer_offset(twiddles.root_domain()), 0);
+        assert_eq!(twiddles.itwiddles().to_vec(), tree.itwiddles.to_vec());
+    }
+
+    #[test]
+    fn test_n_doublings() {
+        let root_coset = Coset::half_odds(8);
+
+        let doubled = LineDomain::new(root_coset.repeated_double(3));
+        let foreign = LineDomain::new(Coset::odds(6));
+
+        assert_eq!(n_doublings(root_coset, doubled), 3);
+        assert!(std::panic::catch_unwind(|| n_doublings(root_coset, foreign)).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::ops::Add;
+
+use stwo_prover::core::backend::Column;
+
+use crate::cuda::{self, BaseFieldVec, SecureFieldVec};
+
+/// A column of fractions `numerators[i] / denominators[i]` stored on the device,
+/// as used when building LogUp interaction columns.
+#[derive(Debug)]
+pub struct FractionVec {
+    pub numerators: SecureFieldVec,
+    pub denominators: SecureFieldVec,
+}
+
+impl FractionVec {
+    pub fn new(numerators: SecureFieldVec, denominators: SecureFieldVec) -> Self {
+        assert_eq!(numerators.size, denominators.size);
+        Self {
+            numerators,
+            denominators,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.numerators.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replaces each fraction by the sum of all the fractions up to and including it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.len()))
+    )]
+    pub fn cumulative_sum(&mut self) {
+        unsafe {
+            cuda::bindings::fractions_cumulative_sum(
+                self.numerators.device_ptr(),
+                self.denominators.device_ptr(),
+                self.len() as u32,
+            );
+        }
+    }
+}
+
+impl Add for &FractionVec {
+    type Output = FractionVec;
+
+    /// Returns the element-wise sum of both columns of fractions.
+    fn add(self, other: Self) -> FractionVec {
+        assert_eq!(self.len(), other.len());
+        let result = FractionVec::new(
+            SecureFieldVec::new_uninitialized(self.len()),
+            SecureFieldVec::new_uninitialized(self.len()),
+        );
+        unsafe {
+            cuda::bindings::add_fractions(
+                self.numerators.device_ptr(),
+                self.denominators.device_ptr(),
+                other.numerators.device_ptr(),
+                other.denominators.device_ptr(),
+                result.numerators.device_ptr(),
+                result.denominators.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+}
+
+/// Counts, for each entry `v` of a lookup table of `table_size` entries, how many times `v`
+/// appears in `columns`, producing the multiplicity column of the table.
+///
+/// Values of the columns are indices into the table. Values outside of it are ignored.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(n_columns = columns.len(), table_size = table_size)
+    )
+)]
+pub fn multiplicities(columns: &[&BaseFieldVec], table_size: usize) -> BaseFieldVec {
+    let result = BaseFieldVec::new_zeroes(table_size);
+    for column in columns {
+        unsafe {
+            cuda::bindings::accumulate_multiplicities(
+                column.device_ptr(),
+                column.len() as u32,
+                result.device_ptr(),
+                table_size as u32,
+            );
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
+
+    use super::{multiplicities, FractionVec};
+    use crate::cuda::{BaseFieldVec, SecureFieldVec};
+
+    fn secure_field_values(size: usize, offset: u32) -> Vec<SecureField> {
+        (offset..offset + 4 * size as u32)
+            .collect::<Vec<u32>>()
+            .chunks(4)
+            .map(|a| SecureField::from_u32_unchecked(a[0], a[1], a[2], a[3]))
+            .collect::<Vec<_>>()
+    }
+
+    fn add_fraction(
+        (numerator_a, denominator_a): (SecureField, SecureField),
+        (numerator_b, denominator_b): (SecureField, SecureField),
+    ) -> (SecureField, SecureField) {
+        (
+            numerator_a * denominator_b + numerator_b * denominator_a,
+            denominator_a * denominator_b,
+        )
+    }
+
+    #[test]
+    fn test_add() {
+        require_gpu!();
+        let size = 1 << 12;
+        let numerators_a = secure_field_values(size, 1);
+        let denominators_a = secure_field_values(size, 2);
+        let numerators_b = secure_field_values(size, 3);
+        let denominators_b = secure_field_values(size, 4);
+        let (expected_numerators, expected_denominators): (Vec<_>, Vec<_>) = (0..size)
+            .map(|i| {
+                add_fraction(
+                    (numerators_a[i], denominators_a[i]),
+                    (numerators_b[i], denominators_b[i]),
+                )
+            })
+            .unzip();
+
+        let a = FractionVec::new(
+            SecureFieldVec::from_vec(numerators_a),
+            SecureFieldVec::from_vec(denominators_a),
+        );
+        let b = FractionVec::new(
+            SecureFieldVec::from_vec(numerators_b),
+            SecureFieldVec::from_vec(denominators_b),
+        );
+        let result = &a + &b;
+
+        assert_eq!(result.numerators.to_vec(), expected_numerators);
+        assert_eq!(result.denominators.to_vec(), expected_denominators);
+    }
+
+    #[test]
+    fn test_cumulative_sum() {
+        require_gpu!();
+        let size = (1 << 12) + 7;
+        let numerators = secure_field_values(size, 1);
+        let denominators = secure_field_values(size, 2);
+        let mut expected_result: Vec<(SecureField, SecureField)> = Vec::with_capacity(size);
+        for fraction in numerators.iter().copied().zip(denominators.iter().copied()) {
+            let accumulated = match expected_result.last() {
+                Some(&last) => add_fraction(last, fraction),
+                None => fraction,
+            };
+            expected_result.push(accumulated);
+        }
+
+        let mut fractions = FractionVec::new(
+            SecureFieldVec::from_vec(numerators),
+            SecureFieldVec::from_vec(denominators),
+        );
+        fractions.cumulative_sum();
+        let result = fractions
+            .numerators
+            .to_vec()
+            .into_iter()
+            .zip(fractions.denominators.to_vec())
+            .collect::<Vec<_>>();
+
+        // Fractions are compared by value since the scan order changes the representation.
+        for ((numerator, denominator), (expected_numerator, expected_denominator)) in
+            result.into_iter().zip(expected_result)
+        {
+            assert_eq!(
+                numerator * expected_denominator,
+                expected_numerator * denominator
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiplicities() {
+        require_gpu!();
+        // One table per histogram kernel: small enough for shared memory, and too large for it.
+        for table_size in [1 << 8, 1 << 14] {
+            let columns = (0..3u32)
+                .map(|i| {
+                    (0..1 << 16)
+                        .map(|j| BaseField::from((j * j + i) % (table_size as u32 + 5)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let mut expected_result = vec![0u32; table_size];
+            for value in columns.iter().flatten() {
+                if let Some(count) = expected_result.get_mut(value.0 as usize) {
+                    *count += 1;
+                }
+            }
+
+            let device_columns = columns
+                .into_iter()
+                .map(BaseFieldVec::from_vec)
+                .collect::<Vec<_>>();
+            let result = multiplicities(&device_columns.iter().collect::<Vec<_>>(), table_size);
+
+            assert_eq!(
+                result.to_vec(),
+                expected_result
+                    .into_iter()
+                    .map(BaseField::from)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
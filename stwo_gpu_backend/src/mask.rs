@@ -0,0 +1,81 @@
+use stwo_prover::core::backend::Column;
+
+use crate::cuda::{self, BaseFieldVec};
+
+/// Materializes, for each mask offset, a column whose row `i` holds the value of `column` at
+/// row `i + offset` of the trace.
+///
+/// `column` is an evaluation in bit reversed order over a circle domain larger than the trace
+/// domain of log size `trace_log_size`, as consumed by constraint evaluation.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(size = column.len(), n_offsets = offsets.len())
+    )
+)]
+pub fn gather_mask(
+    column: &BaseFieldVec,
+    trace_log_size: u32,
+    offsets: &[i32],
+) -> Vec<BaseFieldVec> {
+    let size = column.len();
+    assert!(size.is_power_of_two());
+    let eval_log_size = size.ilog2();
+    assert!(eval_log_size > trace_log_size);
+
+    offsets
+        .iter()
+        .map(|&offset| {
+            let shifted_column = BaseFieldVec::new_uninitialized(size);
+            unsafe {
+                cuda::bindings::gather_mask_base_field(
+                    column.device_ptr(),
+                    shifted_column.device_ptr(),
+                    trace_log_size,
+                    eval_log_size,
+                    offset,
+                );
+            }
+            shifted_column
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column, fields::m31::BaseField, utils::offset_bit_reversed_circle_domain_index,
+    };
+
+    use super::gather_mask;
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_gather_mask() {
+        require_gpu!();
+        let trace_log_size = 10;
+        let eval_log_size = trace_log_size + 2;
+        let size = 1 << eval_log_size;
+        let offsets = [-2, -1, 0, 1, 5];
+        let values = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
+
+        let column = BaseFieldVec::from_vec(values.clone());
+        let result = gather_mask(&column, trace_log_size, &offsets);
+
+        for (shifted_column, offset) in result.iter().zip(offsets) {
+            let expected_result = (0..size)
+                .map(|i| {
+                    values[offset_bit_reversed_circle_domain_index(
+                        i,
+                        trace_log_size,
+                        eval_log_size,
+                        offset,
+                    )]
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(shifted_column.to_cpu(), expected_result);
+        }
+    }
+}
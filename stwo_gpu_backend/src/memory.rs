@@ -0,0 +1,172 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::cuda;
+
+/// Highest device memory held by columns during the phases of a given name, see
+/// [`memory_phase`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseMemory {
+    pub phase: &'static str,
+    /// Bytes of the device vectors alive at the busiest point of any of the phases.
+    pub peak_bytes: usize,
+    /// Number of phases of this name that ended.
+    pub n_runs: usize,
+}
+
+/// The high-water marks of the phases run since the report was last reset, in the order the
+/// phases first ended, as returned by [`memory_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub phases: Vec<PhaseMemory>,
+}
+
+impl MemoryReport {
+    /// The highest peak of all the phases.
+    pub fn peak_bytes(&self) -> usize {
+        self.phases
+            .iter()
+            .map(|phase| phase.peak_bytes)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn phase(&self, phase: &str) -> Option<&PhaseMemory> {
+        self.phases.iter().find(|memory| memory.phase == phase)
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for phase in &self.phases {
+            writeln!(
+                f,
+                "{}: peak {:.1} MiB over {} runs",
+                phase.phase,
+                phase.peak_bytes as f64 / (1 << 20) as f64,
+                phase.n_runs
+            )?;
+        }
+        Ok(())
+    }
+}
+
+static REPORT: Mutex<MemoryReport> = Mutex::new(MemoryReport { phases: vec![] });
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Turns the accounting of device vectors behind [`memory_phase`] and [`device_memory_in_use`]
+/// on or off. It is off by default, as it costs a global lock on every allocation.
+///
+/// Only the vectors allocated while it is on are counted, so turn it on before the prover
+/// allocates anything.
+pub fn set_memory_tracking(enabled: bool) {
+    if TRACKING.swap(enabled, Ordering::AcqRel) != enabled {
+        unsafe {
+            match enabled {
+                true => cuda::bindings::retain_memory_tracking(),
+                false => cuda::bindings::release_memory_tracking(),
+            }
+        }
+    }
+}
+
+/// Starts a phase of the prover named `phase`, which ends when the returned guard is dropped and
+/// then adds the highest device memory held by columns during the phase to the
+/// [`memory_report`], e.g. to choose trace sizes or find the phase that runs out of memory on a
+/// given card.
+///
+/// The backend marks its own `upload`, `commit`, `quotients` and `fri` phases. Phases can nest
+/// and overlap, and the memory of every thread counts, as they share the device. Only device
+/// vectors are tracked: the temporaries of kernels and the scratch buffers, see
+/// [`crate::scratch_buffers_size`], come on top. Nothing is recorded unless
+/// [`set_memory_tracking`] turned the tracking on.
+pub fn memory_phase(phase: &'static str) -> MemoryPhase {
+    MemoryPhase {
+        phase,
+        peak_id: TRACKING
+            .load(Ordering::Acquire)
+            .then(|| unsafe { cuda::bindings::begin_memory_peak() }),
+    }
+}
+
+/// Guard returned by [`memory_phase`].
+#[must_use = "the phase ends when the guard is dropped"]
+pub struct MemoryPhase {
+    phase: &'static str,
+    peak_id: Option<i32>,
+}
+
+impl Drop for MemoryPhase {
+    fn drop(&mut self) {
+        let Some(peak_id) = self.peak_id else {
+            return;
+        };
+        let peak = unsafe { cuda::bindings::end_memory_peak(peak_id) };
+
+        let mut report = REPORT.lock().unwrap();
+        match report
+            .phases
+            .iter_mut()
+            .find(|memory| memory.phase == self.phase)
+        {
+            Some(memory) => {
+                memory.peak_bytes = memory.peak_bytes.max(peak);
+                memory.n_runs += 1;
+            }
+            None => report.phases.push(PhaseMemory {
+                phase: self.phase,
+                peak_bytes: peak,
+                n_runs: 1,
+            }),
+        }
+    }
+}
+
+/// The high-water marks of the phases run since the last [`reset_memory_report`].
+pub fn memory_report() -> MemoryReport {
+    REPORT.lock().unwrap().clone()
+}
+
+pub fn reset_memory_report() {
+    REPORT.lock().unwrap().phases.clear();
+}
+
+/// Bytes of the device vectors alive, on every thread, among those allocated while
+/// [`set_memory_tracking`] had the tracking on.
+pub fn device_memory_in_use() -> usize {
+    unsafe { cuda::bindings::device_memory_in_use() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{memory_phase, memory_report, set_memory_tracking};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_memory_phases() {
+        require_gpu!();
+        set_memory_tracking(true);
+        let outer = memory_phase("test_outer");
+        let column = BaseFieldVec::new_uninitialized(1 << 20);
+        {
+            let _inner = memory_phase("test_inner");
+            let temporary = BaseFieldVec::new_uninitialized(1 << 18);
+            drop(temporary);
+        }
+        drop(column);
+        drop(outer);
+
+        // Other tests may allocate concurrently, so only lower bounds hold.
+        let report = memory_report();
+        let inner = report.phase("test_inner").unwrap();
+        let outer = report.phase("test_outer").unwrap();
+        assert!(inner.peak_bytes >= (4 << 20) + (4 << 18));
+        assert!(outer.peak_bytes >= inner.peak_bytes);
+        assert_eq!(outer.n_runs, 1);
+    }
+}
@@ -0,0 +1,115 @@
+use stwo_prover::core::{
+    backend::{Col, Column},
+    fields::m31::BaseField,
+    vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, ops::MerkleOps},
+};
+
+use crate::{backend::CudaBackend, cuda, watchdog::watch};
+
+impl MerkleOps<Blake2sMerkleHasher> for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(log_size = log_size, n_columns = columns.len())
+        )
+    )]
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Col<Self, Blake2sHash>>,
+        columns: &[&Col<Self, BaseField>],
+    ) -> Col<Self, Blake2sHash> {
+        let _watch = watch("commit_on_layer");
+        if let Some(prev_layer) = prev_layer {
+            assert_eq!(prev_layer.len(), 1 << (log_size + 1));
+        }
+        for column in columns {
+            assert_eq!(column.len(), 1 << log_size);
+        }
+
+        let layer = cuda::Blake2sHashVec::new_uninitialized(1 << log_size);
+        let column_ptrs = columns
+            .iter()
+            .map(|column| column.device_ptr())
+            .collect::<Vec<_>>();
+        unsafe {
+            cuda::bindings::commit_on_layer(
+                log_size,
+                prev_layer.map_or(std::ptr::null(), |prev_layer| prev_layer.device_ptr()),
+                column_ptrs.as_ptr(),
+                columns.len() as u32,
+                layer.device_ptr(),
+            );
+        }
+        layer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        fields::m31::BaseField,
+        vcs::{blake2_merkle::Blake2sMerkleHasher, ops::MerkleOps},
+    };
+
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    fn columns(log_size: u32, n_columns: u32) -> Vec<Vec<BaseField>> {
+        (0..n_columns)
+            .map(|i| {
+                (0..1 << log_size)
+                    .map(|j| BaseField::from(i * 1000 + j))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_commit_on_layer() {
+        require_gpu!();
+        let log_size = 8;
+        // Cover the empty message, a single partial block and several blocks.
+        for (n_leaf_columns, n_node_columns) in [(0, 0), (3, 1), (40, 17)] {
+            let leaf_columns = columns(log_size + 1, n_leaf_columns);
+            let node_columns = columns(log_size, n_node_columns);
+            let gpu_leaf_columns = leaf_columns
+                .iter()
+                .cloned()
+                .map(BaseFieldVec::from_vec)
+                .collect::<Vec<_>>();
+            let gpu_node_columns = node_columns
+                .iter()
+                .cloned()
+                .map(BaseFieldVec::from_vec)
+                .collect::<Vec<_>>();
+
+            let expected_leaves = <CpuBackend as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                log_size + 1,
+                None,
+                &leaf_columns.iter().collect::<Vec<_>>(),
+            );
+            let expected_nodes = <CpuBackend as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&expected_leaves),
+                &node_columns.iter().collect::<Vec<_>>(),
+            );
+
+            let leaves = <CudaBackend as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                log_size + 1,
+                None,
+                &gpu_leaf_columns.iter().collect::<Vec<_>>(),
+            );
+            let nodes = <CudaBackend as MerkleOps<Blake2sMerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&leaves),
+                &gpu_node_columns.iter().collect::<Vec<_>>(),
+            );
+
+            assert_eq!(leaves.to_cpu(), expected_leaves);
+            assert_eq!(nodes.to_cpu(), expected_nodes);
+            assert_eq!(nodes.at(3), expected_nodes[3]);
+        }
+    }
+}
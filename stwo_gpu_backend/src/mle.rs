@@ -0,0 +1,102 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::{m31::BaseField, qm31::SecureField},
+    lookups::mle::{Mle, MleOps},
+};
+
+use crate::{backend::CudaBackend, cuda};
+
+impl MleOps<BaseField> for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_variables = mle.n_variables()))
+    )]
+    fn fix_first_variable(
+        mle: Mle<Self, BaseField>,
+        assignment: SecureField,
+    ) -> Mle<Self, SecureField> {
+        let evals = mle.into_evals();
+        let result = cuda::SecureFieldVec::new_uninitialized(evals.len() / 2);
+        unsafe {
+            cuda::bindings::fix_first_variable_base_field(
+                evals.device_ptr(),
+                evals.len() as u32,
+                assignment,
+                result.device_ptr(),
+            );
+        }
+        Mle::new(result)
+    }
+}
+
+impl MleOps<SecureField> for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_variables = mle.n_variables()))
+    )]
+    fn fix_first_variable(
+        mle: Mle<Self, SecureField>,
+        assignment: SecureField,
+    ) -> Mle<Self, SecureField> {
+        let evals = mle.into_evals();
+        let result = cuda::SecureFieldVec::new_uninitialized(evals.len() / 2);
+        unsafe {
+            cuda::bindings::fix_first_variable_secure_field(
+                evals.device_ptr(),
+                evals.len() as u32,
+                assignment,
+                result.device_ptr(),
+            );
+        }
+        Mle::new(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        fields::{m31::BaseField, qm31::SecureField},
+        lookups::mle::{Mle, MleOps},
+    };
+
+    use crate::{backend::CudaBackend, cuda};
+
+    #[test]
+    fn test_fix_first_variable_base_field() {
+        require_gpu!();
+        let size: usize = 1 << 12;
+        let assignment = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let values = (0..size as u32).map(BaseField::from).collect::<Vec<_>>();
+
+        let cpu_mle = Mle::<CpuBackend, BaseField>::new(values.clone());
+        let expected_result =
+            <CpuBackend as MleOps<BaseField>>::fix_first_variable(cpu_mle, assignment);
+
+        let gpu_mle = Mle::<CudaBackend, BaseField>::new(cuda::BaseFieldVec::from_vec(values));
+        let result = <CudaBackend as MleOps<BaseField>>::fix_first_variable(gpu_mle, assignment);
+
+        assert_eq!(result.into_evals().to_cpu(), expected_result.into_evals());
+    }
+
+    #[test]
+    fn test_fix_first_variable_secure_field() {
+        require_gpu!();
+        let size: usize = 1 << 12;
+        let assignment = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let from_raw = (1..(4 * size + 1) as u32).collect::<Vec<u32>>();
+        let values = from_raw
+            .chunks(4)
+            .map(|a| SecureField::from_u32_unchecked(a[0], a[1], a[2], a[3]))
+            .collect::<Vec<_>>();
+
+        let cpu_mle = Mle::<CpuBackend, SecureField>::new(values.clone());
+        let expected_result =
+            <CpuBackend as MleOps<SecureField>>::fix_first_variable(cpu_mle, assignment);
+
+        let gpu_mle = Mle::<CudaBackend, SecureField>::new(cuda::SecureFieldVec::from_vec(values));
+        let result = <CudaBackend as MleOps<SecureField>>::fix_first_variable(gpu_mle, assignment);
+
+        assert_eq!(result.into_evals().to_cpu(), expected_result.into_evals());
+    }
+}
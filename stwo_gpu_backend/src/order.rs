@@ -0,0 +1,253 @@
+use stwo_prover::core::{backend::Column, fields::secure_column::SecureColumn};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+    stats::column_stats,
+};
+
+/// Reorders a trace column from row order, as produced by row-oriented trace generators, to the
+/// bit reversed circle domain order of the evaluations committed to, without leaving the device.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+)]
+pub fn to_circle_domain_order(column: &BaseFieldVec) -> BaseFieldVec {
+    let log_size = log_size(column);
+    let result = BaseFieldVec::new_uninitialized(column.len());
+    unsafe {
+        cuda::bindings::natural_to_circle_domain_order(
+            column.device_ptr(),
+            result.device_ptr(),
+            log_size,
+        );
+    }
+    result
+}
+
+/// Inverse of [`to_circle_domain_order`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+)]
+pub fn to_natural_order(column: &BaseFieldVec) -> BaseFieldVec {
+    let log_size = log_size(column);
+    let result = BaseFieldVec::new_uninitialized(column.len());
+    unsafe {
+        cuda::bindings::circle_domain_to_natural_order(
+            column.device_ptr(),
+            result.device_ptr(),
+            log_size,
+        );
+    }
+    result
+}
+
+/// Arbitrary permutations of device columns, given by a column of indices, e.g. to build
+/// interaction columns in a custom order.
+///
+/// # Panics
+///
+/// If the lengths differ, or an index is not in `0..len`, where `len` is the length of both
+/// columns.
+pub trait Permute {
+    /// The column whose value `i` is value `indices[i]` of this one. Indices may repeat.
+    fn gather(&self, indices: &BaseFieldVec) -> Self;
+
+    /// The column whose value `indices[i]` is value `i` of this one, the inverse of
+    /// [`Permute::gather`]. Indices must be a permutation of `0..len`.
+    fn scatter(&self, indices: &BaseFieldVec) -> Self;
+}
+
+impl Permute for BaseFieldVec {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.len()))
+    )]
+    fn gather(&self, indices: &BaseFieldVec) -> Self {
+        check_indices(indices, self.len());
+        let result = BaseFieldVec::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::gather_base_field(
+                self.device_ptr(),
+                indices.device_ptr(),
+                self.len() as u32,
+                result.device_ptr(),
+            );
+        }
+        result
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.len()))
+    )]
+    fn scatter(&self, indices: &BaseFieldVec) -> Self {
+        check_indices(indices, self.len());
+        let result = BaseFieldVec::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::scatter_base_field(
+                self.device_ptr(),
+                indices.device_ptr(),
+                self.len() as u32,
+                result.device_ptr(),
+            );
+        }
+        result
+    }
+}
+
+impl Permute for SecureColumn<CudaBackend> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.len()))
+    )]
+    fn gather(&self, indices: &BaseFieldVec) -> Self {
+        check_indices(indices, self.len());
+        let result = cuda::new_uninitialized_secure_column(self.len());
+        unsafe {
+            cuda::bindings::gather_secure_field(
+                cuda::secure_column_device_ptrs(self).as_ptr(),
+                indices.device_ptr(),
+                self.len() as u32,
+                cuda::secure_column_device_ptrs(&result).as_ptr(),
+            );
+        }
+        result
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.len()))
+    )]
+    fn scatter(&self, indices: &BaseFieldVec) -> Self {
+        check_indices(indices, self.len());
+        let result = cuda::new_uninitialized_secure_column(self.len());
+        unsafe {
+            cuda::bindings::scatter_secure_field(
+                cuda::secure_column_device_ptrs(self).as_ptr(),
+                indices.device_ptr(),
+                self.len() as u32,
+                cuda::secure_column_device_ptrs(&result).as_ptr(),
+            );
+        }
+        result
+    }
+}
+
+fn check_indices(indices: &BaseFieldVec, len: usize) {
+    assert_eq!(indices.len(), len);
+    let n_out_of_range = column_stats(indices, 0..len as u32).n_out_of_range;
+    assert_eq!(
+        n_out_of_range, 0,
+        "{n_out_of_range} indices out of 0..{len}"
+    );
+}
+
+fn log_size(column: &BaseFieldVec) -> u32 {
+    let size = column.len();
+    assert!(size.is_power_of_two() && size >= 2);
+    size.ilog2()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        fields::{m31::BaseField, secure_column::SecureColumn},
+        utils::{bit_reverse_index, coset_index_to_circle_domain_index},
+    };
+
+    use super::{to_circle_domain_order, to_natural_order, Permute};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    #[test]
+    fn test_to_circle_domain_order() {
+        require_gpu!();
+        let log_size = 10;
+        let rows = (0..1 << log_size).map(BaseField::from).collect::<Vec<_>>();
+        let mut expected_result = vec![BaseField::from(0); rows.len()];
+        for (row, &value) in rows.iter().enumerate() {
+            let index = coset_index_to_circle_domain_index(row, log_size);
+            expected_result[bit_reverse_index(index, log_size)] = value;
+        }
+
+        let column = to_circle_domain_order(&BaseFieldVec::from_vec(rows.clone()));
+
+        assert_eq!(column.to_cpu(), expected_result);
+        assert_eq!(to_natural_order(&column).to_cpu(), rows);
+    }
+
+    #[test]
+    fn test_gather_out_of_range() {
+        require_gpu!();
+        let column = BaseFieldVec::from_vec((0..16).map(BaseField::from).collect());
+        let mut indices = (0..16).map(BaseField::from).collect::<Vec<_>>();
+        indices[3] = BaseField::from(16);
+        let indices = BaseFieldVec::from_vec(indices);
+
+        let result = std::panic::catch_unwind(|| column.gather(&indices));
+
+        assert!(result.is_err());
+    }
+
+    fn permutation(size: u32) -> Vec<u32> {
+        // Multiplication by an odd constant permutes 0..size for a power of two size.
+        (0..size)
+            .map(|i| i.wrapping_mul(0x9e3779b9) % size)
+            .collect()
+    }
+
+    #[test]
+    fn test_gather_and_scatter_base_field() {
+        require_gpu!();
+        let size = 1 << 10;
+        let permutation = permutation(size);
+        let values = (0..size)
+            .map(|i| BaseField::from(i * 7))
+            .collect::<Vec<_>>();
+        let indices = BaseFieldVec::from_vec(permutation.iter().map(|&i| i.into()).collect());
+
+        let column = BaseFieldVec::from_vec(values.clone());
+        let gathered = column.gather(&indices);
+
+        assert_eq!(
+            gathered.to_cpu(),
+            permutation
+                .iter()
+                .map(|&i| values[i as usize])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(gathered.scatter(&indices).to_cpu(), values);
+    }
+
+    #[test]
+    fn test_gather_and_scatter_secure_field() {
+        require_gpu!();
+        let size = 1 << 10;
+        let permutation = permutation(size);
+        let columns: [Vec<BaseField>; 4] = std::array::from_fn(|c| {
+            (0..size)
+                .map(|i| BaseField::from(4 * i + c as u32))
+                .collect()
+        });
+        let indices = BaseFieldVec::from_vec(permutation.iter().map(|&i| i.into()).collect());
+
+        let column = SecureColumn::<CudaBackend> {
+            columns: columns.clone().map(BaseFieldVec::from_vec),
+        };
+        let gathered = column.gather(&indices);
+        let scattered = gathered.scatter(&indices);
+
+        for c in 0..4 {
+            assert_eq!(
+                gathered.columns[c].to_cpu(),
+                permutation
+                    .iter()
+                    .map(|&i| columns[c][i as usize])
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(scattered.columns[c].to_cpu(), columns[c]);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use stwo_prover::core::backend::Column;
+
+use crate::cuda::{self, BaseFieldVec};
+
+/// How the values past the end of a padded column are filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    Zeros,
+    /// The column is repeated until the new size is reached.
+    Repeat,
+}
+
+/// Copies `column` into a new column of `size` values, filling the rest according to `padding`.
+pub fn pad(column: &BaseFieldVec, size: usize, padding: Padding) -> BaseFieldVec {
+    assert!(
+        size >= column.len(),
+        "New size must be larger than the old size"
+    );
+    assert!(
+        padding == Padding::Zeros || column.size > 0,
+        "Cannot repeat an empty column"
+    );
+
+    let result = BaseFieldVec::new_uninitialized(size);
+    unsafe {
+        cuda::bindings::pad_base_field(
+            column.device_ptr(),
+            column.len() as u32,
+            result.device_ptr(),
+            size as u32,
+            padding == Padding::Repeat,
+        );
+    }
+    result
+}
+
+/// Pads `column` to the next power of two.
+pub fn pad_to_power_of_two(column: &BaseFieldVec, padding: Padding) -> BaseFieldVec {
+    pad(column, column.len().next_power_of_two(), padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{backend::Column, fields::m31::BaseField};
+
+    use super::{pad, pad_to_power_of_two, Padding};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_pad_with_zeros() {
+        require_gpu!();
+        let values = (1..1001).map(BaseField::from).collect::<Vec<_>>();
+        let column = BaseFieldVec::from_vec(values.clone());
+
+        let result = pad_to_power_of_two(&column, Padding::Zeros);
+
+        let mut expected_result = values;
+        expected_result.resize(1024, BaseField::from(0));
+        assert_eq!(result.to_cpu(), expected_result);
+    }
+
+    #[test]
+    fn test_pad_with_repetition() {
+        require_gpu!();
+        let values = (1..13).map(BaseField::from).collect::<Vec<_>>();
+        let column = BaseFieldVec::from_vec(values.clone());
+
+        let result = pad(&column, 40, Padding::Repeat);
+
+        let expected_result = values.iter().cycle().take(40).copied().collect::<Vec<_>>();
+        assert_eq!(result.to_cpu(), expected_result);
+    }
+}
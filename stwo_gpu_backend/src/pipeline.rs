@@ -0,0 +1,275 @@
+use std::cmp::Reverse;
+
+use stwo_prover::core::{
+    backend::Column,
+    fields::qm31::SecureField,
+    poly::line::LineEvaluation,
+    vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec, Blake2sHashVec},
+    line_twiddles::LineTwiddleLayers,
+    stream::{Pending, Stream},
+};
+
+type Tree = MerkleProver<CudaBackend, Blake2sMerkleHasher>;
+
+/// Same as [`MerkleProver::commit`], queuing the hashing of every layer on `stream` without
+/// blocking the host.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_columns = columns.len()))
+)]
+pub fn commit_on_stream<'a>(columns: &[&'a BaseFieldVec], stream: &'a Stream) -> Pending<'a, Tree> {
+    Pending::new(queue_commit(columns, stream), stream)
+}
+
+/// Same as [`stwo_prover::core::fri::FriOps::fold_line`], queuing the folding on `stream`
+/// without blocking the host.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = eval.len()))
+)]
+pub fn fold_line_on_stream<'a>(
+    eval: &'a LineEvaluation<CudaBackend>,
+    alpha: SecureField,
+    twiddles: &'a impl LineTwiddleLayers,
+    stream: &'a Stream,
+) -> Pending<'a, LineEvaluation<CudaBackend>> {
+    let n = eval.len();
+    assert!(n >= 2, "Evaluation too small");
+    let domain = eval.domain();
+
+    let folded_values = cuda::new_uninitialized_secure_column(n >> 1);
+    let twiddle_offset = twiddles.layer_offset(domain);
+    unsafe {
+        cuda::bindings::stream_wait_default_stream(stream.ptr);
+        cuda::bindings::fold_line_on_stream(
+            cuda::secure_column_device_ptrs(&eval.values).as_ptr(),
+            cuda::secure_column_device_ptrs(&folded_values).as_ptr(),
+            n as u32,
+            twiddles.itwiddles().device_ptr(),
+            twiddle_offset,
+            alpha,
+            stream.ptr,
+        );
+    }
+    let used = eval.values.columns.iter().chain(&folded_values.columns);
+    cuda::free_after(used.map(|column| column.device_ptr()), stream);
+    Pending::new(LineEvaluation::new(domain.double(), folded_values), stream)
+}
+
+/// Schedules the phases of a proof over several trees so that the Merkle hashing of tree `i + 1`
+/// runs on the device while tree `i` is being folded.
+///
+/// Hashing and folding are queued on two [`Stream::non_blocking`] streams, ordered by events:
+/// the folding of a tree only starts once its hashing is done, and nothing else waits.
+pub struct PhasePipeline {
+    hash_stream: Stream,
+    fold_stream: Stream,
+}
+
+impl PhasePipeline {
+    pub fn new() -> Self {
+        Self {
+            hash_stream: Stream::non_blocking(),
+            fold_stream: Stream::non_blocking(),
+        }
+    }
+
+    /// Commits to each tree of `trees` in order, calling `fold` with the index of every tree, the
+    /// tree once committed, and the stream to queue its folding on, e.g. with
+    /// [`fold_line_on_stream`]. Returns the committed trees and the results of `fold`.
+    ///
+    /// The hashing of the next tree is already queued when `fold` is called, so `fold` can read
+    /// the root of its tree, draw its folding randomness and even wait for its folding to be
+    /// done without stalling the hashing. It must not queue work on the default stream, which
+    /// isn't ordered with the pipeline streams.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_trees = trees.len()))
+    )]
+    pub fn run<'a, T>(
+        &'a self,
+        trees: &[Vec<&'a BaseFieldVec>],
+        mut fold: impl FnMut(usize, &Tree, &'a Stream) -> T,
+    ) -> (Vec<Tree>, Vec<T>) {
+        let queue_tree = |columns: &[&'a BaseFieldVec]| {
+            let tree = queue_commit(columns, &self.hash_stream);
+            (tree, self.hash_stream.record())
+        };
+
+        let mut committed = Vec::with_capacity(trees.len());
+        let mut results = Vec::with_capacity(trees.len());
+        let mut next = trees.first().map(|columns| queue_tree(columns));
+        for i in 0..trees.len() {
+            let (tree, hashed) = next.take().unwrap();
+            next = trees.get(i + 1).map(|columns| queue_tree(columns));
+
+            // The host reads the root through the default stream, so it waits for the hashing
+            // itself rather than only ordering the folding after it.
+            hashed.synchronize();
+            self.fold_stream.wait_for(&hashed);
+            results.push(fold(i, &tree, &self.fold_stream));
+            committed.push(tree);
+        }
+        self.fold_stream.synchronize();
+        (committed, results)
+    }
+}
+
+impl Default for PhasePipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queues the layers of the tree of `columns` on `stream`, with the layout of
+/// [`MerkleProver::commit`]. The tree can only be read once the stream is done with it.
+fn queue_commit(columns: &[&BaseFieldVec], stream: &Stream) -> Tree {
+    assert!(!columns.is_empty());
+    let mut columns = columns.to_vec();
+    columns.sort_by_key(|column| Reverse(column.len()));
+    let max_log_size = columns[0].len().ilog2();
+
+    let mut layers: Vec<Blake2sHashVec> = vec![];
+    let mut remaining = columns.as_slice();
+    for log_size in (0..=max_log_size).rev() {
+        let n_layer_columns = remaining
+            .iter()
+            .take_while(|column| column.len() == 1 << log_size)
+            .count();
+        let (layer_columns, rest) = remaining.split_at(n_layer_columns);
+        remaining = rest;
+
+        let layer = Blake2sHashVec::new_uninitialized(1 << log_size);
+        let column_ptrs = layer_columns
+            .iter()
+            .map(|column| column.device_ptr())
+            .collect::<Vec<_>>();
+        unsafe {
+            // The layer is allocated on the default stream.
+            cuda::bindings::stream_wait_default_stream(stream.ptr);
+            cuda::bindings::commit_on_layer_on_stream(
+                log_size,
+                layers
+                    .last()
+                    .map_or(std::ptr::null(), |prev_layer| prev_layer.device_ptr()),
+                column_ptrs.as_ptr(),
+                column_ptrs.len() as u32,
+                layer.device_ptr(),
+                stream.ptr,
+            );
+        }
+        layers.push(layer);
+    }
+    assert!(remaining.is_empty(), "column sizes must be powers of two");
+    let used = columns
+        .iter()
+        .map(|column| column.device_ptr())
+        .chain(layers.iter().map(|layer| layer.device_ptr()));
+    cuda::free_after(used, stream);
+    layers.reverse();
+    MerkleProver { layers }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        circle::Coset,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+        fri::FriOps,
+        poly::{
+            circle::PolyOps,
+            line::{LineDomain, LineEvaluation},
+        },
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{commit_on_stream, fold_line_on_stream, PhasePipeline};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec, stream::Stream};
+
+    fn column(log_size: u32, offset: u32) -> Vec<BaseField> {
+        (0..1 << log_size)
+            .map(|i| BaseField::from(offset + i))
+            .collect()
+    }
+
+    #[test]
+    fn test_commit_on_stream() {
+        require_gpu!();
+        let values = [(6, 0), (8, 1), (6, 2), (3, 3)].map(|(log_size, i)| column(log_size, i));
+        let columns = values.clone().map(BaseFieldVec::from_vec);
+        let stream = Stream::non_blocking();
+
+        let tree = commit_on_stream(&columns.iter().collect::<Vec<_>>(), &stream).wait();
+
+        let cpu_tree =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(values.iter().collect());
+        assert_eq!(tree.root(), cpu_tree.root());
+    }
+
+    #[test]
+    fn test_phase_pipeline() {
+        require_gpu!();
+        let log_size = 10;
+        let root_coset = Coset::half_odds(log_size);
+        let twiddles = CudaBackend::precompute_twiddles(root_coset);
+        let trees = (0..3)
+            .map(|i| {
+                (0..4)
+                    .map(|j| column(log_size - j, 100 * i + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let device_trees = trees
+            .iter()
+            .map(|columns| {
+                columns
+                    .iter()
+                    .cloned()
+                    .map(BaseFieldVec::from_vec)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let evals = trees
+            .iter()
+            .map(|columns| {
+                LineEvaluation::<CudaBackend>::new(
+                    LineDomain::new(root_coset),
+                    SecureColumn {
+                        columns: std::array::from_fn(|_| {
+                            BaseFieldVec::from_vec(columns[0].clone())
+                        }),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let alpha = |i: usize| SecureField::from_u32_unchecked(i as u32 + 1, 2, 3, 4);
+
+        let pipeline = PhasePipeline::new();
+        let (committed, folded) = pipeline.run(
+            &device_trees
+                .iter()
+                .map(|columns| columns.iter().collect())
+                .collect::<Vec<_>>(),
+            |i, tree, stream| {
+                assert_eq!(tree.layers.len(), log_size as usize + 1);
+                fold_line_on_stream(&evals[i], alpha(i), &twiddles, stream)
+                    .wait()
+                    .values
+            },
+        );
+
+        for (i, columns) in trees.iter().enumerate() {
+            let cpu_tree =
+                MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(columns.iter().collect());
+            assert_eq!(committed[i].root(), cpu_tree.root());
+            let expected = CudaBackend::fold_line(&evals[i], alpha(i), &twiddles);
+            assert_eq!(folded[i].columns, expected.values.columns);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::ops::Add;
+
+use stwo_prover::core::{
+    backend::Column,
+    circle::{CirclePoint, Coset},
+    fields::{m31::BaseField, qm31::SecureField},
+};
+
+use crate::cuda::{self, BaseFieldVec, SecureFieldVec};
+
+/// Circle points stored on the device as a column of x coordinates and a column of y
+/// coordinates.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CirclePointVec<C> {
+    pub x: C,
+    pub y: C,
+}
+
+impl CirclePointVec<BaseFieldVec> {
+    pub fn from_points(points: &[CirclePoint<BaseField>]) -> Self {
+        Self {
+            x: BaseFieldVec::from_vec(points.iter().map(|p| p.x).collect()),
+            y: BaseFieldVec::from_vec(points.iter().map(|p| p.y).collect()),
+        }
+    }
+
+    /// The points of `coset`, in natural order.
+    pub fn coset(coset: Coset) -> Self {
+        let result = Self::new_uninitialized(coset.size());
+        unsafe {
+            cuda::bindings::coset_points(
+                coset.initial.into(),
+                coset.step.into(),
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                coset.size() as u32,
+            );
+        }
+        result
+    }
+
+    pub fn to_points(&self) -> Vec<CirclePoint<BaseField>> {
+        self.x
+            .to_cpu()
+            .into_iter()
+            .zip(self.y.to_cpu())
+            .map(|(x, y)| CirclePoint { x, y })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn double(&self) -> Self {
+        let result = Self::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::double_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+
+    pub fn scalar_mul(&self, scalar: u32) -> Self {
+        let result = Self::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::mul_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                scalar,
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+
+    fn new_uninitialized(size: usize) -> Self {
+        Self {
+            x: BaseFieldVec::new_uninitialized(size),
+            y: BaseFieldVec::new_uninitialized(size),
+        }
+    }
+}
+
+impl Add for &CirclePointVec<BaseFieldVec> {
+    type Output = CirclePointVec<BaseFieldVec>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.len(), rhs.len());
+        let result = CirclePointVec::<BaseFieldVec>::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::add_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                rhs.x.device_ptr(),
+                rhs.y.device_ptr(),
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+}
+
+impl CirclePointVec<SecureFieldVec> {
+    pub fn from_points(points: &[CirclePoint<SecureField>]) -> Self {
+        Self {
+            x: SecureFieldVec::from_vec(points.iter().map(|p| p.x).collect()),
+            y: SecureFieldVec::from_vec(points.iter().map(|p| p.y).collect()),
+        }
+    }
+
+    pub fn to_points(&self) -> Vec<CirclePoint<SecureField>> {
+        self.x
+            .to_cpu()
+            .into_iter()
+            .zip(self.y.to_cpu())
+            .map(|(x, y)| CirclePoint { x, y })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn double(&self) -> Self {
+        let result = Self::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::double_secure_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+
+    pub fn scalar_mul(&self, scalar: u32) -> Self {
+        let result = Self::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::mul_secure_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                scalar,
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+
+    fn new_uninitialized(size: usize) -> Self {
+        Self {
+            x: SecureFieldVec::new_uninitialized(size),
+            y: SecureFieldVec::new_uninitialized(size),
+        }
+    }
+}
+
+impl Add for &CirclePointVec<SecureFieldVec> {
+    type Output = CirclePointVec<SecureFieldVec>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.len(), rhs.len());
+        let result = CirclePointVec::<SecureFieldVec>::new_uninitialized(self.len());
+        unsafe {
+            cuda::bindings::add_secure_points(
+                self.x.device_ptr(),
+                self.y.device_ptr(),
+                rhs.x.device_ptr(),
+                rhs.y.device_ptr(),
+                result.x.device_ptr(),
+                result.y.device_ptr(),
+                self.len() as u32,
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        circle::{CirclePoint, Coset, M31_CIRCLE_GEN, SECURE_FIELD_CIRCLE_GEN},
+        fields::{m31::BaseField, qm31::SecureField},
+    };
+
+    use super::CirclePointVec;
+    use crate::cuda::{BaseFieldVec, SecureFieldVec};
+
+    const SCALARS: [u32; 4] = [0, 1, 12345, u32::MAX];
+
+    #[test]
+    fn test_base_field_point_ops() {
+        require_gpu!();
+        let lhs = (1..100u128)
+            .map(|i| M31_CIRCLE_GEN.mul(i * 3))
+            .collect::<Vec<CirclePoint<BaseField>>>();
+        let rhs = (1..100u128)
+            .map(|i| M31_CIRCLE_GEN.mul(i * 11 + 5))
+            .collect::<Vec<CirclePoint<BaseField>>>();
+        let gpu_lhs = CirclePointVec::<BaseFieldVec>::from_points(&lhs);
+        let gpu_rhs = CirclePointVec::<BaseFieldVec>::from_points(&rhs);
+
+        let expected_sum = lhs
+            .iter()
+            .zip(&rhs)
+            .map(|(&a, &b)| a + b)
+            .collect::<Vec<_>>();
+        let expected_double = lhs.iter().map(|p| p.double()).collect::<Vec<_>>();
+        assert_eq!((&gpu_lhs + &gpu_rhs).to_points(), expected_sum);
+        assert_eq!(gpu_lhs.double().to_points(), expected_double);
+        for scalar in SCALARS {
+            let expected_result = lhs
+                .iter()
+                .map(|p| p.mul(scalar as u128))
+                .collect::<Vec<_>>();
+            assert_eq!(gpu_lhs.scalar_mul(scalar).to_points(), expected_result);
+        }
+    }
+
+    #[test]
+    fn test_secure_field_point_ops() {
+        require_gpu!();
+        let lhs = (1..100u128)
+            .map(|i| SECURE_FIELD_CIRCLE_GEN.mul(i * 3))
+            .collect::<Vec<CirclePoint<SecureField>>>();
+        let rhs = (1..100u128)
+            .map(|i| SECURE_FIELD_CIRCLE_GEN.mul(i * 11 + 5))
+            .collect::<Vec<CirclePoint<SecureField>>>();
+        let gpu_lhs = CirclePointVec::<SecureFieldVec>::from_points(&lhs);
+        let gpu_rhs = CirclePointVec::<SecureFieldVec>::from_points(&rhs);
+
+        let expected_sum = lhs
+            .iter()
+            .zip(&rhs)
+            .map(|(&a, &b)| a + b)
+            .collect::<Vec<_>>();
+        let expected_double = lhs.iter().map(|p| p.double()).collect::<Vec<_>>();
+        assert_eq!((&gpu_lhs + &gpu_rhs).to_points(), expected_sum);
+        assert_eq!(gpu_lhs.double().to_points(), expected_double);
+        for scalar in SCALARS {
+            let expected_result = lhs
+                .iter()
+                .map(|p| p.mul(scalar as u128))
+                .collect::<Vec<_>>();
+            assert_eq!(gpu_lhs.scalar_mul(scalar).to_points(), expected_result);
+        }
+    }
+
+    #[test]
+    fn test_coset_points() {
+        require_gpu!();
+        let coset = Coset::half_odds(10);
+        let points = CirclePointVec::<BaseFieldVec>::coset(coset);
+        assert_eq!(points.to_points(), coset.iter().collect::<Vec<_>>());
+    }
+}
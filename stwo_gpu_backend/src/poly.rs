@@ -12,27 +12,41 @@ use stwo_prover::core::{
 use crate::{
     backend::CudaBackend,
     cuda::{self},
+    watchdog::watch,
 };
 
 impl PolyOps for CudaBackend {
     type Twiddles = cuda::BaseFieldVec;
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = values.len()))
+    )]
     fn new_canonical_ordered(
         coset: CanonicCoset,
         values: Col<Self, BaseField>,
     ) -> CircleEvaluation<Self, BaseField, BitReversedOrder> {
+        let _watch = watch("new_canonical_ordered");
         let size = values.len();
         let device_ptr = unsafe {
-            cuda::bindings::sort_values_and_permute_with_bit_reverse_order(values.device_ptr, size)
+            cuda::bindings::sort_values_and_permute_with_bit_reverse_order(
+                values.device_ptr(),
+                size,
+            )
         };
         let result = cuda::BaseFieldVec::new(device_ptr, size);
         CircleEvaluation::new(coset.circle_domain(), result)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = eval.len()))
+    )]
     fn interpolate(
         eval: CircleEvaluation<Self, BaseField, BitReversedOrder>,
         twiddle_tree: &TwiddleTree<Self>,
     ) -> CirclePoly<Self> {
+        let _watch = watch("interpolate");
         let values = eval.values;
         assert!(eval
             .domain
@@ -40,18 +54,23 @@ impl PolyOps for CudaBackend {
             .is_doubling_of(twiddle_tree.root_coset));
         unsafe {
             cuda::bindings::interpolate(
-                values.device_ptr,
-                twiddle_tree.itwiddles.device_ptr,
+                values.device_ptr(),
+                twiddle_tree.itwiddles.device_ptr(),
                 values.len() as u32,
             );
         }
         CirclePoly::new(values)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = poly.coeffs.len()))
+    )]
     fn eval_at_point(poly: &CirclePoly<Self>, point: CirclePoint<SecureField>) -> SecureField {
+        let _watch = watch("eval_at_point");
         unsafe {
             cuda::bindings::eval_at_point(
-                poly.coeffs.device_ptr,
+                poly.coeffs.device_ptr(),
                 poly.coeffs.len() as u32,
                 point.x,
                 point.y,
@@ -59,7 +78,16 @@ impl PolyOps for CudaBackend {
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(size = poly.coeffs.len(), log_size = log_size)
+        )
+    )]
     fn extend(poly: &CirclePoly<Self>, log_size: u32) -> CirclePoly<Self> {
+        let _watch = watch("extend");
         let new_size = 1 << log_size;
         assert!(
             new_size >= poly.coeffs.len(),
@@ -71,17 +99,26 @@ impl PolyOps for CudaBackend {
         CirclePoly::new(new_coeffs)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(size = poly.coeffs.len(), log_size = domain.log_size())
+        )
+    )]
     fn evaluate(
         poly: &CirclePoly<Self>,
         domain: CircleDomain,
         twiddle_tree: &TwiddleTree<Self>,
     ) -> CircleEvaluation<Self, BaseField, BitReversedOrder> {
+        let _watch = watch("evaluate");
         let values = poly.extend(domain.log_size()).coeffs;
         assert!(domain.half_coset.is_doubling_of(twiddle_tree.root_coset));
         unsafe {
             cuda::bindings::evaluate(
-                values.device_ptr,
-                twiddle_tree.twiddles.device_ptr,
+                values.device_ptr(),
+                twiddle_tree.twiddles.device_ptr(),
                 values.len() as u32,
             );
         }
@@ -89,7 +126,12 @@ impl PolyOps for CudaBackend {
         CircleEvaluation::new(domain, values)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(log_size = coset.log_size()))
+    )]
     fn precompute_twiddles(coset: Coset) -> TwiddleTree<Self> {
+        let _watch = watch("precompute_twiddles");
         unsafe {
             let twiddles = cuda::BaseFieldVec::new(
                 cuda::bindings::precompute_twiddles(
@@ -101,8 +143,8 @@ impl PolyOps for CudaBackend {
             );
             let itwiddles = cuda::BaseFieldVec::new_uninitialized(coset.size());
             cuda::bindings::batch_inverse_base_field(
-                twiddles.device_ptr,
-                itwiddles.device_ptr,
+                twiddles.device_ptr(),
+                itwiddles.device_ptr(),
                 coset.size(),
             );
             TwiddleTree {
@@ -114,6 +156,116 @@ impl PolyOps for CudaBackend {
     }
 }
 
+impl CudaBackend {
+    /// Evaluates each of `polys` at each of `points` in a single launch.
+    ///
+    /// Returns one vector per polynomial holding its values at `points`, in order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(n_polys = polys.len(), n_points = points.len())
+        )
+    )]
+    pub fn eval_polys_at_points(
+        polys: &[&CirclePoly<Self>],
+        points: &[CirclePoint<SecureField>],
+    ) -> Vec<Vec<SecureField>> {
+        if points.is_empty() {
+            return vec![vec![]; polys.len()];
+        }
+
+        let coeffs = polys
+            .iter()
+            .map(|poly| poly.coeffs.device_ptr())
+            .collect::<Vec<_>>();
+        let log_sizes = polys.iter().map(|poly| poly.log_size()).collect::<Vec<_>>();
+        let points_x = points.iter().map(|point| point.x).collect::<Vec<_>>();
+        let points_y = points.iter().map(|point| point.y).collect::<Vec<_>>();
+        let mut result =
+            vec![SecureField::from_u32_unchecked(0, 0, 0, 0); polys.len() * points.len()];
+        unsafe {
+            cuda::bindings::eval_polys_at_points(
+                coeffs.as_ptr(),
+                log_sizes.as_ptr(),
+                polys.len() as u32,
+                points_x.as_ptr(),
+                points_y.as_ptr(),
+                points.len() as u32,
+                result.as_mut_ptr(),
+            );
+        }
+        result
+            .chunks(points.len())
+            .map(|values| values.to_vec())
+            .collect()
+    }
+
+    /// Evaluates each of `polys` at its own `sample_points`, e.g. the out of domain point shifted
+    /// by each offset of its mask, in a single launch.
+    ///
+    /// Points shared by several polynomials are only prepared once. Returns one vector per
+    /// polynomial holding its values at its sample points, in order.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(n_polys = polys.len()))
+    )]
+    pub fn eval_polys_at_sample_points(
+        polys: &[&CirclePoly<Self>],
+        sample_points: &[Vec<CirclePoint<SecureField>>],
+    ) -> Vec<Vec<SecureField>> {
+        assert_eq!(polys.len(), sample_points.len());
+
+        let mut points: Vec<CirclePoint<SecureField>> = vec![];
+        let mut sample_polys = vec![];
+        let mut sample_point_indices = vec![];
+        for (poly_index, poly_points) in sample_points.iter().enumerate() {
+            for point in poly_points {
+                // There are only a few distinct points, so a linear search is enough.
+                let point_index = points.iter().position(|p| p == point).unwrap_or_else(|| {
+                    points.push(*point);
+                    points.len() - 1
+                });
+                sample_polys.push(poly_index as u32);
+                sample_point_indices.push(point_index as u32);
+            }
+        }
+        if sample_polys.is_empty() {
+            return vec![vec![]; polys.len()];
+        }
+
+        let coeffs = polys
+            .iter()
+            .map(|poly| poly.coeffs.device_ptr())
+            .collect::<Vec<_>>();
+        let log_sizes = polys.iter().map(|poly| poly.log_size()).collect::<Vec<_>>();
+        let points_x = points.iter().map(|point| point.x).collect::<Vec<_>>();
+        let points_y = points.iter().map(|point| point.y).collect::<Vec<_>>();
+        let mut result = vec![SecureField::from_u32_unchecked(0, 0, 0, 0); sample_polys.len()];
+        unsafe {
+            cuda::bindings::eval_samples(
+                coeffs.as_ptr(),
+                log_sizes.as_ptr(),
+                polys.len() as u32,
+                points_x.as_ptr(),
+                points_y.as_ptr(),
+                points.len() as u32,
+                sample_polys.as_ptr(),
+                sample_point_indices.as_ptr(),
+                sample_polys.len() as u32,
+                result.as_mut_ptr(),
+            );
+        }
+
+        let mut values = result.into_iter();
+        sample_points
+            .iter()
+            .map(|poly_points| values.by_ref().take(poly_points.len()).collect())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{backend::CudaBackend, cuda};
@@ -126,6 +278,7 @@ mod tests {
 
     #[test]
     fn test_new_canonical_ordered() {
+        require_gpu!();
         let log_size = 23;
         let coset = CanonicCoset::new(log_size);
         let size: usize = 1 << log_size;
@@ -145,6 +298,7 @@ mod tests {
 
     #[test]
     fn test_precompute_twiddles() {
+        require_gpu!();
         let log_size = 3;
 
         let half_coset = CanonicCoset::new(log_size).half_coset();
@@ -161,6 +315,7 @@ mod tests {
 
     #[test]
     fn test_extend() {
+        require_gpu!();
         let log_size = 20;
         let size = 1 << log_size;
         let new_log_size = log_size + 5;
@@ -176,6 +331,7 @@ mod tests {
 
     #[test]
     fn test_interpolate() {
+        require_gpu!();
         let log_size = 20;
 
         let size = 1 << log_size;
@@ -200,6 +356,7 @@ mod tests {
 
     #[test]
     fn test_evaluate() {
+        require_gpu!();
         let log_size = 20;
 
         let size = 1 << log_size;
@@ -227,6 +384,7 @@ mod tests {
 
     #[test]
     fn test_eval_at_point() {
+        require_gpu!();
         let log_size = 25;
 
         let size = 1 << log_size;
@@ -251,4 +409,103 @@ mod tests {
 
         assert_eq!(result, expected_result);
     }
+
+    #[test]
+    fn test_eval_polys_at_points() {
+        require_gpu!();
+        let points = [
+            SECURE_FIELD_CIRCLE_GEN,
+            SECURE_FIELD_CIRCLE_GEN.double(),
+            SECURE_FIELD_CIRCLE_GEN.double().double() + SECURE_FIELD_CIRCLE_GEN,
+        ];
+        let cpu_polys = [2, 4, 10, 17]
+            .map(|log_size| {
+                CirclePoly::<CpuBackend>::new(
+                    (0..1 << log_size)
+                        .map(|i| BaseField::from(i * 7 + log_size))
+                        .collect(),
+                )
+            })
+            .to_vec();
+        let gpu_polys = cpu_polys
+            .iter()
+            .map(|poly| {
+                CirclePoly::<CudaBackend>::new(cuda::BaseFieldVec::from_vec(poly.coeffs.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let result =
+            CudaBackend::eval_polys_at_points(&gpu_polys.iter().collect::<Vec<_>>(), &points);
+
+        let expected_result = cpu_polys
+            .iter()
+            .map(|poly| {
+                points
+                    .iter()
+                    .map(|&point| CpuBackend::eval_at_point(poly, point))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
+
+    #[test]
+    fn test_eval_at_point_small_sizes() {
+        require_gpu!();
+        let point = SECURE_FIELD_CIRCLE_GEN;
+        // Up to twice the number of coefficients reduced by a block.
+        for log_size in 1..=10 {
+            let coeffs = (1..(1u32 << log_size) + 1)
+                .map(BaseField::from)
+                .collect::<Vec<_>>();
+            let cpu_poly = CirclePoly::<CpuBackend>::new(coeffs.clone());
+            let gpu_poly = CirclePoly::<CudaBackend>::new(cuda::BaseFieldVec::from_vec(coeffs));
+
+            assert_eq!(
+                CudaBackend::eval_at_point(&gpu_poly, point),
+                CpuBackend::eval_at_point(&cpu_poly, point),
+                "log_size = {log_size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_polys_at_sample_points() {
+        require_gpu!();
+        let oods_point = SECURE_FIELD_CIRCLE_GEN;
+        let shifted = oods_point.double() + oods_point;
+        let cpu_polys = [3, 9, 12]
+            .map(|log_size| {
+                CirclePoly::<CpuBackend>::new(
+                    (0..1 << log_size)
+                        .map(|i| BaseField::from(i * 5 + log_size))
+                        .collect(),
+                )
+            })
+            .to_vec();
+        let gpu_polys = cpu_polys
+            .iter()
+            .map(|poly| {
+                CirclePoly::<CudaBackend>::new(cuda::BaseFieldVec::from_vec(poly.coeffs.clone()))
+            })
+            .collect::<Vec<_>>();
+        let sample_points = vec![vec![oods_point, shifted], vec![], vec![shifted, oods_point]];
+
+        let result = CudaBackend::eval_polys_at_sample_points(
+            &gpu_polys.iter().collect::<Vec<_>>(),
+            &sample_points,
+        );
+
+        let expected_result = cpu_polys
+            .iter()
+            .zip(&sample_points)
+            .map(|(poly, points)| {
+                points
+                    .iter()
+                    .map(|&point| CpuBackend::eval_at_point(poly, point))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
 }
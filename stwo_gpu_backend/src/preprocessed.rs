@@ -0,0 +1,215 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::m31::BaseField,
+    poly::{
+        circle::{CanonicCoset, CircleEvaluation},
+        BitReversedOrder,
+    },
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+};
+
+/// Returns a column of size `2^log_size` repeating `pattern`, in natural row order.
+pub fn periodic_column(pattern: &BaseFieldVec, log_size: u32) -> BaseFieldVec {
+    let size = 1 << log_size;
+    assert!(!pattern.is_empty() && pattern.len() <= size);
+
+    let result = BaseFieldVec::new_uninitialized(size);
+    unsafe {
+        cuda::bindings::tile_base_field(
+            pattern.device_ptr(),
+            pattern.len() as u32,
+            result.device_ptr(),
+            size as u32,
+        );
+    }
+    result
+}
+
+/// Same as [`periodic_column`], uploading only the (short) host `pattern`.
+pub fn periodic_column_from_host(pattern: &[BaseField], log_size: u32) -> BaseFieldVec {
+    periodic_column(&BaseFieldVec::from_vec(pattern.to_vec()), log_size)
+}
+
+/// Returns the selector that is one on the rows of the trace congruent to `offset` modulo `step`,
+/// and zero elsewhere.
+pub fn gen_is_step_with_offset(
+    log_size: u32,
+    step: usize,
+    offset: usize,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    assert!(log_size > 0);
+    assert!(offset < step);
+
+    let values = BaseFieldVec::new_uninitialized(1 << log_size);
+    unsafe {
+        cuda::bindings::gen_step_selector(
+            values.device_ptr(),
+            log_size,
+            step as u32,
+            offset as u32,
+        );
+    }
+    CircleEvaluation::new(CanonicCoset::new(log_size).circle_domain(), values)
+}
+
+/// Returns the selector of the first row of the trace.
+pub fn gen_is_first(log_size: u32) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    gen_is_step_with_offset(log_size, 1 << log_size, 0)
+}
+
+/// Returns the selector of the last row of the trace.
+pub fn gen_is_last(log_size: u32) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    gen_is_step_with_offset(log_size, 1 << log_size, (1 << log_size) - 1)
+}
+
+/// Returns the column of the row indices `0..2^log_size`, e.g. the table of a range check on
+/// `log_size` bits.
+pub fn gen_seq(log_size: u32) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    row_bits(log_size, 0, u32::MAX)
+}
+
+/// Returns bit `bit` of the row index, e.g. to decompose a sequence into its bits.
+pub fn gen_row_bit(
+    log_size: u32,
+    bit: u32,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    assert!(bit < log_size);
+    row_bits(log_size, bit, 1)
+}
+
+/// Returns limb `limb` of the row index, split into limbs of `limb_bits` bits from the least
+/// significant one. The limbs of a trace of `log_size = 2 * limb_bits` form the table of all the
+/// pairs of limbs, e.g. of bytes for `limb_bits = 8`.
+pub fn gen_row_limb(
+    log_size: u32,
+    limb_bits: u32,
+    limb: u32,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    assert!(limb_bits > 0 && limb * limb_bits < log_size);
+    row_bits(log_size, limb * limb_bits, (1 << limb_bits) - 1)
+}
+
+fn row_bits(
+    log_size: u32,
+    shift: u32,
+    mask: u32,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    assert!(log_size > 0 && log_size < 31);
+
+    let values = BaseFieldVec::new_uninitialized(1 << log_size);
+    unsafe { cuda::bindings::gen_row_bits(values.device_ptr(), log_size, shift, mask) };
+    CircleEvaluation::new(CanonicCoset::new(log_size).circle_domain(), values)
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        fields::m31::BaseField,
+        poly::circle::{CanonicCoset, PolyOps},
+    };
+
+    use super::{
+        gen_is_first, gen_is_last, gen_is_step_with_offset, gen_row_bit, gen_row_limb, gen_seq,
+        periodic_column_from_host,
+    };
+
+    fn expected_selector(log_size: u32, step: usize, offset: usize) -> Vec<BaseField> {
+        expected_column(log_size, |row| (row % step == offset) as u32)
+    }
+
+    fn expected_column(log_size: u32, f: impl Fn(usize) -> u32) -> Vec<BaseField> {
+        let rows = (0..1 << log_size)
+            .map(|row| BaseField::from(f(row)))
+            .collect::<Vec<_>>();
+        CpuBackend::new_canonical_ordered(CanonicCoset::new(log_size), rows).values
+    }
+
+    #[test]
+    fn test_periodic_column() {
+        require_gpu!();
+        let log_size = 20;
+        let pattern = (1..6).map(BaseField::from).collect::<Vec<_>>();
+        let expected_result = pattern
+            .iter()
+            .copied()
+            .cycle()
+            .take(1 << log_size)
+            .collect::<Vec<_>>();
+
+        let result = periodic_column_from_host(&pattern, log_size);
+
+        assert_eq!(result.to_cpu(), expected_result);
+    }
+
+    #[test]
+    fn test_gen_is_first() {
+        require_gpu!();
+        let log_size = 12;
+        let result = gen_is_first(log_size);
+        assert_eq!(
+            result.values.to_cpu(),
+            expected_selector(log_size, 1 << log_size, 0)
+        );
+    }
+
+    #[test]
+    fn test_gen_is_last() {
+        require_gpu!();
+        let log_size = 12;
+        let result = gen_is_last(log_size);
+        assert_eq!(
+            result.values.to_cpu(),
+            expected_selector(log_size, 1 << log_size, (1 << log_size) - 1)
+        );
+    }
+
+    #[test]
+    fn test_gen_is_step_with_offset() {
+        require_gpu!();
+        let log_size = 12;
+        let result = gen_is_step_with_offset(log_size, 8, 3);
+        assert_eq!(result.values.to_cpu(), expected_selector(log_size, 8, 3));
+    }
+
+    #[test]
+    fn test_gen_seq() {
+        require_gpu!();
+        let log_size = 12;
+        let result = gen_seq(log_size);
+        assert_eq!(
+            result.values.to_cpu(),
+            expected_column(log_size, |row| row as u32)
+        );
+    }
+
+    #[test]
+    fn test_gen_row_bit() {
+        require_gpu!();
+        let log_size = 12;
+        for bit in [0, 5, 11] {
+            let result = gen_row_bit(log_size, bit);
+            assert_eq!(
+                result.values.to_cpu(),
+                expected_column(log_size, |row| (row >> bit) as u32 & 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_gen_row_limb() {
+        require_gpu!();
+        let log_size = 16;
+        for limb in 0..2 {
+            let result = gen_row_limb(log_size, 8, limb);
+            assert_eq!(
+                result.values.to_cpu(),
+                expected_column(log_size, |row| (row >> (8 * limb)) as u32 & 0xff)
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Python module `stwo_gpu`, to script experiments against the backend from notebooks. Built
+//! with maturin, see `pyproject.toml`.
+
+use std::time::Instant;
+
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use stwo_prover::core::{
+    backend::Column,
+    fields::m31::{BaseField, P},
+    poly::{
+        circle::{CanonicCoset, CircleEvaluation, PolyOps},
+        BitReversedOrder,
+    },
+};
+
+use crate::{
+    air::{prove_squares, SquaresProof},
+    backend::CudaBackend,
+    commitment::{commit_on_gpu, queries_per_log_size, GpuCommitment},
+    cuda::BaseFieldVec,
+    device::try_init,
+    twiddles::cached_twiddles,
+};
+
+/// A column of field elements on the device.
+#[pyclass(name = "Column")]
+struct PyColumn(BaseFieldVec);
+
+#[pymethods]
+impl PyColumn {
+    /// Uploads `values`, which must be integers in `0..P`.
+    #[new]
+    fn new(values: Vec<u32>) -> PyResult<Self> {
+        let values = field_values(&values)?;
+        // `field_values` checked that the values are reduced.
+        Ok(Self(unsafe { BaseFieldVec::from_u32_slice(values) }))
+    }
+
+    /// A column of `size` pseudo-random values generated on the device from `seed`.
+    #[staticmethod]
+    fn random(size: usize, seed: u64) -> Self {
+        Self(BaseFieldVec::random(size, seed))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn to_list(&self) -> Vec<u32> {
+        self.0.to_cpu().iter().map(|value| value.0).collect()
+    }
+}
+
+/// A commitment to columns, kept on the device until it is opened.
+#[pyclass(name = "Commitment")]
+struct PyCommitment(GpuCommitment);
+
+#[pymethods]
+impl PyCommitment {
+    fn root<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.0.root().0)
+    }
+
+    /// Opens the commitment at `positions`, given in the largest extended columns. Returns the
+    /// queried values of each column, the hashes of the Merkle decommitment and the column values
+    /// it needs.
+    fn open<'py>(
+        &self,
+        py: Python<'py>,
+        positions: Vec<usize>,
+    ) -> PyResult<(Vec<Vec<u32>>, Vec<&'py PyBytes>, Vec<u32>)> {
+        let log_sizes = self.0.extended_log_sizes();
+        let max_log_size = log_sizes.iter().copied().max().unwrap_or(0);
+        let size = 1usize
+            .checked_shl(max_log_size)
+            .ok_or_else(|| PyValueError::new_err("columns too large to be opened"))?;
+        if positions.iter().any(|&position| position >= size) {
+            return Err(PyValueError::new_err("position out of the columns"));
+        }
+        let (queried_values, decommitment) = self
+            .0
+            .decommit(queries_per_log_size(&log_sizes, &positions));
+        Ok((
+            queried_values
+                .iter()
+                .map(|values| raw_values(values))
+                .collect(),
+            decommitment
+                .hash_witness
+                .iter()
+                .map(|hash| PyBytes::new(py, &hash.0))
+                .collect(),
+            raw_values(&decommitment.column_witness),
+        ))
+    }
+}
+
+/// A proof of the squares AIR, see `prove_squares`.
+#[pyclass(name = "Proof")]
+struct PyProof(Option<SquaresProof>);
+
+#[pymethods]
+impl PyProof {
+    /// Checks the proof with the verifier of stwo, raising `ValueError` if it is invalid. The
+    /// verifier consumes the proof, so it can only be checked once.
+    fn verify(&mut self) -> PyResult<()> {
+        let proof = self
+            .0
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("proof already verified"))?;
+        proof
+            .verify()
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+}
+
+/// Creates the CUDA context, raising `RuntimeError` with the reason if the device can't be used.
+/// Other functions initialize it on first use anyway.
+#[pyfunction]
+fn init() -> PyResult<()> {
+    try_init()
+        .map(|_| ())
+        .map_err(|error| PyRuntimeError::new_err(error.to_string()))
+}
+
+/// Commits to `columns`, given in natural order over canonic cosets of power of two sizes,
+/// extended by `2^log_blowup_factor`, see [`commit_on_gpu`]. The columns are left untouched.
+#[pyfunction]
+fn commit(columns: Vec<PyRef<'_, PyColumn>>, log_blowup_factor: u32) -> PyResult<PyCommitment> {
+    let evaluations = columns
+        .iter()
+        .map(|column| {
+            let column = &column.0;
+            if !column.len().is_power_of_two() {
+                return Err(PyValueError::new_err("column sizes must be powers of two"));
+            }
+            let mut values = BaseFieldVec::new_uninitialized(column.len());
+            values.copy_from(column);
+            let domain = CanonicCoset::new(column.len().ilog2()).circle_domain();
+            Ok(CircleEvaluation::new(domain, values))
+        })
+        .collect::<PyResult<_>>()?;
+    Ok(PyCommitment(
+        commit_on_gpu(evaluations, log_blowup_factor).1,
+    ))
+}
+
+/// Proves, on the device, that a second column holds the squares of `column`, which is read as
+/// an evaluation in bit reversed order over the canonic coset of its size, see
+/// [`prove_squares`].
+///
+/// A smoke test exercising the whole prover, constraint evaluation and FRI included, not an
+/// entry point for other AIRs.
+#[pyfunction]
+#[pyo3(name = "prove_squares")]
+fn prove(column: PyRef<'_, PyColumn>) -> PyResult<PyProof> {
+    if !column.0.len().is_power_of_two() {
+        return Err(PyValueError::new_err("column sizes must be powers of two"));
+    }
+    prove_squares(&column.0)
+        .map(|proof| PyProof(Some(proof)))
+        .map_err(|error| PyRuntimeError::new_err(error.to_string()))
+}
+
+/// Times `n_runs` commitments to `n_columns` random columns of size `2^log_size`, returning the
+/// duration of each run in seconds. Twiddles are computed before the first run.
+#[pyfunction]
+fn bench_commit(
+    log_size: u32,
+    n_columns: usize,
+    log_blowup_factor: u32,
+    n_runs: usize,
+) -> Vec<f64> {
+    cached_twiddles(CanonicCoset::new(log_size).circle_domain().half_coset);
+    cached_twiddles(
+        CanonicCoset::new(log_size + log_blowup_factor)
+            .circle_domain()
+            .half_coset,
+    );
+    (0..n_runs)
+        .map(|run| {
+            let columns = (0..n_columns)
+                .map(|i| {
+                    CircleEvaluation::new(
+                        CanonicCoset::new(log_size).circle_domain(),
+                        BaseFieldVec::random(1 << log_size, (run * n_columns + i) as u64),
+                    )
+                })
+                .collect();
+            let start = Instant::now();
+            commit_on_gpu(columns, log_blowup_factor);
+            start.elapsed().as_secs_f64()
+        })
+        .collect()
+}
+
+/// Times `n_runs` interpolations of a random column of size `2^log_size`, returning the duration
+/// of each run in seconds. Twiddles are computed before the first run.
+#[pyfunction]
+fn bench_interpolate(log_size: u32, n_runs: usize) -> Vec<f64> {
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    let twiddles = cached_twiddles(domain.half_coset);
+    (0..n_runs)
+        .map(|run| {
+            let evaluation = CircleEvaluation::<CudaBackend, _, BitReversedOrder>::new(
+                domain,
+                BaseFieldVec::random(1 << log_size, run as u64),
+            );
+            let start = Instant::now();
+            CudaBackend::interpolate(evaluation, &twiddles);
+            start.elapsed().as_secs_f64()
+        })
+        .collect()
+}
+
+#[pymodule]
+fn stwo_gpu(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add("P", P)?;
+    module.add_class::<PyColumn>()?;
+    module.add_class::<PyCommitment>()?;
+    module.add_class::<PyProof>()?;
+    module.add_function(wrap_pyfunction!(init, module)?)?;
+    module.add_function(wrap_pyfunction!(commit, module)?)?;
+    module.add_function(wrap_pyfunction!(prove, module)?)?;
+    module.add_function(wrap_pyfunction!(bench_commit, module)?)?;
+    module.add_function(wrap_pyfunction!(bench_interpolate, module)?)?;
+    Ok(())
+}
+
+/// Checks that `values` are field elements, so Python integers can't wrap silently.
+fn field_values(values: &[u32]) -> PyResult<&[u32]> {
+    match values.iter().position(|&value| value >= P) {
+        Some(index) => Err(PyValueError::new_err(format!(
+            "value {} at index {index} is not a field element",
+            values[index]
+        ))),
+        None => Ok(values),
+    }
+}
+
+fn raw_values(values: &[BaseField]) -> Vec<u32> {
+    values.iter().map(|value| value.0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::P;
+
+    use super::field_values;
+
+    #[test]
+    fn test_field_values() {
+        assert_eq!(field_values(&[0, 1, P - 1]).unwrap(), &[0, 1, P - 1]);
+        assert!(field_values(&[0, P, 2]).is_err());
+    }
+}
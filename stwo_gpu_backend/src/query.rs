@@ -0,0 +1,206 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::m31::BaseField,
+    vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+};
+
+/// Returns, for each column, its values at the queried `positions`.
+///
+/// All the values are gathered into a single device buffer, which is copied to the host in one
+/// transfer.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(n_columns = columns.len(), n_positions = positions.len())
+    )
+)]
+pub fn gather_query_values(columns: &[&BaseFieldVec], positions: &[usize]) -> Vec<Vec<BaseField>> {
+    if positions.is_empty() {
+        return vec![vec![]; columns.len()];
+    }
+    for column in columns {
+        assert!(positions.iter().all(|&position| position < column.len()));
+    }
+
+    let column_ptrs = columns
+        .iter()
+        .map(|column| column.device_ptr())
+        .collect::<Vec<_>>();
+    let positions = positions
+        .iter()
+        .map(|&position| position as u32)
+        .collect::<Vec<_>>();
+    let mut result = vec![BaseField::from(0); columns.len() * positions.len()];
+    unsafe {
+        cuda::bindings::gather_query_values(
+            column_ptrs.as_ptr(),
+            columns.len() as u32,
+            positions.as_ptr(),
+            positions.len() as u32,
+            result.as_mut_ptr() as *const u32,
+        );
+    }
+
+    result
+        .chunks(positions.len())
+        .map(|values| values.to_vec())
+        .collect()
+}
+
+/// Returns the authentication path of each queried leaf of `tree`: the sibling hashes from the
+/// leaves up to the children of the root.
+///
+/// Only the siblings are gathered on the device and copied back, in a single transfer, instead
+/// of whole layers.
+pub fn gather_authentication_paths(
+    tree: &MerkleProver<CudaBackend, Blake2sMerkleHasher>,
+    positions: &[usize],
+) -> Vec<Vec<Blake2sHash>> {
+    gather_capped_authentication_paths(tree, 0, positions)
+}
+
+/// Returns the `2^cap_log_size` hashes of the layer of `tree` used as its cap. A cap of log size
+/// 0 is the root.
+pub fn merkle_cap(
+    tree: &MerkleProver<CudaBackend, Blake2sMerkleHasher>,
+    cap_log_size: u32,
+) -> Vec<Blake2sHash> {
+    assert!((cap_log_size as usize) < tree.layers.len());
+    tree.layers[cap_log_size as usize].to_cpu()
+}
+
+/// Like [`gather_authentication_paths`], but the paths stop at the children of the cap layer of
+/// log size `cap_log_size`, so they are `cap_log_size` hashes shorter.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            n_layers = tree.layers.len(),
+            cap_log_size = cap_log_size,
+            n_positions = positions.len()
+        )
+    )
+)]
+pub fn gather_capped_authentication_paths(
+    tree: &MerkleProver<CudaBackend, Blake2sMerkleHasher>,
+    cap_log_size: u32,
+    positions: &[usize],
+) -> Vec<Vec<Blake2sHash>> {
+    assert!((cap_log_size as usize) < tree.layers.len());
+    // The cap layer plays the role of the root: the kernel only sees the layers below it.
+    let layers = &tree.layers[cap_log_size as usize..];
+    let depth = layers.len() - 1;
+    if depth == 0 {
+        return vec![vec![]; positions.len()];
+    }
+    let n_leaves = layers[depth].len();
+    assert!(positions.iter().all(|&position| position < n_leaves));
+
+    let layer_ptrs = layers
+        .iter()
+        .map(|layer| layer.device_ptr())
+        .collect::<Vec<_>>();
+    let positions = positions
+        .iter()
+        .map(|&position| position as u32)
+        .collect::<Vec<_>>();
+    let mut result = vec![0u32; positions.len() * depth * cuda::HASH_WORDS];
+    unsafe {
+        cuda::bindings::gather_authentication_paths(
+            layer_ptrs.as_ptr(),
+            layer_ptrs.len() as u32,
+            positions.as_ptr(),
+            positions.len() as u32,
+            result.as_mut_ptr() as *const u32,
+        );
+    }
+
+    cuda::words_to_hashes(&result)
+        .chunks(depth)
+        .map(|path| path.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::CpuBackend,
+        fields::m31::BaseField,
+        vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+    };
+
+    use super::{gather_authentication_paths, gather_query_values};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    #[test]
+    fn test_gather_query_values() {
+        require_gpu!();
+        let size = 1 << 12;
+        let n_columns = 5;
+        let positions = [0, 7, 7, 1000, size - 1];
+        let values = (0..n_columns)
+            .map(|i| {
+                (0..size as u32)
+                    .map(|j| BaseField::from(i * size as u32 + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let columns = values
+            .iter()
+            .cloned()
+            .map(BaseFieldVec::from_vec)
+            .collect::<Vec<_>>();
+
+        let result = gather_query_values(&columns.iter().collect::<Vec<_>>(), &positions);
+
+        let expected_result = values
+            .iter()
+            .map(|column| positions.iter().map(|&p| column[p]).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
+
+    #[test]
+    fn test_gather_authentication_paths() {
+        require_gpu!();
+        let log_size = 10;
+        let positions = [0, 1, 7, 7, 513, (1 << log_size) - 1];
+        let values = (0..3)
+            .map(|i| {
+                (0..1 << log_size)
+                    .map(|j| BaseField::from(i * 10000 + j))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let columns = values
+            .iter()
+            .cloned()
+            .map(BaseFieldVec::from_vec)
+            .collect::<Vec<_>>();
+        let cpu_tree =
+            MerkleProver::<CpuBackend, Blake2sMerkleHasher>::commit(values.iter().collect());
+        let tree =
+            MerkleProver::<CudaBackend, Blake2sMerkleHasher>::commit(columns.iter().collect());
+
+        let result = gather_authentication_paths(&tree, &positions);
+
+        let expected_result = positions
+            .iter()
+            .map(|&position| {
+                (0..log_size)
+                    .map(|level| cpu_tree.layers[log_size - level][(position >> level) ^ 1])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
+}
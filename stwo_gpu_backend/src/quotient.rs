@@ -1,5 +1,6 @@
 use stwo_prover::core::{
-    fields::{m31::BaseField, qm31::SecureField},
+    backend::cpu::quotients::{batch_random_coeffs, column_line_coeffs},
+    fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
     pcs::quotients::{ColumnSampleBatch, QuotientOps},
     poly::{
         circle::{CircleDomain, CircleEvaluation, SecureEvaluation},
@@ -7,15 +8,159 @@ use stwo_prover::core::{
     },
 };
 
-use crate::backend::CudaBackend;
+use crate::{
+    backend::CudaBackend, cuda, memory::memory_phase, point::CirclePointVec, watchdog::watch,
+};
 
 impl QuotientOps for CudaBackend {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(log_size = domain.log_size(), n_batches = sample_batches.len())
+        )
+    )]
     fn accumulate_quotients(
-        _domain: CircleDomain,
-        _columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
-        _random_coeff: SecureField,
-        _sample_batches: &[ColumnSampleBatch],
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[ColumnSampleBatch],
     ) -> SecureEvaluation<Self> {
-        todo!()
+        let _watch = watch("accumulate_quotients");
+        let _phase = memory_phase("quotients");
+        // Only the per sample constants are computed on the host, the domain points are
+        // generated on the device.
+        let half_coset = CirclePointVec::coset(domain.half_coset);
+        let line_coeffs = column_line_coeffs(sample_batches, random_coeff)
+            .into_iter()
+            .flatten()
+            .flat_map(|(a, b, c)| [a, b, c])
+            .collect::<Vec<_>>();
+        let batch_random_coeffs = batch_random_coeffs(sample_batches, random_coeff);
+        let sample_points_x = sample_batches
+            .iter()
+            .map(|batch| batch.point.x)
+            .collect::<Vec<_>>();
+        let sample_points_y = sample_batches
+            .iter()
+            .map(|batch| batch.point.y)
+            .collect::<Vec<_>>();
+        let batch_sizes = sample_batches
+            .iter()
+            .map(|batch| batch.columns_and_values.len() as u32)
+            .collect::<Vec<_>>();
+        let column_indices = sample_batches
+            .iter()
+            .flat_map(|batch| &batch.columns_and_values)
+            .map(|&(column_index, _)| column_index as u32)
+            .collect::<Vec<_>>();
+        let column_ptrs = columns
+            .iter()
+            .map(|column| column.values.device_ptr())
+            .collect::<Vec<_>>();
+
+        let values: SecureColumn<Self> = cuda::new_uninitialized_secure_column(domain.size());
+        unsafe {
+            cuda::bindings::accumulate_quotients(
+                half_coset.x.device_ptr(),
+                half_coset.y.device_ptr(),
+                domain.log_size(),
+                column_ptrs.as_ptr(),
+                column_ptrs.len() as u32,
+                sample_points_x.as_ptr(),
+                sample_points_y.as_ptr(),
+                batch_random_coeffs.as_ptr(),
+                batch_sizes.as_ptr(),
+                batch_sizes.len() as u32,
+                column_indices.as_ptr(),
+                line_coeffs.as_ptr(),
+                column_indices.len() as u32,
+                cuda::secure_column_device_ptrs(&values).as_ptr(),
+            );
+        }
+        SecureEvaluation { domain, values }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        circle::SECURE_FIELD_CIRCLE_GEN,
+        fields::{m31::BaseField, qm31::SecureField},
+        pcs::quotients::{ColumnSampleBatch, QuotientOps},
+        poly::{
+            circle::{CanonicCoset, CircleEvaluation},
+            BitReversedOrder,
+        },
+    };
+
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    #[test]
+    fn test_accumulate_quotients() {
+        require_gpu!();
+        let log_size = 10;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let columns = (0..3)
+            .map(|i: u32| {
+                (0..1 << log_size)
+                    .map(|row: u32| BaseField::from(row * 7 + i))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let random_coeff = SecureField::from_u32_unchecked(1, 2, 3, 4);
+        let sample_batches = [(3, vec![0, 2]), (5, vec![1, 0, 2])]
+            .into_iter()
+            .map(|(scalar, column_indices)| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(scalar),
+                columns_and_values: column_indices
+                    .iter()
+                    .map(|&i| {
+                        (
+                            i,
+                            SecureField::from_u32_unchecked(i as u32, 5, 6, scalar as u32),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let cpu_columns = columns
+            .iter()
+            .map(|values| {
+                CircleEvaluation::<CpuBackend, BaseField, BitReversedOrder>::new(
+                    domain,
+                    values.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected_result = CpuBackend::accumulate_quotients(
+            domain,
+            &cpu_columns.iter().collect::<Vec<_>>(),
+            random_coeff,
+            &sample_batches,
+        );
+        let gpu_columns = columns
+            .iter()
+            .map(|values| {
+                CircleEvaluation::<CudaBackend, BaseField, BitReversedOrder>::new(
+                    domain,
+                    BaseFieldVec::from_vec(values.clone()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let result = CudaBackend::accumulate_quotients(
+            domain,
+            &gpu_columns.iter().collect::<Vec<_>>(),
+            random_coeff,
+            &sample_batches,
+        );
+
+        assert_eq!(
+            result.values.columns.map(|column| column.to_cpu()),
+            expected_result.values.columns
+        );
     }
 }
@@ -0,0 +1,234 @@
+//! Kernels running on a range of the values of a device column, so that several columns, e.g.
+//! the layers of FRI, can be stored one after the other in a single allocation and processed
+//! where they are.
+
+use std::ops::Range;
+
+use stwo_prover::core::{
+    backend::Column,
+    fields::{qm31::SecureField, secure_column::SecureColumn},
+    poly::line::LineDomain,
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, DeviceVec, Pod},
+    line_twiddles::LineTwiddleLayers,
+};
+
+/// Bit reverses the values of `column` in `range`, leaving the others untouched. The length of
+/// `range` must be a power of two, and for base field values of 16 or more, its start a multiple
+/// of 4, as the kernel moves them 4 at a time.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = range.len()))
+)]
+pub fn bit_reverse_range<T: Pod>(column: &mut DeviceVec<T>, range: Range<usize>) {
+    assert!(range.end <= column.size);
+    assert!(range.len().is_power_of_two() && range.len() < u32::MAX as usize);
+    assert!(
+        T::WORDS != 1 || range.len() < 16 || range.start % 4 == 0,
+        "ranges of 16 base field values or more must start at a multiple of 4"
+    );
+    let device_ptr = unsafe { column.device_ptr().add(T::WORDS * range.start) };
+    unsafe {
+        match T::WORDS {
+            1 => cuda::bindings::bit_reverse_base_field(device_ptr, range.len()),
+            4 => cuda::bindings::bit_reverse_secure_field(device_ptr, range.len()),
+            words => unreachable!("no bit reversal of values of {words} words"),
+        }
+    }
+}
+
+/// Writes the inverses of the values of `column` in `range` to `dst`, from position
+/// `dst_start`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = range.len()))
+)]
+pub fn batch_inverse_range<T: Pod>(
+    column: &DeviceVec<T>,
+    range: Range<usize>,
+    dst: &mut DeviceVec<T>,
+    dst_start: usize,
+) {
+    assert!(range.end <= column.size);
+    assert!(dst_start + range.len() <= dst.size);
+    unsafe {
+        let from = column.device_ptr().add(T::WORDS * range.start);
+        let to = dst.device_ptr().add(T::WORDS * dst_start);
+        match T::WORDS {
+            1 => cuda::bindings::batch_inverse_base_field(from, to, range.len()),
+            4 => cuda::bindings::batch_inverse_secure_field(from, to, range.len()),
+            words => unreachable!("no batch inverse of values of {words} words"),
+        }
+    }
+}
+
+/// Sum of the values of `column` in `range`.
+pub fn sum_range(column: &SecureColumn<CudaBackend>, range: Range<usize>) -> SecureField {
+    CudaBackend::sum_secure_column_range(column, range)
+}
+
+/// Folds the line evaluation over `domain` stored in `range` of `layers` as
+/// [`stwo_prover::core::fri::FriOps::fold_line`] does, writing the folded evaluation, half as
+/// long, to `layers` from position `dst_start`.
+///
+/// With the layers of FRI stored one after the other, each layer is folded into the next
+/// without allocating. The folded values must not overlap `range`, which must start at an even
+/// position, as the kernel reads the values by pairs.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = range.len()))
+)]
+pub fn fold_line_range(
+    layers: &mut SecureColumn<CudaBackend>,
+    range: Range<usize>,
+    dst_start: usize,
+    domain: LineDomain,
+    alpha: SecureField,
+    twiddles: &impl LineTwiddleLayers,
+) {
+    let n = range.len();
+    assert_eq!(n, domain.size());
+    assert!(n >= 2, "Evaluation too small");
+    assert!(
+        range.start % 2 == 0,
+        "the evaluation must start at an even position"
+    );
+    let dst = dst_start..dst_start + n / 2;
+    assert!(range.end <= layers.len() && dst.end <= layers.len());
+    assert!(
+        dst.end <= range.start || range.end <= dst.start,
+        "the folded values overlap the evaluation"
+    );
+
+    let device_ptrs = cuda::secure_column_device_ptrs(layers);
+    let eval_ptrs = device_ptrs.map(|ptr| unsafe { ptr.add(range.start) });
+    let folded_ptrs = device_ptrs.map(|ptr| unsafe { ptr.add(dst.start) });
+    unsafe {
+        cuda::bindings::fold_line(
+            eval_ptrs.as_ptr(),
+            folded_ptrs.as_ptr(),
+            n as u32,
+            twiddles.itwiddles().device_ptr(),
+            twiddles.layer_offset(domain),
+            alpha,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        circle::Coset,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn, FieldExpOps},
+        fri::FriOps,
+        poly::{
+            circle::PolyOps,
+            line::{LineDomain, LineEvaluation},
+        },
+        utils::bit_reverse,
+    };
+
+    use super::{batch_inverse_range, bit_reverse_range, fold_line_range, sum_range};
+    use crate::{
+        backend::CudaBackend,
+        cuda::{self, BaseFieldVec},
+    };
+
+    #[test]
+    fn test_bit_reverse_and_inverse_ranges() {
+        require_gpu!();
+        let values = (1..=100).map(BaseField::from).collect::<Vec<_>>();
+        let mut column = BaseFieldVec::from_vec(values.clone());
+        let mut inverses = BaseFieldVec::new_zeroes(10);
+
+        bit_reverse_range(&mut column, 20..52);
+        batch_inverse_range(&column, 0..8, &mut inverses, 2);
+
+        let mut expected_result = values.clone();
+        bit_reverse(&mut expected_result[20..52]);
+        assert_eq!(column.to_cpu(), expected_result);
+        let mut expected_inverses = vec![BaseField::from(0); 10];
+        for (inverse, value) in expected_inverses[2..].iter_mut().zip(&values) {
+            *inverse = value.inverse();
+        }
+        assert_eq!(inverses.to_cpu(), expected_inverses);
+    }
+
+    #[test]
+    fn test_misaligned_bit_reverse_range() {
+        require_gpu!();
+        let values = (0..64).map(BaseField::from).collect::<Vec<_>>();
+        let mut column = BaseFieldVec::from_vec(values.clone());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            bit_reverse_range(&mut column, 2..34)
+        }));
+        // Short ranges are reversed a value at a time, from any position.
+        bit_reverse_range(&mut column, 2..10);
+
+        assert!(result.is_err());
+        let mut expected_result = values;
+        bit_reverse(&mut expected_result[2..10]);
+        assert_eq!(column.to_cpu(), expected_result);
+    }
+
+    #[test]
+    fn test_fri_layers_in_one_buffer() {
+        require_gpu!();
+        let root_log_size = 10;
+        let alphas = [1, 2, 3].map(|i| SecureField::from_u32_unchecked(i, 4, 5, 6));
+        let root_coset = Coset::half_odds(root_log_size);
+        let twiddles = CudaBackend::precompute_twiddles(root_coset);
+        let n = 1 << root_log_size;
+        let first_layer = SecureColumn::<CudaBackend> {
+            columns: std::array::from_fn(|i| {
+                BaseFieldVec::from_vec(
+                    (0..n as u32)
+                        .map(|j| BaseField::from(4 * j + i as u32))
+                        .collect(),
+                )
+            }),
+        };
+        // Layers of sizes n, n / 2, n / 4 and n / 8, one after the other.
+        let mut layers = cuda::new_uninitialized_secure_column(2 * n - n / 8);
+        for (column, first_column) in layers.columns.iter_mut().zip(&first_layer.columns) {
+            column.copy_from(first_column);
+        }
+
+        let mut domain = LineDomain::new(root_coset);
+        let mut start = 0;
+        let mut expected_layer = LineEvaluation::new(domain, first_layer);
+        for alpha in alphas {
+            let len = domain.size();
+            fold_line_range(
+                &mut layers,
+                start..start + len,
+                start + len,
+                domain,
+                alpha,
+                &twiddles,
+            );
+            start += len;
+            domain = domain.double();
+            expected_layer = CudaBackend::fold_line(&expected_layer, alpha, &twiddles);
+
+            let layer = (start..start + len / 2)
+                .map(|i| layers.at(i))
+                .collect::<Vec<_>>();
+            assert_eq!(layer, expected_layer.values.to_vec());
+        }
+        assert_eq!(
+            sum_range(&layers, start..layers.len()),
+            expected_layer
+                .values
+                .to_vec()
+                .into_iter()
+                .reduce(|sum, value| sum + value)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::panic::{self, UnwindSafe};
+
+use crate::{
+    cuda::{self, forget_context},
+    device::{clear_scratch_buffers, cuda_available, InitError},
+    twiddles::clear_twiddle_cache,
+};
+
+/// Waits for the work in flight on the device and clears the error it may have left, so later
+/// proofs in the process can use the context again.
+///
+/// Fails with [`InitError::ContextCorrupted`] if a sticky error, such as an illegal memory
+/// access, left the context unusable: it then has to be replaced with [`reset_device`].
+pub fn recover_device() -> Result<(), InitError> {
+    match unsafe { cuda::bindings::recover_device() } {
+        0 => Ok(()),
+        code => Err(InitError::ContextCorrupted(code)),
+    }
+}
+
+/// Replaces the CUDA context with a new one, e.g. after [`recover_device`] found it corrupted,
+/// so long-running services can go on without a restart.
+///
+/// Everything the old context held is gone: device vectors of the backend allocated before are
+/// forgotten rather than freed when dropped, and the twiddle cache, scratch buffers and arenas
+/// are emptied. A memory pool reserved by [`crate::CudaBackend::warm_up`] has to be reserved
+/// again.
+///
+/// # Safety
+///
+/// No other thread may use the device during the reset, and the vectors, streams, events and
+/// constraint kernels of the old context must not be used afterwards, only dropped.
+pub unsafe fn reset_device() -> Result<(), InitError> {
+    let code = cuda::bindings::reset_device();
+    forget_context();
+    clear_twiddle_cache();
+    match code {
+        0 => Ok(()),
+        code => Err(InitError::Cuda(code)),
+    }
+}
+
+/// Installs a panic hook, after the current one, that clears the error a failed launch may have
+/// left before the panic unwinds, so the frees of unwinding don't report it.
+///
+/// The hook doesn't wait for the device, as a hung kernel may be what panicked: call
+/// [`recover_device`] once the panic is caught to wait for the work in flight.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // Doesn't create the context if the panic happened before the device was used.
+        if cuda_available() {
+            unsafe { cuda::bindings::clear_last_error() };
+        }
+        previous(info);
+    }));
+}
+
+/// Runs `f`, e.g. a whole proof, catching its panics as [`std::panic::catch_unwind`] does and
+/// leaving the device reusable when it panics.
+///
+/// After a panic, the device is synchronized, the twiddle cache and the scratch buffers are
+/// emptied, and the device vectors the current thread allocated in `f` that unwinding didn't
+/// free, e.g. those of forgotten [`crate::Pending`] values, are freed. The allocations are
+/// tracked while `f` runs, whether or not [`crate::set_memory_tracking`] turned tracking on.
+///
+/// # Safety
+///
+/// Every device vector the current thread allocates while `f` runs is freed if it panics, so
+/// none of them may outlive the panic: `f` must not move them out of itself other than through
+/// its result, e.g. into a collection it borrows, and no [`crate::batch_frees`] batch opened
+/// before the call may still be open, as it would free the vectors `f` dropped a second time.
+pub unsafe fn run_on_device<T>(f: impl FnOnce() -> T + UnwindSafe) -> std::thread::Result<T> {
+    unsafe { cuda::bindings::retain_memory_tracking() };
+    let mark = unsafe { cuda::bindings::allocation_mark() };
+    let result = panic::catch_unwind(f);
+    if result.is_err() {
+        clear_twiddle_cache();
+        clear_scratch_buffers();
+        unsafe { cuda::bindings::free_allocations_since(mark) };
+        let _ = recover_device();
+    }
+    unsafe { cuda::bindings::release_memory_tracking() };
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::{recover_device, reset_device, run_on_device};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_run_on_device() {
+        require_gpu!();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+
+        let result = unsafe {
+            run_on_device(|| {
+                std::mem::forget(BaseFieldVec::new_uninitialized(1 << 20));
+                panic!("failed mid-proof");
+            })
+        };
+        assert!(result.is_err());
+
+        assert_eq!(recover_device(), Ok(()));
+        let column = unsafe { run_on_device(|| BaseFieldVec::from_vec(values.clone())) }.unwrap();
+        assert_eq!(column.to_vec(), values);
+    }
+
+    #[test]
+    #[ignore = "resets the context under the tests running concurrently"]
+    fn test_reset_device() {
+        require_gpu!();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let old_column = BaseFieldVec::from_vec(values.clone());
+
+        unsafe { reset_device() }.unwrap();
+        let column = BaseFieldVec::from_vec(values.clone());
+        // Only forgets the memory of the old context, which may be handed out again.
+        drop(old_column);
+
+        assert_eq!(column.to_vec(), values);
+        assert_eq!(recover_device(), Ok(()));
+    }
+}
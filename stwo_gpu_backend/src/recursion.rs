@@ -0,0 +1,178 @@
+use stwo_prover::core::{
+    fields::{m31::BaseField, qm31::SecureField},
+    vcs::blake2_hash::Blake2sHash,
+};
+
+use crate::cuda::{self, BaseFieldVec, DeviceVec, Pod, SecureFieldVec};
+
+/// Values per pinned buffer when staging the inputs of the batched kernels to the device.
+const UPLOAD_CHUNK_SIZE: usize = 1 << 20;
+
+/// Uploads field values through the pinned buffers of
+/// [`BaseFieldVec::from_u32_slice_chunked`], word by word.
+fn upload_field_values<T: Pod>(values: &[T]) -> BaseFieldVec {
+    let words = unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u32, T::WORDS * values.len())
+    };
+    BaseFieldVec::from_u32_slice_chunked(words, UPLOAD_CHUNK_SIZE)
+        .expect("field values are reduced")
+}
+
+/// Folds many pairs of FRI query values at once, as the verifier of an inner proof does at each
+/// layer, e.g. to fill the trace of an outer proof verifying many inner ones.
+///
+/// Pair `i` holds the values `f_x[i]` and `f_neg_x[i]` at the conjugate points `x[i]` and
+/// `-x[i]` and is folded with `alphas[i]`. The first fold of a circle evaluation works the same
+/// with the `y` coordinates of the points; its accumulation into the line evaluation is left to
+/// the caller.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_pairs = f_x.len()))
+)]
+pub fn fold_pairs(
+    f_x: &[SecureField],
+    f_neg_x: &[SecureField],
+    x: &[BaseField],
+    alphas: &[SecureField],
+) -> Vec<SecureField> {
+    let n_pairs = f_x.len();
+    assert_eq!(f_neg_x.len(), n_pairs);
+    assert_eq!(x.len(), n_pairs);
+    assert_eq!(alphas.len(), n_pairs);
+
+    if n_pairs == 0 {
+        return vec![];
+    }
+
+    let [f_x, f_neg_x, alphas] = [f_x, f_neg_x, alphas].map(upload_field_values);
+    let x = upload_field_values(x);
+    let folded = SecureFieldVec::new_uninitialized(n_pairs);
+    unsafe {
+        cuda::bindings::fold_pairs(
+            f_x.device_ptr(),
+            f_neg_x.device_ptr(),
+            x.device_ptr(),
+            alphas.device_ptr(),
+            n_pairs as u32,
+            folded.device_ptr(),
+        );
+    }
+    folded.to_vec()
+}
+
+/// Recomputes the hashes on many Merkle authentication paths at once, returning for each path
+/// the `depth + 1` hashes from its leaf to the root it leads to.
+///
+/// Path `i` starts at the leaf `positions[i]`, hashing the `n_columns` values of
+/// `leaf_values[i * n_columns..]`, and climbs through the sibling hashes
+/// `siblings[i * depth..]`, ordered from the leaves up. As with
+/// [`crate::BatchVerifier::add_merkle_path`], trees must only have columns at their leaves.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_paths = positions.len()))
+)]
+pub fn merkle_path_nodes(
+    n_columns: usize,
+    leaf_values: &[BaseField],
+    positions: &[usize],
+    siblings: &[Blake2sHash],
+    depth: usize,
+) -> Vec<Vec<Blake2sHash>> {
+    let n_paths = positions.len();
+    assert_eq!(leaf_values.len(), n_columns * n_paths);
+    assert_eq!(siblings.len(), depth * n_paths);
+    assert!(
+        positions.iter().all(|&position| position >> depth == 0),
+        "position out of the tree"
+    );
+
+    if n_paths == 0 {
+        return vec![];
+    }
+
+    let leaf_values = upload_field_values(leaf_values);
+    // Positions and hash words needn't be reduced, so they skip the staging of field values.
+    let positions = DeviceVec::from_slice(
+        &positions
+            .iter()
+            .map(|&position| position as u32)
+            .collect::<Vec<_>>(),
+    );
+    let siblings = DeviceVec::from_slice(
+        &siblings
+            .iter()
+            .flat_map(cuda::hash_to_words)
+            .collect::<Vec<_>>(),
+    );
+    let nodes = DeviceVec::<u32>::new_uninitialized(cuda::HASH_WORDS * (depth + 1) * n_paths);
+    unsafe {
+        cuda::bindings::merkle_path_nodes(
+            leaf_values.device_ptr(),
+            n_columns as u32,
+            positions.device_ptr(),
+            siblings.device_ptr(),
+            depth as u32,
+            n_paths as u32,
+            nodes.device_ptr(),
+        );
+    }
+    nodes
+        .to_vec()
+        .chunks(cuda::HASH_WORDS * (depth + 1))
+        .map(cuda::words_to_hashes)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fold_pairs, merkle_path_nodes};
+    use crate::batch_verify::fixtures::{committed_columns, merkle_path, LineFold};
+
+    #[test]
+    fn test_fold_pairs() {
+        require_gpu!();
+        let fold = LineFold::new(6);
+        let pairs = (0..fold.n_pairs())
+            .map(|i| fold.pair(i))
+            .collect::<Vec<_>>();
+
+        let folded = fold_pairs(
+            &pairs.iter().map(|pair| pair.0).collect::<Vec<_>>(),
+            &pairs.iter().map(|pair| pair.1).collect::<Vec<_>>(),
+            &pairs.iter().map(|pair| pair.2).collect::<Vec<_>>(),
+            &vec![fold.alpha; fold.n_pairs()],
+        );
+
+        assert_eq!(
+            folded,
+            (0..fold.n_pairs())
+                .map(|i| fold.folded.values.at(i))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_merkle_path_nodes() {
+        require_gpu!();
+        let log_size = 6;
+        let (columns, tree) = committed_columns(log_size, 0);
+        let positions = [0, 5, 63, 5];
+
+        let leaf_values = positions
+            .iter()
+            .flat_map(|&position| columns.iter().map(move |column| column[position]))
+            .collect::<Vec<_>>();
+        let siblings = positions
+            .iter()
+            .flat_map(|&position| merkle_path(&tree, position, log_size))
+            .collect::<Vec<_>>();
+        let nodes = merkle_path_nodes(3, &leaf_values, &positions, &siblings, log_size);
+
+        for (&position, path_nodes) in positions.iter().zip(&nodes) {
+            let expected = (0..=log_size)
+                .map(|level| tree.layers[log_size - level][position >> level])
+                .collect::<Vec<_>>();
+            assert_eq!(path_nodes, &expected);
+        }
+    }
+}
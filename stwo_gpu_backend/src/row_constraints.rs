@@ -0,0 +1,155 @@
+use stwo_prover::core::{
+    backend::Column,
+    fields::{qm31::SecureField, secure_column::SecureColumn},
+};
+
+use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+/// A value read by a row constraint kernel: column `column` at row `row + offset` of the trace.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaskItem {
+    pub column: u32,
+    pub offset: i32,
+}
+
+/// A launcher defined on the CUDA side with `DEFINE_ROW_CONSTRAINTS` (see
+/// `row_constraints.cuh`). Returns nonzero when the mask doesn't match the evaluator.
+pub type RowConstraintsLauncher = unsafe extern "C" fn(
+    columns: *const *const u32,
+    n_columns: u32,
+    mask: *const MaskItem,
+    n_mask_items: u32,
+    trace_log_size: u32,
+    eval_log_size: u32,
+    random_coeff_powers: *const SecureField,
+    n_constraints: u32,
+    accumulator: *const *const u32,
+) -> i32;
+
+/// Adds the random linear combination of a handwritten component's constraints to each row of
+/// `accumulator`, one thread per row.
+///
+/// `columns` are evaluations in bit reversed order over the domain of `accumulator`, which is
+/// larger than the trace domain of log size `trace_log_size`.
+///
+/// # Safety
+///
+/// `launcher` must be defined with `DEFINE_ROW_CONSTRAINTS`, and its evaluator must read at most
+/// `random_coeff_powers.len()` coefficients. The lengths of the columns and the mask are checked,
+/// but the launcher runs the evaluator on them as is.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(size = accumulator.len(), n_mask_items = mask.len())
+    )
+)]
+pub unsafe fn evaluate_row_constraints(
+    launcher: RowConstraintsLauncher,
+    columns: &[&BaseFieldVec],
+    mask: &[MaskItem],
+    trace_log_size: u32,
+    random_coeff_powers: &[SecureField],
+    accumulator: &mut SecureColumn<CudaBackend>,
+) {
+    let size = accumulator.len();
+    assert!(size.is_power_of_two());
+    let eval_log_size = size.ilog2();
+    assert!(eval_log_size > trace_log_size);
+    assert!(columns.iter().all(|column| column.len() == size));
+    assert!(mask
+        .iter()
+        .all(|item| (item.column as usize) < columns.len()));
+
+    let column_ptrs = columns
+        .iter()
+        .map(|column| column.device_ptr())
+        .collect::<Vec<_>>();
+    let accumulator_ptrs = accumulator
+        .columns
+        .iter()
+        .map(|column| column.device_ptr())
+        .collect::<Vec<_>>();
+    let status = launcher(
+        column_ptrs.as_ptr(),
+        column_ptrs.len() as u32,
+        mask.as_ptr(),
+        mask.len() as u32,
+        trace_log_size,
+        eval_log_size,
+        random_coeff_powers.as_ptr(),
+        random_coeff_powers.len() as u32,
+        accumulator_ptrs.as_ptr(),
+    );
+    assert_eq!(
+        status, 0,
+        "mask does not match the row constraints evaluator"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        fields::{m31::BaseField, qm31::SecureField, secure_column::SecureColumn},
+        utils::offset_bit_reversed_circle_domain_index,
+    };
+
+    use super::{evaluate_row_constraints, MaskItem};
+    use crate::cuda::{self, BaseFieldVec};
+
+    #[test]
+    fn test_evaluate_row_constraints() {
+        require_gpu!();
+        let trace_log_size = 8;
+        let eval_log_size = trace_log_size + 1;
+        let size = 1 << eval_log_size;
+        let mask = [0, 1, 2].map(|offset| MaskItem { column: 0, offset });
+        let random_coeff_powers = [SecureField::from_u32_unchecked(1, 2, 3, 4)];
+        let values = (0..size as u32)
+            .map(|i| BaseField::from(i.wrapping_mul(2654435761) % (1 << 30)))
+            .collect::<Vec<_>>();
+        let initial = SecureField::from_u32_unchecked(5, 6, 7, 8);
+        let expected_result = (0..size)
+            .map(|row| {
+                let [a, b, c] = mask.map(|item| {
+                    values[offset_bit_reversed_circle_domain_index(
+                        row,
+                        trace_log_size,
+                        eval_log_size,
+                        item.offset,
+                    )]
+                });
+                initial + random_coeff_powers[0] * (c - (a * a + b * b))
+            })
+            .collect::<Vec<_>>();
+
+        let column = BaseFieldVec::from_vec(values);
+        let initial_coordinates = initial.to_m31_array();
+        let mut accumulator = SecureColumn {
+            columns: std::array::from_fn(|i| {
+                BaseFieldVec::from_vec(vec![initial_coordinates[i]; size])
+            }),
+        };
+        // The example evaluator reads a single coefficient.
+        unsafe {
+            evaluate_row_constraints(
+                cuda::bindings::example_row_constraints,
+                &[&column],
+                &mask,
+                trace_log_size,
+                &random_coeff_powers,
+                &mut accumulator,
+            )
+        };
+
+        let [a, b, c, d] = &accumulator.columns;
+        let (a, b, c, d) = (a.to_cpu(), b.to_cpu(), c.to_cpu(), d.to_cpu());
+        let result = (0..size)
+            .map(|row| SecureField::from_m31_array([a[row], b[row], c[row], d[row]]))
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected_result);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::cuda::{self, BaseFieldVec, SecureFieldVec};
+
+/// In-place inclusive scans of device columns, computed in `O(log n)` passes, for the cumulative
+/// columns of logup, permutation and range-check gadgets.
+pub trait CumulativeScan {
+    /// Replaces each value by the sum of all the values up to and including it.
+    fn cumulative_sum(&mut self);
+
+    /// Replaces each value by the product of all the values up to and including it, e.g. to
+    /// build the running product column of a permutation argument.
+    fn cumulative_product(&mut self);
+}
+
+impl CumulativeScan for BaseFieldVec {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn cumulative_sum(&mut self) {
+        unsafe { cuda::bindings::cumulative_sum_base_field(self.device_ptr(), self.size as u32) };
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn cumulative_product(&mut self) {
+        unsafe {
+            cuda::bindings::cumulative_product_base_field(self.device_ptr(), self.size as u32)
+        };
+    }
+}
+
+impl CumulativeScan for SecureFieldVec {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn cumulative_sum(&mut self) {
+        unsafe { cuda::bindings::cumulative_sum_secure_field(self.device_ptr(), self.size as u32) };
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn cumulative_product(&mut self) {
+        unsafe {
+            cuda::bindings::cumulative_product_secure_field(self.device_ptr(), self.size as u32)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::{m31::BaseField, qm31::SecureField};
+
+    use super::CumulativeScan;
+    use crate::cuda::{BaseFieldVec, SecureFieldVec};
+
+    #[test]
+    fn test_cumulative_product_base_field() {
+        require_gpu!();
+        let values = (1..(1 << 12) + 8).map(BaseField::from).collect::<Vec<_>>();
+        let expected_result = values
+            .iter()
+            .scan(BaseField::from(1), |product, &value| {
+                *product *= value;
+                Some(*product)
+            })
+            .collect::<Vec<_>>();
+
+        let mut column = BaseFieldVec::from_vec(values);
+        column.cumulative_product();
+
+        assert_eq!(column.to_vec(), expected_result);
+    }
+
+    #[test]
+    fn test_cumulative_product_secure_field() {
+        require_gpu!();
+        let values = (1..(1 << 10) + 3)
+            .map(|i| SecureField::from_u32_unchecked(i, i + 1, i + 2, i + 3))
+            .collect::<Vec<_>>();
+        let expected_result = values
+            .iter()
+            .scan(
+                SecureField::from_u32_unchecked(1, 0, 0, 0),
+                |product, &value| {
+                    *product *= value;
+                    Some(*product)
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let mut column = SecureFieldVec::from_vec(values);
+        column.cumulative_product();
+
+        assert_eq!(column.to_vec(), expected_result);
+    }
+
+    #[test]
+    fn test_cumulative_sum() {
+        require_gpu!();
+        let values = (0..(1 << 12) + 5).map(BaseField::from).collect::<Vec<_>>();
+        let secure_values = values
+            .iter()
+            .map(|value| SecureField::from_u32_unchecked(value.0, 1, 2, value.0 + 3))
+            .collect::<Vec<_>>();
+        let prefix_sums = |values: &[SecureField]| {
+            values
+                .iter()
+                .scan(
+                    SecureField::from_u32_unchecked(0, 0, 0, 0),
+                    |sum, &value| {
+                        *sum += value;
+                        Some(*sum)
+                    },
+                )
+                .collect::<Vec<_>>()
+        };
+
+        let mut column = BaseFieldVec::from_vec(values);
+        let mut secure_column = SecureFieldVec::from_vec(secure_values.clone());
+        column.cumulative_sum();
+        secure_column.cumulative_sum();
+
+        let expected_result = prefix_sums(&secure_values);
+        assert_eq!(
+            column.to_vec(),
+            expected_result
+                .iter()
+                .map(|value| value.to_m31_array()[0])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(secure_column.to_vec(), expected_result);
+    }
+}
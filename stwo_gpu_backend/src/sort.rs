@@ -0,0 +1,135 @@
+use crate::cuda::{self, BaseFieldVec};
+
+/// Radix sorts of device columns, e.g. to build sorted lookup tables and deduplicated
+/// range-check columns without a round trip through the host.
+///
+/// Values are ordered by their canonical representatives in `0..P`.
+pub trait DeviceSort {
+    /// Sorts the column in place, in increasing order.
+    fn sort(&mut self);
+
+    /// Sorts `keys` in place and moves the values of the column along with them, so value `i`
+    /// stays paired with key `i`. The sort is stable: values of equal keys keep their order.
+    fn sort_by_key(&mut self, keys: &mut BaseFieldVec);
+
+    /// The first value of each run of equal values, in order: the distinct values of the column
+    /// once sorted.
+    fn unique(&self) -> Self;
+}
+
+impl DeviceSort for BaseFieldVec {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn sort(&mut self) {
+        unsafe { cuda::bindings::sort_base_field(self.device_ptr(), self.size as u32) };
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn sort_by_key(&mut self, keys: &mut BaseFieldVec) {
+        assert_eq!(keys.size, self.size);
+        unsafe {
+            cuda::bindings::sort_base_field_by_key(
+                keys.device_ptr(),
+                self.device_ptr(),
+                self.size as u32,
+            )
+        };
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = self.size))
+    )]
+    fn unique(&self) -> Self {
+        let selected = BaseFieldVec::new_uninitialized(self.size);
+        let n_unique = unsafe {
+            cuda::bindings::unique_base_field(
+                self.device_ptr(),
+                self.size as u32,
+                selected.device_ptr(),
+            )
+        };
+        // Copied to a column of the exact size, so the rest of the buffer is released.
+        let result = BaseFieldVec::new_uninitialized(n_unique as usize);
+        unsafe {
+            cuda::bindings::copy_uint32_t_vec_from_device_to_device(
+                selected.device_ptr(),
+                result.device_ptr(),
+                n_unique,
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::DeviceSort;
+    use crate::cuda::BaseFieldVec;
+
+    fn values(size: u32) -> Vec<BaseField> {
+        (0..size)
+            .map(|i| BaseField::from(i.wrapping_mul(0x9e3779b9) % 1000))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        require_gpu!();
+        let values = values((1 << 14) + 7);
+        let mut expected = values.clone();
+        expected.sort_by_key(|value| value.0);
+
+        let mut column = BaseFieldVec::from_vec(values);
+        column.sort();
+
+        assert_eq!(column.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        require_gpu!();
+        let keys = values(1 << 12);
+        let indices = (0..1 << 12).map(BaseField::from).collect::<Vec<_>>();
+        let mut expected = keys
+            .iter()
+            .copied()
+            .zip(indices.clone())
+            .collect::<Vec<_>>();
+        expected.sort_by_key(|(key, _)| key.0);
+
+        let mut key_column = BaseFieldVec::from_vec(keys);
+        let mut column = BaseFieldVec::from_vec(indices);
+        column.sort_by_key(&mut key_column);
+
+        assert_eq!(
+            key_column.to_vec(),
+            expected.iter().map(|&(key, _)| key).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            column.to_vec(),
+            expected.iter().map(|&(_, index)| index).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_unique() {
+        require_gpu!();
+        let values = values(1 << 12);
+        let mut expected = values.clone();
+        expected.sort_by_key(|value| value.0);
+        expected.dedup();
+
+        let mut column = BaseFieldVec::from_vec(values);
+        column.sort();
+
+        assert_eq!(column.unique().to_vec(), expected);
+    }
+}
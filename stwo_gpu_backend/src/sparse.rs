@@ -0,0 +1,124 @@
+use stwo_prover::core::fields::m31::BaseField;
+
+use crate::cuda::{self, BaseFieldVec, DeviceVec};
+
+/// A column that is zero except in a few rows, e.g. a huge witness column of an AIR that only
+/// has values at the rows of rare operations, stored as the nonzero rows and their values.
+///
+/// Only the values of those rows are uploaded and kept on the device; [`SparseColumn::densify`]
+/// builds the full column when a kernel needs it, and
+/// [`crate::CudaBackend::accumulate_sparse`] adds it to an accumulator without ever building it.
+pub struct SparseColumn {
+    len: usize,
+    /// The rows of the values, increasing.
+    rows: DeviceVec<u32>,
+    values: BaseFieldVec,
+}
+
+impl SparseColumn {
+    /// The column of `len` rows with `values[i]` at row `rows[i]` and zeros elsewhere. Rows must
+    /// be increasing and smaller than `len`.
+    pub fn new(len: usize, rows: &[u32], values: &[BaseField]) -> Self {
+        assert_eq!(rows.len(), values.len());
+        assert!(len <= 1 << 31, "column too long");
+        assert!(
+            rows.windows(2).all(|pair| pair[0] < pair[1]),
+            "rows not increasing"
+        );
+        assert!(rows.last().map_or(true, |&row| (row as usize) < len));
+        Self {
+            len,
+            rows: DeviceVec::from_slice(rows),
+            values: BaseFieldVec::from_slice(values),
+        }
+    }
+
+    /// The sparse column of the nonzero values of `values`.
+    pub fn from_dense(values: &[BaseField]) -> Self {
+        let (rows, nonzero_values): (Vec<_>, Vec<_>) = values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != BaseField::from(0))
+            .map(|(row, &value)| (row as u32, value))
+            .unzip();
+        Self::new(values.len(), &rows, &nonzero_values)
+    }
+
+    /// Number of rows of the column, zero ones included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of rows that hold a value.
+    pub fn n_values(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn rows(&self) -> &DeviceVec<u32> {
+        &self.rows
+    }
+
+    pub(crate) fn values(&self) -> &BaseFieldVec {
+        &self.values
+    }
+
+    /// The full column, with zeros in the rows without a value.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(len = self.len, n_values = self.n_values())
+        )
+    )]
+    pub fn densify(&self) -> BaseFieldVec {
+        let result = BaseFieldVec::new_uninitialized(self.len);
+        unsafe {
+            cuda::bindings::densify(
+                self.rows.device_ptr(),
+                self.values.device_ptr(),
+                self.n_values() as u32,
+                result.device_ptr(),
+                self.len as u32,
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::SparseColumn;
+
+    #[test]
+    fn test_densify() {
+        require_gpu!();
+        let mut values = vec![BaseField::from(0); 1 << 12];
+        for row in [0, 5, 1000, (1 << 12) - 1] {
+            values[row] = BaseField::from(row as u32 + 7);
+        }
+
+        let column = SparseColumn::from_dense(&values);
+
+        assert_eq!(column.n_values(), 4);
+        assert_eq!(column.densify().to_vec(), values);
+        assert_eq!(
+            SparseColumn::new(8, &[], &[]).densify().to_vec(),
+            vec![BaseField::from(0); 8]
+        );
+    }
+
+    #[test]
+    fn test_rows_must_increase() {
+        let values = [1, 2].map(BaseField::from);
+
+        assert!(std::panic::catch_unwind(|| SparseColumn::new(8, &[3, 3], &values)).is_err());
+        assert!(std::panic::catch_unwind(|| SparseColumn::new(8, &[3, 8], &values)).is_err());
+    }
+}
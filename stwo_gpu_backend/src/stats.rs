@@ -0,0 +1,87 @@
+use std::ops::Range;
+
+use stwo_prover::core::{backend::Column, fields::m31::BaseField};
+
+use crate::cuda::{self, BaseFieldVec};
+
+/// Summary of the values of a column, for debugging witness generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnStats {
+    /// The smallest value, as an integer in `0..P`. `None` for an empty column.
+    pub min: Option<BaseField>,
+    /// The largest value, as an integer in `0..P`. `None` for an empty column.
+    pub max: Option<BaseField>,
+    pub n_zeros: usize,
+    /// The number of values outside of the range passed to [`column_stats`].
+    pub n_out_of_range: usize,
+}
+
+/// Computes the [`ColumnStats`] of `column` on the device, counting the values outside of
+/// `range` as out of range, e.g. `0..1 << 16` for a column checked against a 16 bit range.
+///
+/// Only the statistics are copied back, so huge columns can be inspected without downloading
+/// them.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = column.len()))
+)]
+pub fn column_stats(column: &BaseFieldVec, range: Range<u32>) -> ColumnStats {
+    let mut stats = cuda::bindings::ColumnStats::default();
+    unsafe {
+        cuda::bindings::base_field_column_stats(
+            column.device_ptr(),
+            column.len() as u32,
+            range.start,
+            range.end,
+            &mut stats,
+        );
+    }
+    let is_empty = column.len() == 0;
+    ColumnStats {
+        min: (!is_empty).then(|| BaseField::from_u32_unchecked(stats.min)),
+        max: (!is_empty).then(|| BaseField::from_u32_unchecked(stats.max)),
+        n_zeros: stats.n_zeros as usize,
+        n_out_of_range: stats.n_out_of_range as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::{BaseField, P};
+
+    use super::{column_stats, ColumnStats};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_column_stats() {
+        require_gpu!();
+        let size = (1 << 20) + 5;
+        let values = (0..size)
+            .map(|i: u32| BaseField::from(i.wrapping_mul(2654435761) % (1 << 17)))
+            .chain([0, P - 1].map(BaseField::from_u32_unchecked))
+            .collect::<Vec<_>>();
+        let column = BaseFieldVec::from_vec(values.clone());
+
+        let stats = column_stats(&column, 3..1 << 16);
+
+        let raw = values.iter().map(|value| value.0).collect::<Vec<_>>();
+        assert_eq!(
+            stats,
+            ColumnStats {
+                min: raw.iter().min().map(|&v| BaseField::from_u32_unchecked(v)),
+                max: raw.iter().max().map(|&v| BaseField::from_u32_unchecked(v)),
+                n_zeros: raw.iter().filter(|&&v| v == 0).count(),
+                n_out_of_range: raw.iter().filter(|&&v| !(3..1 << 16).contains(&v)).count(),
+            }
+        );
+        assert_eq!(
+            column_stats(&BaseFieldVec::from_vec(vec![]), 0..1),
+            ColumnStats {
+                min: None,
+                max: None,
+                n_zeros: 0,
+                n_out_of_range: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::ffi::c_void;
+
+use stwo_prover::core::fields::m31::BaseField;
+
+use crate::{
+    cuda::{self, BaseFieldVec, Blake2sHashVec, DeviceVec, Pod},
+    memory::memory_phase,
+};
+
+/// A CUDA stream, on which transfers can run concurrently with the host and with work queued on
+/// other streams.
+///
+/// Kernels of the backend run on the default stream, which waits for every stream created by
+/// [`Stream::new`] to be idle, so they always see the results of the transfers queued before
+/// them.
+pub struct Stream {
+    pub(crate) ptr: *mut c_void,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self {
+            ptr: unsafe { cuda::bindings::create_stream() },
+        }
+    }
+
+    /// A stream the default stream doesn't wait for, so its transfers overlap with the kernels of
+    /// the backend. Its results are only ordered with them through [`Pending::wait`].
+    pub fn non_blocking() -> Self {
+        Self {
+            ptr: unsafe { cuda::bindings::create_non_blocking_stream() },
+        }
+    }
+
+    /// Blocks until all the work queued on the stream is done.
+    pub fn synchronize(&self) {
+        unsafe { cuda::bindings::synchronize_stream(self.ptr) };
+    }
+
+    /// Returns an event reached once the work queued on the stream so far is done.
+    pub fn record(&self) -> Event {
+        let event = Event {
+            ptr: unsafe { cuda::bindings::create_event() },
+        };
+        unsafe { cuda::bindings::record_event(event.ptr, self.ptr) };
+        event
+    }
+
+    /// Makes the work queued on the stream from now on wait for `event`, without blocking the
+    /// host.
+    pub fn wait_for(&self, event: &Event) {
+        unsafe { cuda::bindings::stream_wait_event(self.ptr, event.ptr) };
+    }
+}
+
+// The CUDA runtime is thread safe, so streams and events can be used from any thread, e.g. by
+// the tasks of an async executor.
+unsafe impl Send for Stream {}
+
+unsafe impl Sync for Stream {}
+
+/// A point in the work queued on a stream, see [`Stream::record`]. Used to order work across
+/// streams.
+pub struct Event {
+    pub(crate) ptr: *mut c_void,
+}
+
+impl Event {
+    /// An event reached once the work queued on the default stream so far, i.e. the kernels of
+    /// the backend, is done.
+    pub(crate) fn record_default_stream() -> Self {
+        let event = Event {
+            ptr: unsafe { cuda::bindings::create_event() },
+        };
+        unsafe { cuda::bindings::record_event(event.ptr, std::ptr::null_mut()) };
+        event
+    }
+
+    /// Whether the work before the event is done, without blocking.
+    pub fn is_done(&self) -> bool {
+        unsafe { cuda::bindings::query_event(self.ptr) }
+    }
+
+    /// Blocks until the work before the event is done.
+    pub fn synchronize(&self) {
+        unsafe { cuda::bindings::synchronize_event(self.ptr) };
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        unsafe { cuda::bindings::destroy_event(self.ptr) };
+    }
+}
+
+unsafe impl Send for Event {}
+
+unsafe impl Sync for Event {}
+
+impl Default for Stream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        unsafe { cuda::bindings::destroy_stream(self.ptr) };
+    }
+}
+
+/// The result of work queued on a [`Stream`], only available once the stream reached it.
+///
+/// It borrows the stream and every buffer the work reads or writes for `'a`, so they can't be
+/// dropped or touched by the host while the work may still be running. Dropping it without
+/// calling [`Pending::wait`] blocks until the work is done for the same reason.
+#[must_use = "the result is only available through `wait`"]
+pub struct Pending<'a, T> {
+    value: Option<T>,
+    stream: &'a Stream,
+    /// Host memory page-locked for the work, released once it is done.
+    registered: Vec<*const u32>,
+}
+
+impl<'a, T> Pending<'a, T> {
+    pub(crate) fn new(value: T, stream: &'a Stream) -> Self {
+        Self {
+            value: Some(value),
+            stream,
+            registered: vec![],
+        }
+    }
+
+    pub(crate) fn stream(&self) -> &'a Stream {
+        self.stream
+    }
+
+    /// Blocks until the stream is done with the work, then returns its result.
+    pub fn wait(mut self) -> T {
+        self.finish();
+        self.value.take().unwrap()
+    }
+
+    fn finish(&mut self) {
+        self.stream.synchronize();
+        for host_ptr in self.registered.drain(..) {
+            unsafe { cuda::bindings::unregister_host_memory(host_ptr) };
+        }
+    }
+}
+
+// The registered pointers are only passed back to CUDA.
+unsafe impl<T: Send> Send for Pending<'_, T> {}
+
+impl<T> Drop for Pending<'_, T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.finish();
+        }
+    }
+}
+
+impl<T: Pod> DeviceVec<T> {
+    /// Orders the free of the vector after the work queued on `stream` so far. Dropping the
+    /// vector while that work may still use it, e.g. queued through [`crate::bindings`] or with
+    /// its [`Pending`] forgotten, then frees it once the work is done instead of under it.
+    ///
+    /// Work queued by the backend on streams already does this for the vectors it uses.
+    pub fn free_after(&self, stream: &Stream) {
+        cuda::free_after([self.device_ptr()], stream);
+    }
+}
+
+impl Blake2sHashVec {
+    /// Same as [`DeviceVec::free_after`].
+    pub fn free_after(&self, stream: &Stream) {
+        cuda::free_after([self.device_ptr()], stream);
+    }
+}
+
+/// Queues the uploads of `columns` on `stream`, typically a [`Stream::non_blocking`] one, so
+/// that e.g. the columns of the next tree are resident by the time the prover needs them.
+///
+/// The host memory of the columns is page-locked in place while the transfers run, so they
+/// proceed in the background of both the host and the device. Where that fails, they still
+/// complete, with less overlap.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(n_columns = columns.len()))
+)]
+pub fn prefetch_columns<'a>(
+    columns: &[&'a [BaseField]],
+    stream: &'a Stream,
+) -> Pending<'a, Vec<BaseFieldVec>> {
+    let _phase = memory_phase("upload");
+    let mut registered = vec![];
+    let device_columns = columns
+        .iter()
+        .map(|column| {
+            let host_ptr = column.as_ptr() as *const u32;
+            let bytes = std::mem::size_of_val(*column);
+            if !column.is_empty()
+                && unsafe { cuda::bindings::register_host_memory(host_ptr, bytes) } == 0
+            {
+                registered.push(host_ptr);
+            }
+            let device_column = BaseFieldVec::new_uninitialized(column.len());
+            unsafe {
+                cuda::bindings::stream_wait_default_stream(stream.ptr);
+                cuda::bindings::copy_uint32_t_vec_from_host_to_device_async(
+                    host_ptr,
+                    device_column.device_ptr(),
+                    column.len() as u32,
+                    stream.ptr,
+                );
+            }
+            device_column.free_after(stream);
+            device_column
+        })
+        .collect();
+    let mut pending = Pending::new(device_columns, stream);
+    pending.registered = registered;
+    pending
+}
+
+impl BaseFieldVec {
+    /// Same as [`BaseFieldVec::from_slice`], queuing the transfer on `stream`.
+    ///
+    /// The transfer only overlaps with the host if `host_array` is page-locked.
+    pub fn from_slice_async<'a>(
+        host_array: &'a [BaseField],
+        stream: &'a Stream,
+    ) -> Pending<'a, Self> {
+        let result = Self::new_uninitialized(host_array.len());
+        unsafe {
+            cuda::bindings::stream_wait_default_stream(stream.ptr);
+            cuda::bindings::copy_uint32_t_vec_from_host_to_device_async(
+                host_array.as_ptr() as *const u32,
+                result.device_ptr(),
+                result.size as u32,
+                stream.ptr,
+            );
+        }
+        result.free_after(stream);
+        Pending::new(result, stream)
+    }
+
+    /// Queues a copy of the vector into `host_array` on `stream`. `host_array` can be read again
+    /// once the returned [`Pending`] is waited for or dropped.
+    ///
+    /// # Safety
+    ///
+    /// The device writes `host_array` until `stream` is past the copy, which dropping the
+    /// [`Pending`] waits for. If it is forgotten instead, `host_array` must not be used nor freed
+    /// until the stream is synchronized.
+    pub unsafe fn copy_to_slice_async<'a>(
+        &'a self,
+        host_array: &'a mut [BaseField],
+        stream: &'a Stream,
+    ) -> Pending<'a, ()> {
+        assert_eq!(host_array.len(), self.size);
+        unsafe {
+            cuda::bindings::stream_wait_default_stream(stream.ptr);
+            cuda::bindings::copy_uint32_t_vec_from_device_to_host_async(
+                self.device_ptr(),
+                host_array.as_mut_ptr() as *const u32,
+                self.size as u32,
+                stream.ptr,
+            );
+        }
+        self.free_after(stream);
+        Pending::new((), stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::fields::m31::BaseField;
+
+    use super::{prefetch_columns, Stream};
+    use crate::cuda::BaseFieldVec;
+
+    #[test]
+    fn test_async_round_trip() {
+        require_gpu!();
+        let host_data = (0..1 << 16).map(BaseField::from).collect::<Vec<_>>();
+        let stream = Stream::new();
+
+        let column = BaseFieldVec::from_slice_async(&host_data, &stream).wait();
+        let mut result = vec![BaseField::from(0); host_data.len()];
+        unsafe { column.copy_to_slice_async(&mut result, &stream) }.wait();
+
+        assert_eq!(result, host_data);
+    }
+
+    #[test]
+    fn test_dropped_pending_completes() {
+        require_gpu!();
+        let host_data = (0..1 << 16).map(BaseField::from).collect::<Vec<_>>();
+        let column = BaseFieldVec::from_vec(host_data.clone());
+        let stream = Stream::new();
+
+        let mut result = vec![BaseField::from(0); host_data.len()];
+        let _ = unsafe { column.copy_to_slice_async(&mut result, &stream) };
+
+        assert_eq!(result, host_data);
+    }
+
+    #[test]
+    fn test_prefetch_columns() {
+        require_gpu!();
+        let columns = (0..3)
+            .map(|i| (0..(1 << 16) + i).map(BaseField::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let stream = Stream::non_blocking();
+
+        let pending = prefetch_columns(
+            &columns.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            &stream,
+        );
+        // Device work can be queued while the uploads run.
+        let other = BaseFieldVec::from_vec(columns[0].clone());
+        let device_columns = pending.wait();
+
+        assert_eq!(other.to_vec(), columns[0]);
+        for (device_column, column) in device_columns.iter().zip(&columns) {
+            assert_eq!(&device_column.to_vec(), column);
+        }
+    }
+}
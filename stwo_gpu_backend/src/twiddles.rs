@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use stwo_prover::core::{
+    circle::Coset,
+    poly::{circle::PolyOps, twiddles::TwiddleTree},
+};
+
+use crate::{backend::CudaBackend, cuda};
+
+/// Default bound of the shared twiddle cache, in bytes of device memory, on devices with 8 GiB of
+/// memory or more.
+const MAX_DEFAULT_CAPACITY: usize = 1 << 30;
+
+/// Least recently used cache of device twiddle trees, bounded by the device memory they use.
+struct TwiddleCache {
+    /// The bound set with [`set_twiddle_cache_capacity`], if any.
+    capacity: Option<usize>,
+    /// The trees with the device they were computed on and their coset, most recently used
+    /// last.
+    entries: Vec<(i32, Coset, Arc<TwiddleTree<CudaBackend>>)>,
+}
+
+// Device pointers are valid on every thread of the process, and cached trees are never written.
+unsafe impl Send for TwiddleCache {}
+
+static CACHE: Mutex<TwiddleCache> = Mutex::new(TwiddleCache::new(None));
+
+static DEFAULT_CAPACITY: OnceLock<usize> = OnceLock::new();
+
+/// The bound of the cache unless set: 1 GiB, or an eighth of the memory of the device current on
+/// first use if smaller, e.g. a MIG slice.
+fn default_capacity() -> usize {
+    *DEFAULT_CAPACITY.get_or_init(|| {
+        CudaBackend::current_device().map_or(MAX_DEFAULT_CAPACITY, |device| {
+            MAX_DEFAULT_CAPACITY.min(device.total_memory / 8)
+        })
+    })
+}
+
+impl TwiddleCache {
+    const fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    /// The tree of `coset` on the device current on this thread.
+    fn get(&mut self, coset: Coset) -> Arc<TwiddleTree<CudaBackend>> {
+        let device = unsafe { cuda::bindings::get_current_device() };
+        let cached = self
+            .entries
+            .iter()
+            .position(|(d, c, _)| (*d, *c) == (device, coset));
+        let twiddles = match cached {
+            Some(index) => self.entries.remove(index).2,
+            None => Arc::new(CudaBackend::precompute_twiddles(coset)),
+        };
+        self.entries.push((device, coset, twiddles.clone()));
+        self.evict();
+        twiddles
+    }
+
+    fn size(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(_, coset, _)| tree_size(*coset))
+            .sum()
+    }
+
+    /// Drops the least recently used trees until the cache fits its capacity. Trees still held
+    /// by a prover are only freed once it drops them.
+    fn evict(&mut self) {
+        let capacity = self.capacity.unwrap_or_else(default_capacity);
+        while self.size() > capacity {
+            self.entries.remove(0);
+        }
+    }
+}
+
+/// Device memory used by the twiddles and inverse twiddles of `coset`.
+fn tree_size(coset: Coset) -> usize {
+    2 * coset.size() * std::mem::size_of::<u32>()
+}
+
+/// Returns the twiddle tree of `coset` on the current device from a cache shared by every prover
+/// of the process, computing it on the first use on that device.
+///
+/// Long-running services proving the same domain sizes repeatedly only pay for
+/// [`PolyOps::precompute_twiddles`] once per size while it stays in the cache.
+pub fn cached_twiddles(coset: Coset) -> Arc<TwiddleTree<CudaBackend>> {
+    CACHE.lock().unwrap().get(coset)
+}
+
+/// Bounds the device memory used by the twiddle cache, evicting the least recently used trees
+/// if needed. Defaults to 1 GiB, or an eighth of the device memory on smaller devices such as MIG
+/// slices.
+pub fn set_twiddle_cache_capacity(bytes: usize) {
+    let mut cache = CACHE.lock().unwrap();
+    cache.capacity = Some(bytes);
+    cache.evict();
+}
+
+/// Empties the twiddle cache, e.g. to release device memory under pressure.
+pub fn clear_twiddle_cache() {
+    CACHE.lock().unwrap().entries.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use stwo_prover::core::{
+        backend::{Column, CpuBackend},
+        poly::circle::{CanonicCoset, PolyOps},
+    };
+
+    use super::{tree_size, TwiddleCache};
+
+    #[test]
+    fn test_twiddle_cache() {
+        require_gpu!();
+        let cosets = [5, 6, 7].map(|log_size| CanonicCoset::new(log_size).half_coset());
+        let mut cache = TwiddleCache::new(Some(tree_size(cosets[1]) + tree_size(cosets[2])));
+
+        let first = cache.get(cosets[0]);
+        assert!(Arc::ptr_eq(&first, &cache.get(cosets[0])));
+        assert_eq!(
+            first.twiddles.to_cpu(),
+            CpuBackend::precompute_twiddles(cosets[0]).twiddles
+        );
+
+        // Using the first tree again makes the second one the least recently used.
+        let second = cache.get(cosets[1]);
+        cache.get(cosets[0]);
+        cache.get(cosets[2]);
+        assert!(Arc::ptr_eq(&first, &cache.get(cosets[0])));
+        assert!(!Arc::ptr_eq(&second, &cache.get(cosets[1])));
+        assert!(cache.size() <= cache.capacity.unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+use stwo_prover::core::{
+    circle::{CirclePoint, Coset},
+    fields::{m31::BaseField, qm31::SecureField},
+    poly::{
+        circle::{CircleDomain, CircleEvaluation},
+        BitReversedOrder,
+    },
+};
+
+use crate::{
+    backend::CudaBackend,
+    cuda::{self, BaseFieldVec},
+};
+
+/// Evaluates the vanishing polynomial of `coset` on `domain`, as `coset_vanishing` of stwo, e.g.
+/// to normalize the constraints of a component smaller than the evaluation domain without
+/// computing the domain points on the host.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(log_coset_size = coset.log_size, log_size = domain.log_size())
+    )
+)]
+pub fn coset_vanishing_evaluation(
+    coset: Coset,
+    domain: CircleDomain,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    let values = BaseFieldVec::new_uninitialized(domain.size());
+    let shift = coset.step_size.half().to_point() - coset.initial;
+    unsafe {
+        cuda::bindings::coset_vanishing(
+            domain.half_coset.initial.into(),
+            domain.half_coset.step.into(),
+            domain.log_size(),
+            shift.into(),
+            coset.log_size,
+            values.device_ptr(),
+        );
+    }
+    CircleEvaluation::new(domain, values)
+}
+
+/// Inverse of [`coset_vanishing_evaluation`], by which constraints are divided. The vanishing
+/// polynomial must not vanish on `domain`, i.e. `domain` must be disjoint from `coset`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(log_coset_size = coset.log_size, log_size = domain.log_size())
+    )
+)]
+pub fn inverse_coset_vanishing_evaluation(
+    coset: Coset,
+    domain: CircleDomain,
+) -> CircleEvaluation<CudaBackend, BaseField, BitReversedOrder> {
+    let evaluation = coset_vanishing_evaluation(coset, domain);
+    let inverses = BaseFieldVec::new_uninitialized(domain.size());
+    unsafe {
+        cuda::bindings::batch_inverse_base_field(
+            evaluation.values.device_ptr(),
+            inverses.device_ptr(),
+            domain.size(),
+        );
+    }
+    CircleEvaluation::new(domain, inverses)
+}
+
+/// The denominators of the quotients by `sample` and its complex conjugate at the points of
+/// `domain`, in bit reversed order, as [`stwo_prover::core::pcs::quotients::QuotientOps`]
+/// divides by: the line through both points, a `CM31` value per point, given as the columns of
+/// the real and of the imaginary parts.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(log_size = domain.log_size()))
+)]
+pub fn pair_vanishing_denominators(
+    sample: CirclePoint<SecureField>,
+    domain: CircleDomain,
+) -> [BaseFieldVec; 2] {
+    let [real, imaginary] = [(); 2].map(|_| BaseFieldVec::new_uninitialized(domain.size()));
+    unsafe {
+        cuda::bindings::pair_vanishing_denominators(
+            domain.half_coset.initial.into(),
+            domain.half_coset.step.into(),
+            domain.log_size(),
+            sample.x,
+            sample.y,
+            real.device_ptr(),
+            imaginary.device_ptr(),
+        );
+    }
+    [real, imaginary]
+}
+
+/// Inverses of [`pair_vanishing_denominators`], computed with a single batch inverse of their
+/// norms, ready to multiply quotient numerators with. `sample` must not be on `domain`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(log_size = domain.log_size()))
+)]
+pub fn pair_vanishing_denominator_inverses(
+    sample: CirclePoint<SecureField>,
+    domain: CircleDomain,
+) -> [BaseFieldVec; 2] {
+    let [real, imaginary] = [(); 2].map(|_| BaseFieldVec::new_uninitialized(domain.size()));
+    unsafe {
+        cuda::bindings::pair_vanishing_denominator_inverses(
+            domain.half_coset.initial.into(),
+            domain.half_coset.step.into(),
+            domain.log_size(),
+            sample.x,
+            sample.y,
+            real.device_ptr(),
+            imaginary.device_ptr(),
+        );
+    }
+    [real, imaginary]
+}
+
+#[cfg(test)]
+mod tests {
+    use stwo_prover::core::{
+        backend::Column,
+        circle::SECURE_FIELD_CIRCLE_GEN,
+        constraints::coset_vanishing,
+        fields::{cm31::CM31, m31::BaseField, FieldExpOps},
+        poly::circle::CanonicCoset,
+        utils::bit_reverse_index,
+    };
+
+    use super::{
+        coset_vanishing_evaluation, inverse_coset_vanishing_evaluation,
+        pair_vanishing_denominator_inverses, pair_vanishing_denominators,
+    };
+
+    #[test]
+    fn test_coset_vanishing_evaluation() {
+        require_gpu!();
+        // A component of size 2^6 evaluated on a domain blown up by 2^3.
+        let coset = CanonicCoset::new(6).coset();
+        let domain = CanonicCoset::new(9).circle_domain();
+
+        let values = coset_vanishing_evaluation(coset, domain).values.to_cpu();
+        let inverses = inverse_coset_vanishing_evaluation(coset, domain)
+            .values
+            .to_cpu();
+
+        for (row, (&value, &inverse)) in values.iter().zip(&inverses).enumerate() {
+            let point = domain.at(bit_reverse_index(row, domain.log_size()));
+            let expected = coset_vanishing(coset, point);
+            assert_eq!(value, expected);
+            assert_eq!(inverse, expected.inverse());
+        }
+    }
+
+    #[test]
+    fn test_pair_vanishing_denominators() {
+        require_gpu!();
+        let domain = CanonicCoset::new(9).circle_domain();
+        let sample = SECURE_FIELD_CIRCLE_GEN.mul(17);
+
+        let [real, imaginary] =
+            pair_vanishing_denominators(sample, domain).map(|column| column.to_cpu());
+        let [inverse_real, inverse_imaginary] =
+            pair_vanishing_denominator_inverses(sample, domain).map(|column| column.to_cpu());
+
+        for row in 0..domain.size() {
+            let point = domain.at(bit_reverse_index(row, domain.log_size()));
+            let zero = BaseField::from(0);
+            let expected = (sample.x.0 - CM31(point.x, zero)) * sample.y.1
+                - (sample.y.0 - CM31(point.y, zero)) * sample.x.1;
+            assert_eq!(CM31(real[row], imaginary[row]), expected);
+            assert_eq!(
+                CM31(inverse_real[row], inverse_imaginary[row]),
+                expected.inverse()
+            );
+        }
+    }
+}
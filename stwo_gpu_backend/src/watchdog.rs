@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::stream::Event;
+
+/// The bound set by [`set_watchdog`], in milliseconds, 0 when the watchdog is off.
+static TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The operations running, by id.
+static RUNNING: Mutex<BTreeMap<u64, Running>> = Mutex::new(BTreeMap::new());
+
+static MONITOR: OnceLock<()> = OnceLock::new();
+
+struct Running {
+    operation: &'static str,
+    thread: String,
+    start: Instant,
+    /// Reached once the device is done with the work queued before the operation.
+    queued_before: Event,
+}
+
+/// Bounds the wall-clock time of each backend operation, such as an FFT, a layer of Merkle
+/// hashing or a FRI fold, to `timeout`, or lifts the bound with `None`.
+///
+/// A monitor thread checks the running operations and aborts the process with a diagnostic once
+/// one of them runs longer, instead of hanging forever when a kernel deadlocks or the GPU is
+/// wedged. The diagnostic tells whether the device even finished the work queued before the
+/// operation, e.g. on other streams. Bounds should leave room for the largest operations of the
+/// proof, which can take seconds.
+pub fn set_watchdog(timeout: Option<Duration>) {
+    let timeout_ms = timeout.map_or(0, |timeout| (timeout.as_millis() as u64).max(1));
+    TIMEOUT_MS.store(timeout_ms, Ordering::Relaxed);
+    if timeout_ms > 0 {
+        MONITOR.get_or_init(|| {
+            thread::Builder::new()
+                .name("stwo-gpu-watchdog".into())
+                .spawn(monitor)
+                .expect("failed to spawn the watchdog thread");
+        });
+    }
+}
+
+/// Marks a backend operation, watched until the returned guard is dropped if the watchdog is on.
+pub(crate) fn watch(operation: &'static str) -> Watch {
+    if TIMEOUT_MS.load(Ordering::Relaxed) == 0 {
+        return Watch { id: None };
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let running = Running {
+        operation,
+        thread: thread_name(),
+        start: Instant::now(),
+        queued_before: Event::record_default_stream(),
+    };
+    RUNNING.lock().unwrap().insert(id, running);
+    Watch { id: Some(id) }
+}
+
+/// Guard returned by [`watch`].
+pub(crate) struct Watch {
+    id: Option<u64>,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            RUNNING.lock().unwrap().remove(&id);
+        }
+    }
+}
+
+fn monitor() {
+    loop {
+        let timeout_ms = TIMEOUT_MS.load(Ordering::Relaxed);
+        // Checks a few times per bound, so overruns are caught soon after they happen.
+        let period = Duration::from_millis((timeout_ms / 4).clamp(10, 1000));
+        thread::sleep(period);
+        if timeout_ms == 0 {
+            continue;
+        }
+        let timeout = Duration::from_millis(timeout_ms);
+        let running = RUNNING.lock().unwrap();
+        if let Some(overrun) = running
+            .values()
+            .find(|running| running.start.elapsed() > timeout)
+        {
+            eprintln!(
+                "{}",
+                Overrun {
+                    operation: overrun.operation,
+                    thread: &overrun.thread,
+                    elapsed: overrun.start.elapsed(),
+                    timeout,
+                    queued_before_done: overrun.queued_before.is_done(),
+                }
+            );
+            std::process::abort();
+        }
+    }
+}
+
+fn thread_name() -> String {
+    let thread = thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+/// The diagnostic printed when an operation overruns its bound.
+struct Overrun<'a> {
+    operation: &'static str,
+    thread: &'a str,
+    elapsed: Duration,
+    timeout: Duration,
+    queued_before_done: bool,
+}
+
+impl fmt::Display for Overrun<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stwo-gpu watchdog: `{}` on thread `{}` has run for {:?}, over the {:?} bound; ",
+            self.operation, self.thread, self.elapsed, self.timeout
+        )?;
+        if self.queued_before_done {
+            write!(f, "the device hangs in the operation itself")?;
+        } else {
+            write!(
+                f,
+                "the device didn't even finish the work queued before it, e.g. on other streams"
+            )?;
+        }
+        write!(f, ". Aborting.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use stwo_prover::core::{
+        backend::Column,
+        fields::m31::BaseField,
+        poly::{
+            circle::{CanonicCoset, CircleEvaluation, PolyOps},
+            BitReversedOrder,
+        },
+    };
+
+    use super::{set_watchdog, Overrun, RUNNING};
+    use crate::{backend::CudaBackend, cuda::BaseFieldVec};
+
+    /// Turns the watchdog back off when dropped, even if the test fails, so it doesn't outlive
+    /// the test.
+    struct WatchdogOff;
+
+    impl Drop for WatchdogOff {
+        fn drop(&mut self) {
+            set_watchdog(None);
+        }
+    }
+
+    #[test]
+    fn test_overrun_diagnostic() {
+        let overrun = Overrun {
+            operation: "interpolate",
+            thread: "prover",
+            elapsed: Duration::from_secs(12),
+            timeout: Duration::from_secs(10),
+            queued_before_done: true,
+        };
+
+        assert_eq!(
+            overrun.to_string(),
+            "stwo-gpu watchdog: `interpolate` on thread `prover` has run for 12s, over the 10s \
+             bound; the device hangs in the operation itself. Aborting."
+        );
+    }
+
+    #[test]
+    fn test_watched_operations_complete() {
+        require_gpu!();
+        // Generous, as other tests run operations concurrently.
+        set_watchdog(Some(Duration::from_secs(600)));
+        let _off = WatchdogOff;
+        let domain = CanonicCoset::new(10).circle_domain();
+        let values = (0..1 << 10).map(BaseField::from).collect::<Vec<_>>();
+        let evaluation = CircleEvaluation::<CudaBackend, _, BitReversedOrder>::new(
+            domain,
+            BaseFieldVec::from_vec(values.clone()),
+        );
+
+        let twiddles = CudaBackend::precompute_twiddles(domain.half_coset);
+        let poly = CudaBackend::interpolate(evaluation, &twiddles);
+        let evaluation = CudaBackend::evaluate(&poly, domain, &twiddles);
+
+        assert_eq!(evaluation.values.to_cpu(), values);
+        // The thread of this test has no operation left running.
+        let thread = super::thread_name();
+        assert!(RUNNING
+            .lock()
+            .unwrap()
+            .values()
+            .all(|running| running.thread != thread));
+    }
+}